@@ -3,12 +3,18 @@ use gpui_component::input::InputState;
 use log::{debug, error, info};
 
 use crate::config;
-use crate::config::connection::{ConnectionConfig, ConnectionStatus};
+use crate::config::connection::{ConnectionConfig, ConnectionStatus, ConnectionType, LoadTestConfig, MaxConnectionsPolicy, MqttConfig, ProxyConfig, QueueConfig, QueuePolicy, RelayConfig, RelayImpairment, RttCorrelation, SendShaping, SerialConfig, WebSocketSendFrame};
 use crate::config::storage::ConfigStorage;
 use crate::export::{self, ExportFormat};
 use crate::log_writer::LogWriter;
-use crate::message::{Message, MessageDirection, MessageType};
+use crate::message::{Message, MessageDirection, MessageType, MqttMessageInfo};
 use crate::network::events::ConnectionEvent;
+use crate::network::protocol::mqtt::MqttPublish;
+use crate::network::protocol::relay::{RelayHeldChunk, RelayHoldState};
+use crate::network::protocol::serial::SerialLine;
+use crate::network::protocol::tcp::ClientCloseMode;
+use crate::network::queue::{EnqueueError, QueueItem, WriteSender};
+use crate::raw_capture::RawCapture;
 
 use crate::ui::connection_tab::ConnectionTabState;
 use crate::ui::main_window::MainWindow;
@@ -19,6 +25,60 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use smol::channel::{Sender, Receiver, unbounded as smol_unbounded};
 
+/// 收发队列积压时界面刷新队列深度的间隔
+const QUEUE_REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 中继单个方向的网络损伤输入框，留空视为 0
+pub struct RelayImpairmentInputs {
+    pub latency: Entity<InputState>,
+    pub jitter: Entity<InputState>,
+    pub loss: Entity<InputState>,
+    pub bandwidth: Entity<InputState>,
+    pub corrupt: Entity<InputState>,
+    pub reorder: Entity<InputState>,
+    pub segment: Entity<InputState>,
+}
+
+impl RelayImpairmentInputs {
+    fn new(window: &mut Window, cx: &mut Context<NetAssistantApp>) -> Self {
+        let mut input = || cx.new(|cx| InputState::new(window, cx).placeholder("0"));
+        Self {
+            latency: input(),
+            jitter: input(),
+            loss: input(),
+            bandwidth: input(),
+            corrupt: input(),
+            reorder: input(),
+            segment: input(),
+        }
+    }
+
+    pub fn all(&self) -> [&Entity<InputState>; 7] {
+        [&self.latency, &self.jitter, &self.loss, &self.bandwidth, &self.corrupt, &self.reorder, &self.segment]
+    }
+
+    /// 读取损伤设置，数值无效或百分比超出范围时返回 None
+    pub fn read(&self, cx: &App) -> Option<RelayImpairment> {
+        fn number<T: std::str::FromStr + Default>(input: &Entity<InputState>, cx: &App) -> Option<T> {
+            match input.read(cx).value().trim() {
+                "" => Some(T::default()),
+                value => value.parse().ok(),
+            }
+        }
+        let impairment = RelayImpairment {
+            latency_ms: number(&self.latency, cx)?,
+            jitter_ms: number(&self.jitter, cx)?,
+            loss_percent: number(&self.loss, cx)?,
+            bandwidth: number(&self.bandwidth, cx)?,
+            corrupt_percent: number(&self.corrupt, cx)?,
+            reorder_percent: number(&self.reorder, cx)?,
+            segment_size: number(&self.segment, cx)?,
+        };
+        impairment.validate().ok()?;
+        Some(impairment)
+    }
+}
+
 pub struct NetAssistantApp {
     // 配置存储
     pub storage: ConfigStorage,
@@ -30,6 +90,86 @@ pub struct NetAssistantApp {
     pub host_input: Entity<InputState>,
     pub port_input: Entity<InputState>,
     pub new_connection_protocol: String,
+    // 新建连接的超时设置（秒）
+    pub timeout_input: Entity<InputState>,
+    pub read_idle_timeout_input: Entity<InputState>,
+    pub new_connection_read_idle_close: bool,
+    // 新建服务端的最大连接数设置
+    pub max_connections_input: Entity<InputState>,
+    pub new_connection_max_connections_policy: MaxConnectionsPolicy,
+    // 新建TCP/WebSocket服务端是否双栈监听（IPv6套接字同时接受IPv4客户端）
+    pub new_connection_dual_stack: bool,
+    // 新建UDP连接的接收缓冲区大小
+    pub udp_recv_buffer_input: Entity<InputState>,
+    // 新建客户端的本地绑定地址与端口
+    pub local_address_input: Entity<InputState>,
+    pub local_port_input: Entity<InputState>,
+    // 新建TCP连接的套接字选项
+    pub new_connection_nodelay: bool,
+    pub new_connection_keepalive: bool,
+    pub keepalive_idle_input: Entity<InputState>,
+    pub keepalive_interval_input: Entity<InputState>,
+    pub keepalive_count_input: Entity<InputState>,
+    pub linger_input: Entity<InputState>,
+    pub send_buffer_input: Entity<InputState>,
+    pub recv_buffer_input: Entity<InputState>,
+    pub ip_ttl_input: Entity<InputState>,
+    pub ip_tos_input: Entity<InputState>,
+    // 新建TCP连接的TLS设置
+    pub new_connection_tls_enabled: bool,
+    pub new_connection_tls_insecure: bool,
+    pub new_connection_tls_require_client_cert: bool,
+    pub tls_server_name_input: Entity<InputState>,
+    pub tls_ca_input: Entity<InputState>,
+    pub tls_cert_input: Entity<InputState>,
+    pub tls_key_input: Entity<InputState>,
+    // 新建UDP服务端的组播设置
+    pub multicast_groups_input: Entity<InputState>,
+    pub multicast_interface_input: Entity<InputState>,
+    pub multicast_ttl_input: Entity<InputState>,
+    pub multicast_source_input: Entity<InputState>,
+    pub new_connection_multicast_loopback: bool,
+    // 新建WebSocket连接的设置
+    pub ws_path_input: Entity<InputState>,
+    pub ws_headers_input: Entity<InputState>,
+    pub ws_subprotocols_input: Entity<InputState>,
+    pub new_connection_ws_send_frame: WebSocketSendFrame,
+    // 新建串口连接的设置（设备路径和波特率来自输入框，其余参数保存在 new_connection_serial 中）
+    pub serial_path_input: Entity<InputState>,
+    pub serial_baud_input: Entity<InputState>,
+    pub new_connection_serial: SerialConfig,
+    // 新建Unix域套接字连接的套接字路径、服务端文件权限和数据报客户端本地路径
+    pub unix_path_input: Entity<InputState>,
+    pub unix_permissions_input: Entity<InputState>,
+    pub unix_local_path_input: Entity<InputState>,
+    // 新建MQTT连接的设置（协议版本和清除会话保存在 new_connection_mqtt 中，其余来自输入框）
+    pub mqtt_client_id_input: Entity<InputState>,
+    pub mqtt_username_input: Entity<InputState>,
+    pub mqtt_password_input: Entity<InputState>,
+    pub mqtt_keep_alive_input: Entity<InputState>,
+    pub mqtt_subscriptions_input: Entity<InputState>,
+    pub mqtt_publish_topic_input: Entity<InputState>,
+    pub new_connection_mqtt: MqttConfig,
+    // 新建中继的上游地址（传输层协议保存在 new_connection_relay 中）
+    pub relay_upstream_address_input: Entity<InputState>,
+    pub relay_upstream_port_input: Entity<InputState>,
+    // 新建中继的网络损伤设置（按方向）
+    pub relay_to_upstream_inputs: RelayImpairmentInputs,
+    pub relay_to_client_inputs: RelayImpairmentInputs,
+    pub new_connection_relay: RelayConfig,
+    // 新建压力测试的设置（传输层协议和十六进制模板保存在 new_connection_load_test 中，其余来自输入框）
+    pub load_test_clients_input: Entity<InputState>,
+    pub load_test_ramp_up_input: Entity<InputState>,
+    pub load_test_send_rate_input: Entity<InputState>,
+    pub load_test_payload_input: Entity<InputState>,
+    pub load_test_duration_input: Entity<InputState>,
+    pub new_connection_load_test: LoadTestConfig,
+    // 新建客户端的上游代理（类型和远程解析保存在 new_connection_proxy 中，其余来自输入框）
+    pub proxy_address_input: Entity<InputState>,
+    pub proxy_port_input: Entity<InputState>,
+    pub proxy_username_input: Entity<InputState>,
+    pub proxy_password_input: Entity<InputState>,
+    pub new_connection_proxy: ProxyConfig,
 
     // 解码器选择对话框状态
     pub show_decoder_selection: bool,
@@ -56,8 +196,10 @@ pub struct NetAssistantApp {
     
 
     // 写入发送器映射（无锁设计，每个标签页独立管理）- 使用smol channel
-    pub client_write_senders: HashMap<String, Sender<Vec<u8>>>,
-    pub server_clients: HashMap<String, HashMap<SocketAddr, Sender<Vec<u8>>>>,
+    pub client_write_senders: HashMap<String, WriteSender>,
+    // MQTT客户端的发布通道（发送时需附带主题）
+    pub mqtt_publishers: HashMap<String, WriteSender<MqttPublish>>,
+    pub server_clients: HashMap<String, HashMap<SocketAddr, WriteSender>>,
 
     // 右键菜单状态
     pub show_context_menu: bool,
@@ -104,6 +246,55 @@ impl NetAssistantApp {
         // 使用window创建InputState实体
         let host_input = cx.new(|cx| InputState::new(window, cx));
         let port_input = cx.new(|cx| InputState::new(window, cx));
+        let timeout_input = cx.new(|cx| InputState::new(window, cx));
+        let read_idle_timeout_input = cx.new(|cx| InputState::new(window, cx));
+        let max_connections_input = cx.new(|cx| InputState::new(window, cx));
+        let udp_recv_buffer_input = cx.new(|cx| InputState::new(window, cx));
+        let local_address_input = cx.new(|cx| InputState::new(window, cx).placeholder("留空由系统选择"));
+        let local_port_input = cx.new(|cx| InputState::new(window, cx).placeholder("0 表示临时端口"));
+        let keepalive_idle_input = cx.new(|cx| InputState::new(window, cx));
+        let keepalive_interval_input = cx.new(|cx| InputState::new(window, cx));
+        let keepalive_count_input = cx.new(|cx| InputState::new(window, cx));
+        let linger_input = cx.new(|cx| InputState::new(window, cx));
+        let send_buffer_input = cx.new(|cx| InputState::new(window, cx));
+        let recv_buffer_input = cx.new(|cx| InputState::new(window, cx));
+        let ip_ttl_input = cx.new(|cx| InputState::new(window, cx));
+        let ip_tos_input = cx.new(|cx| InputState::new(window, cx));
+        let tls_server_name_input = cx.new(|cx| InputState::new(window, cx));
+        let tls_ca_input = cx.new(|cx| InputState::new(window, cx).placeholder("PEM 文件路径"));
+        let tls_cert_input = cx.new(|cx| InputState::new(window, cx).placeholder("PEM 文件路径"));
+        let tls_key_input = cx.new(|cx| InputState::new(window, cx).placeholder("PEM 文件路径"));
+        let multicast_groups_input = cx.new(|cx| InputState::new(window, cx).placeholder("如 239.255.0.1, ff02::1234"));
+        let multicast_interface_input = cx.new(|cx| InputState::new(window, cx).placeholder("IPv4地址或IPv6接口索引"));
+        let multicast_ttl_input = cx.new(|cx| InputState::new(window, cx));
+        let multicast_source_input = cx.new(|cx| InputState::new(window, cx).placeholder("源特定组播，可留空"));
+        let ws_path_input = cx.new(|cx| InputState::new(window, cx).placeholder("/"));
+        let ws_headers_input = cx.new(|cx| InputState::new(window, cx).placeholder("如 Authorization: Bearer xxx; X-Id: 1"));
+        let ws_subprotocols_input = cx.new(|cx| InputState::new(window, cx).placeholder("如 mqtt, graphql-ws"));
+        let serial_path_input = cx.new(|cx| InputState::new(window, cx).placeholder("如 /dev/ttyUSB0、/dev/pts/3 或 COM3"));
+        let serial_baud_input = cx.new(|cx| InputState::new(window, cx).placeholder("115200"));
+        let unix_path_input = cx.new(|cx| InputState::new(window, cx).placeholder("如 /tmp/app.sock"));
+        let unix_permissions_input = cx.new(|cx| InputState::new(window, cx).placeholder("如 660，留空使用默认"));
+        let unix_local_path_input = cx.new(|cx| InputState::new(window, cx).placeholder("留空自动生成"));
+        let mqtt_client_id_input = cx.new(|cx| InputState::new(window, cx).placeholder("留空自动生成"));
+        let mqtt_username_input = cx.new(|cx| InputState::new(window, cx).placeholder("可选"));
+        let mqtt_password_input = cx.new(|cx| InputState::new(window, cx).placeholder("可选"));
+        let mqtt_keep_alive_input = cx.new(|cx| InputState::new(window, cx).placeholder("60"));
+        let mqtt_subscriptions_input = cx.new(|cx| InputState::new(window, cx).placeholder("如 sensors/+/temp:1, devices/#"));
+        let mqtt_publish_topic_input = cx.new(|cx| InputState::new(window, cx).placeholder("如 devices/1/cmd"));
+        let relay_upstream_address_input = cx.new(|cx| InputState::new(window, cx).placeholder("如 192.168.1.10"));
+        let relay_upstream_port_input = cx.new(|cx| InputState::new(window, cx).placeholder("如 502"));
+        let relay_to_upstream_inputs = RelayImpairmentInputs::new(window, cx);
+        let relay_to_client_inputs = RelayImpairmentInputs::new(window, cx);
+        let load_test_clients_input = cx.new(|cx| InputState::new(window, cx).placeholder("10"));
+        let load_test_ramp_up_input = cx.new(|cx| InputState::new(window, cx).placeholder("0 表示同时建立"));
+        let load_test_send_rate_input = cx.new(|cx| InputState::new(window, cx).placeholder("1"));
+        let load_test_payload_input = cx.new(|cx| InputState::new(window, cx).placeholder("如 ping {client}-{seq}\\n"));
+        let load_test_duration_input = cx.new(|cx| InputState::new(window, cx).placeholder("0 表示手动停止"));
+        let proxy_address_input = cx.new(|cx| InputState::new(window, cx).placeholder("如 127.0.0.1"));
+        let proxy_port_input = cx.new(|cx| InputState::new(window, cx).placeholder("1080"));
+        let proxy_username_input = cx.new(|cx| InputState::new(window, cx).placeholder("可选"));
+        let proxy_password_input = cx.new(|cx| InputState::new(window, cx).placeholder("可选"));
 
         // 初始化空的连接标签页状态（不预先创建）
         let connection_tabs = HashMap::new();
@@ -133,6 +324,70 @@ impl NetAssistantApp {
             host_input,
             port_input,
             new_connection_protocol: String::from("TCP"),
+            timeout_input,
+            read_idle_timeout_input,
+            new_connection_read_idle_close: true,
+            max_connections_input,
+            new_connection_max_connections_policy: MaxConnectionsPolicy::default(),
+            new_connection_dual_stack: false,
+            udp_recv_buffer_input,
+            local_address_input,
+            local_port_input,
+            new_connection_nodelay: false,
+            new_connection_keepalive: false,
+            keepalive_idle_input,
+            keepalive_interval_input,
+            keepalive_count_input,
+            linger_input,
+            send_buffer_input,
+            recv_buffer_input,
+            ip_ttl_input,
+            ip_tos_input,
+            new_connection_tls_enabled: false,
+            new_connection_tls_insecure: false,
+            new_connection_tls_require_client_cert: false,
+            tls_server_name_input,
+            tls_ca_input,
+            tls_cert_input,
+            tls_key_input,
+            multicast_groups_input,
+            multicast_interface_input,
+            multicast_ttl_input,
+            multicast_source_input,
+            new_connection_multicast_loopback: true,
+            ws_path_input,
+            ws_headers_input,
+            ws_subprotocols_input,
+            new_connection_ws_send_frame: WebSocketSendFrame::default(),
+            serial_path_input,
+            serial_baud_input,
+            new_connection_serial: SerialConfig::default(),
+            unix_path_input,
+            unix_permissions_input,
+            unix_local_path_input,
+            mqtt_client_id_input,
+            mqtt_username_input,
+            mqtt_password_input,
+            mqtt_keep_alive_input,
+            mqtt_subscriptions_input,
+            mqtt_publish_topic_input,
+            new_connection_mqtt: MqttConfig::default(),
+            relay_upstream_address_input,
+            relay_upstream_port_input,
+            relay_to_upstream_inputs,
+            relay_to_client_inputs,
+            new_connection_relay: RelayConfig::default(),
+            load_test_clients_input,
+            load_test_ramp_up_input,
+            load_test_send_rate_input,
+            load_test_payload_input,
+            load_test_duration_input,
+            new_connection_load_test: LoadTestConfig::default(),
+            proxy_address_input,
+            proxy_port_input,
+            proxy_username_input,
+            proxy_password_input,
+            new_connection_proxy: ProxyConfig::default(),
             // 初始化解码器选择对话框状态
             show_decoder_selection: false,
             decoder_selection_tab_id: None,
@@ -146,6 +401,7 @@ impl NetAssistantApp {
             connection_event_receiver: Some(connection_event_receiver),
             network_manager,
             client_write_senders,
+            mqtt_publishers: HashMap::new(),
             server_clients,
             show_context_menu: false,
             context_menu_connection: None,
@@ -201,6 +457,22 @@ impl NetAssistantApp {
             }
         }).detach();
 
+        // 队列深度不随事件变化，有数据积压时定时刷新界面
+        let weak_app = cx.entity().downgrade();
+        cx.spawn(async move |_, async_app: &mut gpui::AsyncApp| {
+            loop {
+                async_app.background_executor().timer(QUEUE_REFRESH_INTERVAL).await;
+                let Some(app) = weak_app.upgrade() else {
+                    break;
+                };
+                let _ = app.update(async_app, |app, cx| {
+                    if app.has_queued_data() {
+                        cx.notify();
+                    }
+                });
+            }
+        }).detach();
+
         // 主题事件处理已由GPUI窗口的observe_window_appearance处理，不再需要定期检查
 
         app
@@ -208,7 +480,8 @@ impl NetAssistantApp {
 
     pub fn toggle_connection(&mut self, tab_id: String, cx: &mut Context<Self>) {
         if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
-            if tab_state.is_connected {
+            // 等待重连时也视为连接中，点击即停止重连
+            if tab_state.is_connected || tab_state.is_reconnecting() {
                 // 断开连接
                 if tab_state.connection_config.is_client() {
                     self.disconnect_client(tab_id, cx);
@@ -341,6 +614,13 @@ impl NetAssistantApp {
         debug!("[关闭标签页] 开始关闭标签页: {}", tab_id);
 
         if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+            if tab_state.raw_capture_enabled {
+                let network_manager_arc = self.network_manager.clone();
+                let tab_id_clone = tab_id.clone();
+                tokio::spawn(async move {
+                    network_manager_arc.lock().await.set_raw_capture(&tab_id_clone, None);
+                });
+            }
             tab_state.disconnect();
         }
 
@@ -356,6 +636,7 @@ impl NetAssistantApp {
         if self.client_write_senders.remove(&tab_id).is_some() {
             debug!("[关闭标签页] 移除客户端连接发送器: {}", tab_id);
         }
+        self.mqtt_publishers.remove(&tab_id);
 
         // 清理服务端客户端连接
         if self.server_clients.remove(&tab_id).is_some() {
@@ -382,6 +663,7 @@ impl NetAssistantApp {
                             if let Err(e) = network_manager.disconnect_client(&tab_id_clone).await {
                 error!("断开客户端连接失败: {:?}", e);
             }
+            network_manager.set_raw_capture(&tab_id_clone, None);
             
             // 发送断开连接事件
             if let Some(sender) = sender {
@@ -408,6 +690,7 @@ impl NetAssistantApp {
             if let Err(e) = network_manager.stop_server(&tab_id_clone).await {
                 error!("停止服务器失败: {:?}", e);
             }
+            network_manager.set_raw_capture(&tab_id_clone, None);
             
             if let Some(sender) = sender {
                 let _ = sender.try_send(ConnectionEvent::Disconnected(tab_id_clone));
@@ -417,20 +700,26 @@ impl NetAssistantApp {
 
     /// 客户端连接到服务端
     pub fn connect_to_server(&mut self, tab_id: String) {
-        if let Some(tab_state) = self.connection_tabs.get(&tab_id) {
+        if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
             let client_config = if let ConnectionConfig::Client(client_config) = tab_state.connection_config.clone() {
                 client_config
             } else {
                 return;
             };
+
+            // 连接（含解析和超时）可能耗时较长，先显示连接中；失败原因通过 Error 事件返回
+            tab_state.connection_status = ConnectionStatus::Connecting;
+            tab_state.error_message = None;
+            tab_state.queue_metrics.reset_dropped();
             
             let network_manager_arc = self.network_manager.clone();
             let client_config_clone = client_config.clone();
             let connection_event_sender_clone = self.connection_event_sender.clone();
+            let queue_metrics = tab_state.queue_metrics.clone();
             
             tokio::spawn(async move {
                 let mut network_manager = network_manager_arc.lock().await;
-                if let Err(e) = network_manager.create_and_connect_client(&client_config_clone, connection_event_sender_clone).await {
+                if let Err(e) = network_manager.create_and_connect_client(&client_config_clone, connection_event_sender_clone, queue_metrics).await {
                     error!("客户端连接失败: {:?}", e);
                 }
             });
@@ -443,15 +732,17 @@ impl NetAssistantApp {
             // 立即更新UI状态为正在启动
             tab_state.is_connected = true;
             tab_state.connection_status = ConnectionStatus::Connecting;
+            tab_state.queue_metrics.reset_dropped();
             
             if let ConnectionConfig::Server(server_config) = &tab_state.connection_config {
                 let network_manager_arc = self.network_manager.clone();
                 let server_config_clone = server_config.clone();
                 let connection_event_sender_clone = self.connection_event_sender.clone();
+                let queue_metrics = tab_state.queue_metrics.clone();
                 
                 tokio::spawn(async move {
                     let mut network_manager = network_manager_arc.lock().await;
-                    if let Err(e) = network_manager.create_and_start_server(&server_config_clone, connection_event_sender_clone).await {
+                    if let Err(e) = network_manager.create_and_start_server(&server_config_clone, connection_event_sender_clone, queue_metrics).await {
                         error!("服务端启动失败: {:?}", e);
                     }
                 });
@@ -507,22 +798,40 @@ impl NetAssistantApp {
             }
             return;
         }
+
+        // 客户端已关闭写方向（发送过 FIN）后不能再发送
+        if is_client && self.connection_tabs.get(&tab_id).is_some_and(|tab| tab.local_write_closed) {
+            if let Some(sender) = sender {
+                let _ = sender.try_send(ConnectionEvent::Warning(
+                    tab_id_clone,
+                    "写方向已关闭（已发送 FIN），无法继续发送".to_string(),
+                ));
+            }
+            return;
+        }
         
         // 直接使用client_write_senders和server_clients来发送消息
         let bytes = content_clone.into_bytes();
+
+        if self.is_relay_tab(&tab_id) {
+            self.inject_relay(&tab_id, bytes);
+            return;
+        }
         
         if is_client {
             // 客户端模式：发送给服务器
             debug!("[send_message] 客户端模式，发送给服务器");
+
+            if self.mqtt_publishers.contains_key(&tab_id) {
+                self.publish_mqtt(&tab_id, bytes, message_type);
+                return;
+            }
             
             if let Some(write_sender) = self.client_write_senders.get(&tab_id) {
-                if write_sender.try_send(bytes.clone()).is_err() {
-                    error!("[send_message] 无法发送消息到服务器");
+                if let Err(e) = self.enqueue_write(&tab_id, write_sender, bytes.clone()) {
+                    error!("[send_message] 无法发送消息到服务器: {}", e);
                     if let Some(sender) = sender {
-                        let _ = sender.try_send(ConnectionEvent::Error(
-                            tab_id_clone,
-                            "发送消息失败".to_string(),
-                        ));
+                        let _ = sender.try_send(Self::enqueue_failure_event(tab_id_clone, e, "发送消息失败".to_string()));
                     }
                 } else {
                     debug!("[send_message] 发送成功");
@@ -555,13 +864,10 @@ impl NetAssistantApp {
                     // 定向发送给选中的客户端
                     debug!("[send_message] 服务端模式，定向发送给: {}", target_addr);
                     if let Some(write_sender) = clients.get(&target_addr) {
-                        if write_sender.try_send(bytes.clone()).is_err() {
-                            error!("[send_message] 发送给客户端 {} 失败", target_addr);
+                        if let Err(e) = self.enqueue_write(&tab_id, write_sender, bytes.clone()) {
+                            error!("[send_message] 发送给客户端 {} 失败: {}", target_addr, e);
                             if let Some(sender) = sender {
-                                let _ = sender.try_send(ConnectionEvent::Error(
-                                    tab_id_clone,
-                                    format!("发送给客户端 {} 失败", target_addr),
-                                ));
+                                let _ = sender.try_send(Self::enqueue_failure_event(tab_id_clone, e, format!("发送给客户端 {} 失败", target_addr)));
                             }
                         } else {
                             debug!("[send_message] 定向发送成功");
@@ -581,19 +887,19 @@ impl NetAssistantApp {
                         }
                     }
                 } else {
-                    // 广播给所有客户端（并行发送）
+                    // 广播给所有客户端（逐个放入各客户端的发送队列）
                     debug!("[send_message] 服务端模式，广播给所有客户端，共 {} 个", clients.len());
-                    let bytes_arc = std::sync::Arc::new(bytes.clone());
-                    
                     for (addr, write_sender) in clients.iter() {
-                        let sender_clone = write_sender.clone();
-                        let bytes_clone = bytes_arc.clone();
-                        let addr_str = addr.to_string();
-                        tokio::spawn(async move {
-                            if sender_clone.send((*bytes_clone).clone()).await.is_err() {
-                                error!("[send_message] 广播发送给客户端 {} 失败", addr_str);
+                        if let Err(e) = self.enqueue_write(&tab_id, write_sender, bytes.clone()) {
+                            error!("[send_message] 广播发送给客户端 {} 失败: {}", addr, e);
+                            if let Some(sender) = &sender {
+                                let _ = sender.try_send(Self::enqueue_failure_event(
+                                    tab_id.clone(),
+                                    e,
+                                    format!("发送给客户端 {} 失败", addr),
+                                ));
                             }
-                        });
+                        }
                     }
                     
                     debug!("[send_message] 广播发送成功");
@@ -614,6 +920,221 @@ impl NetAssistantApp {
         }
     }
 
+    /// MQTT客户端发布消息，主题、QoS和保留标志取自标签页的发布设置
+    fn publish_mqtt(&self, tab_id: &str, payload: Vec<u8>, message_type: MessageType) {
+        let report = |event: ConnectionEvent| {
+            if let Some(sender) = &self.connection_event_sender {
+                let _ = sender.try_send(event);
+            }
+        };
+        let Some(tab_state) = self.connection_tabs.get(tab_id) else {
+            return;
+        };
+        if let Err(e) = MqttConfig::validate_topic(&tab_state.mqtt_publish_topic) {
+            report(ConnectionEvent::Warning(tab_id.to_string(), e));
+            return;
+        }
+
+        let mqtt = MqttMessageInfo {
+            topic: tab_state.mqtt_publish_topic.clone(),
+            qos: tab_state.mqtt_publish_qos.level(),
+            retain: tab_state.mqtt_publish_retain,
+        };
+        let publish = MqttPublish {
+            topic: tab_state.mqtt_publish_topic.clone(),
+            qos: tab_state.mqtt_publish_qos,
+            retain: tab_state.mqtt_publish_retain,
+            payload: payload.clone(),
+        };
+        let Some(publisher) = self.mqtt_publishers.get(tab_id) else {
+            error!("[publish_mqtt] MQTT发布通道不可用");
+            report(ConnectionEvent::Error(tab_id.to_string(), "发布消息失败".to_string()));
+            return;
+        };
+        match self.enqueue_write(tab_id, publisher, publish) {
+            Ok(()) => {
+                debug!("[publish_mqtt] 发布到 {}", mqtt.topic);
+                let message = Message::new(MessageDirection::Sent, payload, message_type).with_mqtt(mqtt);
+                report(ConnectionEvent::MessageReceived(tab_id.to_string(), message));
+            }
+            Err(e) => report(Self::enqueue_failure_event(tab_id.to_string(), e, "发布消息失败".to_string())),
+        }
+    }
+
+    /// 把数据放入发送队列，因队列已满丢弃或拒绝的消息计入标签页的队列统计
+    fn enqueue_write<T: QueueItem>(&self, tab_id: &str, write_sender: &WriteSender<T>, data: T) -> Result<(), EnqueueError> {
+        let metrics = self.connection_tabs.get(tab_id).map(|tab| &tab.queue_metrics);
+        match write_sender.enqueue(data) {
+            Ok(dropped) => {
+                if dropped > 0 {
+                    if let Some(metrics) = metrics {
+                        metrics.add_sent_dropped(dropped);
+                    }
+                }
+                Ok(())
+            }
+            Err(e) => {
+                if let Some(metrics) = metrics {
+                    match e {
+                        EnqueueError::Full => metrics.add_sent_dropped(1),
+                        EnqueueError::Blocked => metrics.add_sent_rejected(1),
+                        EnqueueError::Closed => {}
+                    }
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// 发送队列拒绝数据时上报的事件：队列已满只是警告，连接已关闭则按发送失败处理
+    fn enqueue_failure_event(tab_id: String, error: EnqueueError, context: String) -> ConnectionEvent {
+        match error {
+            EnqueueError::Full | EnqueueError::Blocked => ConnectionEvent::Warning(tab_id, format!("{}：{}", context, error)),
+            EnqueueError::Closed => ConnectionEvent::Error(tab_id, context),
+        }
+    }
+
+    /// 标签页发送队列中的消息条数和字节数（服务端为所有客户端之和，MQTT为发布队列）
+    pub fn send_queue_depth(&self, tab_id: &str) -> (usize, usize) {
+        let clients = self.server_clients.get(tab_id).into_iter().flat_map(|clients| clients.values());
+        let (depth, bytes) = self.client_write_senders
+            .get(tab_id)
+            .into_iter()
+            .chain(clients)
+            .fold((0, 0), |(depth, bytes), write_sender| {
+                (depth + write_sender.len(), bytes + write_sender.pending_bytes())
+            });
+        match self.mqtt_publishers.get(tab_id) {
+            Some(publisher) => (depth + publisher.len(), bytes + publisher.pending_bytes()),
+            None => (depth, bytes),
+        }
+    }
+
+    /// 是否有标签页的收发队列中还有数据（界面据此定时刷新队列深度）
+    fn has_queued_data(&self) -> bool {
+        self.client_write_senders.values().any(|write_sender| !write_sender.is_empty())
+            || self.mqtt_publishers.values().any(|publisher| !publisher.is_empty())
+            || self
+                .server_clients
+                .values()
+                .flat_map(|clients| clients.values())
+                .any(|write_sender| !write_sender.is_empty())
+            || self
+                .connection_tabs
+                .values()
+                .any(|tab| tab.queue_metrics.received_depth() > 0)
+    }
+
+    /// 阻塞策略下标签页的发送队列已满（周期发送据此跳过本次发送，并计入拒绝次数）
+    fn send_queue_blocked(&self, tab_id: &str) -> bool {
+        let blocked = |write_sender: &WriteSender| {
+            write_sender.policy() == QueuePolicy::Block && write_sender.is_full()
+        };
+        let is_blocked = self.client_write_senders.get(tab_id).is_some_and(blocked)
+            || self
+                .server_clients
+                .get(tab_id)
+                .is_some_and(|clients| clients.values().any(blocked))
+            || self
+                .mqtt_publishers
+                .get(tab_id)
+                .is_some_and(|publisher| publisher.policy() == QueuePolicy::Block && publisher.is_full());
+        if is_blocked {
+            if let Some(tab_state) = self.connection_tabs.get(tab_id) {
+                tab_state.queue_metrics.add_sent_rejected(1);
+            }
+        }
+        is_blocked
+    }
+
+    /// 标签页是否为中继
+    fn is_relay_tab(&self, tab_id: &str) -> bool {
+        self.connection_tabs
+            .get(tab_id)
+            .is_some_and(|tab| tab.connection_config.protocol() == ConnectionType::Relay)
+    }
+
+    /// 中继标签页发送时注入数据：方向取自标签页设置，目标为选中的会话（未选中时为全部会话）
+    ///
+    /// 注入的数据由中继记录到消息列表，这里不再生成发送消息。
+    fn inject_relay(&self, tab_id: &str, payload: Vec<u8>) {
+        let Some(tab_state) = self.connection_tabs.get(tab_id) else {
+            return;
+        };
+        let direction = tab_state.relay_inject_direction;
+        let target = tab_state.selected_client;
+        self.run_relay_control(tab_id.to_string(), "注入数据", move |mgr, tab_id| {
+            mgr.relay_inject(tab_id, target, direction, payload).map(|_| ())
+        });
+    }
+
+    /// 在网络管理器上执行中继操作，失败时以警告提示
+    fn run_relay_control(
+        &self,
+        tab_id: String,
+        action_name: &'static str,
+        action: impl FnOnce(&crate::network::connection::manager::NetworkConnectionManager, &str) -> Result<(), String> + Send + 'static,
+    ) {
+        let manager = self.network_manager.clone();
+        let event_sender = self.connection_event_sender.clone();
+
+        tokio::spawn(async move {
+            let mgr = manager.lock().await;
+            if let Err(e) = action(&mgr, &tab_id) {
+                error!("[中继] {}失败: {}", action_name, e);
+                if let Some(sender) = &event_sender {
+                    let _ = sender.try_send(ConnectionEvent::Warning(
+                        tab_id,
+                        format!("{}失败: {}", action_name, e),
+                    ));
+                }
+            }
+        });
+    }
+
+    /// 暂停或恢复中继转发（恢复时放行全部暂存数据）
+    pub fn set_relay_paused(&mut self, tab_id: String, paused: bool, cx: &mut Context<Self>) {
+        self.run_relay_control(tab_id, if paused { "暂停转发" } else { "恢复转发" }, move |mgr, tab_id| {
+            mgr.relay_set_paused(tab_id, paused)
+        });
+        cx.notify();
+    }
+
+    /// 放行中继暂存的一条（None 为全部）数据
+    pub fn release_relay_held(&mut self, tab_id: String, id: Option<u64>, cx: &mut Context<Self>) {
+        self.run_relay_control(tab_id, "放行数据", move |mgr, tab_id| mgr.relay_release_held(tab_id, id));
+        cx.notify();
+    }
+
+    /// 丢弃中继暂存的一条（None 为全部）数据
+    pub fn drop_relay_held(&mut self, tab_id: String, id: Option<u64>, cx: &mut Context<Self>) {
+        self.run_relay_control(tab_id, "丢弃数据", move |mgr, tab_id| mgr.relay_drop_held(tab_id, id));
+        cx.notify();
+    }
+
+    /// 编辑暂存的数据：丢弃原数据，把内容载入发送框，并将注入方向和目标设为该数据所属的会话
+    pub fn edit_relay_held(&mut self, tab_id: String, chunk: RelayHeldChunk, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(data) = chunk.data else {
+            return;
+        };
+        let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) else {
+            return;
+        };
+        let content = if tab_state.message_input_mode == "hex" {
+            data.iter().map(|b| format!("{:02X}", b)).collect::<Vec<String>>().join(" ")
+        } else {
+            String::from_utf8_lossy(&data).into_owned()
+        };
+        if let Some(message_input) = &tab_state.message_input {
+            message_input.update(cx, |input, cx| {
+                input.set_value(content, window, cx);
+            });
+        }
+        tab_state.relay_inject_direction = chunk.direction;
+        tab_state.selected_client = Some(chunk.client);
+        self.drop_relay_held(tab_id, Some(chunk.id), cx);
+    }
+
     pub fn send_message_bytes(&mut self, tab_id: String, bytes: Vec<u8>, hex_input: String) {
         debug!(
             "[send_message_bytes] 开始，tab_id: {}, bytes: {:?}, hex_input: '{}'",
@@ -661,20 +1182,38 @@ impl NetAssistantApp {
             }
             return;
         }
+
+        // 客户端已关闭写方向（发送过 FIN）后不能再发送
+        if is_client && self.connection_tabs.get(&tab_id).is_some_and(|tab| tab.local_write_closed) {
+            if let Some(sender) = sender {
+                let _ = sender.try_send(ConnectionEvent::Warning(
+                    tab_id_clone,
+                    "写方向已关闭（已发送 FIN），无法继续发送".to_string(),
+                ));
+            }
+            return;
+        }
         
+        if self.is_relay_tab(&tab_id) {
+            self.inject_relay(&tab_id, bytes);
+            return;
+        }
+
         // 直接使用client_write_senders和server_clients来发送消息
         if is_client {
             // 客户端模式：发送给服务器
             debug!("[send_message_bytes] 客户端模式，发送给服务器");
+
+            if self.mqtt_publishers.contains_key(&tab_id) {
+                self.publish_mqtt(&tab_id, bytes, message_type);
+                return;
+            }
             
             if let Some(write_sender) = self.client_write_senders.get(&tab_id) {
-                if write_sender.try_send(bytes.clone()).is_err() {
-                    error!("[send_message_bytes] 无法发送消息到服务器");
+                if let Err(e) = self.enqueue_write(&tab_id, write_sender, bytes.clone()) {
+                    error!("[send_message_bytes] 无法发送消息到服务器: {}", e);
                     if let Some(sender) = sender {
-                        let _ = sender.try_send(ConnectionEvent::Error(
-                            tab_id_clone,
-                            "发送消息失败".to_string(),
-                        ));
+                        let _ = sender.try_send(Self::enqueue_failure_event(tab_id_clone, e, "发送消息失败".to_string()));
                     }
                 } else {
                     debug!("[send_message_bytes] 发送成功");
@@ -707,13 +1246,10 @@ impl NetAssistantApp {
                     // 定向发送给选中的客户端
                     debug!("[send_message_bytes] 服务端模式，定向发送给: {}", target_addr);
                     if let Some(write_sender) = clients.get(&target_addr) {
-                        if write_sender.try_send(bytes.clone()).is_err() {
-                            error!("[send_message_bytes] 发送给客户端 {} 失败", target_addr);
+                        if let Err(e) = self.enqueue_write(&tab_id, write_sender, bytes.clone()) {
+                            error!("[send_message_bytes] 发送给客户端 {} 失败: {}", target_addr, e);
                             if let Some(sender) = sender {
-                                let _ = sender.try_send(ConnectionEvent::Error(
-                                    tab_id_clone,
-                                    format!("发送给客户端 {} 失败", target_addr),
-                                ));
+                                let _ = sender.try_send(Self::enqueue_failure_event(tab_id_clone, e, format!("发送给客户端 {} 失败", target_addr)));
                             }
                         } else {
                             debug!("[send_message_bytes] 定向发送成功");
@@ -733,19 +1269,19 @@ impl NetAssistantApp {
                         }
                     }
                 } else {
-                    // 广播给所有客户端（并行发送）
+                    // 广播给所有客户端（逐个放入各客户端的发送队列）
                     debug!("[send_message_bytes] 服务端模式，广播给所有客户端，共 {} 个", clients.len());
-                    let bytes_arc = std::sync::Arc::new(bytes.clone());
-                    
                     for (addr, write_sender) in clients.iter() {
-                        let sender_clone = write_sender.clone();
-                        let bytes_clone = bytes_arc.clone();
-                        let addr_str = addr.to_string();
-                        tokio::spawn(async move {
-                            if sender_clone.send((*bytes_clone).clone()).await.is_err() {
-                                error!("[send_message_bytes] 广播发送给客户端 {} 失败", addr_str);
+                        if let Err(e) = self.enqueue_write(&tab_id, write_sender, bytes.clone()) {
+                            error!("[send_message_bytes] 广播发送给客户端 {} 失败: {}", addr, e);
+                            if let Some(sender) = &sender {
+                                let _ = sender.try_send(Self::enqueue_failure_event(
+                                    tab_id.clone(),
+                                    e,
+                                    format!("发送给客户端 {} 失败", addr),
+                                ));
                             }
-                        });
+                        }
                     }
                     
                     debug!("[send_message_bytes] 广播发送成功");
@@ -841,13 +1377,10 @@ impl NetAssistantApp {
                     // 直接使用server_clients发送消息给指定客户端
                     if let Some(clients) = self.server_clients.get(&tab_id) {
                         if let Some(write_sender) = clients.get(&addr) {
-                            if write_sender.try_send(bytes.clone()).is_err() {
-                                error!("[send_message_to_client] 发送失败");
+                            if let Err(e) = self.enqueue_write(&tab_id, write_sender, bytes.clone()) {
+                                error!("[send_message_to_client] 发送失败: {}", e);
                                 if let Some(sender) = sender {
-                                    let _ = sender.try_send(ConnectionEvent::Error(
-                                        tab_id_clone,
-                                        "发送消息失败".to_string(),
-                                    ));
+                                    let _ = sender.try_send(Self::enqueue_failure_event(tab_id_clone, e, "发送消息失败".to_string()));
                                 }
                             } else {
                                 debug!("[send_message_to_client] 发送成功");
@@ -900,7 +1433,7 @@ impl NetAssistantApp {
 
     /// 向UDP服务端手动添加客户端地址
     pub fn add_client_to_server(&mut self, tab_id: String, addr_str: String, cx: &mut Context<Self>) {
-        let addr: std::net::SocketAddr = match addr_str.parse() {
+        let addr = match crate::network::resolve::parse_socket_addr(&addr_str) {
             Ok(a) => a,
             Err(_) => {
                 error!("[add_client_to_server] 无效的地址格式: {}", addr_str);
@@ -938,6 +1471,73 @@ impl NetAssistantApp {
         cx.notify();
     }
 
+    /// 关闭TCP客户端连接的写方向（发送 FIN），仍继续接收数据
+    pub fn shutdown_client_write(&mut self, tab_id: String, cx: &mut Context<Self>) {
+        let manager = self.network_manager.clone();
+        let event_sender = self.connection_event_sender.clone();
+
+        tokio::spawn(async move {
+            let mgr = manager.lock().await;
+            if let Err(e) = mgr.shutdown_client_write(&tab_id) {
+                error!("[shutdown_client_write] 关闭写方向失败: {}", e);
+                if let Some(sender) = &event_sender {
+                    let _ = sender.try_send(ConnectionEvent::Warning(
+                        tab_id,
+                        format!("关闭写方向失败: {}", e),
+                    ));
+                }
+            }
+        });
+
+        cx.notify();
+    }
+
+    /// 设置串口客户端的 DTR 或 RTS 电平，设置成功后由 SerialLines 事件更新界面
+    pub fn set_serial_line(&mut self, tab_id: String, line: SerialLine, level: bool, cx: &mut Context<Self>) {
+        let manager = self.network_manager.clone();
+        let event_sender = self.connection_event_sender.clone();
+
+        tokio::spawn(async move {
+            let mgr = manager.lock().await;
+            if let Err(e) = mgr.set_serial_line(&tab_id, line, level) {
+                error!("[set_serial_line] 设置 {} 失败: {}", line, e);
+                if let Some(sender) = &event_sender {
+                    let _ = sender.try_send(ConnectionEvent::Warning(
+                        tab_id,
+                        format!("设置 {} 失败: {}", line, e),
+                    ));
+                }
+            }
+        });
+
+        cx.notify();
+    }
+
+    /// 关闭服务端的单个客户端连接（TCP可选 FIN 或 RST）
+    pub fn close_server_client(&mut self, tab_id: String, addr: SocketAddr, mode: ClientCloseMode, cx: &mut Context<Self>) {
+        let manager = self.network_manager.clone();
+        let event_sender = self.connection_event_sender.clone();
+
+        tokio::spawn(async move {
+            let mgr = manager.lock().await;
+            match mgr.close_server_client(&tab_id, addr, mode).await {
+                Ok(()) => {
+                    info!("[close_server_client] 已关闭客户端: {}", addr);
+                }
+                Err(e) => {
+                    error!("[close_server_client] 关闭客户端失败: {}", e);
+                    if let Some(sender) = &event_sender {
+                        let _ = sender.try_send(ConnectionEvent::Warning(
+                            tab_id,
+                            format!("关闭客户端失败: {}", e),
+                        ));
+                    }
+                }
+            }
+        });
+
+        cx.notify();
+    }
 
     /// 导出指定标签页的通信记录
     pub fn export_messages(&mut self, tab_id: String, _cx: &mut Context<Self>) {
@@ -1066,26 +1666,193 @@ impl NetAssistantApp {
                 } else {
                     path
                 };
+                Self::open_directory(dir);
+            }
+        }
+    }
 
-                #[cfg(target_os = "windows")]
-                {
-                    let _ = std::process::Command::new("explorer")
-                        .arg(dir)
-                        .spawn();
-                }
-                #[cfg(target_os = "macos")]
-                {
-                    let _ = std::process::Command::new("open")
-                        .arg(dir)
-                        .spawn();
-                }
-                #[cfg(target_os = "linux")]
-                {
-                    let _ = std::process::Command::new("xdg-open")
-                        .arg(dir)
-                        .spawn();
+    /// 使用系统文件管理器打开目录
+    fn open_directory(dir: &std::path::Path) {
+        #[cfg(target_os = "windows")]
+        {
+            let _ = std::process::Command::new("explorer")
+                .arg(dir)
+                .spawn();
+        }
+        #[cfg(target_os = "macos")]
+        {
+            let _ = std::process::Command::new("open")
+                .arg(dir)
+                .spawn();
+        }
+        #[cfg(target_os = "linux")]
+        {
+            let _ = std::process::Command::new("xdg-open")
+                .arg(dir)
+                .spawn();
+        }
+    }
+
+    /// 切换客户端自动重连开关（下次连接时生效）
+    pub fn toggle_auto_reconnect(&mut self, tab_id: String, cx: &mut Context<Self>) {
+        if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+            if let ConnectionConfig::Client(config) = &mut tab_state.connection_config {
+                config.auto_reconnect = !config.auto_reconnect;
+                debug!("[自动重连] {}: {}", tab_id, config.auto_reconnect);
+            }
+            // 保存到JSON配置
+            self.storage.update_connection(tab_state.connection_config.clone());
+            cx.notify();
+        }
+    }
+
+    /// 设置发送整形（下次连接时生效）
+    ///
+    /// 切换方式时使用 template 的默认参数并填入输入框；read_inputs 为 true 时从输入框读取参数，
+    /// 无法解析的参数沿用 template 中的值。
+    pub fn set_send_shaping(&mut self, tab_id: String, template: SendShaping, read_inputs: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) else {
+            return;
+        };
+        let shaping = if read_inputs {
+            let value = tab_state.send_shaping_value_input.read(cx).value().trim().parse::<u64>().ok();
+            let delay = tab_state.send_shaping_delay_input.read(cx).value().trim().parse::<u64>().ok();
+            match template {
+                SendShaping::Off => SendShaping::Off,
+                SendShaping::Split { parts, delay_ms } => SendShaping::Split {
+                    parts: value.map_or(parts, |value| value as usize).max(1),
+                    delay_ms: delay.unwrap_or(delay_ms),
+                },
+                SendShaping::Chunks { size, delay_ms } => SendShaping::Chunks {
+                    size: value.map_or(size, |value| value as usize).max(1),
+                    delay_ms: delay.unwrap_or(delay_ms),
+                },
+                SendShaping::Coalesce { window_ms } => SendShaping::Coalesce {
+                    window_ms: value.unwrap_or(window_ms),
+                },
+            }
+        } else {
+            template
+        };
+
+        let (value, delay) = ConnectionTabState::send_shaping_input_values(shaping);
+        for (input, value) in [(&tab_state.send_shaping_value_input, value), (&tab_state.send_shaping_delay_input, delay)] {
+            input.update(cx, |input, cx| {
+                input.set_value(value, window, cx);
+            });
+        }
+        tab_state.connection_config.set_send_shaping(shaping);
+        debug!("[发送整形] {}: {}", tab_id, shaping);
+        // 保存到JSON配置
+        self.storage.update_connection(tab_state.connection_config.clone());
+        cx.notify();
+    }
+
+    /// 设置往返时间测量的配对规则，立即生效并清空已有的统计
+    ///
+    /// read_inputs 为 true 时从输入框读取相同字节规则的偏移和长度，无法解析的参数沿用 template 中的值。
+    pub fn set_rtt_correlation(&mut self, tab_id: String, template: RttCorrelation, read_inputs: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) else {
+            return;
+        };
+        let correlation = match template {
+            RttCorrelation::SameBytes { offset, length } if read_inputs => {
+                let offset_input = tab_state.rtt_offset_input.read(cx).value().trim().parse::<usize>().ok();
+                let length_input = tab_state.rtt_length_input.read(cx).value().trim().parse::<usize>().ok();
+                RttCorrelation::SameBytes {
+                    offset: offset_input.unwrap_or(offset),
+                    length: length_input.unwrap_or(length).max(1),
                 }
             }
+            _ => template,
+        };
+
+        let (offset, length) = ConnectionTabState::rtt_input_values(correlation);
+        for (input, value) in [(&tab_state.rtt_offset_input, offset), (&tab_state.rtt_length_input, length)] {
+            input.update(cx, |input, cx| {
+                input.set_value(value, window, cx);
+            });
+        }
+        tab_state.rtt.set_correlation(correlation);
+        tab_state.connection_config.set_rtt_correlation(correlation);
+        debug!("[往返时间] {}: {}", tab_id, correlation);
+        // 保存到JSON配置
+        self.storage.update_connection(tab_state.connection_config.clone());
+        cx.notify();
+    }
+
+    /// 清空往返时间统计
+    pub fn reset_rtt(&mut self, tab_id: &str, cx: &mut Context<Self>) {
+        if let Some(tab_state) = self.connection_tabs.get_mut(tab_id) {
+            tab_state.rtt.reset();
+            cx.notify();
+        }
+    }
+
+    /// 设置收发队列的满时策略和容量（下次连接时生效）
+    ///
+    /// read_inputs 为 true 时从输入框读取容量，无法解析时沿用当前容量。
+    pub fn set_queue(&mut self, tab_id: String, policy: QueuePolicy, read_inputs: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) else {
+            return;
+        };
+        let current = tab_state.connection_config.queue();
+        let capacity = if read_inputs {
+            tab_state.queue_capacity_input.read(cx).value().trim().parse::<usize>().unwrap_or(current.capacity)
+        } else {
+            current.capacity
+        };
+        let queue = QueueConfig { capacity: capacity.max(1), policy };
+
+        tab_state.queue_capacity_input.update(cx, |input, cx| {
+            input.set_value(queue.capacity.to_string(), window, cx);
+        });
+        tab_state.connection_config.set_queue(queue);
+        debug!("[收发队列] {}: {}", tab_id, queue);
+        // 保存到JSON配置
+        self.storage.update_connection(tab_state.connection_config.clone());
+        cx.notify();
+    }
+
+    /// 切换原始数据录制开关
+    ///
+    /// 录制器交给网络连接管理器，由读写任务在解码前/编码后直接写入，
+    /// 连接过程中开关即时生效。
+    pub fn toggle_raw_capture(&mut self, tab_id: String, cx: &mut Context<Self>) {
+        let network_manager_arc = self.network_manager.clone();
+
+        if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+            let capture = if tab_state.raw_capture_enabled {
+                tab_state.raw_capture_enabled = false;
+                debug!("[原始录制] 已关闭: {}", tab_id);
+                None
+            } else {
+                let base_path = RawCapture::default_base_path(&tab_state.connection_config.address_label());
+                // 只有服务端才区分客户端
+                let per_client = tab_state.raw_capture_per_client && tab_state.connection_config.is_server();
+                let capture = RawCapture::start(base_path, tab_state.raw_capture_include_sent, per_client, tab_state.connection_config.queue().capacity());
+
+                tab_state.raw_capture_enabled = true;
+                tab_state.raw_capture_name = Some(capture.display_name());
+                tab_state.raw_capture_dir = Some(capture.directory().display().to_string());
+                debug!("[原始录制] 已开启: {} -> {}", tab_id, capture.display_name());
+                Some(capture)
+            };
+
+            tokio::spawn(async move {
+                let mut network_manager = network_manager_arc.lock().await;
+                network_manager.set_raw_capture(&tab_id, capture);
+            });
+            cx.notify();
+        }
+    }
+
+    /// 打开原始录制文件所在目录
+    pub fn open_raw_capture_directory(&self, tab_id: String) {
+        if let Some(tab_state) = self.connection_tabs.get(&tab_id) {
+            if let Some(dir) = &tab_state.raw_capture_dir {
+                Self::open_directory(std::path::Path::new(dir));
+            }
         }
     }
 
@@ -1198,6 +1965,13 @@ impl NetAssistantApp {
                     tab_state.is_connected = true;
                     tab_state.connection_status = ConnectionStatus::Connected;
                     tab_state.error_message = None;
+                    tab_state.peer_write_closed = false;
+                    tab_state.local_write_closed = false;
+                    tab_state.tls_info = None;
+                    tab_state.websocket_events.clear();
+                    tab_state.serial_lines = None;
+                    // 重新连接后旧请求不会再收到响应
+                    tab_state.rtt.clear_pending();
                     cx.notify();
                 }
             }
@@ -1205,16 +1979,93 @@ impl NetAssistantApp {
                 if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
                     tab_state.is_connected = false;
                     tab_state.connection_status = ConnectionStatus::Disconnected;
+                    tab_state.local_address = None;
+                    tab_state.peer_write_closed = false;
+                    tab_state.local_write_closed = false;
+                    tab_state.tls_info = None;
+                    tab_state.client_tls.clear();
+                    tab_state.client_labels.clear();
+                    tab_state.client_local_addresses.clear();
+                    tab_state.serial_lines = None;
+                    tab_state.relay_hold = RelayHoldState::default();
                     cx.notify();
                 }
                 self.client_write_senders.remove(&tab_id);
+                self.mqtt_publishers.remove(&tab_id);
                 self.server_clients.remove(&tab_id);
             }
+            ConnectionEvent::Reconnecting(tab_id, attempt, delay) => {
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    tab_state.is_connected = false;
+                    tab_state.connection_status = ConnectionStatus::Reconnecting {
+                        attempt,
+                        delay_ms: delay.as_millis() as u64,
+                    };
+                    tab_state.local_address = None;
+                    cx.notify();
+                }
+                // 旧连接的写入发送器已失效，重连成功后会收到新的
+                self.client_write_senders.remove(&tab_id);
+                self.mqtt_publishers.remove(&tab_id);
+            }
+            ConnectionEvent::Resolved(tab_id, addrs) => {
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    tab_state.resolved_addresses = addrs;
+                    cx.notify();
+                }
+            }
+            ConnectionEvent::PeerHalfClosed(tab_id) => {
+                info!("[连接事件] {}: 对端已关闭写方向", tab_id);
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    tab_state.peer_write_closed = true;
+                    cx.notify();
+                }
+            }
+            ConnectionEvent::WriteShutdown(tab_id) => {
+                info!("[连接事件] {}: 已关闭写方向", tab_id);
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    tab_state.local_write_closed = true;
+                    cx.notify();
+                }
+                self.client_write_senders.remove(&tab_id);
+            }
+            ConnectionEvent::TlsEstablished(tab_id, client_addr, tls_info) => {
+                info!("[连接事件] {}: TLS握手完成 {} {}", tab_id, tls_info.version, tls_info.cipher);
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    match client_addr {
+                        Some(addr) => {
+                            tab_state.client_tls.insert(addr, tls_info);
+                        }
+                        None => tab_state.tls_info = Some(tls_info),
+                    }
+                    cx.notify();
+                }
+            }
+            ConnectionEvent::WebSocket(tab_id, peer, ws_event) => {
+                info!("[连接事件] {}: WebSocket {:?} {}", tab_id, peer, ws_event);
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    tab_state.push_websocket_event(peer, ws_event);
+                    cx.notify();
+                }
+            }
+            ConnectionEvent::SerialLines(tab_id, lines) => {
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    tab_state.serial_lines = Some(lines);
+                    cx.notify();
+                }
+            }
+            ConnectionEvent::LocalAddress(tab_id, local_addr) => {
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    tab_state.local_address = Some(local_addr);
+                    cx.notify();
+                }
+            }
             ConnectionEvent::Listening(tab_id) => {
                 if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
                     tab_state.is_connected = true;
                     tab_state.connection_status = ConnectionStatus::Listening;
                     tab_state.error_message = None;
+                    tab_state.websocket_events.clear();
                     cx.notify();
                 }
             }
@@ -1223,12 +2074,28 @@ impl NetAssistantApp {
                     tab_state.is_connected = false;
                     tab_state.connection_status = ConnectionStatus::Error;
                     tab_state.error_message = Some(error);
+                    tab_state.local_address = None;
                     cx.notify();
                 }
                 // 清理连接信息，确保下次发送时直接失败
                 self.client_write_senders.remove(&tab_id);
+                self.mqtt_publishers.remove(&tab_id);
                 self.server_clients.remove(&tab_id);
             }
+            ConnectionEvent::Warning(tab_id, warning) => {
+                info!("[连接警告] {}: {}", tab_id, warning);
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    tab_state.error_message = Some(warning);
+                    cx.notify();
+                }
+            }
+            ConnectionEvent::TimeoutError(tab_id, addr, error) => {
+                error!("[连接超时] {} {:?}: {}", tab_id, addr, error);
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    tab_state.error_message = Some(error);
+                    cx.notify();
+                }
+            }
             ConnectionEvent::ClientWriteSenderReady(tab_id, write_sender) => {
                 debug!(
                     "[handle_connection_events] 客户端写入发送器就绪: {}",
@@ -1236,6 +2103,10 @@ impl NetAssistantApp {
                 );
                 self.client_write_senders.insert(tab_id, write_sender);
             }
+            ConnectionEvent::MqttPublisherReady(tab_id, publisher) => {
+                debug!("[handle_connection_events] MQTT发布通道就绪: {}", tab_id);
+                self.mqtt_publishers.insert(tab_id, publisher);
+            }
             ConnectionEvent::ServerClientConnected(tab_id, addr, write_sender) => {
                 debug!(
                     "[handle_connection_events] 服务端客户端连接: tab_id={}, addr={}",
@@ -1255,6 +2126,30 @@ impl NetAssistantApp {
                     }
                 }
             }
+            ConnectionEvent::RelayHold(tab_id, hold) => {
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    tab_state.relay_hold = hold;
+                    cx.notify();
+                }
+            }
+            ConnectionEvent::LoadTestReport(tab_id, report) => {
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    tab_state.load_test_report = Some(report);
+                    cx.notify();
+                }
+            }
+            ConnectionEvent::ServerClientLabel(tab_id, addr, label) => {
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    tab_state.client_labels.insert(addr, label);
+                    cx.notify();
+                }
+            }
+            ConnectionEvent::ServerClientLocalAddress(tab_id, addr, local_addr) => {
+                if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
+                    tab_state.client_local_addresses.insert(addr, local_addr);
+                    cx.notify();
+                }
+            }
             ConnectionEvent::ServerClientDisconnected(tab_id, addr) => {
                 debug!(
                     "[handle_connection_events] 服务端客户端断开: tab_id={}, addr={}",
@@ -1268,6 +2163,12 @@ impl NetAssistantApp {
                     tab_state
                         .client_connections
                         .retain(|&client_addr| client_addr != addr);
+                    tab_state.client_tls.remove(&addr);
+                    tab_state.client_labels.remove(&addr);
+                    tab_state.client_local_addresses.remove(&addr);
+                    if tab_state.selected_client == Some(addr) {
+                        tab_state.selected_client = None;
+                    }
                     cx.notify();
                 }
             }
@@ -1275,21 +2176,24 @@ impl NetAssistantApp {
                 if let Some(tab_state) = self.connection_tabs.get_mut(&tab_id) {
                     let mut message = message.clone();
                     let message_for_auto_reply = message.clone();
-                    // 设置消息类型（对接收和发送的消息都设置）
-                    message.set_message_type(if tab_state.message_input_mode == "text" {
+                    // 设置消息类型（对接收和发送的消息都设置），WebSocket消息按帧类型显示
+                    message.set_message_type(message.frame_type.unwrap_or(if tab_state.message_input_mode == "text" {
                         MessageType::Text
                     } else {
                         MessageType::Hex
-                    });
+                    }));
+                    // 往返时间测量：记录请求，或为配对到请求的响应标注往返时间
+                    message.rtt = tab_state.rtt.observe(&message);
                     // 使用 GPUI list 自动测量高度，无需手动计算宽度
                     tab_state.add_message(message);
                     // 消息接收是关键事件，立即触发UI更新
                     cx.notify();
 
                     // 只有当消息方向是 Received 且是真正从网络接收到的消息时才触发自动回复
-                    // 避免自动回复生成的消息又被当作新消息处理
+                    // 避免自动回复生成的消息又被当作新消息处理；中继转发的消息不触发自动回复
                     if tab_state.auto_reply_enabled
                         && message_for_auto_reply.direction == MessageDirection::Received
+                        && message_for_auto_reply.relay.is_none()
                     {
                         if let Some(auto_reply_input) = self.auto_reply_inputs.get(&tab_id)
                         {
@@ -1302,10 +2206,28 @@ impl NetAssistantApp {
                 }
             }
             ConnectionEvent::PeriodicSend(tab_id, content) => {
+                // 重连期间跳过本次发送，连接恢复后周期发送自动继续
+                if self.connection_tabs.get(&tab_id).is_some_and(|t| t.is_reconnecting()) {
+                    debug!("[周期发送] 等待重连，跳过本次发送: {}", tab_id);
+                    return;
+                }
+                // 阻塞策略下发送队列已满时跳过本次发送，避免积压越来越多
+                if self.send_queue_blocked(&tab_id) {
+                    debug!("[周期发送] 发送队列已满，跳过本次发送: {}", tab_id);
+                    return;
+                }
                 // 处理周期发送文本消息
                 self.send_message(tab_id, content);
             }
             ConnectionEvent::PeriodicSendBytes(tab_id, bytes, hex_input) => {
+                if self.connection_tabs.get(&tab_id).is_some_and(|t| t.is_reconnecting()) {
+                    debug!("[周期发送] 等待重连，跳过本次发送: {}", tab_id);
+                    return;
+                }
+                if self.send_queue_blocked(&tab_id) {
+                    debug!("[周期发送] 发送队列已满，跳过本次发送: {}", tab_id);
+                    return;
+                }
                 // 处理周期发送十六进制消息
                 self.send_message_bytes(tab_id, bytes, hex_input);
             }
@@ -1338,6 +2260,7 @@ impl Drop for NetAssistantApp {
             if self.client_write_senders.remove(&tab_id).is_some() {
                 debug!("[关闭标签页] 移除客户端连接发送器: {}", tab_id);
             }
+            self.mqtt_publishers.remove(&tab_id);
             
             // 清理服务端客户端连接
             if self.server_clients.remove(&tab_id).is_some() {
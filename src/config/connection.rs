@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
 
 /// 连接类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -7,6 +9,26 @@ use std::fmt;
 pub enum ConnectionType {
     Tcp,
     Udp,
+    WebSocket,
+    /// 串口（仅客户端）
+    Serial,
+    /// Unix域流式套接字
+    UnixStream,
+    /// Unix域数据报套接字
+    UnixDatagram,
+    /// MQTT（仅客户端）
+    Mqtt,
+    /// TCP/UDP中继（仅服务端）：转发到上游并记录两个方向的数据
+    Relay,
+    /// 压力测试（仅客户端）：多个并发客户端向同一目标发送并统计响应
+    LoadTest,
+}
+
+impl ConnectionType {
+    /// 是否为Unix域套接字（以套接字路径代替地址和端口）
+    pub fn is_unix(&self) -> bool {
+        matches!(self, ConnectionType::UnixStream | ConnectionType::UnixDatagram)
+    }
 }
 
 impl fmt::Display for ConnectionType {
@@ -14,6 +36,13 @@ impl fmt::Display for ConnectionType {
         match self {
             ConnectionType::Tcp => write!(f, "TCP"),
             ConnectionType::Udp => write!(f, "UDP"),
+            ConnectionType::WebSocket => write!(f, "WebSocket"),
+            ConnectionType::Serial => write!(f, "Serial"),
+            ConnectionType::UnixStream => write!(f, "Unix"),
+            ConnectionType::UnixDatagram => write!(f, "Unix DGRAM"),
+            ConnectionType::Mqtt => write!(f, "MQTT"),
+            ConnectionType::Relay => write!(f, "Relay"),
+            ConnectionType::LoadTest => write!(f, "Load Test"),
         }
     }
 }
@@ -27,6 +56,8 @@ pub enum ConnectionStatus {
     Connecting,
     Connected,
     Listening,
+    /// 等待自动重连（第几次尝试，距下次尝试的毫秒数）
+    Reconnecting { attempt: u32, delay_ms: u64 },
     Error,
 }
 
@@ -38,56 +69,1113 @@ impl fmt::Display for ConnectionStatus {
             ConnectionStatus::Connecting => write!(f, "连接中"),
             ConnectionStatus::Connected => write!(f, "已连接"),
             ConnectionStatus::Listening => write!(f, "监听中"),
+            ConnectionStatus::Reconnecting { attempt, delay_ms } => {
+                if delay_ms % 1000 == 0 {
+                    write!(f, "重连中 (第{}次, {}秒后)", attempt, delay_ms / 1000)
+                } else {
+                    write!(f, "重连中 (第{}次, {:.1}秒后)", attempt, *delay_ms as f64 / 1000.0)
+                }
+            }
             ConnectionStatus::Error => write!(f, "错误"),
         }
     }
 }
 
-/// 长度前缀解码器配置
+/// UDP接收缓冲区的最大值（UDP数据报长度上限）
+pub const MAX_UDP_RECV_BUFFER_SIZE: usize = 65535;
+
+/// 服务端达到最大连接数后的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaxConnectionsPolicy {
+    /// 拒绝新连接
+    #[default]
+    Reject,
+    /// 关闭最早建立的连接，接受新连接
+    CloseOldest,
+}
+
+impl fmt::Display for MaxConnectionsPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaxConnectionsPolicy::Reject => write!(f, "拒绝新连接"),
+            MaxConnectionsPolicy::CloseOldest => write!(f, "关闭最早连接"),
+        }
+    }
+}
+
+/// 长度前缀解码器配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LengthDelimitedConfig {
+    pub max_frame_length: usize, // 最大帧长度
+    pub length_field_offset: u8, // 长度字段偏移量
+    pub length_field_length: u8, // 长度字段长度
+    pub length_adjustment: i32,  // 长度调整值
+    pub length_field_is_including_length_field: bool, // 长度字段是否包含自身长度
+}
+
+impl Default for LengthDelimitedConfig {
+    fn default() -> Self {
+        Self {
+            max_frame_length: 8192,
+            length_field_offset: 0,
+            length_field_length: 4,
+            length_adjustment: 0,
+            length_field_is_including_length_field: false,
+        }
+    }
+}
+
+/// 解码器配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DecoderConfig {
+    Bytes,
+    LineBased,
+    LengthDelimited(LengthDelimitedConfig),
+    Json,
+}
+
+impl Default for DecoderConfig {
+    fn default() -> Self {
+        DecoderConfig::Bytes
+    }
+}
+
+impl fmt::Display for DecoderConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecoderConfig::Bytes => write!(f, "原始数据"),
+            DecoderConfig::LineBased => write!(f, "换行符"),
+            DecoderConfig::LengthDelimited(_) => write!(f, "长度前缀"),
+            DecoderConfig::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+/// TCP套接字选项（客户端连接及服务端接受的每个连接）
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TcpSocketOptions {
+    /// TCP_NODELAY，禁用 Nagle 算法
+    pub nodelay: bool,
+    /// SO_KEEPALIVE
+    pub keepalive: bool,
+    /// 保活探测前的空闲时间（秒，0 使用系统默认）
+    pub keepalive_idle: u64,
+    /// 保活探测间隔（秒，0 使用系统默认）
+    pub keepalive_interval: u64,
+    /// 保活探测次数（0 使用系统默认）
+    pub keepalive_count: u32,
+    /// SO_LINGER（秒），None 使用系统默认，Some(0) 关闭时直接发送 RST
+    pub linger: Option<u64>,
+    /// SO_SNDBUF（字节，0 使用系统默认）
+    pub send_buffer_size: usize,
+    /// SO_RCVBUF（字节，0 使用系统默认）
+    pub recv_buffer_size: usize,
+    /// IP TTL / IPv6 跳数限制（0 使用系统默认）
+    pub ttl: u32,
+    /// IP TOS / IPv6 流量类别，None 使用系统默认
+    pub tos: Option<u32>,
+}
+
+impl TcpSocketOptions {
+    /// 保活参数，未启用 SO_KEEPALIVE 时返回 None
+    ///
+    /// 返回 (空闲时间, 探测间隔, 探测次数)，未设置的项为 None。
+    pub fn keepalive_params(&self) -> Option<(Option<Duration>, Option<Duration>, Option<u32>)> {
+        self.keepalive.then(|| {
+            (
+                secs_to_timeout(self.keepalive_idle),
+                secs_to_timeout(self.keepalive_interval),
+                (self.keepalive_count > 0).then_some(self.keepalive_count),
+            )
+        })
+    }
+
+    /// SO_LINGER 超时
+    pub fn linger_duration(&self) -> Option<Duration> {
+        self.linger.map(Duration::from_secs)
+    }
+}
+
+/// 发送整形（TCP和Unix流式套接字），用于构造粘包和拆包
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SendShaping {
+    /// 每条消息一次写入
+    #[default]
+    Off,
+    /// 每条消息平均拆成 parts 次写入，两次写入之间等待 delay_ms
+    Split { parts: usize, delay_ms: u64 },
+    /// 每条消息按 size 字节分块写入，两次写入之间等待 delay_ms
+    Chunks { size: usize, delay_ms: u64 },
+    /// 收到消息后再等待 window_ms，把期间排队的消息合并为一次写入
+    Coalesce { window_ms: u64 },
+}
+
+impl SendShaping {
+    /// 一条长度为 len 的已编码消息的各次写入范围
+    pub fn segments(&self, len: usize) -> Vec<std::ops::Range<usize>> {
+        match *self {
+            SendShaping::Split { parts, .. } => {
+                let parts = parts.clamp(1, len.max(1));
+                let (base, remainder) = (len / parts, len % parts);
+                let mut start = 0;
+                (0..parts)
+                    .map(|index| {
+                        let end = start + base + usize::from(index < remainder);
+                        let range = start..end;
+                        start = end;
+                        range
+                    })
+                    .collect()
+            }
+            SendShaping::Chunks { size, .. } if len > 0 => {
+                let size = size.max(1);
+                (0..len).step_by(size).map(|start| start..(start + size).min(len)).collect()
+            }
+            _ => vec![0..len],
+        }
+    }
+
+    /// 是否可能把一条消息拆成多次写入（此时需关闭 Nagle 算法，否则拆开的写入会被合并发送）
+    pub fn splits_writes(&self) -> bool {
+        matches!(*self, SendShaping::Split { parts, .. } if parts > 1) || matches!(self, SendShaping::Chunks { .. })
+    }
+
+    /// 两次写入之间的等待时间
+    pub fn delay(&self) -> Option<Duration> {
+        match *self {
+            SendShaping::Split { delay_ms, .. } | SendShaping::Chunks { delay_ms, .. } if delay_ms > 0 => {
+                Some(Duration::from_millis(delay_ms))
+            }
+            _ => None,
+        }
+    }
+
+    /// 合并发送的等待时间
+    pub fn coalesce_window(&self) -> Option<Duration> {
+        match *self {
+            SendShaping::Coalesce { window_ms } => Some(Duration::from_millis(window_ms)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SendShaping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendShaping::Off => write!(f, "不整形"),
+            SendShaping::Split { parts, delay_ms } => write!(f, "拆成 {} 次写入，间隔 {}ms", parts, delay_ms),
+            SendShaping::Chunks { size, delay_ms } => write!(f, "每 {} 字节写入一次，间隔 {}ms", size, delay_ms),
+            SendShaping::Coalesce { window_ms } => write!(f, "合并 {}ms 内的消息", window_ms),
+        }
+    }
+}
+
+/// 往返时间测量的请求与响应配对规则（仅客户端）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum RttCorrelation {
+    /// 不测量
+    #[default]
+    Off,
+    /// 收到的帧与最早一条未应答的请求配对
+    NextFrame,
+    /// 从 offset 起的 length 个字节与请求相同位置的字节相同时配对（如序列号）
+    SameBytes { offset: usize, length: usize },
+}
+
+impl RttCorrelation {
+    pub fn is_enabled(&self) -> bool {
+        *self != RttCorrelation::Off
+    }
+
+    /// 消息中用于配对的字节，消息长度不足时返回 None
+    pub fn key<'a>(&self, data: &'a [u8]) -> Option<&'a [u8]> {
+        match *self {
+            RttCorrelation::Off => None,
+            RttCorrelation::NextFrame => Some(&[]),
+            RttCorrelation::SameBytes { offset, length } => data.get(offset..offset.checked_add(length)?),
+        }
+    }
+}
+
+impl fmt::Display for RttCorrelation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RttCorrelation::Off => write!(f, "不测量"),
+            RttCorrelation::NextFrame => write!(f, "与下一帧配对"),
+            RttCorrelation::SameBytes { offset, length } => {
+                write!(f, "第 {} 字节起 {} 字节相同时配对", offset, length)
+            }
+        }
+    }
+}
+
+/// 收发队列满时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueuePolicy {
+    /// 等待队列有空位：接收方向暂停读取套接字；发送方向暂存一条消息，队列有空位后自动放入，
+    /// 暂存位也被占用时拒绝新消息并暂停周期发送
+    #[default]
+    Block,
+    /// 丢弃队列中最早的消息
+    DropOldest,
+    /// 丢弃新到的消息
+    DropNewest,
+}
+
+impl fmt::Display for QueuePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueuePolicy::Block => write!(f, "阻塞"),
+            QueuePolicy::DropOldest => write!(f, "丢弃最旧"),
+            QueuePolicy::DropNewest => write!(f, "丢弃最新"),
+        }
+    }
+}
+
+/// 默认的队列容量（消息条数）
+pub const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+/// 收发队列设置，发送队列按连接（服务端按客户端）计，接收队列按标签页计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueueConfig {
+    /// 每个队列最多容纳的消息条数
+    pub capacity: usize,
+    pub policy: QueuePolicy,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_QUEUE_CAPACITY,
+            policy: QueuePolicy::default(),
+        }
+    }
+}
+
+impl QueueConfig {
+    /// 实际使用的容量，至少为 1
+    pub fn capacity(&self) -> usize {
+        self.capacity.max(1)
+    }
+}
+
+impl fmt::Display for QueueConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} 条，满时{}", self.capacity(), self.policy)
+    }
+}
+
+/// 客户端使用的上游代理类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyKind {
+    /// 直接连接
+    #[default]
+    None,
+    /// SOCKS5，TCP使用 CONNECT，UDP使用 UDP ASSOCIATE
+    Socks5,
+    /// HTTP CONNECT 隧道（仅TCP）
+    HttpConnect,
+}
+
+impl fmt::Display for ProxyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProxyKind::None => write!(f, "不使用"),
+            ProxyKind::Socks5 => write!(f, "SOCKS5"),
+            ProxyKind::HttpConnect => write!(f, "HTTP CONNECT"),
+        }
+    }
+}
+
+/// 客户端的上游代理配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    pub kind: ProxyKind,
+    /// 代理服务器主机（IP地址或主机名）
+    pub address: String,
+    pub port: u16,
+    /// 用户名为空表示不认证（SOCKS5 用户名/密码认证，HTTP 为 Basic 认证）
+    pub username: String,
+    pub password: String,
+    /// SOCKS5 把目标主机名交给代理解析，否则在本地解析后发送IP地址
+    pub remote_dns: bool,
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self {
+            kind: ProxyKind::None,
+            address: String::new(),
+            port: 1080,
+            username: String::new(),
+            password: String::new(),
+            remote_dns: true,
+        }
+    }
+}
+
+impl ProxyConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.kind != ProxyKind::None
+    }
+
+    /// 代理服务器地址，IPv6地址自动加方括号
+    pub fn endpoint(&self) -> String {
+        crate::network::resolve::format_host_port(self.address.trim(), self.port)
+    }
+
+    /// 检查代理设置是否可用于指定协议的客户端
+    pub fn validate(&self, protocol: ConnectionType) -> Result<(), String> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        if self.address.trim().is_empty() || self.port == 0 {
+            return Err("代理地址和端口不能为空".to_string());
+        }
+        match protocol {
+            ConnectionType::Tcp | ConnectionType::WebSocket => Ok(()),
+            ConnectionType::Udp if self.kind == ProxyKind::Socks5 => Ok(()),
+            _ => Err(format!("{} 客户端不支持 {} 代理", protocol, self.kind)),
+        }
+    }
+}
+
+impl fmt::Display for ProxyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind, self.endpoint())
+    }
+}
+
+/// TLS配置（仅TCP）
+///
+/// 证书与私钥均为 PEM 文件路径。
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    /// 是否启用TLS
+    pub enabled: bool,
+    /// 客户端：用于 SNI 和证书校验的服务器名称，留空使用服务器地址
+    pub server_name: String,
+    /// 信任的CA证书；客户端留空使用系统根证书，服务端用于校验客户端证书
+    pub ca_cert_path: String,
+    /// 证书链：服务端证书（留空时自动生成自签名证书）或客户端证书
+    pub cert_path: String,
+    /// 与证书对应的私钥
+    pub key_path: String,
+    /// 客户端：不校验服务器证书（仅用于调试）
+    pub insecure: bool,
+    /// 服务端：要求客户端提供由 ca_cert_path 签发的证书
+    pub require_client_cert: bool,
+}
+
+impl TlsConfig {
+    /// 客户端用于 SNI 和证书校验的名称
+    pub fn effective_server_name<'a>(&'a self, server_address: &'a str) -> &'a str {
+        match self.server_name.trim() {
+            "" => server_address.trim().trim_start_matches('[').trim_end_matches(']'),
+            name => name,
+        }
+    }
+}
+
+/// WebSocket 发送数据时使用的帧类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebSocketSendFrame {
+    /// 合法 UTF-8 数据用文本帧，否则用二进制帧
+    #[default]
+    Auto,
+    Text,
+    Binary,
+}
+
+impl fmt::Display for WebSocketSendFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebSocketSendFrame::Auto => write!(f, "自动"),
+            WebSocketSendFrame::Text => write!(f, "文本帧"),
+            WebSocketSendFrame::Binary => write!(f, "二进制帧"),
+        }
+    }
+}
+
+/// WebSocket配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebSocketConfig {
+    /// 客户端：请求路径（可含查询参数）
+    pub path: String,
+    /// 客户端：握手请求附加的头部
+    pub headers: Vec<(String, String)>,
+    /// 客户端：请求的子协议；服务端：支持的子协议，按客户端请求顺序选择第一个支持的
+    pub subprotocols: Vec<String>,
+    /// 发送数据使用的帧类型
+    pub send_frame: WebSocketSendFrame,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            path: "/".to_string(),
+            headers: Vec::new(),
+            subprotocols: Vec::new(),
+            send_frame: WebSocketSendFrame::default(),
+        }
+    }
+}
+
+impl WebSocketConfig {
+    /// 客户端连接的URL，启用TLS时为 wss://
+    pub fn url(&self, host: &str, port: u16, tls: bool) -> String {
+        let scheme = if tls { "wss" } else { "ws" };
+        let host = host.trim();
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let host = if host.contains(':') {
+            // URL中IPv6区域的 % 需编码为 %25（RFC 6874）
+            format!("[{}]", host.replacen('%', "%25", 1))
+        } else {
+            host.to_string()
+        };
+        let path = self.path.trim();
+        let separator = if path.starts_with('/') { "" } else { "/" };
+        format!("{}://{}:{}{}{}", scheme, host, port, separator, path)
+    }
+
+    /// 解析 "名称: 值" 形式的头部列表，多个头部以分号或换行分隔
+    pub fn parse_headers(text: &str) -> Result<Vec<(String, String)>, String> {
+        let mut headers = Vec::new();
+        for item in text.split(|c| c == ';' || c == '；' || c == '\n') {
+            let item = item.trim();
+            if item.is_empty() {
+                continue;
+            }
+            let (name, value) = item
+                .split_once(':')
+                .ok_or_else(|| format!("无效的头部 '{}'，应为 名称: 值", item))?;
+            let name = name.trim();
+            let valid_name = !name.is_empty()
+                && name.bytes().all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b));
+            if !valid_name {
+                return Err(format!("无效的头部名称 '{}'", name));
+            }
+            headers.push((name.to_string(), value.trim().to_string()));
+        }
+        Ok(headers)
+    }
+
+    /// 解析逗号或空白分隔的子协议列表
+    pub fn parse_subprotocols(text: &str) -> Vec<String> {
+        let mut subprotocols: Vec<String> = Vec::new();
+        for item in text.split(|c: char| c == ',' || c == '，' || c.is_whitespace()) {
+            if !item.is_empty() && !subprotocols.iter().any(|p| p == item) {
+                subprotocols.push(item.to_string());
+            }
+        }
+        subprotocols
+    }
+}
+
+/// 串口校验位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SerialParity {
+    #[default]
+    None,
+    Odd,
+    Even,
+}
+
+impl fmt::Display for SerialParity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerialParity::None => write!(f, "无"),
+            SerialParity::Odd => write!(f, "奇"),
+            SerialParity::Even => write!(f, "偶"),
+        }
+    }
+}
+
+/// 串口流控方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SerialFlowControl {
+    #[default]
+    None,
+    /// XON/XOFF
+    Software,
+    /// RTS/CTS
+    Hardware,
+}
+
+impl fmt::Display for SerialFlowControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerialFlowControl::None => write!(f, "无"),
+            SerialFlowControl::Software => write!(f, "XON/XOFF"),
+            SerialFlowControl::Hardware => write!(f, "RTS/CTS"),
+        }
+    }
+}
+
+/// 串口配置（仅串口客户端）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SerialConfig {
+    /// 设备路径，如 /dev/ttyUSB0、/dev/pts/3 或 COM3
+    pub path: String,
+    pub baud_rate: u32,
+    /// 数据位（5-8）
+    pub data_bits: u8,
+    pub parity: SerialParity,
+    /// 停止位（1 或 2）
+    pub stop_bits: u8,
+    pub flow_control: SerialFlowControl,
+    /// 打开串口后 DTR 的初始电平
+    pub dtr: bool,
+    /// 打开串口后 RTS 的初始电平（硬件流控时由驱动控制）
+    pub rts: bool,
+}
+
+impl Default for SerialConfig {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            baud_rate: 115200,
+            data_bits: 8,
+            parity: SerialParity::None,
+            stop_bits: 1,
+            flow_control: SerialFlowControl::None,
+            dtr: true,
+            rts: true,
+        }
+    }
+}
+
+impl SerialConfig {
+    /// 检查串口参数是否有效
+    pub fn validate(&self) -> Result<(), String> {
+        if self.path.trim().is_empty() {
+            return Err("串口设备路径不能为空".to_string());
+        }
+        if self.baud_rate == 0 {
+            return Err("波特率必须大于 0".to_string());
+        }
+        if !(5..=8).contains(&self.data_bits) {
+            return Err(format!("无效的数据位 {}，应为 5-8", self.data_bits));
+        }
+        if !matches!(self.stop_bits, 1 | 2) {
+            return Err(format!("无效的停止位 {}，应为 1 或 2", self.stop_bits));
+        }
+        Ok(())
+    }
+
+    /// 设备名称（路径的最后一段），用于标签和文件名
+    pub fn device_name(&self) -> &str {
+        let path = self.path.trim();
+        path.rsplit(|c| c == '/' || c == '\\').next().unwrap_or(path)
+    }
+
+    /// 形如 115200 8N1 的参数摘要
+    pub fn summary(&self) -> String {
+        let parity = match self.parity {
+            SerialParity::None => 'N',
+            SerialParity::Odd => 'O',
+            SerialParity::Even => 'E',
+        };
+        format!("{} {}{}{}", self.baud_rate, self.data_bits, parity, self.stop_bits)
+    }
+}
+
+/// Unix域套接字配置
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnixSocketConfig {
+    /// 客户端：连接的套接字路径；服务端：监听的套接字路径
+    pub path: String,
+    /// 服务端：套接字文件的权限（如 0o660），None 使用 umask 决定的默认权限
+    pub permissions: Option<u32>,
+    /// 数据报客户端：本地绑定路径，留空时自动生成临时路径（服务端需要它才能回复）
+    pub local_path: String,
+}
+
+impl UnixSocketConfig {
+    /// 解析八进制权限，如 660 或 0o660，留空返回 None
+    pub fn parse_permissions(text: &str) -> Result<Option<u32>, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let digits = text.trim_start_matches("0o");
+        match u32::from_str_radix(digits, 8) {
+            Ok(mode) if mode <= 0o7777 => Ok(Some(mode)),
+            _ => Err(format!("无效的权限 '{}'，应为八进制，如 660", text)),
+        }
+    }
+
+    /// 套接字文件名（路径的最后一段），用于标签和文件名
+    pub fn file_name(&self) -> &str {
+        let path = self.path.trim();
+        path.rsplit('/').find(|part| !part.is_empty()).unwrap_or(path)
+    }
+}
+
+/// MQTT协议版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MqttVersion {
+    #[default]
+    V311,
+    V5,
+}
+
+impl fmt::Display for MqttVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MqttVersion::V311 => write!(f, "3.1.1"),
+            MqttVersion::V5 => write!(f, "5.0"),
+        }
+    }
+}
+
+/// MQTT服务质量等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MqttQos {
+    #[default]
+    AtMostOnce,
+    AtLeastOnce,
+    ExactlyOnce,
+}
+
+impl MqttQos {
+    pub const ALL: [MqttQos; 3] = [MqttQos::AtMostOnce, MqttQos::AtLeastOnce, MqttQos::ExactlyOnce];
+
+    /// 等级数值（0、1、2）
+    pub fn level(&self) -> u8 {
+        match self {
+            MqttQos::AtMostOnce => 0,
+            MqttQos::AtLeastOnce => 1,
+            MqttQos::ExactlyOnce => 2,
+        }
+    }
+
+    pub fn from_level(level: u8) -> Option<Self> {
+        MqttQos::ALL.into_iter().find(|qos| qos.level() == level)
+    }
+}
+
+impl fmt::Display for MqttQos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "QoS {}", self.level())
+    }
+}
+
+/// MQTT订阅项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MqttSubscription {
+    /// 主题过滤器，可包含 + 和 # 通配符
+    pub filter: String,
+    pub qos: MqttQos,
+}
+
+/// MQTT客户端配置（服务器地址和端口使用连接的主机与端口）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    pub version: MqttVersion,
+    /// 客户端标识，留空时连接时自动生成
+    pub client_id: String,
+    pub username: String,
+    pub password: String,
+    /// 心跳间隔（秒，0 表示不启用）
+    pub keep_alive: u16,
+    /// 清除会话（MQTT 5 中为 Clean Start）
+    pub clean_session: bool,
+    /// 连接后订阅的主题
+    pub subscriptions: Vec<MqttSubscription>,
+    /// 发送框默认的发布主题
+    pub publish_topic: String,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            version: MqttVersion::V311,
+            client_id: String::new(),
+            username: String::new(),
+            password: String::new(),
+            keep_alive: 60,
+            clean_session: true,
+            subscriptions: Vec::new(),
+            publish_topic: String::new(),
+        }
+    }
+}
+
+impl MqttConfig {
+    /// 解析订阅列表，每项为 `主题过滤器` 或 `主题过滤器:QoS`，以逗号、分号或换行分隔
+    pub fn parse_subscriptions(text: &str) -> Result<Vec<MqttSubscription>, String> {
+        let mut subscriptions: Vec<MqttSubscription> = Vec::new();
+        for item in text.split(|c: char| matches!(c, ',' | '，' | ';' | '；' | '\n')) {
+            let item = item.trim();
+            if item.is_empty() {
+                continue;
+            }
+            // 冒号后为数字时视为 QoS，否则冒号属于主题的一部分
+            let (filter, qos) = match item.rsplit_once(':') {
+                Some((filter, level)) if !level.trim().is_empty() && level.trim().chars().all(|c| c.is_ascii_digit()) => {
+                    match level.trim().parse::<u8>().ok().and_then(MqttQos::from_level) {
+                        Some(qos) => (filter.trim(), qos),
+                        None => return Err(format!("订阅 '{}' 的 QoS 无效，应为 0、1 或 2", item)),
+                    }
+                }
+                _ => (item, MqttQos::AtMostOnce),
+            };
+            Self::validate_filter(filter)?;
+            subscriptions.retain(|subscription| subscription.filter != filter);
+            subscriptions.push(MqttSubscription { filter: filter.to_string(), qos });
+        }
+        Ok(subscriptions)
+    }
+
+    /// 将订阅列表格式化为可再次解析的文本
+    pub fn format_subscriptions(subscriptions: &[MqttSubscription]) -> String {
+        subscriptions
+            .iter()
+            .map(|subscription| format!("{}:{}", subscription.filter, subscription.qos.level()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// 检查主题过滤器：+ 必须独占一级，# 只能作为最后一级
+    pub fn validate_filter(filter: &str) -> Result<(), String> {
+        if filter.is_empty() {
+            return Err("主题过滤器不能为空".to_string());
+        }
+        let levels: Vec<&str> = filter.split('/').collect();
+        for (i, level) in levels.iter().enumerate() {
+            let valid = match *level {
+                "+" => true,
+                "#" => i == levels.len() - 1,
+                level => !level.contains('+') && !level.contains('#'),
+            };
+            if !valid {
+                return Err(format!("无效的主题过滤器 '{}'", filter));
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查发布主题：不能为空，也不能包含通配符
+    pub fn validate_topic(topic: &str) -> Result<(), String> {
+        if topic.is_empty() {
+            return Err("发布主题不能为空".to_string());
+        }
+        if topic.contains('+') || topic.contains('#') {
+            return Err(format!("发布主题 '{}' 不能包含通配符", topic));
+        }
+        Ok(())
+    }
+
+    /// 连接使用的客户端标识，未设置时生成随机标识
+    pub fn effective_client_id(&self) -> String {
+        match self.client_id.trim() {
+            "" => format!("netassistant-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]),
+            client_id => client_id.to_string(),
+        }
+    }
+}
+
+/// 中继转发的传输层协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelayTransport {
+    #[default]
+    Tcp,
+    /// 按客户端来源地址区分会话，每个会话使用独立的上游套接字
+    Udp,
+}
+
+impl fmt::Display for RelayTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelayTransport::Tcp => write!(f, "TCP"),
+            RelayTransport::Udp => write!(f, "UDP"),
+        }
+    }
+}
+
+/// 中继单个方向的网络损伤模拟，全部为 0 时不启用
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RelayImpairment {
+    /// 附加延迟（毫秒）
+    pub latency_ms: u64,
+    /// 延迟抖动（毫秒），实际延迟在 latency_ms ± jitter_ms 内均匀分布
+    pub jitter_ms: u64,
+    /// 丢包率（%，仅UDP）
+    pub loss_percent: f64,
+    /// 带宽上限（字节/秒，0 表示不限）
+    pub bandwidth: u64,
+    /// 损坏率（%）：每段数据或数据报按此概率翻转一个随机字节中的一位
+    pub corrupt_percent: f64,
+    /// 乱序率（%，仅UDP）：数据报按此概率与其后的数据报交换发送顺序
+    pub reorder_percent: f64,
+    /// 强制分段（字节，仅TCP，0 表示不分段）：每次写入不超过该长度，并关闭 Nagle 算法
+    pub segment_size: usize,
+}
+
+impl RelayImpairment {
+    pub fn is_enabled(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// 检查百分比参数是否在 0-100 之间
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [("丢包率", self.loss_percent), ("损坏率", self.corrupt_percent), ("乱序率", self.reorder_percent)] {
+            if !(0.0..=100.0).contains(&value) {
+                return Err(format!("{} {} 无效，应为 0-100", name, value));
+            }
+        }
+        Ok(())
+    }
+
+    /// 启用的损伤项摘要，如“延迟 100±20ms 丢包 5%”
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.latency_ms > 0 || self.jitter_ms > 0 {
+            parts.push(format!("延迟 {}±{}ms", self.latency_ms, self.jitter_ms));
+        }
+        if self.loss_percent > 0.0 {
+            parts.push(format!("丢包 {}%", self.loss_percent));
+        }
+        if self.bandwidth > 0 {
+            parts.push(format!("带宽 {}B/s", self.bandwidth));
+        }
+        if self.corrupt_percent > 0.0 {
+            parts.push(format!("损坏 {}%", self.corrupt_percent));
+        }
+        if self.reorder_percent > 0.0 {
+            parts.push(format!("乱序 {}%", self.reorder_percent));
+        }
+        if self.segment_size > 0 {
+            parts.push(format!("分段 {}B", self.segment_size));
+        }
+        parts.join(" ")
+    }
+}
+
+/// 中继配置（监听地址和端口使用服务端的监听设置）
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RelayConfig {
+    pub transport: RelayTransport,
+    /// 上游主机（IP地址或主机名）
+    pub upstream_address: String,
+    pub upstream_port: u16,
+    /// 发往上游方向的网络损伤
+    pub to_upstream: RelayImpairment,
+    /// 发往客户端方向的网络损伤
+    pub to_client: RelayImpairment,
+}
+
+impl RelayConfig {
+    /// 上游地址，IPv6地址自动加方括号
+    pub fn upstream(&self) -> String {
+        crate::network::resolve::format_host_port(self.upstream_address.trim(), self.upstream_port)
+    }
+}
+
+/// 压力测试客户端使用的传输层协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoadTestTransport {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+impl fmt::Display for LoadTestTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadTestTransport::Tcp => write!(f, "TCP"),
+            LoadTestTransport::Udp => write!(f, "UDP"),
+        }
+    }
+}
+
+/// 压力测试的最大并发客户端数
+pub const MAX_LOAD_TEST_CLIENTS: u32 = 10000;
+
+/// 压力测试配置（目标地址和端口使用客户端的服务器设置）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoadTestConfig {
+    pub transport: LoadTestTransport,
+    /// 并发客户端数
+    pub clients: u32,
+    /// 每秒新建的客户端数（0 表示同时建立全部连接）
+    pub ramp_up_rate: u32,
+    /// 每个客户端每秒发送的消息数（0 表示只建立连接不发送）
+    pub send_rate: f64,
+    /// 发送内容模板，{client} 替换为客户端序号，{seq} 替换为消息序号
+    pub payload: String,
+    /// 模板为十六进制，占位符替换为 4 字节大端序号
+    pub payload_hex: bool,
+    /// 测试时长（秒，0 表示直到手动停止）
+    pub duration: u64,
+}
+
+impl Default for LoadTestConfig {
+    fn default() -> Self {
+        Self {
+            transport: LoadTestTransport::Tcp,
+            clients: 10,
+            ramp_up_rate: 10,
+            send_rate: 1.0,
+            payload: "ping {client}-{seq}\\n".to_string(),
+            payload_hex: false,
+            duration: 0,
+        }
+    }
+}
+
+impl LoadTestConfig {
+    /// 检查客户端数、发送速率和内容模板
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_LOAD_TEST_CLIENTS).contains(&self.clients) {
+            return Err(format!("客户端数 {} 无效，应为 1-{}", self.clients, MAX_LOAD_TEST_CLIENTS));
+        }
+        if !self.send_rate.is_finite() || !(0.0..=1000.0).contains(&self.send_rate) {
+            return Err(format!("发送速率 {} 无效，应为 0-1000 条/秒", self.send_rate));
+        }
+        if self.send_rate > 0.0 && self.payload.is_empty() {
+            return Err("发送内容不能为空".to_string());
+        }
+        if self.payload_hex && !crate::utils::hex::validate_hex_input(&self.payload.replace("{client}", "").replace("{seq}", "")) {
+            return Err("发送内容不是有效的十六进制".to_string());
+        }
+        Ok(())
+    }
+
+    /// 按模板生成第 client 个客户端的第 seq 条消息
+    ///
+    /// 文本模板中的 \n、\r 和 \t 转义为对应的控制字符。
+    pub fn render_payload(&self, client: u32, seq: u32) -> Vec<u8> {
+        if self.payload_hex {
+            let hex = self
+                .payload
+                .replace("{client}", &format!("{:08x}", client))
+                .replace("{seq}", &format!("{:08x}", seq));
+            crate::utils::hex::hex_to_bytes(&hex)
+        } else {
+            self.payload
+                .replace("{client}", &client.to_string())
+                .replace("{seq}", &seq.to_string())
+                .replace("\\n", "\n")
+                .replace("\\r", "\r")
+                .replace("\\t", "\t")
+                .into_bytes()
+        }
+    }
+}
+
+impl fmt::Display for LoadTestConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} × {}", self.transport, self.clients)?;
+        if self.ramp_up_rate > 0 {
+            write!(f, "，每秒新建 {}", self.ramp_up_rate)?;
+        }
+        write!(f, "，每客户端 {} 条/秒", self.send_rate)?;
+        if self.duration > 0 {
+            write!(f, "，持续 {} 秒", self.duration)?;
+        }
+        Ok(())
+    }
+}
+
+/// UDP组播配置（仅UDP服务端）
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct LengthDelimitedConfig {
-    pub max_frame_length: usize, // 最大帧长度
-    pub length_field_offset: u8, // 长度字段偏移量
-    pub length_field_length: u8, // 长度字段长度
-    pub length_adjustment: i32,  // 长度调整值
-    pub length_field_is_including_length_field: bool, // 长度字段是否包含自身长度
+#[serde(default)]
+pub struct MulticastConfig {
+    /// 加入的组播组，为空表示不启用组播
+    pub groups: Vec<IpAddr>,
+    /// 加入组播所用的网络接口：IPv4 为接口地址，IPv6 为接口索引，留空由系统选择
+    pub interface: String,
+    /// 发出组播数据报的 TTL（IPv6 为跳数限制）
+    pub ttl: u32,
+    /// 是否接收本机发出的组播数据报
+    pub loopback: bool,
+    /// 源特定组播（SSM）的源地址，仅支持 IPv4
+    pub source: Option<Ipv4Addr>,
 }
 
-impl Default for LengthDelimitedConfig {
+impl Default for MulticastConfig {
     fn default() -> Self {
         Self {
-            max_frame_length: 8192,
-            length_field_offset: 0,
-            length_field_length: 4,
-            length_adjustment: 0,
-            length_field_is_including_length_field: false,
+            groups: Vec::new(),
+            interface: String::new(),
+            ttl: 1,
+            loopback: true,
+            source: None,
         }
     }
 }
 
-/// 解码器配置
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum DecoderConfig {
-    Bytes,
-    LineBased,
-    LengthDelimited(LengthDelimitedConfig),
-    Json,
-}
+impl MulticastConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.groups.is_empty()
+    }
 
-impl Default for DecoderConfig {
-    fn default() -> Self {
-        DecoderConfig::Bytes
+    /// 解析逗号或空白分隔的组播组地址列表，非组播地址返回错误
+    pub fn parse_groups(text: &str) -> Result<Vec<IpAddr>, String> {
+        let mut groups = Vec::new();
+        for item in text.split(|c: char| c == ',' || c == '，' || c.is_whitespace()) {
+            let item = item.trim();
+            if item.is_empty() {
+                continue;
+            }
+            let addr: IpAddr = item
+                .parse()
+                .map_err(|_| format!("无效的组播地址 '{}'", item))?;
+            if !addr.is_multicast() {
+                return Err(format!("{} 不是组播地址", addr));
+            }
+            if !groups.contains(&addr) {
+                groups.push(addr);
+            }
+        }
+        Ok(groups)
     }
-}
 
-impl fmt::Display for DecoderConfig {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            DecoderConfig::Bytes => write!(f, "原始数据"),
-            DecoderConfig::LineBased => write!(f, "换行符"),
-            DecoderConfig::LengthDelimited(_) => write!(f, "长度前缀"),
-            DecoderConfig::Json => write!(f, "JSON"),
+    /// IPv4 组播使用的接口地址，未指定时为 0.0.0.0（由系统选择）
+    pub fn interface_v4(&self) -> Result<Ipv4Addr, String> {
+        let interface = self.interface.trim();
+        if interface.is_empty() {
+            return Ok(Ipv4Addr::UNSPECIFIED);
         }
+        interface
+            .parse()
+            .map_err(|_| format!("无效的IPv4组播接口地址 '{}'", interface))
+    }
+
+    /// IPv6 组播使用的接口索引，未指定时为 0（由系统选择）
+    pub fn interface_v6(&self) -> Result<u32, String> {
+        let interface = self.interface.trim();
+        if interface.is_empty() {
+            return Ok(0);
+        }
+        interface
+            .parse()
+            .map_err(|_| format!("无效的IPv6组播接口索引 '{}'", interface))
     }
 }
 
@@ -100,12 +1188,159 @@ pub struct ClientConfig {
     pub protocol: ConnectionType,
     pub server_address: String,
     pub server_port: u16,
+    /// 本地绑定地址（留空表示由系统选择）
+    #[serde(default)]
+    pub local_address: String,
+    /// 本地绑定端口（0 表示由系统分配临时端口）
+    #[serde(default)]
+    pub local_port: u16,
+    /// 连接与写入超时（秒，0 表示不限）
     pub timeout: u64,
+    /// 读取空闲超时（秒，0 表示关闭）
+    #[serde(default)]
+    pub read_idle_timeout: u64,
+    /// 读取空闲超时后是否断开连接（否则仅提示）
+    #[serde(default = "default_true")]
+    pub read_idle_close: bool,
     pub auto_reconnect: bool,
+    /// 最大重连次数（0 表示不限次数）
+    #[serde(default = "default_reconnect_max_attempts")]
+    pub reconnect_max_attempts: u32,
+    /// 首次重连前的等待时间（毫秒），之后每次翻倍
+    #[serde(default = "default_reconnect_initial_delay_ms")]
+    pub reconnect_initial_delay_ms: u64,
+    /// 重连等待时间上限（毫秒）
+    #[serde(default = "default_reconnect_max_delay_ms")]
+    pub reconnect_max_delay_ms: u64,
+    /// UDP接收缓冲区大小（字节），超过该长度的数据报会被截断
+    #[serde(default = "default_udp_recv_buffer_size")]
+    pub udp_recv_buffer_size: usize,
+    /// TCP套接字选项
+    #[serde(default)]
+    pub socket_options: TcpSocketOptions,
+    /// 发送整形（TCP和Unix流式套接字，下次连接时生效）
+    #[serde(default)]
+    pub send_shaping: SendShaping,
+    /// 往返时间测量的配对规则
+    #[serde(default)]
+    pub rtt_correlation: RttCorrelation,
+    /// 上游代理（TCP、WebSocket和UDP）
+    #[serde(default)]
+    pub proxy: ProxyConfig,
+    /// 收发队列（下次连接时生效）
+    #[serde(default)]
+    pub queue: QueueConfig,
+    /// TLS设置（TCP和WebSocket）
+    #[serde(default)]
+    pub tls: TlsConfig,
+    /// WebSocket设置
+    #[serde(default)]
+    pub websocket: WebSocketConfig,
+    /// 串口设置
+    #[serde(default)]
+    pub serial: SerialConfig,
+    /// Unix域套接字设置
+    #[serde(default)]
+    pub unix: UnixSocketConfig,
+    /// MQTT设置
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+    /// 压力测试设置
+    #[serde(default)]
+    pub load_test: LoadTestConfig,
     #[serde(default)]
     pub decoder_config: DecoderConfig,
 }
 
+fn default_true() -> bool {
+    true
+}
+
+/// 将以秒为单位的超时配置转换为 Duration（0 表示不启用）
+fn secs_to_timeout(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+fn default_udp_recv_buffer_size() -> usize {
+    MAX_UDP_RECV_BUFFER_SIZE
+}
+
+fn default_reconnect_max_attempts() -> u32 {
+    10
+}
+
+fn default_reconnect_initial_delay_ms() -> u64 {
+    1000
+}
+
+fn default_reconnect_max_delay_ms() -> u64 {
+    30_000
+}
+
+impl ClientConfig {
+    /// 是否指定了本地绑定地址或端口
+    pub fn has_local_bind(&self) -> bool {
+        !self.local_address.trim().is_empty() || self.local_port != 0
+    }
+
+    /// 连接 remote 时使用的本地绑定地址，未指定时返回 None
+    ///
+    /// 只指定端口时按 remote 的地址族绑定到未指定地址；本地地址与 remote 地址族不一致时返回错误。
+    pub fn local_bind_addr(&self, remote: &SocketAddr) -> Result<Option<SocketAddr>, String> {
+        if !self.has_local_bind() {
+            return Ok(None);
+        }
+        let local_address = self.local_address.trim();
+        let local = if local_address.is_empty() {
+            if remote.is_ipv6() {
+                SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), self.local_port)
+            } else {
+                SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), self.local_port)
+            }
+        } else {
+            // IPv6本地地址可带区域，如 fe80::2%eth0
+            crate::network::resolve::parse_ip_literal(local_address, self.local_port)
+                .unwrap_or_else(|| Err(format!("无效的本地地址 '{}'", local_address)))?
+        };
+        if local.is_ipv6() != remote.is_ipv6() {
+            return Err(format!("本地地址 {} 与服务器地址 {} 的地址族不一致", local.ip(), remote));
+        }
+        Ok(Some(local))
+    }
+
+    /// 实际使用的UDP接收缓冲区大小（限制在 1..=65535）
+    pub fn udp_recv_buffer_len(&self) -> usize {
+        self.udp_recv_buffer_size.clamp(1, MAX_UDP_RECV_BUFFER_SIZE)
+    }
+
+    /// 建立连接的超时时间
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        secs_to_timeout(self.timeout)
+    }
+
+    /// 单次写入的超时时间
+    pub fn write_timeout(&self) -> Option<Duration> {
+        secs_to_timeout(self.timeout)
+    }
+
+    /// 读取空闲超时时间
+    pub fn read_idle_timeout(&self) -> Option<Duration> {
+        secs_to_timeout(self.read_idle_timeout)
+    }
+
+    /// 计算第 attempt 次重连前的等待时间（指数退避，attempt 从 1 开始）
+    pub fn reconnect_delay(&self, attempt: u32) -> Duration {
+        let shift = attempt.saturating_sub(1).min(16);
+        let delay_ms = self.reconnect_initial_delay_ms.saturating_mul(1u64 << shift);
+        Duration::from_millis(delay_ms.min(self.reconnect_max_delay_ms))
+    }
+
+    /// 第 attempt 次重连是否已超出最大重连次数
+    pub fn reconnect_attempts_exhausted(&self, attempt: u32) -> bool {
+        self.reconnect_max_attempts != 0 && attempt > self.reconnect_max_attempts
+    }
+}
+
 impl Default for ClientConfig {
     fn default() -> Self {
         Self {
@@ -114,8 +1349,27 @@ impl Default for ClientConfig {
             protocol: ConnectionType::Tcp,
             server_address: "127.0.0.1".to_string(),
             server_port: 8080,
+            local_address: String::new(),
+            local_port: 0,
             timeout: 30,
+            read_idle_timeout: 0,
+            read_idle_close: true,
             auto_reconnect: false,
+            reconnect_max_attempts: default_reconnect_max_attempts(),
+            reconnect_initial_delay_ms: default_reconnect_initial_delay_ms(),
+            reconnect_max_delay_ms: default_reconnect_max_delay_ms(),
+            udp_recv_buffer_size: default_udp_recv_buffer_size(),
+            socket_options: TcpSocketOptions::default(),
+            send_shaping: SendShaping::default(),
+            rtt_correlation: RttCorrelation::default(),
+            queue: QueueConfig::default(),
+            proxy: ProxyConfig::default(),
+            tls: TlsConfig::default(),
+            websocket: WebSocketConfig::default(),
+            serial: SerialConfig::default(),
+            unix: UnixSocketConfig::default(),
+            mqtt: MqttConfig::default(),
+            load_test: LoadTestConfig::default(),
             decoder_config: DecoderConfig::default(),
         }
     }
@@ -130,12 +1384,132 @@ pub struct ServerConfig {
     pub protocol: ConnectionType,
     pub listen_address: String,
     pub listen_port: u16,
+    /// 额外的监听地址（仅TCP和WebSocket），与 listen_address 使用同一端口
+    #[serde(default)]
+    pub extra_listen_addresses: Vec<String>,
+    /// 双栈监听：IPv6监听套接字关闭 IPV6_V6ONLY，同时接受IPv4客户端（仅TCP和WebSocket）
+    #[serde(default)]
+    pub dual_stack: bool,
+    /// 最大客户端连接数（0 表示不限）
     pub max_connections: usize,
+    /// 达到最大连接数后的处理策略
+    #[serde(default)]
+    pub max_connections_policy: MaxConnectionsPolicy,
+    /// 向客户端写入的超时，中继连接上游时同时作为连接超时（秒，0 表示不限）
     pub timeout: u64,
+    /// 客户端读取空闲超时（秒，0 表示关闭）
+    #[serde(default)]
+    pub read_idle_timeout: u64,
+    /// 读取空闲超时后是否断开该客户端（否则仅提示）
+    #[serde(default = "default_true")]
+    pub read_idle_close: bool,
+    /// UDP接收缓冲区大小（字节），超过该长度的数据报会被截断
+    #[serde(default = "default_udp_recv_buffer_size")]
+    pub udp_recv_buffer_size: usize,
+    /// UDP组播设置
+    #[serde(default)]
+    pub multicast: MulticastConfig,
+    /// 接受的每个TCP连接的套接字选项
+    #[serde(default)]
+    pub socket_options: TcpSocketOptions,
+    /// 向每个客户端发送时的整形（TCP和Unix流式套接字，下次启动时生效）
+    #[serde(default)]
+    pub send_shaping: SendShaping,
+    /// 收发队列（下次启动时生效）
+    #[serde(default)]
+    pub queue: QueueConfig,
+    /// TLS设置（TCP和WebSocket）
+    #[serde(default)]
+    pub tls: TlsConfig,
+    /// WebSocket设置
+    #[serde(default)]
+    pub websocket: WebSocketConfig,
+    /// Unix域套接字设置
+    #[serde(default)]
+    pub unix: UnixSocketConfig,
+    /// 中继设置
+    #[serde(default)]
+    pub relay: RelayConfig,
     #[serde(default)]
     pub decoder_config: DecoderConfig,
 }
 
+impl ServerConfig {
+    /// 全部监听地址：listen_address 在前，其后为额外的监听地址（已去除空白项和重复项）
+    pub fn listen_hosts(&self) -> Vec<String> {
+        let mut hosts: Vec<String> = Vec::new();
+        for host in std::iter::once(&self.listen_address).chain(&self.extra_listen_addresses) {
+            let host = host.trim();
+            if !host.is_empty() && !hosts.iter().any(|existing| existing == host) {
+                hosts.push(host.to_string());
+            }
+        }
+        hosts
+    }
+
+    /// 把逗号分隔的监听地址列表拆分为 (listen_address, extra_listen_addresses)
+    pub fn split_listen_hosts(text: &str) -> (String, Vec<String>) {
+        let mut hosts = text.split(',').map(str::trim).filter(|host| !host.is_empty()).map(str::to_string);
+        let first = hosts.next().unwrap_or_default();
+        (first, hosts.collect())
+    }
+
+    /// 检查多地址和双栈监听的设置
+    ///
+    /// 只有TCP和WebSocket服务端支持多地址和双栈；UDP服务端只有一个套接字，只能监听一个地址。
+    pub fn validate_listen(&self) -> Result<(), String> {
+        let hosts = self.listen_hosts();
+        let multi_address = matches!(self.protocol, ConnectionType::Tcp | ConnectionType::WebSocket);
+        if hosts.len() > 1 && !multi_address {
+            return Err(format!("{} 服务端只能监听一个地址", self.protocol));
+        }
+        if !self.dual_stack {
+            return Ok(());
+        }
+        if !multi_address {
+            return Err(format!("{} 服务端不支持双栈监听", self.protocol));
+        }
+        let ips: Vec<IpAddr> = hosts
+            .iter()
+            .filter_map(|host| crate::network::resolve::parse_ip_literal(host, 0)?.ok())
+            .map(|addr| addr.ip())
+            .collect();
+        if !ips.iter().any(IpAddr::is_ipv6) {
+            return Err("双栈监听需要至少一个IPv6地址（如 ::）".to_string());
+        }
+        // 双栈的 :: 已接受全部IPv4客户端，再监听IPv4地址的同一端口会冲突
+        if ips.iter().any(|ip| ip.is_ipv6() && ip.is_unspecified()) && ips.iter().any(IpAddr::is_ipv4) {
+            return Err("双栈监听 :: 时已接受IPv4客户端，不能再监听IPv4地址".to_string());
+        }
+        Ok(())
+    }
+
+    /// 实际使用的UDP接收缓冲区大小（限制在 1..=65535）
+    pub fn udp_recv_buffer_len(&self) -> usize {
+        self.udp_recv_buffer_size.clamp(1, MAX_UDP_RECV_BUFFER_SIZE)
+    }
+
+    /// 当前已有 current 个客户端时，是否已达到最大连接数
+    pub fn connection_limit_reached(&self, current: usize) -> bool {
+        self.max_connections != 0 && current >= self.max_connections
+    }
+
+    /// 向单个客户端写入的超时时间
+    pub fn write_timeout(&self) -> Option<Duration> {
+        secs_to_timeout(self.timeout)
+    }
+
+    /// 中继连接上游的超时时间
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        secs_to_timeout(self.timeout)
+    }
+
+    /// 客户端读取空闲超时时间
+    pub fn read_idle_timeout(&self) -> Option<Duration> {
+        secs_to_timeout(self.read_idle_timeout)
+    }
+}
+
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
@@ -144,8 +1518,22 @@ impl Default for ServerConfig {
             protocol: ConnectionType::Tcp,
             listen_address: "0.0.0.0".to_string(),
             listen_port: 8080,
+            extra_listen_addresses: Vec::new(),
+            dual_stack: false,
             max_connections: 100,
+            max_connections_policy: MaxConnectionsPolicy::default(),
             timeout: 30,
+            read_idle_timeout: 0,
+            read_idle_close: true,
+            udp_recv_buffer_size: default_udp_recv_buffer_size(),
+            multicast: MulticastConfig::default(),
+            socket_options: TcpSocketOptions::default(),
+            send_shaping: SendShaping::default(),
+            queue: QueueConfig::default(),
+            tls: TlsConfig::default(),
+            websocket: WebSocketConfig::default(),
+            unix: UnixSocketConfig::default(),
+            relay: RelayConfig::default(),
             decoder_config: DecoderConfig::default(),
         }
     }
@@ -179,6 +1567,50 @@ impl ConnectionConfig {
         }
     }
 
+    /// 发送整形设置
+    pub fn send_shaping(&self) -> SendShaping {
+        match self {
+            ConnectionConfig::Client(config) => config.send_shaping,
+            ConnectionConfig::Server(config) => config.send_shaping,
+        }
+    }
+
+    pub fn set_send_shaping(&mut self, send_shaping: SendShaping) {
+        match self {
+            ConnectionConfig::Client(config) => config.send_shaping = send_shaping,
+            ConnectionConfig::Server(config) => config.send_shaping = send_shaping,
+        }
+    }
+
+    /// 往返时间测量的配对规则，服务端不测量
+    pub fn rtt_correlation(&self) -> RttCorrelation {
+        match self {
+            ConnectionConfig::Client(config) => config.rtt_correlation,
+            ConnectionConfig::Server(_) => RttCorrelation::Off,
+        }
+    }
+
+    pub fn set_rtt_correlation(&mut self, rtt_correlation: RttCorrelation) {
+        if let ConnectionConfig::Client(config) = self {
+            config.rtt_correlation = rtt_correlation;
+        }
+    }
+
+    /// 收发队列设置
+    pub fn queue(&self) -> QueueConfig {
+        match self {
+            ConnectionConfig::Client(config) => config.queue,
+            ConnectionConfig::Server(config) => config.queue,
+        }
+    }
+
+    pub fn set_queue(&mut self, queue: QueueConfig) {
+        match self {
+            ConnectionConfig::Client(config) => config.queue = queue,
+            ConnectionConfig::Server(config) => config.queue = queue,
+        }
+    }
+
     pub fn is_client(&self) -> bool {
         matches!(self, ConnectionConfig::Client(_))
     }
@@ -198,6 +1630,15 @@ impl ConnectionConfig {
     /// 获取包含地址端口的标识字符串，格式如 TCP_127.0.0.1_8080
     pub fn address_label(&self) -> String {
         match self {
+            ConnectionConfig::Client(config) if config.protocol == ConnectionType::Serial => {
+                format!("{}_{}_{}", config.protocol, config.serial.device_name(), config.serial.baud_rate)
+            }
+            ConnectionConfig::Client(config) if config.protocol.is_unix() => {
+                format!("{}_{}", config.protocol.to_string().replace(' ', "_"), config.unix.file_name())
+            }
+            ConnectionConfig::Server(config) if config.protocol.is_unix() => {
+                format!("{}_{}", config.protocol.to_string().replace(' ', "_"), config.unix.file_name())
+            }
             ConnectionConfig::Client(config) => {
                 format!("{}_{}_{}", config.protocol, config.server_address, config.server_port)
             }
@@ -206,6 +1647,45 @@ impl ConnectionConfig {
             }
         }
     }
+
+    /// 界面上显示的地址：主机:端口，WebSocket客户端为URL，串口为设备和参数，Unix域套接字为路径
+    pub fn display_address(&self) -> String {
+        match self {
+            ConnectionConfig::Client(config) => match config.protocol {
+                ConnectionType::WebSocket => {
+                    config.websocket.url(&config.server_address, config.server_port, config.tls.enabled)
+                }
+                ConnectionType::Serial => format!("{} ({})", config.serial.path, config.serial.summary()),
+                ConnectionType::UnixStream | ConnectionType::UnixDatagram => config.unix.path.clone(),
+                ConnectionType::Mqtt => format!("mqtt://{}:{}", config.server_address, config.server_port),
+                ConnectionType::LoadTest => format!(
+                    "{} ({})",
+                    crate::network::resolve::format_host_port(&config.server_address, config.server_port),
+                    config.load_test
+                ),
+                ConnectionType::Tcp | ConnectionType::Udp | ConnectionType::Relay => {
+                    format!("{}:{}", config.server_address, config.server_port)
+                }
+            },
+            ConnectionConfig::Server(config) if config.protocol.is_unix() => config.unix.path.clone(),
+            ConnectionConfig::Server(config) if config.protocol == ConnectionType::Relay => {
+                format!("{} → {}", crate::network::resolve::format_host_port(&config.listen_address, config.listen_port), config.relay.upstream())
+            }
+            ConnectionConfig::Server(config) => {
+                let addresses = config
+                    .listen_hosts()
+                    .iter()
+                    .map(|host| crate::network::resolve::format_host_port(host, config.listen_port))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if config.dual_stack {
+                    format!("{} (双栈)", addresses)
+                } else {
+                    addresses
+                }
+            }
+        }
+    }
     
     /// 设置连接名称
     // pub fn set_name(&mut self, name: String) {
@@ -229,8 +1709,27 @@ impl ConnectionConfig {
             protocol,
             server_address,
             server_port,
+            local_address: String::new(),
+            local_port: 0,
             timeout: 30,
+            read_idle_timeout: 0,
+            read_idle_close: true,
             auto_reconnect: false,
+            reconnect_max_attempts: default_reconnect_max_attempts(),
+            reconnect_initial_delay_ms: default_reconnect_initial_delay_ms(),
+            reconnect_max_delay_ms: default_reconnect_max_delay_ms(),
+            udp_recv_buffer_size: default_udp_recv_buffer_size(),
+            socket_options: TcpSocketOptions::default(),
+            send_shaping: SendShaping::default(),
+            rtt_correlation: RttCorrelation::default(),
+            queue: QueueConfig::default(),
+            proxy: ProxyConfig::default(),
+            tls: TlsConfig::default(),
+            websocket: WebSocketConfig::default(),
+            serial: SerialConfig::default(),
+            unix: UnixSocketConfig::default(),
+            mqtt: MqttConfig::default(),
+            load_test: LoadTestConfig::default(),
             decoder_config: DecoderConfig::default(),
         })
     }
@@ -248,8 +1747,22 @@ impl ConnectionConfig {
             protocol,
             listen_address,
             listen_port,
+            extra_listen_addresses: Vec::new(),
+            dual_stack: false,
             max_connections: 100,
+            max_connections_policy: MaxConnectionsPolicy::default(),
             timeout: 30,
+            read_idle_timeout: 0,
+            read_idle_close: true,
+            udp_recv_buffer_size: default_udp_recv_buffer_size(),
+            multicast: MulticastConfig::default(),
+            socket_options: TcpSocketOptions::default(),
+            send_shaping: SendShaping::default(),
+            queue: QueueConfig::default(),
+            tls: TlsConfig::default(),
+            websocket: WebSocketConfig::default(),
+            unix: UnixSocketConfig::default(),
+            relay: RelayConfig::default(),
             decoder_config: DecoderConfig::default(),
         })
     }
@@ -260,7 +1773,8 @@ impl ConnectionConfig {
 
 #[cfg(test)]
 mod tests {
-    use super::{ClientConfig, ConnectionConfig, ConnectionType, ServerConfig};
+    use super::{ClientConfig, ConnectionConfig, ConnectionType, LoadTestConfig, MaxConnectionsPolicy, MqttConfig, MqttQos, MulticastConfig, ProxyConfig, ProxyKind, QueueConfig, QueuePolicy, RelayImpairment, SendShaping, SerialConfig, SerialParity, ServerConfig, TcpSocketOptions, TlsConfig, UnixSocketConfig, WebSocketConfig};
+    use std::time::Duration;
 
     #[test]
     /// 测试客户端配置的默认值
@@ -305,6 +1819,7 @@ mod tests {
         assert_eq!(default_config.listen_address, "0.0.0.0");
         assert_eq!(default_config.listen_port, 8080);
         assert_eq!(default_config.max_connections, 100);
+        assert_eq!(default_config.max_connections_policy, MaxConnectionsPolicy::Reject);
         assert_eq!(default_config.timeout, 30);
     }
 
@@ -355,4 +1870,375 @@ mod tests {
         assert_eq!(connection_config.name(), &server_config.name);
         assert_eq!(connection_config.protocol(), server_config.protocol);
     }
+
+    #[test]
+    /// 测试自动重连的指数退避与最大重连次数
+    fn test_client_reconnect_backoff() {
+        let config = ClientConfig::default();
+        assert_eq!(config.reconnect_delay(1), Duration::from_millis(1000));
+        assert_eq!(config.reconnect_delay(2), Duration::from_millis(2000));
+        assert_eq!(config.reconnect_delay(3), Duration::from_millis(4000));
+        // 超过上限后保持在上限
+        assert_eq!(config.reconnect_delay(10), Duration::from_millis(30_000));
+        assert_eq!(config.reconnect_delay(u32::MAX), Duration::from_millis(30_000));
+
+        assert!(!config.reconnect_attempts_exhausted(10));
+        assert!(config.reconnect_attempts_exhausted(11));
+
+        let unlimited = ClientConfig { reconnect_max_attempts: 0, ..ClientConfig::default() };
+        assert!(!unlimited.reconnect_attempts_exhausted(1000));
+    }
+
+    #[test]
+    /// 测试超时配置转换，0 表示不启用
+    fn test_timeout_config() {
+        let config = ClientConfig::default();
+        assert_eq!(config.connect_timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(config.write_timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(config.read_idle_timeout(), None);
+
+        let config = ClientConfig { timeout: 0, read_idle_timeout: 5, ..ClientConfig::default() };
+        assert_eq!(config.connect_timeout(), None);
+        assert_eq!(config.read_idle_timeout(), Some(Duration::from_secs(5)));
+
+        let server = ServerConfig { read_idle_timeout: 60, ..ServerConfig::default() };
+        assert_eq!(server.write_timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(server.read_idle_timeout(), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    /// 测试最大连接数判断，0 表示不限
+    fn test_server_connection_limit() {
+        let config = ServerConfig { max_connections: 2, ..ServerConfig::default() };
+        assert!(!config.connection_limit_reached(1));
+        assert!(config.connection_limit_reached(2));
+
+        let unlimited = ServerConfig { max_connections: 0, ..ServerConfig::default() };
+        assert!(!unlimited.connection_limit_reached(10_000));
+    }
+
+    #[test]
+    /// 测试UDP接收缓冲区大小的默认值与上下限
+    fn test_udp_recv_buffer_len() {
+        assert_eq!(ClientConfig::default().udp_recv_buffer_len(), 65535);
+        assert_eq!(ServerConfig::default().udp_recv_buffer_len(), 65535);
+
+        let small = ClientConfig { udp_recv_buffer_size: 0, ..ClientConfig::default() };
+        assert_eq!(small.udp_recv_buffer_len(), 1);
+
+        let large = ServerConfig { udp_recv_buffer_size: 1 << 20, ..ServerConfig::default() };
+        assert_eq!(large.udp_recv_buffer_len(), 65535);
+    }
+
+    #[test]
+    /// 测试组播地址列表解析
+    fn test_multicast_parse_groups() {
+        let groups = MulticastConfig::parse_groups("239.1.1.1, ff02::1 239.1.1.1").unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].to_string(), "239.1.1.1");
+        assert_eq!(groups[1].to_string(), "ff02::1");
+
+        assert!(MulticastConfig::parse_groups("").unwrap().is_empty());
+        assert!(MulticastConfig::parse_groups("192.168.1.1").is_err());
+        assert!(MulticastConfig::parse_groups("not-an-ip").is_err());
+
+        let config = MulticastConfig::default();
+        assert!(!config.is_enabled());
+        assert_eq!(config.ttl, 1);
+        assert!(config.loopback);
+        assert!(config.interface_v4().unwrap().is_unspecified());
+        assert_eq!(config.interface_v6().unwrap(), 0);
+    }
+
+    #[test]
+    /// 测试客户端本地绑定地址
+    fn test_client_local_bind_addr() {
+        let remote_v4: std::net::SocketAddr = "192.168.1.10:502".parse().unwrap();
+        let remote_v6: std::net::SocketAddr = "[::1]:502".parse().unwrap();
+
+        let config = ClientConfig::default();
+        assert!(!config.has_local_bind());
+        assert_eq!(config.local_bind_addr(&remote_v4).unwrap(), None);
+
+        let port_only = ClientConfig { local_port: 5000, ..ClientConfig::default() };
+        assert_eq!(port_only.local_bind_addr(&remote_v4).unwrap(), Some("0.0.0.0:5000".parse().unwrap()));
+        assert_eq!(port_only.local_bind_addr(&remote_v6).unwrap(), Some("[::]:5000".parse().unwrap()));
+
+        let with_address = ClientConfig { local_address: "192.168.1.2".to_string(), ..ClientConfig::default() };
+        assert_eq!(with_address.local_bind_addr(&remote_v4).unwrap(), Some("192.168.1.2:0".parse().unwrap()));
+        assert!(with_address.local_bind_addr(&remote_v6).is_err());
+
+        let invalid = ClientConfig { local_address: "eth0".to_string(), ..ClientConfig::default() };
+        assert!(invalid.local_bind_addr(&remote_v4).is_err());
+
+        // 带区域的IPv6链路本地地址
+        let scoped = ClientConfig { local_address: "fe80::2%3".to_string(), ..ClientConfig::default() };
+        let expected = std::net::SocketAddrV6::new("fe80::2".parse().unwrap(), 0, 0, 3);
+        assert_eq!(scoped.local_bind_addr(&remote_v6).unwrap(), Some(expected.into()));
+        let scoped_v4 = ClientConfig { local_address: "192.168.1.2%3".to_string(), ..ClientConfig::default() };
+        assert!(scoped_v4.local_bind_addr(&remote_v4).is_err());
+    }
+
+    #[test]
+    /// 测试TCP套接字选项的保活与 linger 参数
+    fn test_tcp_socket_options() {
+        let options = TcpSocketOptions::default();
+        assert!(!options.nodelay);
+        assert_eq!(options.keepalive_params(), None);
+        assert_eq!(options.linger_duration(), None);
+
+        let options = TcpSocketOptions {
+            keepalive: true,
+            keepalive_idle: 30,
+            keepalive_count: 3,
+            linger: Some(0),
+            ..TcpSocketOptions::default()
+        };
+        assert_eq!(
+            options.keepalive_params(),
+            Some((Some(Duration::from_secs(30)), None, Some(3)))
+        );
+        assert_eq!(options.linger_duration(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    /// 测试TLS服务器名称的默认取值
+    fn test_tls_server_name() {
+        let tls = TlsConfig::default();
+        assert!(!tls.enabled);
+        assert_eq!(tls.effective_server_name("example.com"), "example.com");
+        assert_eq!(tls.effective_server_name("[::1]"), "::1");
+
+        let tls = TlsConfig { server_name: "device.local".to_string(), ..TlsConfig::default() };
+        assert_eq!(tls.effective_server_name("192.168.1.10"), "device.local");
+    }
+
+    #[test]
+    /// 测试WebSocket地址拼接与自定义请求头解析
+    fn test_websocket_config() {
+        let mut websocket = WebSocketConfig::default();
+        assert_eq!(websocket.url("127.0.0.1", 8080, false), "ws://127.0.0.1:8080/");
+        websocket.path = "chat?room=1".to_string();
+        assert_eq!(websocket.url("::1", 443, true), "wss://[::1]:443/chat?room=1");
+        assert_eq!(websocket.url("[::1]", 443, true), "wss://[::1]:443/chat?room=1");
+        assert_eq!(websocket.url("fe80::1%eth0", 80, false), "ws://[fe80::1%25eth0]:80/chat?room=1");
+
+        let headers = WebSocketConfig::parse_headers("Authorization: Bearer a:b; X-Id: 1\n").unwrap();
+        assert_eq!(
+            headers,
+            vec![
+                ("Authorization".to_string(), "Bearer a:b".to_string()),
+                ("X-Id".to_string(), "1".to_string()),
+            ]
+        );
+        assert!(WebSocketConfig::parse_headers("").unwrap().is_empty());
+        assert!(WebSocketConfig::parse_headers("no-colon").is_err());
+        assert!(WebSocketConfig::parse_headers("Bad Name: 1").is_err());
+
+        assert_eq!(
+            WebSocketConfig::parse_subprotocols("mqtt, graphql-ws mqtt"),
+            vec!["mqtt".to_string(), "graphql-ws".to_string()]
+        );
+    }
+
+    #[test]
+    /// 测试串口参数校验与摘要
+    fn test_serial_config() {
+        let mut serial = SerialConfig::default();
+        assert!(serial.validate().is_err());
+        serial.path = "/dev/pts/3".to_string();
+        assert!(serial.validate().is_ok());
+        assert_eq!(serial.device_name(), "3");
+        assert_eq!(serial.summary(), "115200 8N1");
+
+        serial.path = "COM3".to_string();
+        serial.parity = SerialParity::Even;
+        serial.data_bits = 7;
+        serial.stop_bits = 2;
+        assert_eq!(serial.device_name(), "COM3");
+        assert_eq!(serial.summary(), "115200 7E2");
+
+        assert!(SerialConfig { data_bits: 9, ..serial.clone() }.validate().is_err());
+        assert!(SerialConfig { stop_bits: 3, ..serial.clone() }.validate().is_err());
+        assert!(SerialConfig { baud_rate: 0, ..serial.clone() }.validate().is_err());
+
+        let config = ConnectionConfig::Client(ClientConfig {
+            protocol: ConnectionType::Serial,
+            serial: SerialConfig { path: "/dev/ttyUSB0".to_string(), ..SerialConfig::default() },
+            ..ClientConfig::default()
+        });
+        assert_eq!(config.address_label(), "Serial_ttyUSB0_115200");
+    }
+
+    #[test]
+    /// 测试Unix域套接字权限解析与显示名称
+    fn test_unix_socket_config() {
+        assert_eq!(UnixSocketConfig::parse_permissions("").unwrap(), None);
+        assert_eq!(UnixSocketConfig::parse_permissions("660").unwrap(), Some(0o660));
+        assert_eq!(UnixSocketConfig::parse_permissions("0o600").unwrap(), Some(0o600));
+        assert!(UnixSocketConfig::parse_permissions("689").is_err());
+        assert!(UnixSocketConfig::parse_permissions("77777").is_err());
+
+        let unix = UnixSocketConfig { path: "/run/daemon/control.sock".to_string(), ..UnixSocketConfig::default() };
+        assert_eq!(unix.file_name(), "control.sock");
+
+        let config = ConnectionConfig::Server(ServerConfig {
+            protocol: ConnectionType::UnixDatagram,
+            unix,
+            ..ServerConfig::default()
+        });
+        assert_eq!(config.address_label(), "Unix_DGRAM_control.sock");
+        assert_eq!(config.display_address(), "/run/daemon/control.sock");
+    }
+
+    #[test]
+    /// 测试MQTT订阅列表解析与主题校验
+    fn test_mqtt_subscriptions() {
+        let subscriptions = MqttConfig::parse_subscriptions("sensors/+/temp:1, devices/#；status").unwrap();
+        assert_eq!(subscriptions.len(), 3);
+        assert_eq!(subscriptions[0].filter, "sensors/+/temp");
+        assert_eq!(subscriptions[0].qos, MqttQos::AtLeastOnce);
+        assert_eq!(subscriptions[2].qos, MqttQos::AtMostOnce);
+        assert_eq!(
+            MqttConfig::format_subscriptions(&subscriptions),
+            "sensors/+/temp:1, devices/#:0, status:0"
+        );
+
+        assert!(MqttConfig::parse_subscriptions("a/#/b").is_err());
+        assert!(MqttConfig::parse_subscriptions("a/b+").is_err());
+        assert!(MqttConfig::parse_subscriptions("a/b:3").is_err());
+        assert!(MqttConfig::parse_subscriptions("").unwrap().is_empty());
+
+        assert!(MqttConfig::validate_topic("devices/1/cmd").is_ok());
+        assert!(MqttConfig::validate_topic("devices/+/cmd").is_err());
+        assert!(MqttConfig { client_id: String::new(), ..MqttConfig::default() }
+            .effective_client_id()
+            .starts_with("netassistant-"));
+    }
+
+    #[test]
+    /// 测试多地址和双栈监听
+    fn test_server_listen_hosts() {
+        let (listen_address, extra) = ServerConfig::split_listen_hosts(" 192.168.1.2, ::,, ::1 ");
+        assert_eq!(listen_address, "192.168.1.2");
+        assert_eq!(extra, vec!["::".to_string(), "::1".to_string()]);
+
+        let mut config = ServerConfig {
+            listen_address,
+            extra_listen_addresses: vec!["::".to_string(), "192.168.1.2".to_string()],
+            ..ServerConfig::default()
+        };
+        assert_eq!(config.listen_hosts(), vec!["192.168.1.2".to_string(), "::".to_string()]);
+        assert!(config.validate_listen().is_ok());
+        assert_eq!(
+            ConnectionConfig::Server(config.clone()).display_address(),
+            "192.168.1.2:8080, [::]:8080"
+        );
+
+        config.dual_stack = true;
+        assert!(config.validate_listen().is_err());
+        config.listen_address = "::".to_string();
+        config.extra_listen_addresses.clear();
+        assert!(config.validate_listen().is_ok());
+
+        let udp = ServerConfig {
+            protocol: ConnectionType::Udp,
+            extra_listen_addresses: vec!["::".to_string()],
+            ..ServerConfig::default()
+        };
+        assert!(udp.validate_listen().is_err());
+    }
+
+    #[test]
+    /// 测试发送整形的分段与延迟
+    fn test_send_shaping_segments() {
+        assert_eq!(SendShaping::Off.segments(5), vec![0..5]);
+        assert_eq!(SendShaping::Split { parts: 3, delay_ms: 0 }.segments(10), vec![0..4, 4..7, 7..10]);
+        assert_eq!(SendShaping::Split { parts: 4, delay_ms: 0 }.segments(2), vec![0..1, 1..2]);
+        assert_eq!(SendShaping::Chunks { size: 2, delay_ms: 10 }.segments(5), vec![0..2, 2..4, 4..5]);
+        assert_eq!(SendShaping::Chunks { size: 2, delay_ms: 10 }.delay(), Some(Duration::from_millis(10)));
+        assert_eq!(SendShaping::Coalesce { window_ms: 100 }.segments(3), vec![0..3]);
+        assert!(SendShaping::Chunks { size: 2, delay_ms: 0 }.splits_writes());
+        assert!(!SendShaping::Split { parts: 1, delay_ms: 0 }.splits_writes());
+        assert!(!SendShaping::Coalesce { window_ms: 100 }.splits_writes());
+    }
+
+    #[test]
+    /// 测试中继损伤参数的校验与摘要
+    fn test_relay_impairment() {
+        let mut impairment = RelayImpairment::default();
+        assert!(!impairment.is_enabled());
+        assert_eq!(impairment.summary(), "");
+
+        impairment.latency_ms = 100;
+        impairment.jitter_ms = 20;
+        impairment.loss_percent = 5.0;
+        impairment.segment_size = 1;
+        assert!(impairment.is_enabled());
+        assert!(impairment.validate().is_ok());
+        assert_eq!(impairment.summary(), "延迟 100±20ms 丢包 5% 分段 1B");
+
+        impairment.reorder_percent = 120.0;
+        assert!(impairment.validate().is_err());
+    }
+
+    #[test]
+    /// 测试代理设置按协议校验
+    fn test_proxy_validate() {
+        let mut proxy = ProxyConfig::default();
+        assert!(!proxy.is_enabled());
+        assert!(proxy.validate(ConnectionType::Serial).is_ok());
+
+        proxy.kind = ProxyKind::HttpConnect;
+        assert!(proxy.validate(ConnectionType::Tcp).is_err());
+        proxy.address = "::1".to_string();
+        proxy.port = 8080;
+        assert_eq!(proxy.to_string(), "HTTP CONNECT [::1]:8080");
+        assert!(proxy.validate(ConnectionType::WebSocket).is_ok());
+        assert!(proxy.validate(ConnectionType::Udp).is_err());
+
+        proxy.kind = ProxyKind::Socks5;
+        assert!(proxy.validate(ConnectionType::Udp).is_ok());
+        assert!(proxy.validate(ConnectionType::Mqtt).is_err());
+    }
+
+    #[test]
+    /// 测试收发队列的容量下限与显示
+    fn test_queue_config() {
+        let mut queue = QueueConfig::default();
+        assert_eq!(queue.policy, QueuePolicy::Block);
+        assert_eq!(queue.to_string(), "1024 条，满时阻塞");
+
+        queue.capacity = 0;
+        queue.policy = QueuePolicy::DropOldest;
+        assert_eq!(queue.capacity(), 1);
+        assert_eq!(queue.to_string(), "1 条，满时丢弃最旧");
+
+        let mut config = ConnectionConfig::Server(ServerConfig::default());
+        config.set_queue(queue);
+        assert_eq!(config.queue(), queue);
+    }
+
+    #[test]
+    /// 测试压力测试发送模板的渲染与校验
+    fn test_load_test_payload() {
+        let mut load_test = LoadTestConfig::default();
+        assert!(load_test.validate().is_ok());
+        assert_eq!(load_test.render_payload(3, 7), b"ping 3-7\n".to_vec());
+
+        load_test.payload = "AA {client} {seq}".to_string();
+        load_test.payload_hex = true;
+        assert!(load_test.validate().is_ok());
+        assert_eq!(load_test.render_payload(1, 258), vec![0xAA, 0, 0, 0, 1, 0, 0, 1, 2]);
+
+        load_test.payload = "AZ".to_string();
+        assert!(load_test.validate().is_err());
+
+        load_test.payload_hex = false;
+        load_test.clients = 0;
+        assert!(load_test.validate().is_err());
+        load_test.clients = 10;
+        load_test.send_rate = f64::NAN;
+        assert!(load_test.validate().is_err());
+    }
 }
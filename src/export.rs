@@ -1,4 +1,4 @@
-use crate::message::{Message, MessageDirection};
+use crate::message::{Message, MessageDirection, MqttMessageInfo, RelayMessageInfo};
 use std::path::Path;
 
 /// 导出格式
@@ -37,7 +37,7 @@ pub fn format_as_txt(messages: &[Message]) -> String {
             MessageDirection::Received => "接收",
         };
 
-        let source_part = match &msg.source {
+        let source_part = match msg.source_label() {
             Some(src) => format!(" ({})", src),
             None => String::new(),
         };
@@ -76,6 +76,15 @@ pub fn format_as_json(messages: &[Message]) -> Result<String, serde_json::Error>
         #[serde(rename = "type")]
         msg_type: String,
         source: Option<String>,
+        /// MQTT消息的主题、QoS和保留标志
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mqtt: Option<MqttMessageInfo>,
+        /// 中继消息的转发方向和客户端 → 上游会话
+        #[serde(skip_serializing_if = "Option::is_none")]
+        relay: Option<RelayMessageInfo>,
+        /// 响应消息的往返时间（毫秒）
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rtt_ms: Option<f64>,
         content: String,
     }
 
@@ -92,6 +101,9 @@ pub fn format_as_json(messages: &[Message]) -> Result<String, serde_json::Error>
                 crate::message::MessageType::Hex => "hex".to_string(),
             },
             source: msg.source.clone(),
+            mqtt: msg.mqtt.clone(),
+            relay: msg.relay.clone(),
+            rtt_ms: msg.rtt.map(|rtt| rtt.as_secs_f64() * 1000.0),
             content: msg.get_content_by_type().to_string(),
         })
         .collect();
@@ -123,7 +135,7 @@ pub fn format_as_csv(messages: &[Message]) -> String {
             crate::message::MessageType::Hex => "十六进制",
         };
 
-        let source = msg.source.as_deref().unwrap_or("");
+        let source = msg.source_label().unwrap_or_default();
         let content = msg.get_content_by_type();
 
         output.push_str(&format!(
@@ -131,7 +143,7 @@ pub fn format_as_csv(messages: &[Message]) -> String {
             csv_escape(&msg.timestamp),
             csv_escape(direction),
             csv_escape(msg_type),
-            csv_escape(source),
+            csv_escape(&source),
             csv_escape(content),
         ));
     }
@@ -210,6 +222,20 @@ mod tests {
         assert!(result.contains("十六进制"));
     }
 
+    #[test]
+    fn test_format_mqtt_message() {
+        let mqtt = MqttMessageInfo { topic: "devices/1/state".to_string(), qos: 1, retain: true };
+        let messages = vec![
+            create_test_message(MessageDirection::Received, b"on", MessageType::Text).with_mqtt(mqtt),
+        ];
+
+        assert!(format_as_txt(&messages).contains("(devices/1/state QoS1 retain) on"));
+        assert!(format_as_csv(&messages).contains(",devices/1/state QoS1 retain,on"));
+        let json = format_as_json(&messages).unwrap();
+        assert!(json.contains("\"topic\": \"devices/1/state\""));
+        assert!(json.contains("\"retain\": true"));
+    }
+
     #[test]
     fn test_csv_escape() {
         assert_eq!(csv_escape("simple"), "simple");
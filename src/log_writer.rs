@@ -72,7 +72,7 @@ impl LogWriter {
                 MessageDirection::Received => "接收",
             };
 
-            let source_part = match &message.source {
+            let source_part = match message.source_label() {
                 Some(src) => format!(" ({})", src),
                 None => String::new(),
             };
@@ -18,6 +18,7 @@ mod export;
 mod log_writer;
 mod message;
 mod network;
+mod raw_capture;
 mod ui;
 mod utils;
 mod theme_manager;
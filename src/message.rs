@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
+use std::time::{Duration, Instant};
 
 /// 消息方向
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum MessageDirection {
     Sent,
@@ -36,6 +37,79 @@ impl fmt::Display for MessageType {
     }
 }
 
+/// MQTT消息的主题、服务质量等级和保留标志
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MqttMessageInfo {
+    pub topic: String,
+    pub qos: u8,
+    pub retain: bool,
+}
+
+impl fmt::Display for MqttMessageInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} QoS{}", self.topic, self.qos)?;
+        if self.retain {
+            write!(f, " retain")?;
+        }
+        Ok(())
+    }
+}
+
+/// 中继模式下数据的转发方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayDirection {
+    /// 客户端 → 上游
+    ToUpstream,
+    /// 上游 → 客户端
+    ToClient,
+}
+
+impl RelayDirection {
+    pub const ALL: [RelayDirection; 2] = [RelayDirection::ToUpstream, RelayDirection::ToClient];
+
+    /// 消息气泡的方向：客户端发出的数据显示在右侧（发送），上游的回应显示在左侧（接收）
+    pub fn message_direction(&self) -> MessageDirection {
+        match self {
+            RelayDirection::ToUpstream => MessageDirection::Sent,
+            RelayDirection::ToClient => MessageDirection::Received,
+        }
+    }
+}
+
+impl fmt::Display for RelayDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelayDirection::ToUpstream => write!(f, "→ 上游"),
+            RelayDirection::ToClient => write!(f, "← 客户端"),
+        }
+    }
+}
+
+/// 中继消息的转发方向和所属的客户端 → 上游会话
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelayMessageInfo {
+    pub direction: RelayDirection,
+    pub client: String,
+    pub upstream: String,
+    /// 是否为手动注入的数据（而非转发的数据）
+    #[serde(default)]
+    pub injected: bool,
+}
+
+impl fmt::Display for RelayMessageInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.direction {
+            RelayDirection::ToUpstream => write!(f, "{} → {}", self.client, self.upstream)?,
+            RelayDirection::ToClient => write!(f, "{} ← {}", self.client, self.upstream)?,
+        }
+        if self.injected {
+            write!(f, " [注入]")?;
+        }
+        Ok(())
+    }
+}
+
 /// 单条消息记录
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
@@ -48,6 +122,24 @@ pub struct Message {
     /// 源地址是否为非预期地址（如UDP广播场景下，回复来自非目标地址）
     #[serde(default)]
     pub source_unexpected: bool,
+    /// 数据是否被截断（UDP数据报超过接收缓冲区时超出部分被丢弃）
+    #[serde(default)]
+    pub truncated: bool,
+    /// WebSocket帧类型（文本帧为 Text，二进制帧为 Hex），非WebSocket消息为 None
+    #[serde(default)]
+    pub frame_type: Option<MessageType>,
+    /// MQTT发布消息的主题等信息，非MQTT消息为 None
+    #[serde(default)]
+    pub mqtt: Option<MqttMessageInfo>,
+    /// 中继消息的转发方向和会话，非中继消息为 None
+    #[serde(default)]
+    pub relay: Option<RelayMessageInfo>,
+    /// 响应消息的往返时间（与配对的请求之间的间隔），未配对时为 None
+    #[serde(default)]
+    pub rtt: Option<Duration>,
+    /// 消息创建时刻，用于计算往返时间（不序列化）
+    #[serde(skip, default = "Instant::now")]
+    pub created_at: Instant,
     #[serde(default = "default_cached_content")]
     cached_content: String,
 }
@@ -67,6 +159,12 @@ impl Message {
             raw_data,
             source: None,
             source_unexpected: false,
+            truncated: false,
+            frame_type: None,
+            mqtt: None,
+            relay: None,
+            rtt: None,
+            created_at: Instant::now(),
             cached_content,
         }
     }
@@ -76,6 +174,42 @@ impl Message {
         self
     }
 
+    /// 标记数据是否被截断
+    pub fn with_truncated(mut self, truncated: bool) -> Self {
+        self.truncated = truncated;
+        self
+    }
+
+    /// 标记消息来自的WebSocket帧类型，显示时按帧类型而非输入模式解析
+    pub fn with_frame_type(mut self, frame_type: MessageType) -> Self {
+        self.frame_type = Some(frame_type);
+        self
+    }
+
+    /// 标记消息所属的MQTT主题、QoS和保留标志
+    pub fn with_mqtt(mut self, mqtt: MqttMessageInfo) -> Self {
+        self.mqtt = Some(mqtt);
+        self
+    }
+
+    /// 标记中继消息的转发方向和会话
+    pub fn with_relay(mut self, relay: RelayMessageInfo) -> Self {
+        self.relay = Some(relay);
+        self
+    }
+
+    /// 日志和导出中显示的来源：中继消息为客户端 → 上游，其余为对端地址，MQTT消息为主题等信息
+    pub fn source_label(&self) -> Option<String> {
+        if let Some(relay) = &self.relay {
+            return Some(relay.to_string());
+        }
+        match (&self.source, &self.mqtt) {
+            (Some(source), _) => Some(source.clone()),
+            (None, Some(mqtt)) => Some(mqtt.to_string()),
+            (None, None) => None,
+        }
+    }
+
     /// 设置来源并标记是否为非预期地址（IP部分与 expected_host 不匹配时为 true）
     pub fn with_unexpected_source(mut self, source: String, expected_host: &str) -> Self {
         let is_unexpected = match source.split(':').next() {
@@ -168,7 +302,7 @@ impl MessageListState {
 
 #[cfg(test)]
 mod tests {
-    use super::{Message, MessageDirection, MessageListState, MessageType};
+    use super::{Message, MessageDirection, MessageListState, MessageType, RelayDirection, RelayMessageInfo};
 
     #[test]
     fn test_message_creation() {
@@ -205,6 +339,31 @@ mod tests {
         assert_eq!(message.source, Some("127.0.0.1:1234".to_string()));
     }
 
+    #[test]
+    fn test_message_truncated_flag() {
+        let message = Message::new(MessageDirection::Received, vec![0u8; 16], MessageType::Hex);
+        assert!(!message.truncated);
+
+        let message = message.with_truncated(true);
+        assert!(message.truncated);
+    }
+
+    #[test]
+    fn test_relay_source_label() {
+        let relay = RelayMessageInfo {
+            direction: RelayDirection::ToClient,
+            client: "127.0.0.1:50000".to_string(),
+            upstream: "10.0.0.2:502".to_string(),
+            injected: true,
+        };
+        let message = Message::new(RelayDirection::ToClient.message_direction(), b"ok".to_vec(), MessageType::Text)
+            .with_source("127.0.0.1:50000".to_string())
+            .with_relay(relay);
+
+        assert_eq!(message.direction, MessageDirection::Received);
+        assert_eq!(message.source_label(), Some("127.0.0.1:50000 ← 10.0.0.2:502 [注入]".to_string()));
+    }
+
     #[test]
     fn test_message_list_state() {
         let mut state = MessageListState::new();
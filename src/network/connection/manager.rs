@@ -1,33 +1,76 @@
 use std::collections::HashMap;
+use log::error;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use smol::channel::Sender;
 use crate::config::connection::{ClientConfig, ServerConfig, ConnectionType};
 use crate::network::events::ConnectionEvent;
 use crate::network::interfaces::{NetworkConnection, NetworkServer, NetworkFactory};
-use crate::network::protocol::tcp::{TcpClient, TcpServer};
+use crate::network::protocol::tcp::{ClientCloseMode, TcpClient, TcpServer};
+use crate::network::protocol::loadtest::LoadTestClient;
+use crate::network::protocol::mqtt::MqttClient;
+use crate::network::protocol::relay::RelayServer;
+use crate::network::protocol::serial::{SerialClient, SerialLine};
 use crate::network::protocol::udp::{UdpClient, UdpServer};
+use crate::network::protocol::websocket::{WebSocketClient, WebSocketServer};
+use crate::network::queue::{self, QueueMetrics, WriteSender};
+#[cfg(unix)]
+use crate::network::protocol::unix::{UnixDatagramClient, UnixDatagramServer, UnixStreamServer};
+use crate::message::RelayDirection;
+use crate::raw_capture::{self, RawCapture, RawCaptureSlot};
+
+#[cfg(not(unix))]
+const UNIX_UNSUPPORTED: &str = "当前平台不支持 Unix 域套接字";
 
 /// 默认的网络工厂实现
 pub struct DefaultNetworkFactory;
 impl NetworkFactory for DefaultNetworkFactory {
     fn create_client(
         config: &ClientConfig,
-        event_sender: Option<Sender<ConnectionEvent>>
-    ) -> Box<dyn NetworkConnection> {
-        match config.protocol {
-            ConnectionType::Tcp => Box::new(TcpClient::new(config.clone(), event_sender)),
-            ConnectionType::Udp => Box::new(UdpClient::new(config.clone(), event_sender)),
-        }
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Result<Box<dyn NetworkConnection>, Box<dyn std::error::Error>> {
+        let client: Box<dyn NetworkConnection> = match config.protocol {
+            ConnectionType::Tcp => Box::new(TcpClient::new(config.clone(), event_sender, raw_capture)?),
+            ConnectionType::Udp => Box::new(UdpClient::new(config.clone(), event_sender, raw_capture)?),
+            ConnectionType::WebSocket => Box::new(WebSocketClient::new(config.clone(), event_sender, raw_capture)?),
+            ConnectionType::Serial => Box::new(SerialClient::new(config.clone(), event_sender, raw_capture)?),
+            ConnectionType::Mqtt => Box::new(MqttClient::new(config.clone(), event_sender, raw_capture)?),
+            ConnectionType::LoadTest => Box::new(LoadTestClient::new(config.clone(), event_sender, raw_capture)?),
+            ConnectionType::Relay => return Err("中继仅支持服务端模式".into()),
+            // Unix域流式套接字客户端由 TcpClient 驱动
+            #[cfg(unix)]
+            ConnectionType::UnixStream => Box::new(TcpClient::new(config.clone(), event_sender, raw_capture)?),
+            #[cfg(unix)]
+            ConnectionType::UnixDatagram => Box::new(UnixDatagramClient::new(config.clone(), event_sender, raw_capture)?),
+            #[cfg(not(unix))]
+            ConnectionType::UnixStream | ConnectionType::UnixDatagram => return Err(UNIX_UNSUPPORTED.into()),
+        };
+        Ok(client)
     }
     
     fn create_server(
         config: &ServerConfig,
-        event_sender: Option<Sender<ConnectionEvent>>
-    ) -> Box<dyn NetworkServer> {
-        match config.protocol {
-            ConnectionType::Tcp => Box::new(TcpServer::new(config.clone(), event_sender)),
-            ConnectionType::Udp => Box::new(UdpServer::new(config.clone(), event_sender)),
-        }
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Result<Box<dyn NetworkServer>, Box<dyn std::error::Error>> {
+        config.validate_listen()?;
+        let server: Box<dyn NetworkServer> = match config.protocol {
+            ConnectionType::Tcp => Box::new(TcpServer::new(config.clone(), event_sender, raw_capture)),
+            ConnectionType::Udp => Box::new(UdpServer::new(config.clone(), event_sender, raw_capture)),
+            ConnectionType::WebSocket => Box::new(WebSocketServer::new(config.clone(), event_sender, raw_capture)),
+            ConnectionType::Relay => Box::new(RelayServer::new(config.clone(), event_sender, raw_capture)),
+            ConnectionType::Serial | ConnectionType::Mqtt | ConnectionType::LoadTest => {
+                return Err(format!("{} 不支持服务端模式", config.protocol).into());
+            }
+            #[cfg(unix)]
+            ConnectionType::UnixStream => Box::new(UnixStreamServer::new(config.clone(), event_sender, raw_capture)),
+            #[cfg(unix)]
+            ConnectionType::UnixDatagram => Box::new(UnixDatagramServer::new(config.clone(), event_sender, raw_capture)),
+            #[cfg(not(unix))]
+            ConnectionType::UnixStream | ConnectionType::UnixDatagram => return Err(UNIX_UNSUPPORTED.into()),
+        };
+        Ok(server)
     }
 }
 
@@ -35,6 +78,8 @@ impl NetworkFactory for DefaultNetworkFactory {
 pub struct NetworkConnectionManager {
     clients: HashMap<String, Box<dyn NetworkConnection>>,
     servers: HashMap<String, Box<dyn NetworkServer>>,
+    /// 每个连接的原始数据录制插槽（与连接生命周期无关，断开重连后继续生效）
+    raw_captures: HashMap<String, RawCaptureSlot>,
 }
 
 impl NetworkConnectionManager {
@@ -42,14 +87,44 @@ impl NetworkConnectionManager {
         Self {
             clients: HashMap::new(),
             servers: HashMap::new(),
+            raw_captures: HashMap::new(),
+        }
+    }
+
+    /// 获取（必要时创建）指定连接的原始数据录制插槽
+    fn raw_capture_slot(&mut self, id: &str) -> RawCaptureSlot {
+        self.raw_captures
+            .entry(id.to_string())
+            .or_insert_with(raw_capture::new_slot)
+            .clone()
+    }
+
+    /// 开启或关闭指定连接的原始数据录制，正在运行的连接立即生效
+    pub fn set_raw_capture(&mut self, id: &str, capture: Option<RawCapture>) {
+        let slot = self.raw_capture_slot(id);
+        if let Ok(mut guard) = slot.lock() {
+            *guard = capture.map(std::sync::Arc::new);
+        }
+    }
+    
+    /// 将创建或连接失败的原因以 Error 事件发送给UI
+    async fn report_error(event_sender: &Option<Sender<ConnectionEvent>>, id: &str, message: String) {
+        error!("[连接管理器] {}: {}", id, message);
+        if let Some(sender) = event_sender {
+            if let Err(e) = sender.send(ConnectionEvent::Error(id.to_string(), message)).await {
+                error!("[连接管理器] 发送 Error 事件失败: {:?}", e);
+            }
         }
     }
     
     /// 创建并启动客户端连接
+    ///
+    /// 连接产生的事件先进入该连接的接收队列，再转发到 event_sender，队列统计写入 queue_metrics。
     pub async fn create_and_connect_client(
         &mut self,
         config: &ClientConfig,
-        event_sender: Option<Sender<ConnectionEvent>>
+        event_sender: Option<Sender<ConnectionEvent>>,
+        queue_metrics: Arc<QueueMetrics>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         // 如果连接已存在，则先断开
         if self.clients.contains_key(&config.id) {
@@ -57,10 +132,24 @@ impl NetworkConnectionManager {
         }
         
         // 创建客户端连接
-        let mut client = DefaultNetworkFactory::create_client(config, event_sender);
+        let event_sender = event_sender.map(|sender| queue::event_queue(&config.queue, queue_metrics, sender));
+        let raw_capture = self.raw_capture_slot(&config.id);
+        // 错误先转换为字符串，避免在 await 期间持有非 Send 的错误对象
+        let created = DefaultNetworkFactory::create_client(config, event_sender.clone(), raw_capture)
+            .map_err(|e| e.to_string());
+        let mut client = match created {
+            Ok(client) => client,
+            Err(message) => {
+                Self::report_error(&event_sender, &config.id, message.clone()).await;
+                return Err(message.into());
+            }
+        };
         
         // 连接到服务器
-        let _ = client.connect().await;
+        if let Err(message) = client.connect().await.map_err(|e| e.to_string()) {
+            Self::report_error(&event_sender, &config.id, message.clone()).await;
+            return Err(message.into());
+        }
         
         // 保存客户端连接
         self.clients.insert(config.id.clone(), client);
@@ -68,11 +157,12 @@ impl NetworkConnectionManager {
         Ok(())
     }
     
-    /// 创建并启动服务器
+    /// 创建并启动服务器，事件经接收队列转发，见 create_and_connect_client
     pub async fn create_and_start_server(
         &mut self,
         config: &ServerConfig,
-        event_sender: Option<Sender<ConnectionEvent>>
+        event_sender: Option<Sender<ConnectionEvent>>,
+        queue_metrics: Arc<QueueMetrics>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         // 如果服务器已存在，则先停止
         if self.servers.contains_key(&config.id) {
@@ -80,14 +170,28 @@ impl NetworkConnectionManager {
         }
         
         // 创建服务器
-        let server = DefaultNetworkFactory::create_server(config, event_sender);
+        let event_sender = event_sender.map(|sender| queue::event_queue(&config.queue, queue_metrics, sender));
+        let raw_capture = self.raw_capture_slot(&config.id);
+        let created = DefaultNetworkFactory::create_server(config, event_sender.clone(), raw_capture)
+            .map_err(|e| e.to_string());
+        let server = match created {
+            Ok(server) => server,
+            Err(message) => {
+                Self::report_error(&event_sender, &config.id, message.clone()).await;
+                return Err(message.into());
+            }
+        };
         
         // 保存服务器到映射中
         self.servers.insert(config.id.clone(), server);
         
         // 从映射中获取服务器并启动
         if let Some(server) = self.servers.get_mut(&config.id) {
-            let _ = server.start().await;
+            if let Err(message) = server.start().await.map_err(|e| e.to_string()) {
+                self.servers.remove(&config.id);
+                Self::report_error(&event_sender, &config.id, message.clone()).await;
+                return Err(message.into());
+            }
         }
         
         Ok(())
@@ -123,7 +227,7 @@ impl NetworkConnectionManager {
         &self,
         server_id: &str,
         addr: SocketAddr,
-    ) -> Result<Sender<Vec<u8>>, String> {
+    ) -> Result<WriteSender, String> {
         let server = self.servers.get(server_id)
             .ok_or_else(|| format!("服务器 {} 不存在", server_id))?;
         
@@ -136,4 +240,104 @@ impl NetworkConnectionManager {
         }
     }
 
+    /// 关闭TCP客户端连接的写方向（发送 FIN）
+    pub fn shutdown_client_write(&self, client_id: &str) -> Result<(), String> {
+        let client = self.clients.get(client_id)
+            .ok_or_else(|| format!("客户端 {} 不存在", client_id))?;
+        
+        match (**client).as_any().downcast_ref::<TcpClient>() {
+            Some(tcp_client) => tcp_client.shutdown_write(),
+            None => Err("仅TCP客户端支持关闭写方向".to_string()),
+        }
+    }
+
+    /// 设置串口客户端的 DTR 或 RTS 电平
+    pub fn set_serial_line(&self, client_id: &str, line: SerialLine, level: bool) -> Result<(), String> {
+        let client = self.clients.get(client_id)
+            .ok_or_else(|| format!("客户端 {} 不存在", client_id))?;
+        
+        match (**client).as_any().downcast_ref::<SerialClient>() {
+            Some(serial_client) => serial_client.set_line(line, level),
+            None => Err("仅串口客户端支持设置控制线".to_string()),
+        }
+    }
+
+    /// 关闭服务端的单个客户端连接
+    /// TCP和TCP中继按指定方式（FIN/RST）关闭，WebSocket优雅关闭时发送关闭帧，UDP、UDP中继和Unix数据报仅从客户端列表中移除
+    pub async fn close_server_client(
+        &self,
+        server_id: &str,
+        addr: SocketAddr,
+        mode: ClientCloseMode,
+    ) -> Result<(), String> {
+        let server = self.servers.get(server_id)
+            .ok_or_else(|| format!("服务器 {} 不存在", server_id))?;
+        
+        let any_ref = (**server).as_any();
+        
+        if let Some(tcp_server) = any_ref.downcast_ref::<TcpServer>() {
+            tcp_server.close_client(addr, mode).await
+        } else if let Some(ws_server) = any_ref.downcast_ref::<WebSocketServer>() {
+            ws_server.close_client(addr, mode).await
+        } else if let Some(udp_server) = any_ref.downcast_ref::<UdpServer>() {
+            udp_server.remove_client(addr).await
+        } else if let Some(relay_server) = any_ref.downcast_ref::<RelayServer>() {
+            relay_server.close_client(addr, mode).await
+        } else {
+            Self::close_unix_client(any_ref, addr, mode).await
+        }
+    }
+
+    /// 获取中继服务端
+    fn relay_server(&self, server_id: &str) -> Result<&RelayServer, String> {
+        let server = self.servers.get(server_id)
+            .ok_or_else(|| format!("服务器 {} 不存在", server_id))?;
+        (**server).as_any().downcast_ref::<RelayServer>()
+            .ok_or_else(|| "仅中继支持该操作".to_string())
+    }
+
+    /// 暂停或恢复中继转发
+    pub fn relay_set_paused(&self, server_id: &str, paused: bool) -> Result<(), String> {
+        self.relay_server(server_id)?.set_paused(paused);
+        Ok(())
+    }
+
+    /// 放行中继暂存的一条（None 为全部）数据
+    pub fn relay_release_held(&self, server_id: &str, id: Option<u64>) -> Result<(), String> {
+        self.relay_server(server_id)?.release_held(id)
+    }
+
+    /// 丢弃中继暂存的一条（None 为全部）数据
+    pub fn relay_drop_held(&self, server_id: &str, id: Option<u64>) -> Result<(), String> {
+        self.relay_server(server_id)?.drop_held(id)
+    }
+
+    /// 向中继会话注入数据（target 为 None 时注入全部会话）
+    pub fn relay_inject(
+        &self,
+        server_id: &str,
+        target: Option<SocketAddr>,
+        direction: RelayDirection,
+        data: Vec<u8>,
+    ) -> Result<usize, String> {
+        self.relay_server(server_id)?.inject(target, direction, data)
+    }
+
+    /// 关闭Unix域套接字服务端的单个客户端
+    #[cfg(unix)]
+    async fn close_unix_client(any_ref: &dyn std::any::Any, addr: SocketAddr, mode: ClientCloseMode) -> Result<(), String> {
+        if let Some(unix_server) = any_ref.downcast_ref::<UnixStreamServer>() {
+            unix_server.close_client(addr, mode).await
+        } else if let Some(unix_server) = any_ref.downcast_ref::<UnixDatagramServer>() {
+            unix_server.remove_client(addr).await
+        } else {
+            Err("不支持的服务端类型".to_string())
+        }
+    }
+
+    #[cfg(not(unix))]
+    async fn close_unix_client(_any_ref: &dyn std::any::Any, _addr: SocketAddr, _mode: ClientCloseMode) -> Result<(), String> {
+        Err("不支持的服务端类型".to_string())
+    }
+
 }
@@ -1,6 +1,13 @@
 use std::net::SocketAddr;
-use smol::channel::Sender;
+use std::time::Duration;
 use crate::message::Message;
+use crate::network::protocol::loadtest::LoadTestReport;
+use crate::network::protocol::mqtt::MqttPublish;
+use crate::network::protocol::relay::RelayHoldState;
+use crate::network::protocol::serial::SerialLineState;
+use crate::network::protocol::websocket::WebSocketEvent;
+use crate::network::queue::WriteSender;
+use crate::network::tls::TlsSessionInfo;
 
 /// 连接事件枚举，用于在网络线程和UI线程之间传递信息
 #[derive(Debug)]
@@ -9,18 +16,48 @@ pub enum ConnectionEvent {
     Connected(String),
     /// 客户端或服务端连接断开
     Disconnected(String),
+    /// 客户端正在等待重连（第几次尝试，距下次尝试的等待时间）
+    Reconnecting(String, u32, Duration),
+    /// 主机名解析完成（解析得到的全部地址）
+    Resolved(String, Vec<SocketAddr>),
+    /// 对端已关闭写方向（收到 FIN），本端仍可发送
+    PeerHalfClosed(String),
+    /// 本端已关闭写方向（已发送 FIN），仍可接收
+    WriteShutdown(String),
+    /// 客户端连接建立后实际使用的本地端点
+    LocalAddress(String, SocketAddr),
+    /// TLS握手完成（服务端为对应客户端的地址，客户端为 None）
+    TlsEstablished(String, Option<SocketAddr>, TlsSessionInfo),
+    /// WebSocket握手完成或收到控制帧（服务端为对应客户端的地址，客户端为 None）
+    WebSocket(String, Option<SocketAddr>, WebSocketEvent),
+    /// 串口 DTR/RTS 控制线的当前电平
+    SerialLines(String, SerialLineState),
     /// 服务端开始监听
     Listening(String),
     /// 错误事件
     Error(String, String),
+    /// 警告事件（仅提示，不改变连接状态）
+    Warning(String, String),
+    /// 读取空闲或写入超时（服务端为对应客户端的地址，客户端为 None），不改变连接状态
+    TimeoutError(String, Option<SocketAddr>, String),
     /// 收到消息
     MessageReceived(String, Message),
     /// 客户端写入发送器准备就绪
-    ClientWriteSenderReady(String, Sender<Vec<u8>>),
+    ClientWriteSenderReady(String, WriteSender),
+    /// MQTT客户端的发布队列准备就绪（MQTT客户端发送时需指定主题，发布队列的每一项带有主题）
+    MqttPublisherReady(String, WriteSender<MqttPublish>),
     /// 服务端客户端连接
-    ServerClientConnected(String, SocketAddr, Sender<Vec<u8>>),
+    ServerClientConnected(String, SocketAddr, WriteSender),
     /// 服务端客户端断开
     ServerClientDisconnected(String, SocketAddr),
+    /// 服务端客户端的显示名称（Unix域套接字客户端以虚拟地址区分，需另行显示对端名称）
+    ServerClientLabel(String, SocketAddr, String),
+    /// 服务端客户端连接到的本地端点（监听多个地址时区分客户端所在的监听地址）
+    ServerClientLocalAddress(String, SocketAddr, SocketAddr),
+    /// 中继的暂停状态或暂存队列发生变化
+    RelayHold(String, RelayHoldState),
+    /// 压力测试的汇总统计（每秒一次，结束时再发送一次最终结果）
+    LoadTestReport(String, LoadTestReport),
     /// 周期发送文本消息
     PeriodicSend(String, String),
     /// 周期发送字节消息
@@ -3,6 +3,7 @@ use std::pin::Pin;
 use std::any::Any;
 use smol::channel::Sender;
 use crate::network::events::ConnectionEvent;
+use crate::raw_capture::RawCaptureSlot;
 
 /// 网络连接接口
 pub trait NetworkConnection: Send + Sync {
@@ -11,6 +12,9 @@ pub trait NetworkConnection: Send + Sync {
     
     /// 断开连接
     fn disconnect(&mut self) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>>;
+    
+    /// 返回 self 的 Any 引用，用于 downcast 到具体类型
+    fn as_any(&self) -> &dyn Any;
 }
 
 /// 网络服务器接口
@@ -27,15 +31,17 @@ pub trait NetworkServer: Send + Sync {
 
 /// 网络工厂接口
 pub trait NetworkFactory {
-    /// 创建客户端连接
+    /// 创建客户端连接，配置无效时返回错误
     fn create_client(
         config: &crate::config::connection::ClientConfig,
-        event_sender: Option<Sender<ConnectionEvent>>
-    ) -> Box<dyn NetworkConnection> where Self: Sized;
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Result<Box<dyn NetworkConnection>, Box<dyn std::error::Error>> where Self: Sized;
     
-    /// 创建服务器
+    /// 创建服务器，协议不支持服务端模式时返回错误
     fn create_server(
         config: &crate::config::connection::ServerConfig,
-        event_sender: Option<Sender<ConnectionEvent>>
-    ) -> Box<dyn NetworkServer> where Self: Sized;
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Result<Box<dyn NetworkServer>, Box<dyn std::error::Error>> where Self: Sized;
 }
@@ -0,0 +1,452 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use crate::config::connection::RttCorrelation;
+use crate::message::{Message, MessageDirection};
+
+/// 小于该值（微秒）的延迟逐微秒计数
+const EXACT_LIMIT: u64 = 1000;
+
+/// 大于等于 EXACT_LIMIT 的延迟按三位有效数字分桶，每个数量级 900 个桶
+const BUCKETS_PER_DECADE: usize = 900;
+
+/// 可记录的数量级数（1ms 到约 27 小时），更大的值计入最后一个桶
+const DECADES: usize = 8;
+
+const BUCKET_COUNT: usize = EXACT_LIMIT as usize + BUCKETS_PER_DECADE * DECADES;
+
+/// 往返时间滚动统计使用的最近样本数
+pub const RTT_ROLLING_WINDOW: usize = 100;
+
+/// 往返时间测量最多保留的未应答请求数，超出时丢弃最早的请求
+const MAX_PENDING_REQUESTS: usize = 1000;
+
+/// 往返时间直方图各分段的上限
+const RTT_HISTOGRAM_BOUNDS: [Duration; 10] = [
+    Duration::from_millis(1),
+    Duration::from_millis(2),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(20),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(200),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+];
+
+/// 延迟分布的统计摘要
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencySummary {
+    pub min: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// 延迟直方图
+///
+/// 1ms 以下精确到微秒，以上按三位有效数字分桶（相对误差不超过 1%），
+/// 内存占用固定，可长时间记录大量样本。
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    buckets: Vec<u64>,
+    count: u64,
+    sum_micros: u128,
+    min_micros: u64,
+    max_micros: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            buckets: vec![0; BUCKET_COUNT],
+            count: 0,
+            sum_micros: 0,
+            min_micros: u64::MAX,
+            max_micros: 0,
+        }
+    }
+
+    /// 微秒值所在的桶
+    fn bucket_index(micros: u64) -> usize {
+        if micros < EXACT_LIMIT {
+            return micros as usize;
+        }
+        let mut mantissa = micros;
+        let mut decade = 0;
+        while mantissa >= 1000 {
+            mantissa /= 10;
+            decade += 1;
+        }
+        if decade > DECADES {
+            return BUCKET_COUNT - 1;
+        }
+        EXACT_LIMIT as usize + (decade - 1) * BUCKETS_PER_DECADE + (mantissa as usize - 100)
+    }
+
+    /// 桶的代表值（微秒，取桶区间的中点）
+    fn bucket_value(index: usize) -> u64 {
+        if index < EXACT_LIMIT as usize {
+            return index as u64;
+        }
+        let offset = index - EXACT_LIMIT as usize;
+        let scale = 10u64.pow((offset / BUCKETS_PER_DECADE + 1) as u32);
+        let mantissa = (offset % BUCKETS_PER_DECADE) as u64 + 100;
+        mantissa * scale + scale / 2
+    }
+
+    /// 记录一个样本
+    pub fn record(&mut self, latency: Duration) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.buckets[Self::bucket_index(micros)] += 1;
+        self.count += 1;
+        self.sum_micros += micros as u128;
+        self.min_micros = self.min_micros.min(micros);
+        self.max_micros = self.max_micros.max(micros);
+    }
+
+    /// 合并另一个直方图的样本
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        for (bucket, count) in self.buckets.iter_mut().zip(&other.buckets) {
+            *bucket += count;
+        }
+        self.count += other.count;
+        self.sum_micros += other.sum_micros;
+        self.min_micros = self.min_micros.min(other.min_micros);
+        self.max_micros = self.max_micros.max(other.max_micros);
+    }
+
+    /// 清空全部样本
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// 小于 limit 的样本数（limit 不超过三位有效数字时结果精确）
+    pub fn count_below(&self, limit: Duration) -> u64 {
+        let micros = u64::try_from(limit.as_micros()).unwrap_or(u64::MAX);
+        self.buckets[..Self::bucket_index(micros)].iter().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// 第 percentile 百分位（0-100）的延迟，没有样本时返回 None
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0 * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                // 桶的代表值不超出实际记录到的最小和最大值
+                let micros = Self::bucket_value(index).clamp(self.min_micros, self.max_micros);
+                return Some(Duration::from_micros(micros));
+            }
+        }
+        Some(Duration::from_micros(self.max_micros))
+    }
+
+    /// 最小、平均、p50/p95/p99 和最大延迟，没有样本时返回 None
+    pub fn summary(&self) -> Option<LatencySummary> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(LatencySummary {
+            min: Duration::from_micros(self.min_micros),
+            mean: Duration::from_micros((self.sum_micros / self.count as u128) as u64),
+            p50: self.percentile(50.0)?,
+            p95: self.percentile(95.0)?,
+            p99: self.percentile(99.0)?,
+            max: Duration::from_micros(self.max_micros),
+        })
+    }
+}
+
+/// 最近若干个往返时间样本的最小、平均和最大值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RollingRtt {
+    pub samples: usize,
+    pub min: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+/// 客户端往返时间测量
+///
+/// 发送的消息按配对规则记为未应答的请求，收到的消息与最早一条匹配的请求配对，
+/// 两条消息的创建时刻之差即为往返时间。
+///
+/// 发送消息在放入发送队列时创建，接收消息在网络任务解出该帧时创建，
+/// 因此往返时间包含请求在发送队列中等待和按发送整形分段写入的时间。
+#[derive(Debug, Clone, Default)]
+pub struct RttTracker {
+    correlation: RttCorrelation,
+    /// 未应答的请求：发送时刻和用于配对的字节
+    pending: VecDeque<(Instant, Vec<u8>)>,
+    recent: VecDeque<Duration>,
+    histogram: LatencyHistogram,
+    unmatched: u64,
+}
+
+impl RttTracker {
+    pub fn new(correlation: RttCorrelation) -> Self {
+        Self {
+            correlation,
+            ..Default::default()
+        }
+    }
+
+    pub fn correlation(&self) -> RttCorrelation {
+        self.correlation
+    }
+
+    /// 更换配对规则，已有的记录全部清空
+    pub fn set_correlation(&mut self, correlation: RttCorrelation) {
+        *self = Self::new(correlation);
+    }
+
+    /// 清空全部记录，保留配对规则
+    pub fn reset(&mut self) {
+        *self = Self::new(self.correlation);
+    }
+
+    /// 丢弃未应答的请求（重新连接后旧请求不会再有响应）
+    pub fn clear_pending(&mut self) {
+        self.pending.clear();
+    }
+
+    /// 记录一条消息，收到的消息与请求配对成功时返回往返时间
+    pub fn observe(&mut self, message: &Message) -> Option<Duration> {
+        if !self.correlation.is_enabled() {
+            return None;
+        }
+        let key = self.correlation.key(&message.raw_data);
+        match message.direction {
+            MessageDirection::Sent => {
+                let key = key?;
+                if self.pending.len() >= MAX_PENDING_REQUESTS {
+                    self.pending.pop_front();
+                }
+                self.pending.push_back((message.created_at, key.to_vec()));
+                None
+            }
+            MessageDirection::Received => {
+                // 只与在该消息之前发出的请求配对
+                let position = key.and_then(|key| {
+                    self.pending
+                        .iter()
+                        .position(|(sent_at, pending_key)| *sent_at <= message.created_at && pending_key == key)
+                });
+                let Some((sent_at, _)) = position.and_then(|position| self.pending.remove(position)) else {
+                    self.unmatched += 1;
+                    return None;
+                };
+                let rtt = message.created_at.duration_since(sent_at);
+                self.histogram.record(rtt);
+                if self.recent.len() >= RTT_ROLLING_WINDOW {
+                    self.recent.pop_front();
+                }
+                self.recent.push_back(rtt);
+                Some(rtt)
+            }
+        }
+    }
+
+    /// 最近 RTT_ROLLING_WINDOW 个样本的统计，没有样本时返回 None
+    pub fn rolling(&self) -> Option<RollingRtt> {
+        let min = *self.recent.iter().min()?;
+        let max = *self.recent.iter().max()?;
+        let total: Duration = self.recent.iter().sum();
+        Some(RollingRtt {
+            samples: self.recent.len(),
+            min,
+            mean: total / self.recent.len() as u32,
+            max,
+        })
+    }
+
+    /// 全部样本的延迟分布
+    pub fn histogram(&self) -> &LatencyHistogram {
+        &self.histogram
+    }
+
+    /// 直方图各分段的标签和样本数，最后一段为超过最大上限的样本
+    pub fn histogram_bins(&self) -> Vec<(String, u64)> {
+        let mut bins = Vec::with_capacity(RTT_HISTOGRAM_BOUNDS.len() + 1);
+        let mut below = 0;
+        for bound in RTT_HISTOGRAM_BOUNDS {
+            let count = self.histogram.count_below(bound);
+            bins.push((format!("<{}", format_latency(bound)), count - below));
+            below = count;
+        }
+        let last = RTT_HISTOGRAM_BOUNDS[RTT_HISTOGRAM_BOUNDS.len() - 1];
+        bins.push((format!("≥{}", format_latency(last)), self.histogram.count() - below));
+        bins
+    }
+
+    /// 未应答的请求数
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// 没有配对到请求的响应数
+    pub fn unmatched(&self) -> u64 {
+        self.unmatched
+    }
+}
+
+/// 将延迟格式化为便于阅读的文本，如 “850µs”、“12.3ms”、“1.50s”
+pub fn format_latency(latency: Duration) -> String {
+    let micros = latency.as_micros();
+    if micros < 1000 {
+        format!("{}µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.1}ms", micros as f64 / 1000.0)
+    } else {
+        format!("{:.2}s", latency.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_latency, LatencyHistogram, RttTracker};
+    use crate::config::connection::RttCorrelation;
+    use crate::message::{Message, MessageDirection, MessageType};
+    use std::time::Duration;
+
+    #[test]
+    /// 测试1ms以下的延迟精确记录
+    fn test_exact_percentiles() {
+        let mut histogram = LatencyHistogram::new();
+        for micros in 1..=100 {
+            histogram.record(Duration::from_micros(micros));
+        }
+        assert_eq!(histogram.count(), 100);
+        assert_eq!(histogram.percentile(50.0), Some(Duration::from_micros(50)));
+        assert_eq!(histogram.percentile(99.0), Some(Duration::from_micros(99)));
+        assert_eq!(histogram.percentile(100.0), Some(Duration::from_micros(100)));
+
+        let summary = histogram.summary().unwrap();
+        assert_eq!(summary.min, Duration::from_micros(1));
+        assert_eq!(summary.max, Duration::from_micros(100));
+        assert_eq!(summary.mean, Duration::from_micros(50));
+    }
+
+    #[test]
+    /// 测试较大延迟的分桶误差不超过1%
+    fn test_bucketed_percentiles() {
+        let mut histogram = LatencyHistogram::new();
+        for ms in 1..=1000 {
+            histogram.record(Duration::from_millis(ms));
+        }
+        for (percentile, expected_ms) in [(50.0, 500.0), (95.0, 950.0), (99.0, 990.0)] {
+            let actual = histogram.percentile(percentile).unwrap().as_secs_f64() * 1000.0;
+            assert!((actual - expected_ms).abs() / expected_ms <= 0.01, "p{} = {}ms", percentile, actual);
+        }
+        assert_eq!(histogram.percentile(100.0), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    /// 测试空直方图与合并
+    fn test_empty_and_merge() {
+        let mut a = LatencyHistogram::new();
+        assert!(a.is_empty());
+        assert!(a.percentile(50.0).is_none());
+        assert!(a.summary().is_none());
+
+        let mut b = LatencyHistogram::new();
+        b.record(Duration::from_millis(5));
+        b.record(Duration::from_secs(100_000));
+        a.merge(&b);
+        assert_eq!(a.count(), 2);
+        assert_eq!(a.summary().unwrap().min, Duration::from_millis(5));
+        assert_eq!(a.summary().unwrap().max, Duration::from_secs(100_000));
+    }
+
+    #[test]
+    /// 测试延迟的显示格式
+    fn test_format_latency() {
+        assert_eq!(format_latency(Duration::from_micros(850)), "850µs");
+        assert_eq!(format_latency(Duration::from_micros(12_345)), "12.3ms");
+        assert_eq!(format_latency(Duration::from_millis(1500)), "1.50s");
+    }
+
+    #[test]
+    /// 测试按分段统计样本数
+    fn test_count_below() {
+        let mut histogram = LatencyHistogram::new();
+        for micros in [500, 1_500, 1_999, 2_000, 150_000] {
+            histogram.record(Duration::from_micros(micros));
+        }
+        assert_eq!(histogram.count_below(Duration::from_millis(1)), 1);
+        assert_eq!(histogram.count_below(Duration::from_millis(2)), 3);
+        assert_eq!(histogram.count_below(Duration::from_millis(200)), 5);
+    }
+
+    fn message_at(direction: MessageDirection, data: &[u8], base: &Message, offset_ms: u64) -> Message {
+        let mut message = Message::new(direction, data.to_vec(), MessageType::Hex);
+        message.created_at = base.created_at + Duration::from_millis(offset_ms);
+        message
+    }
+
+    #[test]
+    /// 测试按下一帧配对
+    fn test_rtt_next_frame() {
+        let mut tracker = RttTracker::new(RttCorrelation::NextFrame);
+        let request = Message::new(MessageDirection::Sent, vec![1], MessageType::Hex);
+        assert_eq!(tracker.observe(&request), None);
+        assert_eq!(tracker.pending(), 1);
+
+        let response = message_at(MessageDirection::Received, &[2], &request, 5);
+        assert_eq!(tracker.observe(&response), Some(Duration::from_millis(5)));
+        let unsolicited = message_at(MessageDirection::Received, &[3], &request, 6);
+        assert_eq!(tracker.observe(&unsolicited), None);
+        assert_eq!(tracker.unmatched(), 1);
+
+        let rolling = tracker.rolling().unwrap();
+        assert_eq!(rolling.samples, 1);
+        assert_eq!(rolling.mean, Duration::from_millis(5));
+        let bins = tracker.histogram_bins();
+        assert_eq!(bins.len(), 11);
+        assert_eq!(bins[3], ("<10.0ms".to_string(), 1));
+    }
+
+    #[test]
+    /// 测试按相同的序列号字节配对
+    fn test_rtt_same_bytes() {
+        let mut tracker = RttTracker::new(RttCorrelation::SameBytes { offset: 1, length: 2 });
+        let first = Message::new(MessageDirection::Sent, vec![0xAA, 0x00, 0x01], MessageType::Hex);
+        let second = message_at(MessageDirection::Sent, &[0xAA, 0x00, 0x02], &first, 1);
+        tracker.observe(&first);
+        tracker.observe(&second);
+
+        // 先收到第二条请求的响应
+        let response = message_at(MessageDirection::Received, &[0xBB, 0x00, 0x02, 0xFF], &first, 10);
+        assert_eq!(tracker.observe(&response), Some(Duration::from_millis(9)));
+        let short = message_at(MessageDirection::Received, &[0xBB], &first, 11);
+        assert_eq!(tracker.observe(&short), None);
+        let response = message_at(MessageDirection::Received, &[0xBB, 0x00, 0x01], &first, 20);
+        assert_eq!(tracker.observe(&response), Some(Duration::from_millis(20)));
+        assert_eq!(tracker.pending(), 0);
+        assert_eq!(tracker.unmatched(), 1);
+
+        tracker.reset();
+        assert!(tracker.rolling().is_none());
+        assert_eq!(tracker.correlation(), RttCorrelation::SameBytes { offset: 1, length: 2 });
+    }
+}
@@ -2,3 +2,8 @@ pub mod events;
 pub mod interfaces;
 pub mod protocol;
 pub mod connection;
+pub mod resolve;
+pub mod tls;
+pub mod proxy;
+pub mod queue;
+pub mod latency;
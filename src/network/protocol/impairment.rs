@@ -0,0 +1,132 @@
+//! 中继的网络损伤模拟
+//!
+//! 在中继的转发任务中按方向附加延迟与抖动、带宽限制、丢包、损坏、乱序和TCP强制分段，
+//! 全部在本进程内完成，不需要 root 权限，也不影响本机的其他连接。
+
+use std::time::Duration;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tokio::time::Instant;
+use crate::config::connection::RelayImpairment;
+
+/// 单个转发方向的损伤状态
+pub(crate) struct Impairer {
+    config: RelayImpairment,
+    rng: StdRng,
+    /// 带宽限制下链路空闲的时刻
+    link_free_at: Instant,
+    /// 上一段数据的发送时刻
+    last_due: Instant,
+}
+
+impl Impairer {
+    pub(crate) fn new(config: RelayImpairment) -> Self {
+        let now = Instant::now();
+        Self {
+            config,
+            rng: StdRng::from_entropy(),
+            link_free_at: now,
+            last_due: now,
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.config.is_enabled()
+    }
+
+    /// 强制分段的长度，0 表示不分段
+    pub(crate) fn segment_size(&self) -> usize {
+        self.config.segment_size
+    }
+
+    fn chance(&mut self, percent: f64) -> bool {
+        percent > 0.0 && self.rng.gen_bool((percent / 100.0).min(1.0))
+    }
+
+    /// 按丢包率决定是否丢弃这个数据报
+    pub(crate) fn lose(&mut self) -> bool {
+        self.chance(self.config.loss_percent)
+    }
+
+    /// 按乱序率决定是否让这个数据报排到其后的数据报之后
+    pub(crate) fn reorder(&mut self) -> bool {
+        self.chance(self.config.reorder_percent)
+    }
+
+    /// 按损坏率翻转一个随机字节中的一位，返回是否损坏
+    pub(crate) fn corrupt(&mut self, data: &mut [u8]) -> bool {
+        if data.is_empty() || !self.chance(self.config.corrupt_percent) {
+            return false;
+        }
+        let index = self.rng.gen_range(0..data.len());
+        data[index] ^= 1 << self.rng.gen_range(0..8);
+        true
+    }
+
+    /// 计算一段数据的发送时刻：排队时刻加上延迟与抖动，再按带宽上限在链路上排队
+    ///
+    /// ordered 为 true 时（TCP字节流）发送时刻不早于上一段，抖动不会打乱数据顺序。
+    pub(crate) fn schedule(&mut self, queued_at: Instant, len: usize, ordered: bool) -> Instant {
+        let jitter = self.config.jitter_ms as i64;
+        let offset = if jitter > 0 { self.rng.gen_range(-jitter..=jitter) } else { 0 };
+        let delay = Duration::from_millis((self.config.latency_ms as i64 + offset).max(0) as u64);
+        let mut due = queued_at + delay;
+        if ordered {
+            due = due.max(self.last_due);
+        }
+        if self.config.bandwidth > 0 {
+            let transmit = Duration::from_secs_f64(len as f64 / self.config.bandwidth as f64);
+            self.link_free_at = due.max(self.link_free_at) + transmit;
+            due = self.link_free_at;
+        }
+        self.last_due = due;
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// 测试抖动下的发送时刻：都在延迟 ± 抖动范围内，字节流的发送时刻不会倒退
+    fn test_schedule_jitter_ordering() {
+        let config = RelayImpairment { latency_ms: 50, jitter_ms: 40, ..Default::default() };
+        let start = Instant::now();
+
+        let mut unordered = Impairer::new(config.clone());
+        for i in 0..200 {
+            let queued_at = start + Duration::from_millis(i);
+            let due = unordered.schedule(queued_at, 10, false);
+            assert!(due >= queued_at + Duration::from_millis(10));
+            assert!(due <= queued_at + Duration::from_millis(90));
+        }
+
+        let mut ordered = Impairer::new(config);
+        let mut last = start;
+        for i in 0..200 {
+            let queued_at = start + Duration::from_millis(i);
+            let due = ordered.schedule(queued_at, 10, true);
+            assert!(due >= queued_at + Duration::from_millis(10));
+            assert!(due >= last);
+            last = due;
+        }
+    }
+
+    #[test]
+    /// 测试带宽限制：数据按长度依次占用链路，链路空闲后从排队时刻（加延迟）重新计算
+    fn test_schedule_bandwidth() {
+        let start = Instant::now();
+        let mut limited = Impairer::new(RelayImpairment { bandwidth: 1024, ..Default::default() });
+        assert_eq!(limited.schedule(start, 512, true), start + Duration::from_millis(500));
+        assert_eq!(limited.schedule(start, 512, true), start + Duration::from_millis(1000));
+        assert_eq!(limited.schedule(start, 256, false), start + Duration::from_millis(1250));
+
+        let later = start + Duration::from_secs(10);
+        assert_eq!(limited.schedule(later, 128, true), later + Duration::from_millis(125));
+
+        let mut delayed = Impairer::new(RelayImpairment { latency_ms: 100, bandwidth: 2048, ..Default::default() });
+        assert_eq!(delayed.schedule(start, 1024, false), start + Duration::from_millis(600));
+        assert_eq!(delayed.schedule(start, 1024, false), start + Duration::from_millis(1100));
+    }
+}
@@ -0,0 +1,650 @@
+//! 压力测试（多连接负载生成）
+//!
+//! 按配置的速率逐个启动客户端，每个客户端建立一条TCP连接或一个UDP套接字，
+//! 按模板以固定速率发送请求。收到的数据按标签页的解码器切分为响应帧，
+//! 每个响应帧与该客户端最早一条未应答的请求配对，得到响应延迟。
+//! UDP可能丢包：响应优先与内容相同（回显）的请求配对，超过响应超时仍未应答的请求计为丢失。
+//! 解码失败、JSON解码器下无法解析的帧以及长度前缀解码器下不完整的帧计为无效响应。
+//!
+//! 单条消息不显示在消息列表中，统计结果每秒以 LoadTestReport 事件汇总上报。
+
+use std::collections::VecDeque;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use bytes::BytesMut;
+use log::{debug, error, info, warn};
+use smol::channel::Sender;
+use tokio::io::AsyncReadExt;
+use tokio::net::UdpSocket;
+use tokio::task::JoinSet;
+use tokio::time::{Instant, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use crate::config::connection::{ClientConfig, DecoderConfig, LoadTestTransport};
+use crate::message::MessageDirection;
+use crate::network::events::ConnectionEvent;
+use crate::network::interfaces::NetworkConnection;
+use crate::network::latency::{LatencyHistogram, LatencySummary};
+use crate::network::protocol::decoder::{CodecFactory, ExtendedDecoder};
+use crate::network::protocol::tcp;
+use crate::network::resolve;
+use crate::raw_capture::{self, RawCaptureSlot};
+
+/// 统计结果的上报间隔
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 解码器强制输出缓冲数据的间隔（与TCP客户端一致）
+const DECODER_FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 每个客户端最多保留的未应答请求数，超出时丢弃最早的请求
+const MAX_PENDING_REQUESTS: usize = 10_000;
+
+/// UDP请求的响应超时，超时仍未应答的请求计为丢失，不再参与配对
+const UDP_RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 压力测试的汇总统计
+#[derive(Debug, Clone, Default)]
+pub struct LoadTestReport {
+    /// 已运行时间
+    pub elapsed: Duration,
+    /// 已启动的客户端数
+    pub started: u32,
+    /// 连接成功的客户端数
+    pub connected: u64,
+    /// 连接失败的客户端数
+    pub connect_failed: u64,
+    /// 当前在线的客户端数
+    pub active: u64,
+    pub sent: u64,
+    pub sent_bytes: u64,
+    /// 收到的响应帧数（含无效响应）
+    pub responses: u64,
+    pub received_bytes: u64,
+    /// 无效响应数（含被截断的UDP数据报）
+    pub invalid: u64,
+    /// 没有对应未应答请求的响应数
+    pub unmatched: u64,
+    /// 超过响应超时仍未应答的请求数（仅UDP）
+    pub lost: u64,
+    /// 发送失败或连接中途断开的次数
+    pub errors: u64,
+    /// 最近一个统计周期的发送速率（条/秒）
+    pub send_rate: f64,
+    /// 最近一个统计周期的响应速率（条/秒）
+    pub response_rate: f64,
+    /// 最近一个统计周期的接收吞吐量（字节/秒）
+    pub receive_throughput: f64,
+    /// 全部有效响应的延迟分布
+    pub latency: Option<LatencySummary>,
+    /// 最近一次错误
+    pub last_error: Option<String>,
+    /// 测试是否已结束
+    pub finished: bool,
+}
+
+/// 全部客户端共享的计数器
+#[derive(Default)]
+struct LoadTestStats {
+    started: AtomicU32,
+    connected: AtomicU64,
+    connect_failed: AtomicU64,
+    active: AtomicU64,
+    sent: AtomicU64,
+    sent_bytes: AtomicU64,
+    responses: AtomicU64,
+    received_bytes: AtomicU64,
+    invalid: AtomicU64,
+    unmatched: AtomicU64,
+    lost: AtomicU64,
+    errors: AtomicU64,
+    latency: Mutex<LatencyHistogram>,
+    last_error: Mutex<Option<String>>,
+}
+
+impl LoadTestStats {
+    fn set_last_error(&self, error: String) {
+        if let Ok(mut last_error) = self.last_error.lock() {
+            *last_error = Some(error);
+        }
+    }
+
+    /// 生成当前的汇总统计，速率按与上一次统计的差值计算
+    fn snapshot(&self, elapsed: Duration, previous: &LoadTestReport) -> LoadTestReport {
+        let mut report = LoadTestReport {
+            elapsed,
+            started: self.started.load(Ordering::Relaxed),
+            connected: self.connected.load(Ordering::Relaxed),
+            connect_failed: self.connect_failed.load(Ordering::Relaxed),
+            active: self.active.load(Ordering::Relaxed),
+            sent: self.sent.load(Ordering::Relaxed),
+            sent_bytes: self.sent_bytes.load(Ordering::Relaxed),
+            responses: self.responses.load(Ordering::Relaxed),
+            received_bytes: self.received_bytes.load(Ordering::Relaxed),
+            invalid: self.invalid.load(Ordering::Relaxed),
+            unmatched: self.unmatched.load(Ordering::Relaxed),
+            lost: self.lost.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            latency: self.latency.lock().ok().and_then(|histogram| histogram.summary()),
+            last_error: self.last_error.lock().ok().and_then(|error| error.clone()),
+            ..Default::default()
+        };
+        let interval = elapsed.saturating_sub(previous.elapsed).as_secs_f64();
+        if interval > 0.0 {
+            report.send_rate = (report.sent - previous.sent) as f64 / interval;
+            report.response_rate = (report.responses - previous.responses) as f64 / interval;
+            report.receive_throughput = (report.received_bytes - previous.received_bytes) as f64 / interval;
+        }
+        report
+    }
+}
+
+/// 一个客户端未应答的请求（按发送顺序）
+///
+/// TCP按顺序可靠传输，响应总是与最早的请求配对。UDP保留请求内容，响应只与内容相同的请求配对，
+/// 丢失的请求因此不会使之后的响应错配；超时的请求由 expire 移除。
+struct PendingRequests {
+    requests: VecDeque<(Instant, Vec<u8>)>,
+    /// 是否保留请求内容用于配对（UDP）
+    correlate: bool,
+}
+
+impl PendingRequests {
+    fn new(correlate: bool) -> Self {
+        Self { requests: VecDeque::new(), correlate }
+    }
+
+    fn push(&mut self, sent_at: Instant, payload: &[u8]) {
+        if self.requests.len() >= MAX_PENDING_REQUESTS {
+            self.requests.pop_front();
+        }
+        let payload = if self.correlate { payload.to_vec() } else { Vec::new() };
+        self.requests.push_back((sent_at, payload));
+    }
+
+    /// 取出与响应帧配对的请求，返回其发送时刻，没有可配对的请求时返回 None
+    fn take(&mut self, frame: &[u8]) -> Option<Instant> {
+        let index = if self.correlate {
+            self.requests.iter().position(|(_, payload)| is_echo_of(payload, frame))?
+        } else {
+            0
+        };
+        self.requests.remove(index).map(|(sent_at, _)| sent_at)
+    }
+
+    /// 丢弃最早的请求（无法解码的响应仍视为已应答）
+    fn discard_oldest(&mut self) {
+        self.requests.pop_front();
+    }
+
+    /// 移除发送时刻早于 deadline 的请求，返回移除的条数
+    fn expire(&mut self, deadline: Instant) -> usize {
+        let expired = self.requests.iter().take_while(|(sent_at, _)| *sent_at < deadline).count();
+        self.requests.drain(..expired);
+        expired
+    }
+}
+
+/// 响应帧是否为该请求的回显（行解码器会去掉行尾，长度前缀解码器会去掉长度字段）
+fn is_echo_of(request: &[u8], frame: &[u8]) -> bool {
+    let frame = trim_line_ending(frame);
+    !frame.is_empty() && trim_line_ending(request).ends_with(frame)
+}
+
+fn trim_line_ending(data: &[u8]) -> &[u8] {
+    let end = data.iter().rposition(|&b| b != b'\n' && b != b'\r').map_or(0, |i| i + 1);
+    &data[..end]
+}
+
+/// 全部客户端共享的测试上下文
+struct LoadTestContext {
+    config: ClientConfig,
+    target: String,
+    /// 目标的全部地址（已按地址族交替排列）
+    addrs: Vec<SocketAddr>,
+    stats: LoadTestStats,
+    raw_capture: RawCaptureSlot,
+}
+
+impl LoadTestContext {
+    /// 客户端两次发送之间的间隔，不发送时返回 None
+    fn send_period(&self) -> Option<Duration> {
+        let rate = self.config.load_test.send_rate;
+        (rate > 0.0).then(|| Duration::from_secs_f64(1.0 / rate))
+    }
+
+    /// 记录一次发送
+    fn record_sent(&self, data: &[u8], pending: &mut PendingRequests, sent_at: Instant) {
+        self.stats.sent.fetch_add(1, Ordering::Relaxed);
+        self.stats.sent_bytes.fetch_add(data.len() as u64, Ordering::Relaxed);
+        pending.push(sent_at, data);
+    }
+
+    /// 记录一个响应帧：与对应的未应答请求配对，有效时计入延迟，没有对应请求时计为未配对
+    ///
+    /// flushed 表示该帧由解码器强制输出（数据不完整）。
+    fn record_response(&self, frame: &[u8], flushed: bool, pending: &mut PendingRequests) {
+        self.stats.responses.fetch_add(1, Ordering::Relaxed);
+        let sent_at = pending.take(frame);
+        if sent_at.is_none() {
+            self.stats.unmatched.fetch_add(1, Ordering::Relaxed);
+        }
+        if !is_valid_response(&self.config.decoder_config, frame, flushed) {
+            self.stats.invalid.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        if let Some(sent_at) = sent_at {
+            if let Ok(mut histogram) = self.stats.latency.lock() {
+                histogram.record(sent_at.elapsed());
+            }
+        }
+    }
+
+    /// 超过响应超时仍未应答的UDP请求计为丢失
+    fn expire_requests(&self, pending: &mut PendingRequests) {
+        let Some(deadline) = Instant::now().checked_sub(UDP_RESPONSE_TIMEOUT) else {
+            return;
+        };
+        let expired = pending.expire(deadline);
+        if expired > 0 {
+            self.stats.lost.fetch_add(expired as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// 记录连接中途出错
+    fn record_error(&self, index: u32, error: String) {
+        debug!("[压力测试] 客户端 {} {}", index, error);
+        self.stats.errors.fetch_add(1, Ordering::Relaxed);
+        self.stats.set_last_error(format!("客户端 {}: {}", index, error));
+    }
+}
+
+/// 按解码器判断响应帧是否有效
+fn is_valid_response(decoder: &DecoderConfig, frame: &[u8], flushed: bool) -> bool {
+    match decoder {
+        DecoderConfig::Json => serde_json::from_slice::<serde_json::Value>(frame).is_ok(),
+        // 强制输出的数据不足一帧
+        DecoderConfig::LengthDelimited(_) => !flushed,
+        DecoderConfig::Bytes | DecoderConfig::LineBased => true,
+    }
+}
+
+/// 从缓冲区中解出全部完整的帧并记录，解码出错时返回错误
+fn drain_frames(
+    ctx: &LoadTestContext,
+    decoder: &mut dyn ExtendedDecoder,
+    buffer: &mut BytesMut,
+    pending: &mut PendingRequests,
+) -> Result<(), String> {
+    loop {
+        match decoder.decode(buffer) {
+            Ok(Some(frame)) => ctx.record_response(&frame, false, pending),
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                ctx.stats.responses.fetch_add(1, Ordering::Relaxed);
+                ctx.stats.invalid.fetch_add(1, Ordering::Relaxed);
+                pending.discard_oldest();
+                return Err(format!("解码错误: {}", e));
+            }
+        }
+    }
+}
+
+/// 运行一个TCP客户端，直到取消、连接断开或出错
+async fn run_tcp_client(index: u32, ctx: Arc<LoadTestContext>, cancel_token: CancellationToken) {
+    let targets: Vec<(SocketAddr, Option<SocketAddr>)> = ctx.addrs.iter().map(|addr| (*addr, None)).collect();
+    let connected = tokio::select! {
+        connected = tcp::connect_happy_eyeballs(&ctx.target, &targets, ctx.config.connect_timeout()) => connected,
+        _ = cancel_token.cancelled() => return,
+    };
+    let stream = match connected {
+        Ok(outcome) => outcome.stream,
+        Err(e) => {
+            ctx.stats.connect_failed.fetch_add(1, Ordering::Relaxed);
+            ctx.stats.set_last_error(format!("客户端 {}: {}", index, e));
+            return;
+        }
+    };
+    if let Some(warning) = tcp::apply_socket_options(&stream, &ctx.config.socket_options) {
+        debug!("[压力测试] 客户端 {} {}", index, warning);
+    }
+    ctx.stats.connected.fetch_add(1, Ordering::Relaxed);
+    ctx.stats.active.fetch_add(1, Ordering::Relaxed);
+
+    let local_addr = stream.local_addr().ok();
+    let (mut reader, mut writer) = stream.into_split();
+    let mut decoder = CodecFactory::create_decoder(&ctx.config.decoder_config);
+    let mut buffer = BytesMut::with_capacity(16 * 1024);
+    let mut pending = PendingRequests::new(false);
+    let mut ticker = send_ticker(ctx.send_period());
+    let mut flush = tokio::time::interval(DECODER_FLUSH_INTERVAL);
+    let mut seq: u32 = 0;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick(), if ctx.send_period().is_some() => {
+                let data = ctx.config.load_test.render_payload(index, seq);
+                seq = seq.wrapping_add(1);
+                let sent_at = Instant::now();
+                if let Err(e) = tcp::write_all_with_timeout(&mut writer, &data, ctx.config.write_timeout()).await {
+                    ctx.record_error(index, format!("发送失败: {}", e));
+                    break;
+                }
+                raw_capture::record(&ctx.raw_capture, local_addr, MessageDirection::Sent, &data);
+                ctx.record_sent(&data, &mut pending, sent_at);
+            }
+            result = reader.read_buf(&mut buffer) => {
+                match result {
+                    Ok(0) => {
+                        if let Ok(Some(frame)) = decoder.decode_eof(&mut buffer) {
+                            ctx.record_response(&frame, true, &mut pending);
+                        }
+                        ctx.record_error(index, "服务器关闭了连接".to_string());
+                        break;
+                    }
+                    Ok(n) => {
+                        ctx.stats.received_bytes.fetch_add(n as u64, Ordering::Relaxed);
+                        raw_capture::record(&ctx.raw_capture, local_addr, MessageDirection::Received, &buffer[buffer.len() - n..]);
+                        if let Err(e) = drain_frames(&ctx, decoder.as_mut(), &mut buffer, &mut pending) {
+                            ctx.record_error(index, e);
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        ctx.record_error(index, format!("读取失败: {}", e));
+                        break;
+                    }
+                }
+            }
+            _ = flush.tick() => {
+                if let Some(frame) = decoder.force_flush() {
+                    ctx.record_response(&frame, true, &mut pending);
+                }
+            }
+            _ = cancel_token.cancelled() => break,
+        }
+    }
+
+    ctx.stats.active.fetch_sub(1, Ordering::Relaxed);
+}
+
+/// 运行一个UDP客户端，直到取消或出错
+///
+/// 每个数据报单独解码，数据报末尾不足一帧的数据同样计为一个响应。
+async fn run_udp_client(index: u32, ctx: Arc<LoadTestContext>, cancel_token: CancellationToken) {
+    let Some(&remote) = ctx.addrs.first() else {
+        return;
+    };
+    let local = if remote.is_ipv6() {
+        SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))
+    } else {
+        SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))
+    };
+    let socket = match UdpSocket::bind(local).await {
+        Ok(socket) => socket,
+        Err(e) => {
+            ctx.stats.connect_failed.fetch_add(1, Ordering::Relaxed);
+            ctx.stats.set_last_error(format!("客户端 {}: 绑定UDP套接字失败: {}", index, e));
+            return;
+        }
+    };
+    if let Err(e) = socket.connect(remote).await {
+        ctx.stats.connect_failed.fetch_add(1, Ordering::Relaxed);
+        ctx.stats.set_last_error(format!("客户端 {}: 连接 {} 失败: {}", index, remote, e));
+        return;
+    }
+    ctx.stats.connected.fetch_add(1, Ordering::Relaxed);
+    ctx.stats.active.fetch_add(1, Ordering::Relaxed);
+
+    let local_addr = socket.local_addr().ok();
+    let mut decoder = CodecFactory::create_decoder(&ctx.config.decoder_config);
+    // 多留一个字节，收满时说明数据报超过了接收缓冲区，已被截断
+    let recv_buffer_len = ctx.config.udp_recv_buffer_len();
+    let mut recv_buffer = vec![0u8; recv_buffer_len + 1];
+    let mut pending = PendingRequests::new(true);
+    let mut ticker = send_ticker(ctx.send_period());
+    let mut expire = tokio::time::interval(REPORT_INTERVAL);
+    let mut seq: u32 = 0;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick(), if ctx.send_period().is_some() => {
+                let data = ctx.config.load_test.render_payload(index, seq);
+                seq = seq.wrapping_add(1);
+                let sent_at = Instant::now();
+                // 未收到的数据报不计为错误，超过响应超时后计为丢失
+                if let Err(e) = socket.send(&data).await {
+                    ctx.record_error(index, format!("发送失败: {}", e));
+                    continue;
+                }
+                raw_capture::record(&ctx.raw_capture, local_addr, MessageDirection::Sent, &data);
+                ctx.record_sent(&data, &mut pending, sent_at);
+            }
+            result = socket.recv(&mut recv_buffer) => {
+                match result {
+                    Ok(n) => {
+                        let kept = n.min(recv_buffer_len);
+                        ctx.stats.received_bytes.fetch_add(kept as u64, Ordering::Relaxed);
+                        raw_capture::record(&ctx.raw_capture, local_addr, MessageDirection::Received, &recv_buffer[..kept]);
+                        ctx.expire_requests(&mut pending);
+                        if n > recv_buffer_len {
+                            // 被截断的数据报计为无效响应，对应的请求超时后计为丢失
+                            ctx.stats.responses.fetch_add(1, Ordering::Relaxed);
+                            ctx.stats.invalid.fetch_add(1, Ordering::Relaxed);
+                            continue;
+                        }
+                        let mut buffer = BytesMut::from(&recv_buffer[..n]);
+                        if let Err(e) = drain_frames(&ctx, decoder.as_mut(), &mut buffer, &mut pending) {
+                            ctx.record_error(index, e);
+                        }
+                        if let Some(frame) = decoder.force_flush() {
+                            ctx.record_response(&frame, true, &mut pending);
+                        }
+                    }
+                    // 目标端口不可达（ICMP）时继续发送，由统计中的错误数体现
+                    Err(e) => ctx.record_error(index, format!("接收失败: {}", e)),
+                }
+            }
+            _ = expire.tick() => ctx.expire_requests(&mut pending),
+            _ = cancel_token.cancelled() => break,
+        }
+    }
+
+    ctx.stats.active.fetch_sub(1, Ordering::Relaxed);
+}
+
+/// 发送定时器，首次发送随机错开，避免全部客户端同时发送
+fn send_ticker(period: Option<Duration>) -> tokio::time::Interval {
+    let period = period.unwrap_or(Duration::from_secs(3600));
+    let offset = period.mul_f64(rand::random::<f64>());
+    let mut ticker = tokio::time::interval_at(Instant::now() + offset, period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    ticker
+}
+
+/// 压力测试客户端
+pub struct LoadTestClient {
+    config: ClientConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    cancel_token: CancellationToken,
+    raw_capture: RawCaptureSlot,
+}
+
+impl LoadTestClient {
+    /// 创建压力测试，目标地址或测试参数无效时返回错误
+    pub fn new(
+        config: ClientConfig,
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Result<Self, String> {
+        resolve::validate_remote(&config.server_address, config.server_port)
+            .map_err(|e| format!("无效的目标地址: {}", e))?;
+        config.load_test.validate()?;
+
+        Ok(LoadTestClient {
+            config,
+            event_sender,
+            cancel_token: CancellationToken::new(),
+            raw_capture,
+        })
+    }
+
+    /// 发送事件到UI线程
+    async fn emit(event_sender: &Option<Sender<ConnectionEvent>>, event: ConnectionEvent) {
+        if let Some(sender) = event_sender {
+            if let Err(e) = sender.send(event).await {
+                error!("[压力测试] 发送事件失败: {:?}", e);
+            }
+        }
+    }
+
+    /// 按爬坡速率启动客户端并定期上报统计，测试时长到达或取消后停止全部客户端
+    async fn run(
+        ctx: Arc<LoadTestContext>,
+        event_sender: Option<Sender<ConnectionEvent>>,
+        cancel_token: CancellationToken,
+    ) {
+        let id = ctx.config.id.clone();
+        let load_test = ctx.config.load_test.clone();
+        let started_at = Instant::now();
+        let deadline = (load_test.duration > 0).then(|| started_at + Duration::from_secs(load_test.duration));
+        let ramp_interval = (load_test.ramp_up_rate > 0).then(|| Duration::from_secs_f64(1.0 / load_test.ramp_up_rate as f64));
+
+        let mut clients = JoinSet::new();
+        let mut next_index: u32 = 0;
+        let mut next_start = started_at;
+        let mut report_ticker = tokio::time::interval_at(started_at + REPORT_INTERVAL, REPORT_INTERVAL);
+        let mut previous = LoadTestReport::default();
+        let deadline_reached = async {
+            match deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(deadline_reached);
+
+        let timed_out = loop {
+            // 不限爬坡速率时一次启动全部客户端
+            while next_index < load_test.clients && (ramp_interval.is_none() || Instant::now() >= next_start) {
+                let client_ctx = ctx.clone();
+                let token = cancel_token.child_token();
+                let index = next_index;
+                clients.spawn(async move {
+                    match client_ctx.config.load_test.transport {
+                        LoadTestTransport::Tcp => run_tcp_client(index, client_ctx, token).await,
+                        LoadTestTransport::Udp => run_udp_client(index, client_ctx, token).await,
+                    }
+                });
+                ctx.stats.started.fetch_add(1, Ordering::Relaxed);
+                next_index += 1;
+                if let Some(interval) = ramp_interval {
+                    next_start += interval;
+                }
+            }
+            let ramping = next_index < load_test.clients;
+
+            tokio::select! {
+                _ = tokio::time::sleep_until(next_start), if ramping => {}
+                _ = report_ticker.tick() => {
+                    let report = ctx.stats.snapshot(started_at.elapsed(), &previous);
+                    Self::emit(&event_sender, ConnectionEvent::LoadTestReport(id.clone(), report.clone())).await;
+                    previous = report;
+                }
+                // 回收已结束的客户端任务
+                Some(_) = clients.join_next(), if !clients.is_empty() => {}
+                _ = &mut deadline_reached => break true,
+                _ = cancel_token.cancelled() => break false,
+            }
+        };
+
+        cancel_token.cancel();
+        while clients.join_next().await.is_some() {}
+
+        let mut report = ctx.stats.snapshot(started_at.elapsed(), &previous);
+        report.finished = true;
+        info!(
+            "[压力测试] {} 结束：连接成功 {}，失败 {}，发送 {}，响应 {}，无效 {}，未配对 {}，丢失 {}",
+            id, report.connected, report.connect_failed, report.sent, report.responses, report.invalid, report.unmatched, report.lost
+        );
+        Self::emit(&event_sender, ConnectionEvent::LoadTestReport(id.clone(), report)).await;
+        // 手动停止时由UI发出 Disconnected，这里只处理到达测试时长的情况
+        if timed_out {
+            Self::emit(&event_sender, ConnectionEvent::Disconnected(id)).await;
+        }
+    }
+}
+
+impl NetworkConnection for LoadTestClient {
+    fn connect(&mut self) -> Pin<Box<dyn std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        let config = self.config.clone();
+        let event_sender = self.event_sender.clone();
+        let cancel_token = self.cancel_token.clone();
+        let raw_capture = self.raw_capture.clone();
+
+        Pin::from(Box::new(async move {
+            let target = resolve::format_host_port(&config.server_address, config.server_port);
+            info!("[压力测试] 目标 {}: {}", target, config.load_test);
+
+            // 解析失败时由连接管理器将错误转发给UI
+            let addrs = resolve::resolve(&config.server_address, config.server_port).await?;
+            if !resolve::is_ip_literal(&config.server_address) {
+                info!("[压力测试] {} 解析为: {}", config.server_address, resolve::format_addresses(&addrs));
+                Self::emit(&event_sender, ConnectionEvent::Resolved(config.id.clone(), addrs.clone())).await;
+            }
+            if config.load_test.transport == LoadTestTransport::Udp && addrs.len() > 1 {
+                warn!("[压力测试] UDP 只使用第一个地址 {}", addrs[0]);
+            }
+
+            let ctx = Arc::new(LoadTestContext {
+                config: config.clone(),
+                target,
+                addrs: resolve::interleave_families(addrs),
+                stats: LoadTestStats::default(),
+                raw_capture,
+            });
+            Self::emit(&event_sender, ConnectionEvent::Connected(config.id.clone())).await;
+            Self::emit(&event_sender, ConnectionEvent::LoadTestReport(config.id.clone(), LoadTestReport::default())).await;
+            tokio::spawn(Self::run(ctx, event_sender, cancel_token));
+            Ok(())
+        }))
+    }
+
+    fn disconnect(&mut self) -> Pin<Box<dyn std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        self.cancel_token.cancel();
+
+        Pin::from(Box::new(async move {
+            Ok(())
+        }))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// 测试未应答请求的配对：TCP取最早的请求，UDP只与内容相同的请求配对
+    fn test_pending_requests_take() {
+        let start = Instant::now();
+        let mut tcp = PendingRequests::new(false);
+        tcp.push(start, b"a");
+        tcp.push(start + Duration::from_millis(1), b"b");
+        assert_eq!(tcp.take(b"x"), Some(start));
+        assert_eq!(tcp.take(b"x"), Some(start + Duration::from_millis(1)));
+        assert_eq!(tcp.take(b"x"), None);
+
+        let mut udp = PendingRequests::new(true);
+        udp.push(start, b"seq 1\n");
+        udp.push(start + Duration::from_millis(1), b"seq 2\n");
+        assert_eq!(udp.take(b"seq 2"), Some(start + Duration::from_millis(1)));
+        assert_eq!(udp.take(b"other"), None);
+        assert_eq!(udp.requests.len(), 1);
+        assert_eq!(udp.expire(start + Duration::from_millis(1)), 1);
+        assert_eq!(udp.take(b"seq 1"), None);
+    }
+}
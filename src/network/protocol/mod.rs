@@ -1,3 +1,11 @@
 pub mod tcp;
 pub mod udp;
 pub mod decoder;
+pub mod websocket;
+pub mod serial;
+pub mod mqtt;
+pub mod relay;
+pub mod loadtest;
+pub mod impairment;
+#[cfg(unix)]
+pub mod unix;
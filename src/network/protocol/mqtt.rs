@@ -0,0 +1,464 @@
+//! MQTT客户端（3.1.1 与 5.0）
+//!
+//! 基于 rumqttc：连接确认后订阅配置的主题过滤器，收到的每条发布消息以 Message 上报，
+//! 并附带主题、QoS 和保留标志。发送框的数据通过 MqttPublisherReady 事件提供的通道
+//! 发布到指定主题。可用本地 mosquitto 测试：
+//!
+//! ```text
+//! mosquitto -v
+//! mosquitto_sub -t 'test/#' -v
+//! mosquitto_pub -t test/hello -m world -q 1 -r
+//! ```
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use log::{debug, error, info, warn};
+use rumqttc::v5::mqttbytes::v5::{Packet as PacketV5, SubscribeReasonCode as SubscribeReasonCodeV5};
+use rumqttc::v5::mqttbytes::QoS as QoSV5;
+use smol::channel::Sender;
+use tokio_util::sync::CancellationToken;
+use crate::config::connection::{ClientConfig, MqttConfig, MqttQos, MqttVersion};
+use crate::core::message_processor::{DefaultMessageProcessor, MessageProcessor};
+use crate::message::{MessageType, MessageDirection, MqttMessageInfo};
+use crate::network::events::ConnectionEvent;
+use crate::network::interfaces::NetworkConnection;
+use crate::network::queue::{self, QueueItem};
+use crate::network::resolve;
+use crate::raw_capture::{self, RawCaptureSlot};
+
+/// 客户端请求队列长度（不含订阅请求）
+const REQUEST_CAPACITY: usize = 64;
+
+/// 未设置超时时等待连接确认的时间
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 断开时等待 DISCONNECT 报文发出的时间
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 发送框发出的一条发布消息
+#[derive(Debug, Clone)]
+pub struct MqttPublish {
+    pub topic: String,
+    pub qos: MqttQos,
+    pub retain: bool,
+    pub payload: Vec<u8>,
+}
+
+impl QueueItem for MqttPublish {
+    fn byte_len(&self) -> usize {
+        self.payload.len()
+    }
+}
+
+/// 两个协议版本共用的入站事件
+enum Incoming {
+    ConnAck { session_present: bool },
+    Publish { topic: String, payload: Vec<u8>, qos: u8, retain: bool },
+    /// 订阅确认，rejected 为被服务器拒绝的订阅数
+    SubAck { rejected: usize },
+    Other,
+}
+
+fn qos_v311(qos: MqttQos) -> rumqttc::QoS {
+    match qos {
+        MqttQos::AtMostOnce => rumqttc::QoS::AtMostOnce,
+        MqttQos::AtLeastOnce => rumqttc::QoS::AtLeastOnce,
+        MqttQos::ExactlyOnce => rumqttc::QoS::ExactlyOnce,
+    }
+}
+
+fn qos_v5(qos: MqttQos) -> QoSV5 {
+    match qos {
+        MqttQos::AtMostOnce => QoSV5::AtMostOnce,
+        MqttQos::AtLeastOnce => QoSV5::AtLeastOnce,
+        MqttQos::ExactlyOnce => QoSV5::ExactlyOnce,
+    }
+}
+
+/// 向事件循环提交请求的句柄
+#[derive(Clone)]
+enum MqttHandle {
+    V311(rumqttc::AsyncClient),
+    V5(rumqttc::v5::AsyncClient),
+}
+
+impl MqttHandle {
+    async fn subscribe(&self, filter: &str, qos: MqttQos) -> Result<(), String> {
+        match self {
+            MqttHandle::V311(client) => client.subscribe(filter, qos_v311(qos)).await.map_err(|e| e.to_string()),
+            MqttHandle::V5(client) => client.subscribe(filter, qos_v5(qos)).await.map_err(|e| e.to_string()),
+        }
+    }
+
+    async fn publish(&self, publish: MqttPublish) -> Result<(), String> {
+        match self {
+            MqttHandle::V311(client) => client
+                .publish(publish.topic, qos_v311(publish.qos), publish.retain, publish.payload)
+                .await
+                .map_err(|e| e.to_string()),
+            MqttHandle::V5(client) => client
+                .publish(publish.topic, qos_v5(publish.qos), publish.retain, publish.payload)
+                .await
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    fn disconnect(&self) {
+        let result = match self {
+            MqttHandle::V311(client) => client.try_disconnect().map_err(|e| e.to_string()),
+            MqttHandle::V5(client) => client.try_disconnect().map_err(|e| e.to_string()),
+        };
+        if let Err(e) = result {
+            debug!("[MQTT] 提交 DISCONNECT 失败: {}", e);
+        }
+    }
+}
+
+/// 事件循环：负责网络读写、心跳和重传
+enum MqttEventLoop {
+    V311(rumqttc::EventLoop),
+    V5(rumqttc::v5::EventLoop),
+}
+
+impl MqttEventLoop {
+    async fn poll(&mut self) -> Result<Incoming, String> {
+        match self {
+            MqttEventLoop::V311(eventloop) => {
+                let event = eventloop.poll().await.map_err(|e| e.to_string())?;
+                Ok(match event {
+                    rumqttc::Event::Incoming(rumqttc::Packet::ConnAck(ack)) => {
+                        Incoming::ConnAck { session_present: ack.session_present }
+                    }
+                    rumqttc::Event::Incoming(rumqttc::Packet::Publish(publish)) => Incoming::Publish {
+                        topic: publish.topic,
+                        payload: publish.payload.to_vec(),
+                        qos: publish.qos as u8,
+                        retain: publish.retain,
+                    },
+                    rumqttc::Event::Incoming(rumqttc::Packet::SubAck(ack)) => Incoming::SubAck {
+                        rejected: ack
+                            .return_codes
+                            .iter()
+                            .filter(|code| !matches!(code, rumqttc::SubscribeReasonCode::Success(_)))
+                            .count(),
+                    },
+                    _ => Incoming::Other,
+                })
+            }
+            MqttEventLoop::V5(eventloop) => {
+                let event = eventloop.poll().await.map_err(|e| e.to_string())?;
+                Ok(match event {
+                    rumqttc::v5::Event::Incoming(PacketV5::ConnAck(ack)) => {
+                        Incoming::ConnAck { session_present: ack.session_present }
+                    }
+                    rumqttc::v5::Event::Incoming(PacketV5::Publish(publish)) => Incoming::Publish {
+                        topic: String::from_utf8_lossy(&publish.topic).into_owned(),
+                        payload: publish.payload.to_vec(),
+                        qos: publish.qos as u8,
+                        retain: publish.retain,
+                    },
+                    rumqttc::v5::Event::Incoming(PacketV5::SubAck(ack)) => Incoming::SubAck {
+                        rejected: ack
+                            .return_codes
+                            .iter()
+                            .filter(|code| !matches!(code, SubscribeReasonCodeV5::Success(_)))
+                            .count(),
+                    },
+                    _ => Incoming::Other,
+                })
+            }
+        }
+    }
+}
+
+/// 按配置创建客户端句柄和事件循环（此时尚未连接，首次 poll 时才建立连接）
+fn create_session(config: &ClientConfig) -> (MqttHandle, MqttEventLoop) {
+    let mqtt = &config.mqtt;
+    let client_id = mqtt.effective_client_id();
+    let keep_alive = Duration::from_secs(mqtt.keep_alive as u64);
+    let capacity = REQUEST_CAPACITY + mqtt.subscriptions.len();
+
+    match mqtt.version {
+        MqttVersion::V311 => {
+            let mut options = rumqttc::MqttOptions::new(client_id, config.server_address.clone(), config.server_port);
+            options.set_keep_alive(keep_alive);
+            options.set_clean_session(mqtt.clean_session);
+            if !mqtt.username.is_empty() {
+                options.set_credentials(mqtt.username.clone(), mqtt.password.clone());
+            }
+            let (client, eventloop) = rumqttc::AsyncClient::new(options, capacity);
+            (MqttHandle::V311(client), MqttEventLoop::V311(eventloop))
+        }
+        MqttVersion::V5 => {
+            let mut options = rumqttc::v5::MqttOptions::new(client_id, config.server_address.clone(), config.server_port);
+            options.set_keep_alive(keep_alive);
+            options.set_clean_start(mqtt.clean_session);
+            if !mqtt.username.is_empty() {
+                options.set_credentials(mqtt.username.clone(), mqtt.password.clone());
+            }
+            let (client, eventloop) = rumqttc::v5::AsyncClient::new(options, capacity);
+            (MqttHandle::V5(client), MqttEventLoop::V5(eventloop))
+        }
+    }
+}
+
+/// 连接服务器并等待连接确认，然后提交配置的订阅
+async fn open_session(config: &ClientConfig) -> Result<(MqttHandle, MqttEventLoop), String> {
+    let target = resolve::format_host_port(&config.server_address, config.server_port);
+    let (handle, mut eventloop) = create_session(config);
+
+    let timeout = config.connect_timeout().unwrap_or(DEFAULT_CONNECT_TIMEOUT);
+    let connack = async {
+        loop {
+            if let Incoming::ConnAck { session_present } = eventloop.poll().await? {
+                return Ok::<bool, String>(session_present);
+            }
+        }
+    };
+    let session_present = tokio::time::timeout(timeout, connack)
+        .await
+        .map_err(|_| format!("连接 {} 超时", target))?
+        .map_err(|e| format!("连接 {} 失败: {}", target, e))?;
+    info!("[MQTT] 已连接 {} (MQTT {}, 会话保留: {})", target, config.mqtt.version, session_present);
+
+    for subscription in &config.mqtt.subscriptions {
+        handle.subscribe(&subscription.filter, subscription.qos).await
+            .map_err(|e| format!("订阅 {} 失败: {}", subscription.filter, e))?;
+        info!("[MQTT] 订阅 {} ({})", subscription.filter, subscription.qos);
+    }
+
+    Ok((handle, eventloop))
+}
+
+/// MQTT客户端实现
+pub struct MqttClient {
+    config: ClientConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    message_processor: Arc<dyn MessageProcessor>,
+    cancel_token: CancellationToken,
+    raw_capture: RawCaptureSlot,
+}
+
+/// MQTT客户端会话上下文，自动重连时每次会话复用同一份
+#[derive(Clone)]
+struct MqttClientContext {
+    config: ClientConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    message_processor: Arc<dyn MessageProcessor>,
+    raw_capture: RawCaptureSlot,
+}
+
+impl MqttClientContext {
+    /// 发送事件到UI线程
+    async fn emit(&self, event: ConnectionEvent) {
+        if let Some(sender) = &self.event_sender {
+            if let Err(e) = sender.send(event).await {
+                error!("[MQTT] 发送事件失败: {:?}", e);
+            }
+        } else {
+            error!("[MQTT] event_sender 为空，无法发送事件");
+        }
+    }
+}
+
+impl MqttClient {
+    /// 创建客户端，服务器地址或订阅无效时返回错误
+    pub fn new(
+        config: ClientConfig,
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Result<Self, String> {
+        resolve::validate_remote(&config.server_address, config.server_port)
+            .map_err(|e| format!("无效的MQTT服务器地址: {}", e))?;
+        for subscription in &config.mqtt.subscriptions {
+            MqttConfig::validate_filter(&subscription.filter)?;
+        }
+
+        Ok(MqttClient {
+            config,
+            event_sender,
+            message_processor: Arc::new(DefaultMessageProcessor),
+            cancel_token: CancellationToken::new(),
+            raw_capture,
+        })
+    }
+
+    /// 运行一次已连接的会话，连接出错或取消后返回，出错时返回错误描述
+    async fn run_session(
+        handle: MqttHandle,
+        mut eventloop: MqttEventLoop,
+        ctx: MqttClientContext,
+        cancel_token: CancellationToken,
+    ) -> Option<String> {
+        let id = ctx.config.id.clone();
+        let (tx, rx) = queue::write_queue::<MqttPublish>(&ctx.config.queue);
+        ctx.emit(ConnectionEvent::Connected(id.clone())).await;
+        ctx.emit(ConnectionEvent::MqttPublisherReady(id.clone(), tx)).await;
+
+        // 发布任务：提交到事件循环的请求队列，队列满时等待
+        let publisher = {
+            let handle = handle.clone();
+            let ctx = ctx.clone();
+            tokio::spawn(async move {
+                while let Ok(publish) = rx.recv().await {
+                    let topic = publish.topic.clone();
+                    let payload = publish.payload.clone();
+                    match handle.publish(publish).await {
+                        Ok(()) => raw_capture::record(&ctx.raw_capture, None, MessageDirection::Sent, &payload),
+                        Err(e) => {
+                            warn!("[MQTT] 发布到 {} 失败: {}", topic, e);
+                            ctx.emit(ConnectionEvent::Warning(
+                                ctx.config.id.clone(),
+                                format!("发布到 {} 失败: {}", topic, e),
+                            )).await;
+                        }
+                    }
+                }
+                debug!("[MQTT] 发布通道已关闭");
+            })
+        };
+
+        let session_error = loop {
+            let incoming = tokio::select! {
+                incoming = eventloop.poll() => incoming,
+                _ = cancel_token.cancelled() => {
+                    info!("[MQTT] 收到取消信号，断开连接: {}", id);
+                    handle.disconnect();
+                    // 继续驱动事件循环，直到 DISCONNECT 发出后连接关闭
+                    let _ = tokio::time::timeout(DISCONNECT_TIMEOUT, async {
+                        while eventloop.poll().await.is_ok() {}
+                    }).await;
+                    break None;
+                }
+            };
+
+            match incoming {
+                Ok(Incoming::Publish { topic, payload, qos, retain }) => {
+                    raw_capture::record(&ctx.raw_capture, None, MessageDirection::Received, &payload);
+                    let message = ctx.message_processor
+                        .process_received_message(payload, MessageType::Text)
+                        .with_mqtt(MqttMessageInfo { topic, qos, retain });
+                    if let Some(sender) = &ctx.event_sender {
+                        if let Err(e) = sender.send(ConnectionEvent::MessageReceived(id.clone(), message)).await {
+                            error!("[MQTT] 发送 MessageReceived 事件失败: {:?}", e);
+                        }
+                    }
+                }
+                Ok(Incoming::SubAck { rejected }) if rejected > 0 => {
+                    warn!("[MQTT] {} 个订阅被服务器拒绝", rejected);
+                    ctx.emit(ConnectionEvent::Warning(id.clone(), format!("{} 个订阅被服务器拒绝", rejected))).await;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error!("[MQTT] 连接错误: {}", e);
+                    break Some(format!("连接错误: {}", e));
+                }
+            }
+        };
+
+        publisher.abort();
+        session_error
+    }
+
+    /// 自动重连的监督循环：连接失败或会话出错后按退避策略重新连接
+    async fn run_with_reconnect(ctx: MqttClientContext, cancel_token: CancellationToken) {
+        let id = ctx.config.id.clone();
+        let mut attempt: u32 = 0;
+
+        loop {
+            if cancel_token.is_cancelled() {
+                break;
+            }
+
+            let opened = tokio::select! {
+                opened = open_session(&ctx.config) => opened,
+                _ = cancel_token.cancelled() => break,
+            };
+            match opened {
+                Ok((handle, eventloop)) => {
+                    attempt = 0;
+                    let session_error = Self::run_session(handle, eventloop, ctx.clone(), cancel_token.clone()).await;
+                    if cancel_token.is_cancelled() {
+                        break;
+                    }
+                    if let Some(reason) = session_error {
+                        ctx.emit(ConnectionEvent::Warning(id.clone(), reason)).await;
+                    }
+                    info!("[MQTT] 连接已断开，准备重连: {}", id);
+                }
+                Err(e) => {
+                    error!("[MQTT] {}", e);
+                    ctx.emit(ConnectionEvent::Warning(id.clone(), e)).await;
+                }
+            }
+
+            attempt += 1;
+            if ctx.config.reconnect_attempts_exhausted(attempt) {
+                error!("[MQTT] 重连 {} 次仍未成功，放弃重连", ctx.config.reconnect_max_attempts);
+                ctx.emit(ConnectionEvent::Error(
+                    id.clone(),
+                    format!("重连失败：已尝试 {} 次", ctx.config.reconnect_max_attempts),
+                )).await;
+                break;
+            }
+
+            let delay = ctx.config.reconnect_delay(attempt);
+            info!("[MQTT] 第 {} 次重连将在 {:?} 后进行", attempt, delay);
+            ctx.emit(ConnectionEvent::Reconnecting(id.clone(), attempt, delay)).await;
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = cancel_token.cancelled() => break,
+            }
+        }
+
+        debug!("[MQTT] 重连循环已退出: {}", id);
+    }
+}
+
+impl NetworkConnection for MqttClient {
+    fn connect(&mut self) -> Pin<Box<dyn std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        let ctx = MqttClientContext {
+            config: self.config.clone(),
+            event_sender: self.event_sender.clone(),
+            message_processor: self.message_processor.clone(),
+            raw_capture: self.raw_capture.clone(),
+        };
+        let cancel_token = self.cancel_token.clone();
+
+        Pin::from(Box::new(async move {
+            info!("[MQTT] 连接到: {}:{}", ctx.config.server_address, ctx.config.server_port);
+
+            if ctx.config.auto_reconnect {
+                tokio::spawn(Self::run_with_reconnect(ctx, cancel_token));
+                return Ok(());
+            }
+
+            // 连接失败时由连接管理器将错误转发给UI
+            let (handle, eventloop) = open_session(&ctx.config).await?;
+
+            tokio::spawn(async move {
+                let id = ctx.config.id.clone();
+                match Self::run_session(handle, eventloop, ctx.clone(), cancel_token).await {
+                    Some(reason) => ctx.emit(ConnectionEvent::Error(id, reason)).await,
+                    None => ctx.emit(ConnectionEvent::Disconnected(id)).await,
+                }
+            });
+
+            Ok(())
+        }))
+    }
+
+    fn disconnect(&mut self) -> Pin<Box<dyn std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        self.cancel_token.cancel();
+
+        Pin::from(Box::new(async move {
+            Ok(())
+        }))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
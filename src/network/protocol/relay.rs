@@ -0,0 +1,958 @@
+//! TCP/UDP中继（中间人）
+//!
+//! 在本地监听，把每个TCP连接或UDP会话（按客户端来源地址区分）转发到配置的上游，
+//! 两个方向的数据记录在同一个标签页中，并标注方向和“客户端 → 上游”会话。
+//! TCP数据按标签页的解码器切分后显示，转发的仍是读到的原始字节；UDP每个数据报为一条消息。
+//!
+//! 转发可以暂停：暂停期间读到的数据按到达顺序暂存，可逐条放行或丢弃。
+//! 修改数据时丢弃暂存的原数据并注入改写后的内容，注入不受暂停影响。
+//!
+//! 每个方向可以配置网络损伤（见 impairment 模块），在写入任务中按排队时刻施加，暂存期间不计入延迟。
+//!
+//! 每个会话每个方向最多有发送队列容量条数据等待写出（含暂存和注入的数据）：TCP读取任务在名额
+//! 用完时停止读取，背压经TCP窗口传回发送方；UDP没有背压，名额用完时丢弃数据报并计入未转发，
+//! 注入同样不等待，名额用完时不注入。
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::future::Future;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, MutexGuard, PoisonError};
+use std::time::Duration;
+use bytes::BytesMut;
+use log::{debug, error, info, warn};
+use smol::channel::{self, Receiver, Sender};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use crate::config::connection::{RelayTransport, ServerConfig};
+use crate::message::{Message, MessageType, RelayDirection, RelayMessageInfo};
+use crate::network::events::ConnectionEvent;
+use crate::network::interfaces::NetworkServer;
+use crate::network::protocol::decoder::CodecFactory;
+use crate::network::protocol::impairment::Impairer;
+use crate::network::protocol::tcp::{self, ClientCloseMode, ClientControl};
+use crate::network::protocol::udp;
+use crate::network::queue;
+use crate::network::resolve;
+use crate::raw_capture::{self, RawCaptureSlot};
+
+/// TCP每次读取的最大字节数
+const READ_CHUNK_SIZE: usize = 16 * 1024;
+
+/// 解码器强制输出缓冲数据的间隔（与TCP服务端一致）
+const DECODER_FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 界面上最多显示的暂存数据条数
+const HELD_PREVIEW_LIMIT: usize = 50;
+
+/// 乱序时暂扣的数据报最多等待其后数据报的时间，超时后照常发出
+const REORDER_HOLD_LIMIT: Duration = Duration::from_millis(500);
+
+/// UDP会话的默认空闲超时（未设置读取空闲超时时使用），超时后释放上游套接字和转发任务
+const UDP_FLOW_IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 暂存的一段待转发数据（界面显示用）
+#[derive(Debug, Clone)]
+pub struct RelayHeldChunk {
+    pub id: u64,
+    pub client: SocketAddr,
+    pub direction: RelayDirection,
+    /// 读到的数据，None 表示发送方已关闭写方向（待转发的 FIN）
+    pub data: Option<Vec<u8>>,
+}
+
+/// 中继的暂停状态和暂存队列
+#[derive(Debug, Clone, Default)]
+pub struct RelayHoldState {
+    pub paused: bool,
+    /// 最早暂存的若干条数据（最多 HELD_PREVIEW_LIMIT 条）
+    pub held: Vec<RelayHeldChunk>,
+    /// 暂存数据的总条数
+    pub held_total: usize,
+    /// 因会话已结束或写入任务已退出而未能转发的数据段数
+    pub undelivered: u64,
+}
+
+/// 发往写入任务的数据
+#[derive(Debug)]
+enum RelayWrite {
+    Data(Vec<u8>),
+    /// 发送方已关闭写方向，向另一端转发 FIN（UDP没有此项）
+    Shutdown,
+}
+
+/// 写入通道中的一项：交给写入任务的时刻（损伤延迟从该时刻算起）、数据，
+/// 以及占用的队列名额（写出或丢弃后归还）
+type QueuedWrite = (Instant, RelayWrite, OwnedSemaphorePermit);
+
+/// 单个中继会话的写入通道
+struct RelayFlow {
+    upstream: SocketAddr,
+    to_client: Sender<QueuedWrite>,
+    to_upstream: Sender<QueuedWrite>,
+    /// 两个方向写入队列的剩余名额
+    to_client_slots: Arc<Semaphore>,
+    to_upstream_slots: Arc<Semaphore>,
+    cancel_token: CancellationToken,
+    /// 最近一次收到数据的时刻（UDP会话据此判断空闲）
+    last_active: Arc<std::sync::Mutex<Instant>>,
+}
+
+impl RelayFlow {
+    fn writer(&self, direction: RelayDirection) -> &Sender<QueuedWrite> {
+        match direction {
+            RelayDirection::ToUpstream => &self.to_upstream,
+            RelayDirection::ToClient => &self.to_client,
+        }
+    }
+
+    fn slots(&self, direction: RelayDirection) -> &Arc<Semaphore> {
+        match direction {
+            RelayDirection::ToUpstream => &self.to_upstream_slots,
+            RelayDirection::ToClient => &self.to_client_slots,
+        }
+    }
+
+    fn touch(&self) {
+        touch(&self.last_active);
+    }
+}
+
+fn touch(last_active: &std::sync::Mutex<Instant>) {
+    *last_active.lock().unwrap_or_else(PoisonError::into_inner) = Instant::now();
+}
+
+/// 暂停期间暂存的数据
+struct HeldEntry {
+    id: u64,
+    client: SocketAddr,
+    direction: RelayDirection,
+    write: RelayWrite,
+    permit: OwnedSemaphorePermit,
+}
+
+/// 会话表和暂停状态
+///
+/// 读取任务在同一把锁内判断是否暂停并转发或暂存，恢复转发时在锁内按顺序放行暂存数据，
+/// 因此恢复后同一方向的数据不会越过之前暂存的数据。
+#[derive(Default)]
+struct RelayState {
+    flows: HashMap<SocketAddr, RelayFlow>,
+    paused: bool,
+    held: VecDeque<HeldEntry>,
+    next_held_id: u64,
+    undelivered: u64,
+}
+
+impl RelayState {
+    /// 交给会话的写入任务，会话已结束或写入任务已退出时丢弃并计数
+    ///
+    /// 写入通道的容量与名额数相同，每项都占一个名额，因此 try_send 只在写入任务退出后失败。
+    fn deliver(&mut self, client: SocketAddr, direction: RelayDirection, write: RelayWrite, permit: OwnedSemaphorePermit) -> bool {
+        let delivered = match self.flows.get(&client) {
+            Some(flow) => match flow.writer(direction).try_send((Instant::now(), write, permit)) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("[中继] 会话 {} 的 {} 写入任务已退出，丢弃数据: {:?}", client, direction, e);
+                    false
+                }
+            },
+            None => {
+                warn!("[中继] 会话 {} 已结束，丢弃 {} 的数据", client, direction);
+                false
+            }
+        };
+        if !delivered {
+            self.undelivered += 1;
+        }
+        delivered
+    }
+
+    fn snapshot(&self) -> RelayHoldState {
+        RelayHoldState {
+            paused: self.paused,
+            held: self
+                .held
+                .iter()
+                .take(HELD_PREVIEW_LIMIT)
+                .map(|entry| RelayHeldChunk {
+                    id: entry.id,
+                    client: entry.client,
+                    direction: entry.direction,
+                    data: match &entry.write {
+                        RelayWrite::Data(data) => Some(data.clone()),
+                        RelayWrite::Shutdown => None,
+                    },
+                })
+                .collect(),
+            held_total: self.held.len(),
+            undelivered: self.undelivered,
+        }
+    }
+}
+
+/// 中继服务端
+pub struct RelayServer {
+    ctx: RelayContext,
+    cancel_token: Option<CancellationToken>,
+}
+
+/// 中继的共享状态，与监听任务和各会话任务共用
+#[derive(Clone)]
+struct RelayContext {
+    config: ServerConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    raw_capture: RawCaptureSlot,
+    state: Arc<std::sync::Mutex<RelayState>>,
+    /// TCP会话的关闭句柄（按最大连接数策略接纳）
+    client_controls: Arc<Mutex<HashMap<SocketAddr, ClientControl>>>,
+}
+
+impl RelayContext {
+    /// 发送事件到UI线程（接收队列已满时按策略等待）
+    async fn emit(&self, event: ConnectionEvent) {
+        if let Some(sender) = &self.event_sender {
+            if let Err(e) = sender.send(event).await {
+                error!("[中继] 发送事件失败: {:?}", e);
+            }
+        }
+    }
+
+    /// 由UI触发的控制操作不能等待接收队列，在后台任务中按顺序发送事件
+    fn emit_later(&self, events: Vec<ConnectionEvent>) {
+        let ctx = self.clone();
+        tokio::spawn(async move {
+            for event in events {
+                ctx.emit(event).await;
+            }
+        });
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, RelayState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 构造一条中继消息
+    fn relay_message(&self, client: SocketAddr, upstream: SocketAddr, direction: RelayDirection, data: Vec<u8>, injected: bool) -> Message {
+        Message::new(direction.message_direction(), data, MessageType::Text)
+            .with_source(client.to_string())
+            .with_relay(RelayMessageInfo {
+                direction,
+                client: client.to_string(),
+                upstream: upstream.to_string(),
+                injected,
+            })
+    }
+
+    /// 创建一个方向的损伤状态
+    fn impairer(&self, direction: RelayDirection) -> Impairer {
+        Impairer::new(match direction {
+            RelayDirection::ToUpstream => self.config.relay.to_upstream.clone(),
+            RelayDirection::ToClient => self.config.relay.to_client.clone(),
+        })
+    }
+
+    async fn emit_message(&self, message: Message) {
+        self.emit(ConnectionEvent::MessageReceived(self.config.id.clone(), message)).await;
+    }
+
+    /// 转发读到的数据，暂停时暂存
+    ///
+    /// 先取得该方向写入队列的名额：TCP在名额用完时等待，UDP直接丢弃数据报。
+    async fn forward(&self, client: SocketAddr, direction: RelayDirection, write: RelayWrite) {
+        let slots = self.lock_state().flows.get(&client).map(|flow| flow.slots(direction).clone());
+        let permit = match (slots, self.config.relay.transport) {
+            (Some(slots), RelayTransport::Tcp) => slots.acquire_owned().await.ok(),
+            (Some(slots), RelayTransport::Udp) => {
+                let permit = slots.try_acquire_owned().ok();
+                if permit.is_none() {
+                    warn!("[中继] 会话 {} 的 {} 队列已满，丢弃数据报", client, direction);
+                }
+                permit
+            }
+            (None, _) => None,
+        };
+        let mut state = self.lock_state();
+        let Some(permit) = permit else {
+            // 会话已结束或队列已满
+            if !state.flows.contains_key(&client) {
+                warn!("[中继] 会话 {} 已结束，丢弃 {} 的数据", client, direction);
+            }
+            state.undelivered += 1;
+            let snapshot = state.snapshot();
+            drop(state);
+            self.emit(ConnectionEvent::RelayHold(self.config.id.clone(), snapshot)).await;
+            return;
+        };
+        if !state.paused {
+            if !state.deliver(client, direction, write, permit) {
+                // 更新界面上的未转发计数
+                let snapshot = state.snapshot();
+                drop(state);
+                self.emit(ConnectionEvent::RelayHold(self.config.id.clone(), snapshot)).await;
+            }
+            return;
+        }
+        let id = state.next_held_id;
+        state.next_held_id += 1;
+        state.held.push_back(HeldEntry { id, client, direction, write, permit });
+        let snapshot = state.snapshot();
+        drop(state);
+        self.emit(ConnectionEvent::RelayHold(self.config.id.clone(), snapshot)).await;
+    }
+
+    /// 登记新会话并通知UI（客户端列表显示为“客户端 → 上游”）
+    async fn add_flow(&self, client: SocketAddr, flow: RelayFlow) {
+        let upstream = flow.upstream;
+        self.lock_state().flows.insert(client, flow);
+
+        // 客户端列表的发送通道：向该客户端注入数据
+        let (inject_tx, inject_rx) = queue::write_queue(&self.config.queue);
+        let ctx = self.clone();
+        tokio::spawn(async move {
+            while let Ok(data) = inject_rx.recv().await {
+                let Ok(messages) = ctx.inject(Some(client), RelayDirection::ToClient, data) else {
+                    break;
+                };
+                for message in messages {
+                    ctx.emit_message(message).await;
+                }
+            }
+        });
+
+        info!("[中继] 新会话 {} → {}", client, upstream);
+        self.emit(ConnectionEvent::ServerClientLabel(self.config.id.clone(), client, format!("{} → {}", client, upstream))).await;
+        self.emit(ConnectionEvent::ServerClientConnected(self.config.id.clone(), client, inject_tx)).await;
+    }
+
+    /// 移除会话及其暂存数据，并通知UI
+    async fn remove_flow(&self, client: SocketAddr) {
+        let mut state = self.lock_state();
+        if state.flows.remove(&client).is_none() {
+            return;
+        }
+        let held_before = state.held.len();
+        state.held.retain(|entry| entry.client != client);
+        let snapshot = (state.held.len() != held_before).then(|| state.snapshot());
+        drop(state);
+
+        info!("[中继] 会话 {} 已结束", client);
+        if let Some(snapshot) = snapshot {
+            self.emit(ConnectionEvent::RelayHold(self.config.id.clone(), snapshot)).await;
+        }
+        self.emit(ConnectionEvent::ServerClientDisconnected(self.config.id.clone(), client)).await;
+    }
+
+    /// 向指定会话（None 为全部会话）的某个方向注入数据，返回各会话注入记录的消息，由调用方发送到UI
+    fn inject(&self, target: Option<SocketAddr>, direction: RelayDirection, data: Vec<u8>) -> Result<Vec<Message>, String> {
+        if data.is_empty() {
+            return Err("注入的数据为空".to_string());
+        }
+        let mut state = self.lock_state();
+        let targets: Vec<(SocketAddr, SocketAddr)> = match target {
+            Some(client) => match state.flows.get(&client) {
+                Some(flow) => vec![(client, flow.upstream)],
+                None => return Err(format!("会话 {} 不存在", client)),
+            },
+            None => state.flows.iter().map(|(client, flow)| (*client, flow.upstream)).collect(),
+        };
+        if targets.is_empty() {
+            return Err("没有可用的中继会话".to_string());
+        }
+        let delivered: Vec<(SocketAddr, SocketAddr)> = targets
+            .into_iter()
+            .filter(|(client, _)| {
+                let permit = state.flows.get(client).and_then(|flow| flow.slots(direction).clone().try_acquire_owned().ok());
+                match permit {
+                    Some(permit) => state.deliver(*client, direction, RelayWrite::Data(data.clone()), permit),
+                    None => {
+                        warn!("[中继] 会话 {} 的 {} 队列已满，未注入数据", client, direction);
+                        state.undelivered += 1;
+                        false
+                    }
+                }
+            })
+            .collect();
+        drop(state);
+
+        Ok(delivered
+            .into_iter()
+            .map(|(client, upstream)| {
+                debug!("[中继] 向会话 {} 注入 {} 字节（{}）", client, data.len(), direction);
+                self.relay_message(client, upstream, direction, data.clone(), true)
+            })
+            .collect())
+    }
+
+    /// 读取一个方向的数据：按解码器切分后记录，原始字节立即转发（或暂存）
+    async fn pump<R: AsyncRead + Unpin>(
+        &self,
+        mut reader: R,
+        client: SocketAddr,
+        upstream: SocketAddr,
+        direction: RelayDirection,
+    ) -> Result<(), String> {
+        let mut decoder = CodecFactory::create_decoder(&self.config.decoder_config);
+        let mut buffer = BytesMut::with_capacity(READ_CHUNK_SIZE);
+        let mut chunk = vec![0u8; READ_CHUNK_SIZE];
+        loop {
+            tokio::select! {
+                result = reader.read(&mut chunk) => match result {
+                    Ok(0) => {
+                        if let Some(data) = decoder.force_flush() {
+                            self.emit_message(self.relay_message(client, upstream, direction, data.to_vec(), false)).await;
+                        }
+                        debug!("[中继] 会话 {} 的 {} 方向已关闭", client, direction);
+                        self.forward(client, direction, RelayWrite::Shutdown).await;
+                        return Ok(());
+                    }
+                    Ok(n) => {
+                        let data = chunk[..n].to_vec();
+                        // 原始录制：客户端发出的数据记为发送，上游的回应记为接收
+                        raw_capture::record(&self.raw_capture, Some(client), direction.message_direction(), &data);
+                        buffer.extend_from_slice(&data);
+                        loop {
+                            match decoder.decode(&mut buffer) {
+                                Ok(Some(frame)) => self.emit_message(self.relay_message(client, upstream, direction, frame.to_vec(), false)).await,
+                                Ok(None) => break,
+                                Err(e) => {
+                                    // 丢弃无法解码的缓冲数据，从下一次读取重新同步（转发不受影响）
+                                    error!("[中继] 解码会话 {} 的 {} 数据失败: {:?}", client, direction, e);
+                                    buffer.clear();
+                                    break;
+                                }
+                            }
+                        }
+                        self.forward(client, direction, RelayWrite::Data(data)).await;
+                    }
+                    Err(e) => return Err(format!("读取 {} 方向的数据失败: {}", direction, e)),
+                },
+                _ = tokio::time::sleep(DECODER_FLUSH_INTERVAL) => {
+                    if let Some(data) = decoder.force_flush() {
+                        self.emit_message(self.relay_message(client, upstream, direction, data.to_vec(), false)).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// 接受TCP连接，直到监听套接字出错或中继停止
+    async fn accept_loop(self, listener: TcpListener, upstream_addrs: Arc<Vec<SocketAddr>>, cancel_token: CancellationToken) {
+        loop {
+            let (stream, client) = tokio::select! {
+                result = listener.accept() => match result {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        debug!("[中继] 监听失败: {:?}", e);
+                        break;
+                    }
+                },
+                _ = cancel_token.cancelled() => break,
+            };
+            debug!("[中继] 接受来自 {} 的连接", client);
+
+            let Some(close_rx) = tcp::admit_client(&self.config, &self.client_controls, client, &self.event_sender).await else {
+                drop(stream);
+                continue;
+            };
+
+            let ctx = self.clone();
+            let upstream_addrs = upstream_addrs.clone();
+            let flow_cancel_token = cancel_token.child_token();
+            tokio::spawn(async move {
+                ctx.run_tcp_flow(stream, client, &upstream_addrs, close_rx, flow_cancel_token).await;
+                ctx.client_controls.lock().await.remove(&client);
+            });
+        }
+    }
+
+    /// 为一个TCP客户端连接上游并双向转发，任一方向出错或被关闭时结束整个会话
+    async fn run_tcp_flow(
+        &self,
+        client_stream: TcpStream,
+        client: SocketAddr,
+        upstream_addrs: &[SocketAddr],
+        close_rx: tokio::sync::oneshot::Receiver<ClientCloseMode>,
+        cancel_token: CancellationToken,
+    ) {
+        // 连接上游的超时使用服务端的超时设置
+        let target = self.config.relay.upstream();
+        let attempts: Vec<(SocketAddr, Option<SocketAddr>)> = upstream_addrs.iter().map(|addr| (*addr, None)).collect();
+        let connected = tokio::select! {
+            result = tcp::connect_happy_eyeballs(&target, &attempts, self.config.connect_timeout()) => result,
+            _ = cancel_token.cancelled() => return,
+        };
+        let outcome = match connected {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                warn!("[中继] 客户端 {} 的上游连接失败: {}", client, e);
+                self.emit(ConnectionEvent::Warning(self.config.id.clone(), format!("客户端 {} 的上游连接失败：{}", client, e))).await;
+                return;
+            }
+        };
+        let upstream = outcome.peer_addr;
+
+        // 强制分段时关闭 Nagle 算法，使每次写入单独成为一个报文段
+        for (stream, impairment) in [(&client_stream, &self.config.relay.to_client), (&outcome.stream, &self.config.relay.to_upstream)] {
+            if impairment.segment_size > 0 {
+                if let Err(e) = stream.set_nodelay(true) {
+                    warn!("[中继] 设置 TCP_NODELAY 失败: {:?}", e);
+                }
+            }
+        }
+
+        // 强制关闭时需设置 SO_LINGER，拆分读写后无法再取回 TcpStream，先保留一个套接字句柄
+        let abort_handle = socket2::SockRef::from(&client_stream).try_clone();
+
+        let (to_client_tx, to_client_rx) = channel::bounded::<QueuedWrite>(self.config.queue.capacity());
+        let (to_upstream_tx, to_upstream_rx) = channel::bounded::<QueuedWrite>(self.config.queue.capacity());
+        self.add_flow(client, RelayFlow {
+            upstream,
+            to_client: to_client_tx,
+            to_upstream: to_upstream_tx,
+            to_client_slots: Arc::new(Semaphore::new(self.config.queue.capacity())),
+            to_upstream_slots: Arc::new(Semaphore::new(self.config.queue.capacity())),
+            cancel_token: cancel_token.clone(),
+            last_active: Arc::new(std::sync::Mutex::new(Instant::now())),
+        }).await;
+
+        let (client_read, client_write) = client_stream.into_split();
+        let (upstream_read, upstream_write) = outcome.stream.into_split();
+        let write_timeout = self.config.write_timeout();
+        let relay = async {
+            tokio::try_join!(
+                self.pump(client_read, client, upstream, RelayDirection::ToUpstream),
+                write_loop(upstream_write, to_upstream_rx, write_timeout, self.impairer(RelayDirection::ToUpstream)),
+                self.pump(upstream_read, client, upstream, RelayDirection::ToClient),
+                write_loop(client_write, to_client_rx, write_timeout, self.impairer(RelayDirection::ToClient)),
+            )
+        };
+
+        let close_mode = tokio::select! {
+            result = relay => {
+                if let Err(e) = result {
+                    info!("[中继] 会话 {} → {} 中断: {}", client, upstream, e);
+                }
+                None
+            }
+            mode = close_rx => mode.ok(),
+            _ = cancel_token.cancelled() => None,
+        };
+
+        if close_mode == Some(ClientCloseMode::Abort) {
+            // SO_LINGER 为 0 时关闭套接字会直接向客户端发送 RST
+            match &abort_handle {
+                Ok(handle) => {
+                    if let Err(e) = handle.set_linger(Some(Duration::ZERO)) {
+                        error!("[中继] 设置 SO_LINGER 失败: {:?}", e);
+                    }
+                }
+                Err(e) => error!("[中继] 无法获取客户端 {} 的套接字: {:?}", client, e),
+            }
+        }
+        drop(abort_handle);
+        self.remove_flow(client).await;
+    }
+
+    /// 接收UDP客户端的数据报，新的来源地址创建独立的上游套接字
+    async fn udp_loop(self, socket: Arc<UdpSocket>, upstream: SocketAddr, cancel_token: CancellationToken) {
+        let mut buffer = vec![0u8; self.config.udp_recv_buffer_len() + 1];
+        loop {
+            let result = tokio::select! {
+                result = udp::recv_datagram(&socket, &mut buffer) => result,
+                _ = cancel_token.cancelled() => break,
+            };
+            let (data, client, truncated) = match result {
+                Ok(received) => received,
+                Err(e) => {
+                    error!("[中继] UDP读取错误: {:?}", e);
+                    continue;
+                }
+            };
+
+            let is_new = match self.lock_state().flows.get(&client) {
+                Some(flow) => {
+                    flow.touch();
+                    false
+                }
+                None => true,
+            };
+            if is_new {
+                if let Err(e) = self.start_udp_flow(socket.clone(), client, upstream, cancel_token.child_token()).await {
+                    warn!("[中继] {}", e);
+                    self.emit(ConnectionEvent::Warning(self.config.id.clone(), e)).await;
+                    continue;
+                }
+            }
+
+            raw_capture::record(&self.raw_capture, Some(client), RelayDirection::ToUpstream.message_direction(), &data);
+            self.emit_message(self.relay_message(client, upstream, RelayDirection::ToUpstream, data.clone(), false).with_truncated(truncated)).await;
+            self.forward(client, RelayDirection::ToUpstream, RelayWrite::Data(data)).await;
+        }
+    }
+
+    /// 为新的UDP客户端创建上游套接字和转发任务
+    async fn start_udp_flow(
+        &self,
+        socket: Arc<UdpSocket>,
+        client: SocketAddr,
+        upstream: SocketAddr,
+        cancel_token: CancellationToken,
+    ) -> Result<(), String> {
+        let local: SocketAddr = if upstream.is_ipv6() {
+            (Ipv6Addr::UNSPECIFIED, 0).into()
+        } else {
+            (Ipv4Addr::UNSPECIFIED, 0).into()
+        };
+        let upstream_socket = UdpSocket::bind(local)
+            .await
+            .map_err(|e| format!("为客户端 {} 创建上游套接字失败: {}", client, e))?;
+        upstream_socket
+            .connect(upstream)
+            .await
+            .map_err(|e| format!("客户端 {} 连接上游 {} 失败: {}", client, upstream, e))?;
+        let upstream_socket = Arc::new(upstream_socket);
+
+        let (to_client_tx, to_client_rx) = channel::bounded::<QueuedWrite>(self.config.queue.capacity());
+        let (to_upstream_tx, to_upstream_rx) = channel::bounded::<QueuedWrite>(self.config.queue.capacity());
+        let last_active = Arc::new(std::sync::Mutex::new(Instant::now()));
+        self.add_flow(client, RelayFlow {
+            upstream,
+            to_client: to_client_tx,
+            to_upstream: to_upstream_tx,
+            to_client_slots: Arc::new(Semaphore::new(self.config.queue.capacity())),
+            to_upstream_slots: Arc::new(Semaphore::new(self.config.queue.capacity())),
+            cancel_token: cancel_token.clone(),
+            last_active: last_active.clone(),
+        }).await;
+
+        // 两个方向各自发送，会话移除后写入通道关闭，发送任务随之结束
+        tokio::spawn(udp_send_loop(upstream_socket.clone(), None, to_upstream_rx, self.impairer(RelayDirection::ToUpstream)));
+        tokio::spawn(udp_send_loop(socket, Some(client), to_client_rx, self.impairer(RelayDirection::ToClient)));
+
+        // 两个方向都没有数据超过空闲超时后结束会话
+        let idle_timeout = self.config.read_idle_timeout().unwrap_or(UDP_FLOW_IDLE_TIMEOUT);
+        let ctx = self.clone();
+        tokio::spawn(async move {
+            let recv_buffer_len = ctx.config.udp_recv_buffer_len();
+            let mut buffer = vec![0u8; recv_buffer_len + 1];
+            loop {
+                let idle_deadline = *last_active.lock().unwrap_or_else(PoisonError::into_inner) + idle_timeout;
+                tokio::select! {
+                    result = upstream_socket.recv(&mut buffer) => match result {
+                        Ok(n) => {
+                            touch(&last_active);
+                            let truncated = n > recv_buffer_len;
+                            let data = buffer[..n.min(recv_buffer_len)].to_vec();
+                            raw_capture::record(&ctx.raw_capture, Some(client), RelayDirection::ToClient.message_direction(), &data);
+                            ctx.emit_message(ctx.relay_message(client, upstream, RelayDirection::ToClient, data.clone(), false).with_truncated(truncated)).await;
+                            ctx.forward(client, RelayDirection::ToClient, RelayWrite::Data(data)).await;
+                        }
+                        // 上游不可达时会收到 ICMP 错误，会话继续保留
+                        Err(e) => warn!("[中继] 从上游 {} 接收失败: {:?}", upstream, e),
+                    },
+                    _ = tokio::time::sleep_until(idle_deadline) => {
+                        // 等待期间客户端可能发来了数据，重新计算截止时刻
+                        if last_active.lock().unwrap_or_else(PoisonError::into_inner).elapsed() >= idle_timeout {
+                            info!("[中继] UDP会话 {} 空闲超过 {} 秒，结束会话", client, idle_timeout.as_secs());
+                            cancel_token.cancel();
+                        }
+                    }
+                    _ = cancel_token.cancelled() => break,
+                }
+            }
+            ctx.remove_flow(client).await;
+        });
+        Ok(())
+    }
+}
+
+/// 将写入通道中的数据写入一端，收到 Shutdown 时关闭写方向
+///
+/// 启用损伤时按排队时刻等待延迟和带宽限制，FIN 同样排在之前的数据之后；强制分段时逐段写入。
+async fn write_loop<W: AsyncWrite + Unpin>(
+    mut writer: W,
+    rx: Receiver<QueuedWrite>,
+    timeout: Option<Duration>,
+    mut impairer: Impairer,
+) -> Result<(), String> {
+    // 名额在这一项写出后随 _permit 释放
+    while let Ok((queued_at, write, _permit)) = rx.recv().await {
+        if impairer.is_enabled() {
+            let len = match &write {
+                RelayWrite::Data(data) => data.len(),
+                RelayWrite::Shutdown => 0,
+            };
+            tokio::time::sleep_until(impairer.schedule(queued_at, len, true)).await;
+        }
+        match write {
+            RelayWrite::Data(mut data) => {
+                if impairer.corrupt(&mut data) {
+                    debug!("[中继] 损坏一段 {} 字节的数据", data.len());
+                }
+                let segment_size = match impairer.segment_size() {
+                    0 => data.len().max(1),
+                    size => size,
+                };
+                for segment in data.chunks(segment_size) {
+                    tcp::write_all_with_timeout(&mut writer, segment, timeout)
+                        .await
+                        .map_err(|e| format!("写入失败: {}", e))?;
+                }
+            }
+            RelayWrite::Shutdown => {
+                if let Err(e) = writer.shutdown().await {
+                    debug!("[中继] 关闭写方向失败: {:?}", e);
+                }
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 发送一个方向的UDP数据报，target 为 None 时发往已连接的上游
+///
+/// 按损伤设置丢包和损坏；乱序时暂扣一个数据报，排在其后的数据报之后发出；
+/// 延迟和带宽限制下的数据报按发送时刻排队，到期后依次发出。
+async fn udp_send_loop(
+    socket: Arc<UdpSocket>,
+    target: Option<SocketAddr>,
+    rx: Receiver<QueuedWrite>,
+    mut impairer: Impairer,
+) {
+    // 待发送的数据报及其名额，按（发送时刻, 序号）排序，发出后归还名额
+    let mut pending: BTreeMap<(Instant, u64), (Vec<u8>, OwnedSemaphorePermit)> = BTreeMap::new();
+    let mut next_seq = 0u64;
+    // 为乱序暂扣的数据报
+    let mut held_back: Option<(Instant, Vec<u8>, OwnedSemaphorePermit)> = None;
+
+    loop {
+        let next_due = pending.keys().next().map(|(due, _)| *due);
+        let hold_deadline = held_back.as_ref().map(|(queued_at, _, _)| *queued_at + REORDER_HOLD_LIMIT);
+        let mut batch = Vec::new();
+        tokio::select! {
+            item = rx.recv() => match item {
+                Ok((queued_at, RelayWrite::Data(mut data), permit)) => {
+                    if impairer.lose() {
+                        debug!("[中继] 丢弃一个 {} 字节的数据报", data.len());
+                        continue;
+                    }
+                    if impairer.corrupt(&mut data) {
+                        debug!("[中继] 损坏一个 {} 字节的数据报", data.len());
+                    }
+                    if held_back.is_none() && impairer.reorder() {
+                        held_back = Some((queued_at, data, permit));
+                        continue;
+                    }
+                    batch.push((queued_at, data, permit));
+                    batch.extend(held_back.take());
+                }
+                Ok((_, RelayWrite::Shutdown, _)) => {}
+                Err(_) => break,
+            },
+            _ = tokio::time::sleep_until(hold_deadline.unwrap_or_else(Instant::now)), if hold_deadline.is_some() => {
+                batch.extend(held_back.take());
+            }
+            _ = tokio::time::sleep_until(next_due.unwrap_or_else(Instant::now)), if next_due.is_some() => {
+                let now = Instant::now();
+                while let Some(entry) = pending.first_entry() {
+                    if entry.key().0 > now {
+                        break;
+                    }
+                    let (data, _permit) = entry.remove();
+                    let result = match target {
+                        Some(addr) => socket.send_to(&data, addr).await,
+                        None => socket.send(&data).await,
+                    };
+                    if let Err(e) = result {
+                        error!("[中继] 发送数据报失败: {:?}", e);
+                    }
+                }
+            }
+        }
+
+        // 批内后一个数据报（被暂扣的）不早于前一个发出
+        let mut earliest = None;
+        for (queued_at, data, permit) in batch {
+            let due = impairer.schedule(queued_at, data.len(), false).max(earliest.unwrap_or(queued_at));
+            earliest = Some(due);
+            pending.insert((due, next_seq), (data, permit));
+            next_seq += 1;
+        }
+    }
+}
+
+impl RelayServer {
+    pub fn new(
+        config: ServerConfig,
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Self {
+        RelayServer {
+            ctx: RelayContext {
+                config,
+                event_sender,
+                raw_capture,
+                state: Arc::new(std::sync::Mutex::new(RelayState::default())),
+                client_controls: Arc::new(Mutex::new(HashMap::new())),
+            },
+            cancel_token: None,
+        }
+    }
+
+    /// 暂停或恢复转发，恢复时按到达顺序放行全部暂存数据
+    pub fn set_paused(&self, paused: bool) {
+        let mut state = self.ctx.lock_state();
+        state.paused = paused;
+        if !paused {
+            while let Some(entry) = state.held.pop_front() {
+                state.deliver(entry.client, entry.direction, entry.write, entry.permit);
+            }
+        }
+        let snapshot = state.snapshot();
+        drop(state);
+        info!("[中继] {}转发", if paused { "暂停" } else { "恢复" });
+        self.ctx.emit_later(vec![ConnectionEvent::RelayHold(self.ctx.config.id.clone(), snapshot)]);
+    }
+
+    /// 放行一条（None 为全部）暂存数据，仍保持暂停
+    pub fn release_held(&self, id: Option<u64>) -> Result<(), String> {
+        let mut state = self.ctx.lock_state();
+        let entries: Vec<HeldEntry> = match id {
+            Some(id) => {
+                let index = state.held.iter().position(|entry| entry.id == id)
+                    .ok_or_else(|| "暂存数据不存在".to_string())?;
+                state.held.remove(index).into_iter().collect()
+            }
+            None => state.held.drain(..).collect(),
+        };
+        for entry in entries {
+            state.deliver(entry.client, entry.direction, entry.write, entry.permit);
+        }
+        let snapshot = state.snapshot();
+        drop(state);
+        self.ctx.emit_later(vec![ConnectionEvent::RelayHold(self.ctx.config.id.clone(), snapshot)]);
+        Ok(())
+    }
+
+    /// 丢弃一条（None 为全部）暂存数据
+    pub fn drop_held(&self, id: Option<u64>) -> Result<(), String> {
+        let mut state = self.ctx.lock_state();
+        match id {
+            Some(id) => {
+                let index = state.held.iter().position(|entry| entry.id == id)
+                    .ok_or_else(|| "暂存数据不存在".to_string())?;
+                state.held.remove(index);
+            }
+            None => state.held.clear(),
+        }
+        let snapshot = state.snapshot();
+        drop(state);
+        self.ctx.emit_later(vec![ConnectionEvent::RelayHold(self.ctx.config.id.clone(), snapshot)]);
+        Ok(())
+    }
+
+    /// 向指定会话（None 为全部会话）的某个方向注入数据，不受暂停影响
+    pub fn inject(&self, target: Option<SocketAddr>, direction: RelayDirection, data: Vec<u8>) -> Result<usize, String> {
+        let messages = self.ctx.inject(target, direction, data)?;
+        let count = messages.len();
+        self.ctx.emit_later(
+            messages
+                .into_iter()
+                .map(|message| ConnectionEvent::MessageReceived(self.ctx.config.id.clone(), message))
+                .collect(),
+        );
+        Ok(count)
+    }
+
+    /// 关闭单个会话：TCP按指定方式（FIN/RST）关闭客户端连接，UDP仅移除会话
+    pub async fn close_client(&self, addr: SocketAddr, mode: ClientCloseMode) -> Result<(), String> {
+        if let Some(control) = self.ctx.client_controls.lock().await.remove(&addr) {
+            info!("[中继] 关闭客户端 {} ({:?})", addr, mode);
+            let _ = control.close_tx.send(mode);
+            return Ok(());
+        }
+        match self.ctx.lock_state().flows.get(&addr) {
+            Some(flow) => {
+                flow.cancel_token.cancel();
+                Ok(())
+            }
+            None => Err(format!("会话 {} 不存在", addr)),
+        }
+    }
+}
+
+impl Drop for RelayServer {
+    fn drop(&mut self) {
+        if let Some(cancel_token) = self.cancel_token.take() {
+            cancel_token.cancel();
+        }
+    }
+}
+
+impl NetworkServer for RelayServer {
+    fn start(&mut self) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error>>> + Send + '_>> {
+        Pin::from(Box::new(async move {
+            if self.cancel_token.is_some() {
+                debug!("[中继] 已经在运行中");
+                return Ok(());
+            }
+
+            let config = self.ctx.config.clone();
+            resolve::validate_remote(&config.relay.upstream_address, config.relay.upstream_port)
+                .map_err(|e| format!("上游{}", e))?;
+            let upstream_addrs = resolve::resolve(&config.relay.upstream_address, config.relay.upstream_port).await?;
+            let listen_addr = resolve::resolve(&config.listen_address, config.listen_port).await?[0];
+
+            let cancel_token = CancellationToken::new();
+            match config.relay.transport {
+                RelayTransport::Tcp => {
+                    let listener = TcpListener::bind(listen_addr)
+                        .await
+                        .map_err(|e| format!("绑定地址 {} 失败: {}", listen_addr, e))?;
+                    let upstream_addrs = Arc::new(resolve::interleave_families(upstream_addrs));
+                    tokio::spawn(self.ctx.clone().accept_loop(listener, upstream_addrs, cancel_token.clone()));
+                }
+                RelayTransport::Udp => {
+                    let socket = UdpSocket::bind(listen_addr)
+                        .await
+                        .map_err(|e| format!("绑定地址 {} 失败: {}", listen_addr, e))?;
+                    tokio::spawn(self.ctx.clone().udp_loop(Arc::new(socket), upstream_addrs[0], cancel_token.clone()));
+                }
+            }
+            self.cancel_token = Some(cancel_token);
+
+            info!("[中继] {} 监听 {}，转发到 {}", config.relay.transport, listen_addr, config.relay.upstream());
+            self.ctx.emit(ConnectionEvent::Listening(config.id.clone())).await;
+            Ok(())
+        }))
+    }
+
+    fn stop(&mut self) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        let ctx = self.ctx.clone();
+        let cancel_token = self.cancel_token.take();
+
+        Pin::from(Box::new(async move {
+            let Some(cancel_token) = cancel_token else {
+                debug!("[中继] 已经停止");
+                return Ok(());
+            };
+            cancel_token.cancel();
+            ctx.client_controls.lock().await.clear();
+            *ctx.lock_state() = RelayState::default();
+
+            ctx.emit(ConnectionEvent::RelayHold(ctx.config.id.clone(), RelayHoldState::default())).await;
+            ctx.emit(ConnectionEvent::Disconnected(ctx.config.id.clone())).await;
+            info!("[中继] 已停止");
+            Ok(())
+        }))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,443 @@
+//! 串口客户端
+//!
+//! 无硬件时可以用一对伪终端测试，例如：
+//! `socat -d -d pty,raw,echo=0 pty,raw,echo=0`
+//! 输出的两个 /dev/pts/N 一个填入串口路径，另一个用 `cat`/`echo` 或第二个串口连接收发。
+//! 伪终端没有调制解调器控制线，设置 DTR/RTS 会失败并以警告提示。
+
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use bytes::BytesMut;
+use log::{debug, error, info, warn};
+use smol::channel::Sender;
+use tokio::io::AsyncReadExt;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio_serial::{DataBits, FlowControl, Parity, SerialPort, SerialPortBuilderExt, SerialStream, StopBits};
+use tokio_util::sync::CancellationToken;
+use crate::config::connection::{ClientConfig, SerialConfig, SerialFlowControl, SerialParity};
+use crate::core::message_processor::{DefaultMessageProcessor, MessageProcessor};
+use crate::message::{MessageDirection, MessageType};
+use crate::network::events::ConnectionEvent;
+use crate::network::interfaces::NetworkConnection;
+use crate::network::protocol::decoder::CodecFactory;
+use crate::network::protocol::tcp;
+use crate::network::queue;
+use crate::raw_capture::{self, RawCaptureSlot};
+
+/// 可手动控制的串口输出控制线
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SerialLine {
+    Dtr,
+    Rts,
+}
+
+impl fmt::Display for SerialLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerialLine::Dtr => write!(f, "DTR"),
+            SerialLine::Rts => write!(f, "RTS"),
+        }
+    }
+}
+
+/// 串口输出控制线的当前电平
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerialLineState {
+    pub dtr: bool,
+    pub rts: bool,
+}
+
+impl SerialLineState {
+    pub fn get(&self, line: SerialLine) -> bool {
+        match line {
+            SerialLine::Dtr => self.dtr,
+            SerialLine::Rts => self.rts,
+        }
+    }
+
+    fn set(&mut self, line: SerialLine, level: bool) {
+        match line {
+            SerialLine::Dtr => self.dtr = level,
+            SerialLine::Rts => self.rts = level,
+        }
+    }
+}
+
+/// 按配置设置串口参数并打开设备
+fn open_port(config: &SerialConfig) -> Result<SerialStream, String> {
+    let data_bits = match config.data_bits {
+        5 => DataBits::Five,
+        6 => DataBits::Six,
+        7 => DataBits::Seven,
+        _ => DataBits::Eight,
+    };
+    let parity = match config.parity {
+        SerialParity::None => Parity::None,
+        SerialParity::Odd => Parity::Odd,
+        SerialParity::Even => Parity::Even,
+    };
+    let stop_bits = match config.stop_bits {
+        2 => StopBits::Two,
+        _ => StopBits::One,
+    };
+    let flow_control = match config.flow_control {
+        SerialFlowControl::None => FlowControl::None,
+        SerialFlowControl::Software => FlowControl::Software,
+        SerialFlowControl::Hardware => FlowControl::Hardware,
+    };
+
+    tokio_serial::new(config.path.trim(), config.baud_rate)
+        .data_bits(data_bits)
+        .parity(parity)
+        .stop_bits(stop_bits)
+        .flow_control(flow_control)
+        .open_native_async()
+        .map_err(|e| format!("打开串口 {} 失败: {}", config.path.trim(), e))
+}
+
+/// 设置一条控制线的电平
+fn write_line(port: &mut SerialStream, line: SerialLine, level: bool) -> Result<(), String> {
+    let result = match line {
+        SerialLine::Dtr => port.write_data_terminal_ready(level),
+        SerialLine::Rts => port.write_request_to_send(level),
+    };
+    result.map_err(|e| format!("设置 {} 失败: {}", line, e))
+}
+
+/// 串口客户端实现
+pub struct SerialClient {
+    config: ClientConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    message_processor: Arc<dyn MessageProcessor>,
+    cancel_token: CancellationToken,
+    raw_capture: RawCaptureSlot,
+    /// 当前会话的控制线设置通道，未打开时为 None
+    line_control: Arc<std::sync::Mutex<Option<mpsc::Sender<(SerialLine, bool)>>>>,
+}
+
+/// 串口客户端会话上下文，自动重连时每次会话复用同一份
+#[derive(Clone)]
+struct SerialClientContext {
+    config: ClientConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    message_processor: Arc<dyn MessageProcessor>,
+    raw_capture: RawCaptureSlot,
+    line_control: Arc<std::sync::Mutex<Option<mpsc::Sender<(SerialLine, bool)>>>>,
+}
+
+impl SerialClientContext {
+    /// 发送事件到UI线程
+    async fn emit(&self, event: ConnectionEvent) {
+        if let Some(sender) = &self.event_sender {
+            if let Err(e) = sender.send(event).await {
+                error!("[串口] 发送事件失败: {:?}", e);
+            }
+        } else {
+            error!("[串口] event_sender 为空，无法发送事件");
+        }
+    }
+
+    /// 将解码后的一帧数据作为消息发送给UI
+    async fn emit_message(&self, data: BytesMut) {
+        let message = self.message_processor.process_received_message(data.to_vec(), MessageType::Text);
+        if let Some(sender) = &self.event_sender {
+            if let Err(e) = sender.send(ConnectionEvent::MessageReceived(self.config.id.clone(), message)).await {
+                error!("[串口] 发送 MessageReceived 事件失败: {:?}", e);
+            }
+        }
+    }
+}
+
+impl SerialClient {
+    /// 创建客户端，串口参数无效时返回错误
+    pub fn new(
+        config: ClientConfig,
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Result<Self, String> {
+        config.serial.validate()?;
+
+        Ok(SerialClient {
+            config,
+            event_sender,
+            message_processor: Arc::new(DefaultMessageProcessor),
+            cancel_token: CancellationToken::new(),
+            raw_capture,
+            line_control: Arc::new(std::sync::Mutex::new(None)),
+        })
+    }
+
+    /// 设置当前会话的 DTR 或 RTS 电平，结果通过 SerialLines 或 Warning 事件告知UI
+    pub fn set_line(&self, line: SerialLine, level: bool) -> Result<(), String> {
+        let guard = self.line_control.lock().map_err(|_| "控制线状态不可用".to_string())?;
+        match guard.as_ref() {
+            Some(sender) => sender.try_send((line, level)).map_err(|e| match e {
+                TrySendError::Full(_) => "控制线设置过于频繁，请稍后重试".to_string(),
+                TrySendError::Closed(_) => "串口已关闭".to_string(),
+            }),
+            None => Err("串口未打开".to_string()),
+        }
+    }
+
+    /// 运行一次已打开的串口会话
+    ///
+    /// 与TCP客户端不同，串口不拆分读写任务：收发和控制线设置都在同一个循环中完成，
+    /// 以便随时访问设备的控制线。读取出错（如USB转串口被拔出）、超时或取消后返回，
+    /// 出错和超时时返回错误描述。
+    async fn run_session(
+        mut port: SerialStream,
+        ctx: SerialClientContext,
+        cancel_token: CancellationToken,
+    ) -> Option<String> {
+        let config = &ctx.config;
+        let write_timeout = config.write_timeout();
+        let read_idle_timeout = config.read_idle_timeout();
+
+        let (tx, rx) = queue::write_queue(&config.queue);
+        let (line_tx, mut line_rx) = mpsc::channel::<(SerialLine, bool)>(config.queue.capacity());
+        if let Ok(mut guard) = ctx.line_control.lock() {
+            *guard = Some(line_tx);
+        }
+
+        ctx.emit(ConnectionEvent::Connected(config.id.clone())).await;
+        ctx.emit(ConnectionEvent::ClientWriteSenderReady(config.id.clone(), tx)).await;
+
+        // 按配置设置控制线初始电平，硬件流控时 RTS 由驱动控制
+        let mut lines = SerialLineState { dtr: true, rts: true };
+        let mut initial = vec![(SerialLine::Dtr, config.serial.dtr)];
+        if config.serial.flow_control != SerialFlowControl::Hardware {
+            initial.push((SerialLine::Rts, config.serial.rts));
+        }
+        let mut failures = Vec::new();
+        for (line, level) in initial {
+            match write_line(&mut port, line, level) {
+                Ok(()) => lines.set(line, level),
+                Err(e) => failures.push(e),
+            }
+        }
+        if !failures.is_empty() {
+            warn!("[串口] {}", failures.join("；"));
+            ctx.emit(ConnectionEvent::Warning(
+                config.id.clone(),
+                format!("{}（伪终端等设备不支持控制线）", failures.join("；")),
+            )).await;
+        }
+        ctx.emit(ConnectionEvent::SerialLines(config.id.clone(), lines)).await;
+
+        let mut encoder = CodecFactory::create_encoder(&config.decoder_config);
+        let mut decoder = CodecFactory::create_decoder(&config.decoder_config);
+        let mut buffer = BytesMut::with_capacity(4096);
+        let mut last_read = Instant::now();
+        let mut idle_notified = false;
+        let mut session_error = None;
+
+        loop {
+            tokio::select! {
+                result = port.read_buf(&mut buffer) => {
+                    match result {
+                        Ok(0) => {
+                            info!("[串口] 设备已关闭");
+                            break;
+                        }
+                        Ok(n) => {
+                            debug!("[串口] 读取了 {} 字节数据", n);
+                            last_read = Instant::now();
+                            idle_notified = false;
+                            raw_capture::record(&ctx.raw_capture, None, MessageDirection::Received, &buffer[buffer.len() - n..]);
+
+                            loop {
+                                match decoder.decode(&mut buffer) {
+                                    Ok(Some(data)) => ctx.emit_message(data).await,
+                                    Ok(None) => break,
+                                    Err(e) => {
+                                        error!("[串口] 解码错误: {:?}", e);
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            error!("[串口] 读取错误: {:?}", e);
+                            session_error = Some(format!("串口读取错误: {}", e));
+                            break;
+                        }
+                    }
+                }
+
+                data = rx.recv() => {
+                    let Ok(data) = data else {
+                        debug!("[串口] 消息发送通道已关闭");
+                        break;
+                    };
+                    let mut frame = BytesMut::with_capacity(data.len());
+                    if let Err(e) = encoder.encode(BytesMut::from(data.as_slice()), &mut frame) {
+                        error!("[串口] 编码错误: {:?}", e);
+                        continue;
+                    }
+                    if let Err(e) = tcp::write_all_with_timeout(&mut port, &frame, write_timeout).await {
+                        error!("[串口] 写入错误: {:?}", e);
+                        session_error = Some(if e.kind() == std::io::ErrorKind::TimedOut {
+                            format!(
+                                "写入超时：{} 秒内未能写出数据（检查流控设置），已关闭串口",
+                                write_timeout.map(|t| t.as_secs()).unwrap_or_default()
+                            )
+                        } else {
+                            format!("串口写入错误: {}", e)
+                        });
+                        break;
+                    }
+                    raw_capture::record(&ctx.raw_capture, None, MessageDirection::Sent, &frame);
+                }
+
+                Some((line, level)) = line_rx.recv() => {
+                    match write_line(&mut port, line, level) {
+                        Ok(()) => {
+                            info!("[串口] {} 设置为 {}", line, if level { "高" } else { "低" });
+                            lines.set(line, level);
+                            ctx.emit(ConnectionEvent::SerialLines(config.id.clone(), lines)).await;
+                        }
+                        Err(e) => {
+                            warn!("[串口] {}", e);
+                            ctx.emit(ConnectionEvent::Warning(config.id.clone(), e)).await;
+                        }
+                    }
+                }
+
+                _ = tokio::time::sleep(Duration::from_millis(50)) => {
+                    if let Some(data) = decoder.force_flush() {
+                        ctx.emit_message(data).await;
+                    }
+
+                    if let Some(idle_timeout) = read_idle_timeout {
+                        if !idle_notified && last_read.elapsed() >= idle_timeout {
+                            if config.read_idle_close {
+                                info!("[串口] 读取空闲超时，关闭串口");
+                                session_error = Some(format!(
+                                    "读取空闲超时：{} 秒内未收到数据，已关闭串口",
+                                    idle_timeout.as_secs()
+                                ));
+                                break;
+                            }
+                            idle_notified = true;
+                            ctx.emit(ConnectionEvent::Warning(
+                                config.id.clone(),
+                                format!("读取空闲超时：已 {} 秒未收到数据", idle_timeout.as_secs()),
+                            )).await;
+                        }
+                    }
+                }
+
+                _ = cancel_token.cancelled() => {
+                    info!("[串口] 收到取消信号，关闭串口");
+                    break;
+                }
+            }
+        }
+
+        if let Ok(mut guard) = ctx.line_control.lock() {
+            *guard = None;
+        }
+        session_error
+    }
+
+    /// 自动重连监督循环，设备被拔出后按指数退避重新打开
+    async fn run_with_reconnect(ctx: SerialClientContext, cancel_token: CancellationToken) {
+        let id = ctx.config.id.clone();
+        let mut attempt: u32 = 0;
+
+        loop {
+            if cancel_token.is_cancelled() {
+                break;
+            }
+
+            match open_port(&ctx.config.serial) {
+                Ok(port) => {
+                    attempt = 0;
+                    let session_error = Self::run_session(port, ctx.clone(), cancel_token.clone()).await;
+                    if cancel_token.is_cancelled() {
+                        break;
+                    }
+                    if let Some(reason) = session_error {
+                        ctx.emit(ConnectionEvent::Warning(id.clone(), reason)).await;
+                    }
+                    info!("[串口] 串口已关闭，准备重新打开: {}", id);
+                }
+                Err(e) => {
+                    error!("[串口] {}", e);
+                    ctx.emit(ConnectionEvent::Warning(id.clone(), e)).await;
+                }
+            }
+
+            attempt += 1;
+            if ctx.config.reconnect_attempts_exhausted(attempt) {
+                error!("[串口] 重连 {} 次仍未成功，放弃重连", ctx.config.reconnect_max_attempts);
+                ctx.emit(ConnectionEvent::Error(
+                    id.clone(),
+                    format!("重连失败：已尝试 {} 次", ctx.config.reconnect_max_attempts),
+                )).await;
+                break;
+            }
+
+            let delay = ctx.config.reconnect_delay(attempt);
+            info!("[串口] 第 {} 次重连将在 {:?} 后进行", attempt, delay);
+            ctx.emit(ConnectionEvent::Reconnecting(id.clone(), attempt, delay)).await;
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = cancel_token.cancelled() => break,
+            }
+        }
+
+        debug!("[串口] 重连循环已退出: {}", id);
+    }
+}
+
+impl NetworkConnection for SerialClient {
+    fn connect(&mut self) -> Pin<Box<dyn std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        let ctx = SerialClientContext {
+            config: self.config.clone(),
+            event_sender: self.event_sender.clone(),
+            message_processor: self.message_processor.clone(),
+            raw_capture: self.raw_capture.clone(),
+            line_control: self.line_control.clone(),
+        };
+        let cancel_token = self.cancel_token.clone();
+
+        Pin::from(Box::new(async move {
+            let serial = &ctx.config.serial;
+            info!("[串口] 打开 {} ({})", serial.path, serial.summary());
+
+            if ctx.config.auto_reconnect {
+                tokio::spawn(Self::run_with_reconnect(ctx, cancel_token));
+                return Ok(());
+            }
+
+            // 打开失败时由连接管理器将错误转发给UI
+            let port = open_port(serial)?;
+
+            tokio::spawn(async move {
+                let id = ctx.config.id.clone();
+                match Self::run_session(port, ctx.clone(), cancel_token).await {
+                    Some(reason) => ctx.emit(ConnectionEvent::Error(id, reason)).await,
+                    None => ctx.emit(ConnectionEvent::Disconnected(id)).await,
+                }
+            });
+
+            Ok(())
+        }))
+    }
+
+    fn disconnect(&mut self) -> Pin<Box<dyn std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        self.cancel_token.cancel();
+
+        Pin::from(Box::new(async move {
+            Ok(())
+        }))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -1,26 +1,30 @@
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
-use std::str::FromStr;
-use log::{debug, error, info};
+use std::time::{Duration, Instant};
+use log::{debug, error, info, warn};
 use std::pin::Pin;
 use std::net::SocketAddr;
-use tokio::net::{TcpListener, TcpStream};
+use tokio::net::{TcpListener, TcpSocket, TcpStream};
 use tokio::sync::Mutex;
-use smol::channel::{Sender, unbounded as smol_unbounded};
+use smol::channel::Sender;
 use tokio::task::JoinHandle;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use bytes::{BytesMut};
 use tokio_util::sync::CancellationToken;
-use crate::config::connection::{ClientConfig, ServerConfig};
-use crate::message::MessageType;
+use crate::config::connection::{ClientConfig, ConnectionType, MaxConnectionsPolicy, SendShaping, ServerConfig, TcpSocketOptions};
+use crate::message::{MessageDirection, MessageType};
 use crate::network::events::ConnectionEvent;
 use crate::network::interfaces::{NetworkConnection, NetworkServer};
+use crate::network::proxy::{self, ProxyTarget};
+use crate::network::queue::{self, WriteReceiver, WriteSender};
+use crate::network::resolve;
+use crate::network::tls::{self, TlsSessionInfo};
 use crate::core::message_processor::{MessageProcessor, DefaultMessageProcessor};
 use crate::network::protocol::decoder::CodecFactory;
+use crate::raw_capture::{self, RawCaptureSlot};
 
 /// 处理解码后的数据，转换为消息并发送事件（客户端用）
-fn process_decoded_data(
+async fn process_decoded_data(
     data: BytesMut,
     processor: &Arc<dyn MessageProcessor>,
     event_sender: &Option<Sender<ConnectionEvent>>,
@@ -30,14 +34,14 @@ fn process_decoded_data(
     let message = processor.process_received_message(raw_data, MessageType::Text);
     
     if let Some(sender) = event_sender {
-        if let Err(e) = sender.try_send(ConnectionEvent::MessageReceived(connection_id.to_string(), message)) {
+        if let Err(e) = sender.send(ConnectionEvent::MessageReceived(connection_id.to_string(), message)).await {
             error!("[TCP] 发送 MessageReceived 事件失败: {:?}", e);
         }
     }
 }
 
 /// 处理解码后的数据，转换为消息并发送事件（服务器端用，包含地址信息）
-fn process_decoded_data_with_addr(
+async fn process_decoded_data_with_addr(
     data: BytesMut,
     processor: &Arc<dyn MessageProcessor>,
     event_sender: &Option<Sender<ConnectionEvent>>,
@@ -62,12 +66,234 @@ fn process_decoded_data_with_addr(
     
     // 发送消息事件到UI线程
     if let Some(sender) = event_sender {
-        if let Err(e) = sender.try_send(ConnectionEvent::MessageReceived(connection_id.to_string(), message)) {
+        if let Err(e) = sender.send(ConnectionEvent::MessageReceived(connection_id.to_string(), message)).await {
             error!("[TCP服务器] 发送 MessageReceived 事件失败: {:?}", e);
         }
     }
 }
 
+/// 可读写的字节流（明文TCP或TLS）
+pub(crate) trait AsyncStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> AsyncStream for T {}
+
+/// 相邻两次连接尝试之间的间隔（RFC 8305 推荐值）
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// 多地址连接成功的结果
+pub(crate) struct ConnectOutcome {
+    pub(crate) stream: TcpStream,
+    pub(crate) peer_addr: SocketAddr,
+    /// 成功之前失败的连接尝试
+    failed_attempts: Vec<(SocketAddr, std::io::Error)>,
+}
+
+/// 将各次连接尝试的错误格式化为一行文本
+fn describe_attempts(attempts: &[(SocketAddr, std::io::Error)]) -> String {
+    attempts
+        .iter()
+        .map(|(addr, e)| format!("{}: {}", addr, e))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// 连接指定地址；指定了本地地址时先设置 SO_REUSEADDR 并绑定
+async fn connect_from(addr: SocketAddr, local: Option<SocketAddr>) -> std::io::Result<TcpStream> {
+    let Some(local) = local else {
+        return TcpStream::connect(addr).await;
+    };
+    let socket = if addr.is_ipv6() { TcpSocket::new_v6()? } else { TcpSocket::new_v4()? };
+    socket.set_reuseaddr(true)?;
+    socket.bind(local)?;
+    socket.connect(addr).await
+}
+
+/// 依次尝试连接多个地址（Happy Eyeballs）
+///
+/// addrs 中每项为服务器地址及可选的本地绑定地址。
+/// 每隔 250ms 启动下一个地址的连接尝试，前一次尝试失败时立即启动下一个，
+/// 最先建立的连接胜出，其余尝试随即取消。timeout 限制整个过程的总时长。
+pub(crate) async fn connect_happy_eyeballs(
+    target: &str,
+    addrs: &[(SocketAddr, Option<SocketAddr>)],
+    timeout: Option<Duration>,
+) -> Result<ConnectOutcome, String> {
+    let deadline = timeout.map(|t| tokio::time::Instant::now() + t);
+    let mut next_index = 0;
+    let mut attempts = tokio::task::JoinSet::new();
+    let mut failed_attempts = Vec::new();
+
+    loop {
+        if let Some(&(addr, local)) = addrs.get(next_index) {
+            next_index += 1;
+            debug!("[TCP客户端] 尝试连接 {}（本地 {:?}）", addr, local);
+            attempts.spawn(async move { (addr, connect_from(addr, local).await) });
+        } else if attempts.is_empty() {
+            return Err(format!("连接 {} 失败：{}", target, describe_attempts(&failed_attempts)));
+        }
+
+        let has_pending = next_index < addrs.len();
+        let next_attempt = async {
+            if has_pending {
+                tokio::time::sleep(CONNECTION_ATTEMPT_DELAY).await
+            } else {
+                std::future::pending().await
+            }
+        };
+        let deadline_reached = async {
+            match deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            result = attempts.join_next() => match result {
+                Some(Ok((addr, Ok(stream)))) => {
+                    // 返回时 JoinSet 被释放，其余尝试自动取消
+                    return Ok(ConnectOutcome { stream, peer_addr: addr, failed_attempts });
+                }
+                Some(Ok((addr, Err(e)))) => {
+                    error!("[TCP客户端] 连接 {} 失败: {:?}", addr, e);
+                    failed_attempts.push((addr, e));
+                }
+                Some(Err(e)) => error!("[TCP客户端] 连接任务异常: {:?}", e),
+                None => {}
+            },
+            _ = next_attempt => {}
+            _ = deadline_reached => {
+                let mut message = format!(
+                    "连接超时：{} 在 {} 秒内未响应",
+                    target,
+                    timeout.map(|t| t.as_secs()).unwrap_or_default()
+                );
+                if !failed_attempts.is_empty() {
+                    message.push_str(&format!("（{}）", describe_attempts(&failed_attempts)));
+                }
+                return Err(message);
+            }
+        }
+    }
+}
+
+/// 将套接字选项应用到TCP客户端和服务端的连接
+///
+/// 发送整形会把消息拆成多次写入时同时关闭 Nagle 算法，使每次写入单独成为一个报文段。
+pub(crate) fn apply_stream_options(stream: &TcpStream, options: &TcpSocketOptions, shaping: &SendShaping) -> Option<String> {
+    if shaping.splits_writes() && !options.nodelay {
+        return apply_socket_options(stream, &TcpSocketOptions { nodelay: true, ..options.clone() });
+    }
+    apply_socket_options(stream, options)
+}
+
+/// 将套接字选项应用到已建立的TCP连接
+///
+/// 逐项设置，单项失败不影响其余选项；返回所有失败项的说明，全部成功时返回 None。
+pub(crate) fn apply_socket_options(stream: &TcpStream, options: &TcpSocketOptions) -> Option<String> {
+    let sock = socket2::SockRef::from(stream);
+    let is_ipv6 = stream.local_addr().map(|addr| addr.is_ipv6()).unwrap_or(false);
+    let mut failures = Vec::new();
+    let mut check = |name: &str, result: std::io::Result<()>| {
+        if let Err(e) = result {
+            failures.push(format!("{}: {}", name, e));
+        }
+    };
+
+    if options.nodelay {
+        check("TCP_NODELAY", stream.set_nodelay(true));
+    }
+    if let Some((idle, interval, count)) = options.keepalive_params() {
+        let mut keepalive = socket2::TcpKeepalive::new();
+        if let Some(idle) = idle {
+            keepalive = keepalive.with_time(idle);
+        }
+        if let Some(interval) = interval {
+            keepalive = keepalive.with_interval(interval);
+        }
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "netbsd"))]
+        if let Some(count) = count {
+            keepalive = keepalive.with_retries(count);
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "netbsd")))]
+        if count.is_some() {
+            warn!("当前平台不支持设置保活探测次数，已忽略");
+        }
+        check("SO_KEEPALIVE", sock.set_tcp_keepalive(&keepalive));
+    }
+    if let Some(linger) = options.linger_duration() {
+        check("SO_LINGER", sock.set_linger(Some(linger)));
+    }
+    if options.send_buffer_size > 0 {
+        check("SO_SNDBUF", sock.set_send_buffer_size(options.send_buffer_size));
+    }
+    if options.recv_buffer_size > 0 {
+        check("SO_RCVBUF", sock.set_recv_buffer_size(options.recv_buffer_size));
+    }
+    if options.ttl > 0 {
+        if is_ipv6 {
+            check("IPV6_UNICAST_HOPS", sock.set_unicast_hops_v6(options.ttl));
+        } else {
+            check("IP_TTL", stream.set_ttl(options.ttl));
+        }
+    }
+    if let Some(tos) = options.tos {
+        if is_ipv6 {
+            #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "netbsd"))]
+            check("IPV6_TCLASS", sock.set_tclass_v6(tos));
+            #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "netbsd")))]
+            failures.push("IPV6_TCLASS: 当前平台不支持".to_string());
+        } else {
+            check("IP_TOS", sock.set_tos_v4(tos));
+        }
+    }
+
+    (!failures.is_empty()).then(|| format!("部分套接字选项设置失败：{}", failures.join("; ")))
+}
+
+/// 在可选的超时时间内写入全部数据
+pub(crate) async fn write_all_with_timeout<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    data: &[u8],
+    timeout: Option<Duration>,
+) -> std::io::Result<()> {
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, writer.write_all(data))
+            .await
+            .unwrap_or_else(|_| Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "写入超时"))),
+        None => writer.write_all(data).await,
+    }
+}
+
+/// 按发送整形写入一段已编码的数据：拆分时逐段写入，段间按设置等待
+pub(crate) async fn write_shaped<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    data: &[u8],
+    shaping: &SendShaping,
+    timeout: Option<Duration>,
+) -> std::io::Result<()> {
+    for (index, range) in shaping.segments(data.len()).into_iter().enumerate() {
+        if index > 0 {
+            if let Some(delay) = shaping.delay() {
+                tokio::time::sleep(delay).await;
+            }
+        }
+        write_all_with_timeout(writer, &data[range], timeout).await?;
+    }
+    Ok(())
+}
+
+/// 合并发送时在等待时间内继续收取排队的消息，返回包括 first 在内的全部消息
+pub(crate) async fn collect_coalesced(rx: &WriteReceiver, first: Vec<u8>, shaping: &SendShaping) -> Vec<Vec<u8>> {
+    let mut messages = vec![first];
+    if let Some(window) = shaping.coalesce_window() {
+        let deadline = tokio::time::Instant::now() + window;
+        while let Ok(Ok(message)) = tokio::time::timeout_at(deadline, rx.recv()).await {
+            messages.push(message);
+        }
+    }
+    messages
+}
+
 /// TCP客户端实现
 pub struct TcpClient {
     config: ClientConfig,
@@ -75,180 +301,515 @@ pub struct TcpClient {
     message_processor: Arc<dyn MessageProcessor>,
     is_connected: bool,
     cancel_token: CancellationToken,
+    raw_capture: RawCaptureSlot,
+    /// 当前会话的写方向关闭令牌，取消后发送 FIN 并停止写入
+    write_shutdown: Arc<std::sync::Mutex<Option<CancellationToken>>>,
+    /// 启用TLS时的连接器
+    tls_connector: Option<tokio_rustls::TlsConnector>,
+}
+
+/// TCP客户端会话上下文，自动重连时每次会话复用同一份
+#[derive(Clone)]
+struct TcpClientContext {
+    config: ClientConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    message_processor: Arc<dyn MessageProcessor>,
+    raw_capture: RawCaptureSlot,
+    write_shutdown: Arc<std::sync::Mutex<Option<CancellationToken>>>,
+    tls_connector: Option<tokio_rustls::TlsConnector>,
+}
+
+/// 已建立、可以开始收发数据的客户端连接
+pub(crate) struct ClientSession {
+    pub(crate) stream: Box<dyn AsyncStream>,
+    pub(crate) local_addr: Option<SocketAddr>,
+    pub(crate) tls_info: Option<TlsSessionInfo>,
+    /// 需在 Connected 之后提示的说明（否则会被清除）
+    pub(crate) notices: Vec<String>,
+}
+
+impl TcpClientContext {
+    /// 发送事件到UI线程
+    async fn emit(&self, event: ConnectionEvent) {
+        if let Some(sender) = &self.event_sender {
+            if let Err(e) = sender.send(event).await {
+                error!("[TCP客户端] 发送事件失败: {:?}", e);
+            }
+        } else {
+            error!("[TCP客户端] event_sender 为空，无法发送事件");
+        }
+    }
+}
+
+/// 解析服务器地址并建立TCP连接，启用TLS时一并完成握手
+///
+/// 主机名的解析结果通过 Resolved 事件告知UI；双栈主机按地址族交替尝试。
+/// 此前失败的连接尝试、套接字选项设置失败等说明随会话一并返回。
+pub(crate) async fn establish_client_stream(
+    config: &ClientConfig,
+    tls_connector: Option<&tokio_rustls::TlsConnector>,
+    event_sender: &Option<Sender<ConnectionEvent>>,
+) -> Result<ClientSession, String> {
+    let target = resolve::format_host_port(&config.server_address, config.server_port);
+
+    // 使用代理时先连接代理服务器，目标地址交给代理处理
+    let use_proxy = config.proxy.is_enabled();
+    let (dial_host, dial_port) = if use_proxy {
+        (config.proxy.address.trim(), config.proxy.port)
+    } else {
+        (config.server_address.as_str(), config.server_port)
+    };
+    let dial_target = resolve::format_host_port(dial_host, dial_port);
+
+    let addrs = resolve::resolve(dial_host, dial_port).await?;
+    if !use_proxy && !resolve::is_ip_literal(&config.server_address) {
+        info!("[TCP客户端] {} 解析为: {}", config.server_address, resolve::format_addresses(&addrs));
+        if let Some(sender) = event_sender {
+            let _ = sender.send(ConnectionEvent::Resolved(config.id.clone(), addrs.clone())).await;
+        }
+    }
+    let addrs = resolve::interleave_families(addrs);
+
+    // 为每个服务器地址确定本地绑定地址，地址族与本地地址不一致的服务器地址跳过
+    let mut targets = Vec::with_capacity(addrs.len());
+    let mut bind_error = None;
+    for addr in addrs {
+        match config.local_bind_addr(&addr) {
+            Ok(local) => targets.push((addr, local)),
+            Err(e) => bind_error = Some(e),
+        }
+    }
+    if targets.is_empty() {
+        return Err(bind_error.unwrap_or_else(|| format!("连接 {} 失败：没有可用的地址", dial_target)));
+    }
+
+    let outcome = connect_happy_eyeballs(&dial_target, &targets, config.connect_timeout()).await?;
+    info!("TCP客户端连接成功: {}", outcome.peer_addr);
+
+    let mut notices = Vec::new();
+    if !outcome.failed_attempts.is_empty() {
+        notices.push(format!(
+            "已连接到 {}，此前的尝试失败：{}",
+            outcome.peer_addr,
+            describe_attempts(&outcome.failed_attempts)
+        ));
+    }
+
+    let mut stream = outcome.stream;
+    if let Some(warning) = apply_stream_options(&stream, &config.socket_options, &config.send_shaping) {
+        warn!("[TCP客户端] {}", warning);
+        notices.push(warning);
+    }
+    if use_proxy {
+        let (proxy_target, resolved) = ProxyTarget::from_host(&config.proxy, &config.server_address, config.server_port).await?;
+        if let (Some(addrs), Some(sender)) = (resolved, event_sender) {
+            let _ = sender.send(ConnectionEvent::Resolved(config.id.clone(), addrs)).await;
+        }
+        proxy::open_tunnel(&mut stream, &config.proxy, &proxy_target, config.connect_timeout()).await?;
+        notices.push(format!("经 {} 代理连接到 {}", config.proxy, target));
+    }
+    let local_addr = match stream.local_addr() {
+        Ok(local_addr) => {
+            info!("[TCP客户端] 本地端点: {}", local_addr);
+            Some(local_addr)
+        }
+        Err(e) => {
+            warn!("[TCP客户端] 获取本地地址失败: {:?}", e);
+            None
+        }
+    };
+
+    let (stream, tls_info): (Box<dyn AsyncStream>, Option<TlsSessionInfo>) = match tls_connector {
+        Some(connector) => {
+            let server_name = config.tls.effective_server_name(&config.server_address);
+            let (stream, tls_info) = tls::connect(connector, server_name, stream, config.connect_timeout()).await?;
+            info!("[TCP客户端] TLS握手完成: {} {}", tls_info.version, tls_info.cipher);
+            (Box::new(stream), Some(tls_info))
+        }
+        None => (Box::new(stream), None),
+    };
+
+    Ok(ClientSession { stream, local_addr, tls_info, notices })
 }
 
 impl TcpClient {
+    /// 建立本客户端的连接，见 establish_client_stream
+    async fn establish(ctx: &TcpClientContext) -> Result<ClientSession, String> {
+        #[cfg(unix)]
+        if ctx.config.protocol == ConnectionType::UnixStream {
+            return super::unix::connect_stream(&ctx.config).await;
+        }
+        establish_client_stream(&ctx.config, ctx.tls_connector.as_ref(), &ctx.event_sender).await
+    }
+
+    /// 创建客户端，服务器地址格式无效时返回错误
+    ///
+    /// Unix域流式套接字客户端同样由 TcpClient 驱动，仅建立连接的方式不同。
     pub fn new(
         config: ClientConfig,
-        event_sender: Option<Sender<ConnectionEvent>>
-    ) -> Self {
-        TcpClient {
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Result<Self, String> {
+        if config.protocol == ConnectionType::UnixStream {
+            if config.unix.path.trim().is_empty() {
+                return Err("Unix域套接字路径不能为空".to_string());
+            }
+        } else {
+            resolve::validate_remote(&config.server_address, config.server_port)
+                .map_err(|e| format!("无效的TCP服务器地址: {}", e))?;
+            config.proxy.validate(config.protocol)?;
+        }
+        let tls_connector = if config.tls.enabled && config.protocol == ConnectionType::Tcp {
+            Some(tls::connector(&config.tls)?)
+        } else {
+            None
+        };
+
+        Ok(TcpClient {
             config,
             event_sender,
             message_processor: Arc::new(DefaultMessageProcessor),
             is_connected: false,
             cancel_token: CancellationToken::new(),
-        }
+            raw_capture,
+            write_shutdown: Arc::new(std::sync::Mutex::new(None)),
+            tls_connector,
+        })
     }
-}
 
-impl NetworkConnection for TcpClient {
-    fn connect(&mut self) -> Pin<Box<dyn std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
-        let config = self.config.clone();
-        let event_sender = self.event_sender.clone();
-        let message_processor = self.message_processor.clone();
-        let cancel_token = self.cancel_token.clone();
-        
-        Pin::from(Box::new(async move {
-            // 解析地址，支持IPv4和IPv6
-            let address = if config.server_address.contains(':') && !config.server_address.contains('[') {
-                // IPv6地址需要方括号
-                format!("[{}]:{}", config.server_address, config.server_port)
-            } else {
-                format!("{}:{}", config.server_address, config.server_port)
-            };
-            
-            let socket_addr = SocketAddr::from_str(&address)
-                .map_err(|e| format!("无效的地址格式 '{}': {}", address, e))?;
-            
-            info!("TCP客户端连接到地址: {}", socket_addr);
-            
-            let socket = TcpStream::connect(socket_addr).await?;
-            info!("TCP客户端连接成功: {}", socket_addr);
-            
-            // 创建发送器和接收器
-            let (tx, rx) = smol_unbounded::<Vec<u8>>();
-            
-            // 发送连接成功事件到UI线程
-            if let Some(sender) = &event_sender {
-                debug!("[TCP客户端] 发送 Connected 事件");
-                if let Err(e) = sender.send(ConnectionEvent::Connected(config.id.clone())).await {
-                    error!("[TCP客户端] 发送 Connected 事件失败: {:?}", e);
-                }
-                debug!("[TCP客户端] 发送 ClientWriteSenderReady 事件");
-                if let Err(e) = sender.send(ConnectionEvent::ClientWriteSenderReady(config.id.clone(), tx)).await {
-                    error!("[TCP客户端] 发送 ClientWriteSenderReady 事件失败: {:?}", e);
-                }
-            } else {
-                error!("[TCP客户端] event_sender 为空，无法发送事件");
+    /// 关闭当前连接的写方向（发送 FIN），读方向保持打开直到对端关闭
+    pub fn shutdown_write(&self) -> Result<(), String> {
+        let guard = self.write_shutdown.lock().map_err(|_| "写方向状态不可用".to_string())?;
+        match guard.as_ref() {
+            Some(token) if token.is_cancelled() => Err("写方向已关闭".to_string()),
+            Some(token) => {
+                token.cancel();
+                Ok(())
             }
-            
-            // 创建decoder和encoder
-            let (mut socket_read, mut socket_write) = tokio::io::split(socket);
-            
-            // 启动接收消息任务
-            let event_sender_clone = event_sender.clone();
-            let config_clone = config.clone();
-            let message_processor_clone = message_processor.clone();
-            let decoder_config = config.decoder_config.clone();
-            let read_cancel_token = cancel_token.clone();
-            tokio::spawn(async move {
-                let mut buffer = BytesMut::with_capacity(16384);
-                
-                let mut decoder = crate::network::protocol::decoder::CodecFactory::create_decoder(&decoder_config);
-                
-                loop {
-                    tokio::select! {
-                        result = socket_read.read_buf(&mut buffer) => {
-                            match result {
-                                Ok(0) => {
-                                    info!("TCP连接已关闭");
+            None => Err("连接未建立".to_string()),
+        }
+    }
+
+    /// 运行一次已建立的TCP会话
+    ///
+    /// 通知UI连接成功并交出写入发送器，启动发送任务，然后在当前任务中执行接收循环，
+    /// 直到读写两个方向都已关闭、读取出错或收到取消信号后返回。返回时发送任务也会随之结束。
+    /// 对端半关闭（收到 FIN）时仅通知UI，写方向保持可用；本端关闭写方向后继续接收数据。
+    /// 因读取空闲超时或写入超时而结束时返回错误描述。
+    async fn run_session(
+        session: ClientSession,
+        ctx: TcpClientContext,
+        cancel_token: CancellationToken,
+    ) -> Option<String> {
+        let config = &ctx.config;
+        let write_timeout = config.write_timeout();
+        let read_idle_timeout = config.read_idle_timeout();
+        let send_shaping = config.send_shaping;
+
+        // 创建发送器和接收器
+        let (tx, rx) = queue::write_queue(&config.queue);
+
+        // 发送连接成功事件到UI线程
+        debug!("[TCP客户端] 发送 Connected 事件");
+        ctx.emit(ConnectionEvent::Connected(config.id.clone())).await;
+        debug!("[TCP客户端] 发送 ClientWriteSenderReady 事件");
+        ctx.emit(ConnectionEvent::ClientWriteSenderReady(config.id.clone(), tx)).await;
+        for notice in session.notices {
+            ctx.emit(ConnectionEvent::Warning(config.id.clone(), notice)).await;
+        }
+        if let Some(local_addr) = session.local_addr {
+            ctx.emit(ConnectionEvent::LocalAddress(config.id.clone(), local_addr)).await;
+        }
+        if let Some(tls_info) = session.tls_info {
+            ctx.emit(ConnectionEvent::TlsEstablished(config.id.clone(), None, tls_info)).await;
+        }
+
+        // 创建decoder和encoder
+        let (mut socket_read, mut socket_write) = tokio::io::split(session.stream);
+
+        // 每次会话使用独立的子令牌，接收循环结束时一并结束发送任务
+        let session_token = cancel_token.child_token();
+
+        // 发送任务因超时退出时通过该通道告知接收循环
+        let (write_error_tx, mut write_error_rx) = tokio::sync::oneshot::channel::<String>();
+
+        // 本会话的写方向关闭令牌，供 shutdown_write 使用
+        let write_shutdown_token = CancellationToken::new();
+        if let Ok(mut guard) = ctx.write_shutdown.lock() {
+            *guard = Some(write_shutdown_token.clone());
+        }
+
+        // 启动发送消息任务
+        let encoder_for_write = CodecFactory::create_encoder(&config.decoder_config);
+        let write_cancel_token = session_token.clone();
+        let write_raw_capture = ctx.raw_capture.clone();
+        let write_ctx = ctx.clone();
+        let mut write_task = tokio::spawn(async move {
+            let mut encoder = encoder_for_write;
+            loop {
+                tokio::select! {
+                    data = rx.recv() => {
+                        match data {
+                            Ok(data) => {
+                                // 合并发送时先收取等待时间内排队的消息，逐条编码后按整形设置写入
+                                let messages = collect_coalesced(&rx, data, &send_shaping).await;
+                                let mut buffer = BytesMut::with_capacity(messages.iter().map(Vec::len).sum());
+                                
+                                if let Err(e) = messages
+                                    .into_iter()
+                                    .try_for_each(|data| encoder.encode(BytesMut::from(data.as_slice()), &mut buffer))
+                                {
+                                    error!("TCP编码错误: {:?}", e);
                                     break;
-                                },
-                                Ok(n) => {
-                                    debug!("TCP客户端读取了 {} 字节数据", n);
-                                    
-                                    loop {
-                                        match decoder.decode(&mut buffer) {
-                                            Ok(Some(data)) => {
-                                                let data: BytesMut = data;
-                                                process_decoded_data(
-                                                    data, 
-                                                    &message_processor_clone, 
-                                                    &event_sender_clone, 
-                                                    &config_clone.id
-                                                );
-                                            },
-                                            Ok(None) => {
-                                                break;
-                                            },
-                                            Err(e) => {
-                                                error!("TCP解码错误: {:?}", e);
-                                                break;
-                                            }
-                                        }
+                                }
+                                
+                                if let Err(e) = write_shaped(&mut socket_write, &buffer, &send_shaping, write_timeout).await {
+                                    error!("TCP写入错误: {:?}", e);
+                                    if e.kind() == std::io::ErrorKind::TimedOut {
+                                        let timeout_secs = write_timeout.map(|t| t.as_secs()).unwrap_or_default();
+                                        let _ = write_error_tx.send(format!(
+                                            "写入超时：对端 {} 秒内未接收数据，已断开连接",
+                                            timeout_secs
+                                        ));
+                                        write_cancel_token.cancel();
                                     }
-                                },
-                                Err(e) => {
-                                    error!("TCP读取错误: {:?}", e);
                                     break;
                                 }
+                                raw_capture::record(&write_raw_capture, None, MessageDirection::Sent, &buffer);
+                            },
+                            Err(_) => {
+                                debug!("消息发送通道已关闭");
+                                break;
                             }
                         }
-                        
-                        _ = tokio::time::sleep(Duration::from_millis(50)) => {
-                            if let Some(data) = decoder.force_flush() {
-                                let data: BytesMut = data;
-                                process_decoded_data(
-                                    data, 
-                                    &message_processor_clone, 
-                                    &event_sender_clone, 
-                                    &config_clone.id
-                                );
+                    }
+                    
+                    _ = write_shutdown_token.cancelled() => {
+                        info!("TCP客户端关闭写方向（发送 FIN）");
+                        match socket_write.shutdown().await {
+                            Ok(()) => write_ctx.emit(ConnectionEvent::WriteShutdown(write_ctx.config.id.clone())).await,
+                            Err(e) => {
+                                error!("TCP关闭写方向失败: {:?}", e);
+                                write_ctx.emit(ConnectionEvent::Warning(
+                                    write_ctx.config.id.clone(),
+                                    format!("关闭写方向失败: {}", e),
+                                )).await;
                             }
                         }
-                        
-                        _ = read_cancel_token.cancelled() => {
-                            info!("TCP客户端读任务收到取消信号，退出");
-                            break;
-                        }
+                        break;
                     }
-                }
-                
-                if let Some(sender) = &event_sender_clone {
-                    if let Err(e) = sender.send(ConnectionEvent::Disconnected(config_clone.id.clone())).await {
-                        error!("[TCP客户端] 发送 Disconnected 事件失败: {:?}", e);
+
+                    _ = write_cancel_token.cancelled() => {
+                        info!("TCP客户端写任务收到取消信号，执行优雅关闭");
+                        let _ = socket_write.shutdown().await;
+                        break;
                     }
                 }
-            });
-            
-            // 启动发送消息任务
-            let encoder_for_write = CodecFactory::create_encoder(&config.decoder_config);
-            let write_cancel_token = cancel_token.clone();
-            tokio::spawn(async move {
-                let mut encoder = encoder_for_write;
-                loop {
-                    tokio::select! {
-                        data = rx.recv() => {
-                            match data {
-                                Ok(data) => {
-                                    let mut buffer = BytesMut::with_capacity(data.len());
-                                    let data_bytes = BytesMut::from(data.as_slice());
-                                    
-                                    if let Err(e) = encoder.encode(data_bytes, &mut buffer) {
-                                        error!("TCP编码错误: {:?}", e);
+            }
+        });
+
+        // 接收消息循环
+        let mut buffer = BytesMut::with_capacity(16384);
+        let mut decoder = CodecFactory::create_decoder(&config.decoder_config);
+        let mut last_read = Instant::now();
+        let mut idle_notified = false;
+        let mut session_error = None;
+        // 对端已半关闭（收到 FIN），此后只等待写方向结束
+        let mut read_closed = false;
+        
+        loop {
+            tokio::select! {
+                result = socket_read.read_buf(&mut buffer), if !read_closed => {
+                    match result {
+                        Ok(0) => {
+                            if write_task.is_finished() {
+                                info!("TCP连接已关闭");
+                                break;
+                            }
+                            info!("TCP对端已关闭写方向，本端仍可发送");
+                            read_closed = true;
+                            ctx.emit(ConnectionEvent::PeerHalfClosed(config.id.clone())).await;
+                        },
+                        Ok(n) => {
+                            debug!("TCP客户端读取了 {} 字节数据", n);
+                            last_read = Instant::now();
+                            idle_notified = false;
+                            // 解码前录制原始字节
+                            raw_capture::record(&ctx.raw_capture, None, MessageDirection::Received, &buffer[buffer.len() - n..]);
+                            
+                            loop {
+                                match decoder.decode(&mut buffer) {
+                                    Ok(Some(data)) => {
+                                        let data: BytesMut = data;
+                                        process_decoded_data(
+                                            data, 
+                                            &ctx.message_processor, 
+                                            &ctx.event_sender, 
+                                            &config.id
+                                        ).await;
+                                    },
+                                    Ok(None) => {
                                         break;
-                                    }
-                                    
-                                    if let Err(e) = socket_write.write_all(&buffer).await {
-                                        error!("TCP写入错误: {:?}", e);
+                                    },
+                                    Err(e) => {
+                                        error!("TCP解码错误: {:?}", e);
                                         break;
                                     }
-                                },
-                                Err(_) => {
-                                    debug!("消息发送通道已关闭");
-                                    break;
                                 }
                             }
-                        }
-                        
-                        _ = write_cancel_token.cancelled() => {
-                            info!("TCP客户端写任务收到取消信号，执行优雅关闭");
-                            let _ = socket_write.shutdown().await;
+                        },
+                        Err(e) => {
+                            error!("TCP读取错误: {:?}", e);
                             break;
                         }
                     }
                 }
+                
+                // 对端已半关闭时，写方向结束（本端发送 FIN 或写入出错）即结束会话
+                _ = &mut write_task, if read_closed => {
+                    info!("TCP连接读写方向均已关闭");
+                    break;
+                }
+                
+                _ = tokio::time::sleep(Duration::from_millis(50)) => {
+                    if let Some(data) = decoder.force_flush() {
+                        let data: BytesMut = data;
+                        process_decoded_data(
+                            data, 
+                            &ctx.message_processor, 
+                            &ctx.event_sender, 
+                            &config.id
+                        ).await;
+                    }
+
+                    // 读取空闲超时检查（对端半关闭后不再有数据，不再检查）
+                    if let Some(idle_timeout) = read_idle_timeout.filter(|_| !read_closed) {
+                        if !idle_notified && last_read.elapsed() >= idle_timeout {
+                            if config.read_idle_close {
+                                info!("TCP客户端读取空闲超时，断开连接");
+                                session_error = Some(format!(
+                                    "读取空闲超时：{} 秒内未收到数据，已断开连接",
+                                    idle_timeout.as_secs()
+                                ));
+                                break;
+                            }
+                            idle_notified = true;
+                            ctx.emit(ConnectionEvent::TimeoutError(
+                                config.id.clone(),
+                                None,
+                                format!("读取空闲超时：已 {} 秒未收到数据", idle_timeout.as_secs()),
+                            )).await;
+                        }
+                    }
+                }
+                
+                _ = session_token.cancelled() => {
+                    info!("TCP客户端读任务收到取消信号，退出");
+                    break;
+                }
+            }
+        }
+
+        session_token.cancel();
+        if let Ok(mut guard) = ctx.write_shutdown.lock() {
+            *guard = None;
+        }
+        session_error.or_else(|| write_error_rx.try_recv().ok())
+    }
+
+    /// 自动重连监督循环
+    ///
+    /// 连接失败或会话结束后按指数退避重试，达到最大重试次数后放弃；
+    /// 每次成功连接都会重置重试计数。
+    async fn run_with_reconnect(ctx: TcpClientContext, cancel_token: CancellationToken) {
+        let id = ctx.config.id.clone();
+        let mut attempt: u32 = 0;
+
+        loop {
+            // 每次重连重新解析主机名，以便跟随DNS变化
+            let result = tokio::select! {
+                result = Self::establish(&ctx) => result,
+                _ = cancel_token.cancelled() => break,
+            };
+
+            match result {
+                Ok(session) => {
+                    attempt = 0;
+                    let session_error = Self::run_session(session, ctx.clone(), cancel_token.clone()).await;
+                    if cancel_token.is_cancelled() {
+                        break;
+                    }
+                    // 超时断开时报告原因，随后照常重连
+                    if let Some(reason) = session_error {
+                        ctx.emit(ConnectionEvent::TimeoutError(id.clone(), None, reason)).await;
+                    }
+                    info!("[TCP客户端] 连接断开，准备重连: {}", id);
+                }
+                Err(e) => {
+                    error!("[TCP客户端] {}", e);
+                    ctx.emit(ConnectionEvent::Warning(id.clone(), e)).await;
+                }
+            }
+
+            attempt += 1;
+            if ctx.config.reconnect_attempts_exhausted(attempt) {
+                error!("[TCP客户端] 重连 {} 次仍未成功，放弃重连", ctx.config.reconnect_max_attempts);
+                ctx.emit(ConnectionEvent::Error(
+                    id.clone(),
+                    format!("重连失败：已尝试 {} 次", ctx.config.reconnect_max_attempts),
+                )).await;
+                break;
+            }
+
+            let delay = ctx.config.reconnect_delay(attempt);
+            info!("[TCP客户端] 第 {} 次重连将在 {:?} 后进行", attempt, delay);
+            ctx.emit(ConnectionEvent::Reconnecting(id.clone(), attempt, delay)).await;
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = cancel_token.cancelled() => break,
+            }
+        }
+
+        debug!("[TCP客户端] 重连循环已退出: {}", id);
+    }
+}
+
+impl NetworkConnection for TcpClient {
+    fn connect(&mut self) -> Pin<Box<dyn std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        let ctx = TcpClientContext {
+            config: self.config.clone(),
+            event_sender: self.event_sender.clone(),
+            message_processor: self.message_processor.clone(),
+            raw_capture: self.raw_capture.clone(),
+            write_shutdown: self.write_shutdown.clone(),
+            tls_connector: self.tls_connector.clone(),
+        };
+        let cancel_token = self.cancel_token.clone();
+        
+        Pin::from(Box::new(async move {
+            let config = &ctx.config;
+            if config.protocol == ConnectionType::UnixStream {
+                info!("Unix客户端连接到: {}", config.unix.path);
+            } else {
+                info!("TCP客户端连接到: {}:{}", config.server_address, config.server_port);
+            }
+
+            // 开启自动重连时，连接、会话和重试全部交给后台监督任务
+            if config.auto_reconnect {
+                tokio::spawn(Self::run_with_reconnect(ctx, cancel_token));
+                return Ok(());
+            }
+            
+            // 连接失败时由连接管理器将错误转发给UI
+            let session = Self::establish(&ctx).await?;
+            
+            tokio::spawn(async move {
+                let id = ctx.config.id.clone();
+                match Self::run_session(session, ctx.clone(), cancel_token).await {
+                    Some(reason) => ctx.emit(ConnectionEvent::Error(id, reason)).await,
+                    None => ctx.emit(ConnectionEvent::Disconnected(id)).await,
+                }
             });
             
             Ok(())
@@ -263,20 +824,40 @@ impl NetworkConnection for TcpClient {
             Ok(())
         }))
     }
-    
 
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// 服务端关闭单个客户端连接的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientCloseMode {
+    /// 优雅关闭，发送 FIN
+    Graceful,
+    /// 强制关闭（SO_LINGER 为 0），发送 RST
+    Abort,
+}
+
+/// 服务端已接受客户端的控制句柄
+pub(crate) struct ClientControl {
+    /// 接受连接的时间，用于“关闭最早连接”策略
+    pub(crate) accepted_at: Instant,
+    /// 通知客户端任务按指定方式关闭连接
+    pub(crate) close_tx: tokio::sync::oneshot::Sender<ClientCloseMode>,
 }
 
 /// TCP服务器实现
 pub struct TcpServer {
     config: ServerConfig,
     event_sender: Option<Sender<ConnectionEvent>>,
-    clients: Arc<Mutex<HashMap<SocketAddr, Sender<Vec<u8>>>>>,
+    clients: Arc<Mutex<HashMap<SocketAddr, WriteSender>>>,
     message_processor: Arc<dyn MessageProcessor>,
     is_running: bool,
     listener_handle: Option<JoinHandle<()>>,
     client_handles: Arc<Mutex<HashMap<SocketAddr, JoinHandle<()>>>>,
-    listener: Option<Arc<TcpListener>>,
+    client_controls: Arc<Mutex<HashMap<SocketAddr, ClientControl>>>,
+    raw_capture: RawCaptureSlot,
 }
 
 /// 实现Drop trait，确保资源被正确释放
@@ -293,7 +874,8 @@ impl Drop for TcpServer {
 impl TcpServer {
     pub fn new(
         config: ServerConfig,
-        event_sender: Option<Sender<ConnectionEvent>>
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
     ) -> Self {
         TcpServer {
             config,
@@ -303,7 +885,557 @@ impl TcpServer {
             is_running: false,
             listener_handle: None,
             client_handles: Arc::new(Mutex::new(HashMap::new())),
-            listener: None,
+            client_controls: Arc::new(Mutex::new(HashMap::new())),
+            raw_capture,
+        }
+    }
+
+    /// 关闭单个客户端连接，其余客户端和监听不受影响
+    pub async fn close_client(&self, addr: SocketAddr, mode: ClientCloseMode) -> Result<(), String> {
+        let control = self.client_controls.lock().await.remove(&addr);
+        match control {
+            Some(control) => {
+                info!("[TCP服务器] 关闭客户端 {} ({:?})", addr, mode);
+                let _ = control.close_tx.send(mode);
+                Ok(())
+            }
+            None => Err(format!("客户端 {} 不存在", addr)),
+        }
+    }
+}
+
+/// 服务端单个客户端连接的会话信息（TCP和Unix域套接字共用）
+pub(crate) struct ServerClientSession {
+    pub(crate) server_id: String,
+    /// 客户端标识，Unix域套接字为服务端分配的虚拟地址
+    pub(crate) addr: SocketAddr,
+    /// 日志和提示中显示的客户端名称
+    pub(crate) label: String,
+    pub(crate) config: ServerConfig,
+    pub(crate) event_sender: Option<Sender<ConnectionEvent>>,
+    pub(crate) message_processor: Arc<dyn MessageProcessor>,
+    pub(crate) raw_capture: RawCaptureSlot,
+}
+
+/// 按最大连接数策略接纳新客户端，并注册用于单独关闭该客户端的控制句柄
+///
+/// 以控制句柄计数，被关闭的客户端立即不再占用名额。拒绝新连接时返回 None。
+pub(crate) async fn admit_client(
+    config: &ServerConfig,
+    client_controls: &Mutex<HashMap<SocketAddr, ClientControl>>,
+    addr: SocketAddr,
+    event_sender: &Option<Sender<ConnectionEvent>>,
+) -> Option<tokio::sync::oneshot::Receiver<ClientCloseMode>> {
+    let mut controls_guard = client_controls.lock().await;
+    if config.connection_limit_reached(controls_guard.len()) {
+        let warning = match config.max_connections_policy {
+            MaxConnectionsPolicy::Reject => {
+                drop(controls_guard);
+                info!("服务器已达最大连接数 {}，拒绝 {}", config.max_connections, addr);
+                if let Some(sender) = event_sender {
+                    let _ = sender.send(ConnectionEvent::Warning(
+                        config.id.clone(),
+                        format!("已达最大连接数 {}，拒绝来自 {} 的连接", config.max_connections, addr),
+                    )).await;
+                }
+                return None;
+            }
+            MaxConnectionsPolicy::CloseOldest => {
+                let oldest = controls_guard
+                    .iter()
+                    .min_by_key(|(_, control)| control.accepted_at)
+                    .map(|(oldest_addr, _)| *oldest_addr);
+                oldest.and_then(|oldest_addr| {
+                    let control = controls_guard.remove(&oldest_addr)?;
+                    let _ = control.close_tx.send(ClientCloseMode::Graceful);
+                    info!("服务器已达最大连接数 {}，关闭最早的客户端 {}", config.max_connections, oldest_addr);
+                    Some(format!(
+                        "已达最大连接数 {}，已关闭最早的客户端 {}",
+                        config.max_connections, oldest_addr
+                    ))
+                })
+            }
+        };
+        let close_rx = insert_client_control(&mut controls_guard, addr);
+        // 发送事件可能因接收队列已满而等待，先释放锁，避免阻塞关闭客户端等操作
+        drop(controls_guard);
+        if let (Some(sender), Some(warning)) = (event_sender, warning) {
+            let _ = sender.send(ConnectionEvent::Warning(config.id.clone(), warning)).await;
+        }
+        return Some(close_rx);
+    }
+
+    Some(insert_client_control(&mut controls_guard, addr))
+}
+
+fn insert_client_control(
+    controls: &mut HashMap<SocketAddr, ClientControl>,
+    addr: SocketAddr,
+) -> tokio::sync::oneshot::Receiver<ClientCloseMode> {
+    let (close_tx, close_rx) = tokio::sync::oneshot::channel::<ClientCloseMode>();
+    controls.insert(addr, ClientControl {
+        accepted_at: Instant::now(),
+        close_tx,
+    });
+    close_rx
+}
+
+/// 启动客户端任务并登记其句柄
+///
+/// 登记时持有句柄表的锁：客户端立即断开时，任务在 release_client 中移除句柄的操作
+/// 必然发生在登记之后，不会留下已结束任务的句柄。
+pub(crate) async fn spawn_client_task<F>(
+    client_handles: &Mutex<HashMap<SocketAddr, JoinHandle<()>>>,
+    addr: SocketAddr,
+    task: F,
+) where
+    F: std::future::Future<Output = ()> + Send + 'static,
+{
+    let mut handles = client_handles.lock().await;
+    handles.insert(addr, tokio::spawn(task));
+}
+
+/// 创建客户端的发送通道并加入客户端表，通知UI有新客户端连接
+pub(crate) async fn register_client(
+    clients: &Mutex<HashMap<SocketAddr, WriteSender>>,
+    session: &ServerClientSession,
+) -> WriteReceiver {
+    let (tx, rx) = queue::write_queue(&session.config.queue);
+    clients.lock().await.insert(session.addr, tx.clone());
+
+    if let Some(sender) = &session.event_sender {
+        if let Err(e) = sender.send(ConnectionEvent::ServerClientConnected(
+            session.server_id.clone(),
+            session.addr,
+            tx,
+        )).await {
+            error!("[服务器] 发送 ServerClientConnected 事件失败: {:?}", e);
+        }
+    }
+    rx
+}
+
+/// 客户端连接结束后清理其发送通道、任务句柄和控制句柄（主动关闭时已被移除），并通知UI
+pub(crate) async fn release_client(
+    clients: &Mutex<HashMap<SocketAddr, WriteSender>>,
+    client_handles: &Mutex<HashMap<SocketAddr, JoinHandle<()>>>,
+    client_controls: &Mutex<HashMap<SocketAddr, ClientControl>>,
+    session: &ServerClientSession,
+) {
+    client_controls.lock().await.remove(&session.addr);
+    clients.lock().await.remove(&session.addr);
+    client_handles.lock().await.remove(&session.addr);
+
+    if let Some(sender) = &session.event_sender {
+        if let Err(e) = sender.send(ConnectionEvent::ServerClientDisconnected(
+            session.server_id.clone(),
+            session.addr,
+        )).await {
+            error!("[服务器] 发送 ServerClientDisconnected 事件失败: {:?}", e);
+        }
+    }
+}
+
+/// 运行服务端单个客户端的收发循环
+///
+/// 接收循环按配置解码后上报消息，发送循环编码UI发来的数据；任何一个结束都终止另一个，
+/// 也可由服务端通过 close_rx 主动关闭。优雅关闭时先关闭写方向（发送 FIN）再返回，
+/// 返回值为服务端主动关闭的方式，强制关闭的后续处理由调用方完成。
+pub(crate) async fn serve_client_stream<S: AsyncStream>(
+    stream: S,
+    rx: WriteReceiver,
+    close_rx: tokio::sync::oneshot::Receiver<ClientCloseMode>,
+    session: &ServerClientSession,
+) -> Option<ClientCloseMode> {
+    let addr = session.addr;
+    let label = &session.label;
+    let (mut socket_read, mut socket_write) = tokio::io::split(stream);
+
+    // 根据配置创建具体的编解码器
+    let encoder = CodecFactory::create_encoder(&session.config.decoder_config);
+    let write_timeout = session.config.write_timeout();
+    let send_shaping = session.config.send_shaping;
+    let read_idle_timeout = session.config.read_idle_timeout();
+    let read_idle_close = session.config.read_idle_close;
+
+    // 超时错误（仅针对该客户端，不影响服务端状态）
+    let report_timeout = |reason: String| async move {
+        if let Some(sender) = &session.event_sender {
+            if let Err(e) = sender.send(ConnectionEvent::TimeoutError(session.server_id.clone(), Some(addr), reason)).await {
+                error!("[服务器] 发送 TimeoutError 事件失败: {:?}", e);
+            }
+        }
+    };
+
+    // 接收消息循环
+    let recv_fut = async {
+        let mut buffer = BytesMut::with_capacity(16384); // 16KB缓冲区
+        let mut decoder = CodecFactory::create_decoder(&session.config.decoder_config);
+        let mut last_read = Instant::now();
+        let mut idle_notified = false;
+
+        loop {
+            tokio::select! {
+                // 数据读取事件
+                result = socket_read.read_buf(&mut buffer) => {
+                    match result {
+                        Ok(0) => {
+                            // 客户端关闭连接
+                            debug!("客户端 {} 断开连接", label);
+                            break;
+                        },
+                        Ok(n) => {
+                            debug!("服务器从 {} 读取了 {} 字节数据", label, n);
+                            last_read = Instant::now();
+                            idle_notified = false;
+                            // 解码前录制原始字节
+                            raw_capture::record(&session.raw_capture, Some(addr), MessageDirection::Received, &buffer[buffer.len() - n..]);
+
+                            // 使用decoder解码数据，循环处理所有可用消息
+                            loop {
+                                match decoder.decode(&mut buffer) {
+                                    Ok(Some(data)) => {
+                                        process_decoded_data_with_addr(
+                                            data,
+                                            &session.message_processor,
+                                            &session.event_sender,
+                                            &session.server_id,
+                                            &addr.to_string()
+                                        ).await;
+                                    },
+                                    Ok(None) => {
+                                        // 解码器需要更多数据，退出循环
+                                        break;
+                                    },
+                                    Err(e) => {
+                                        error!("服务器解码来自 {} 的消息时发生错误: {:?}", label, e);
+                                        break;
+                                    }
+                                }
+                            }
+                        },
+                        Err(e) => {
+                            error!("服务器读取来自 {} 的消息时发生错误: {:?}", label, e);
+                            break;
+                        }
+                    }
+                }
+
+                // 50ms超时事件 - 强制刷新缓冲区
+                _ = tokio::time::sleep(Duration::from_millis(50)) => {
+                    if let Some(data) = decoder.force_flush() {
+                        process_decoded_data_with_addr(
+                            data,
+                            &session.message_processor,
+                            &session.event_sender,
+                            &session.server_id,
+                            &addr.to_string()
+                        ).await;
+                    }
+
+                    // 读取空闲超时检查
+                    if let Some(idle_timeout) = read_idle_timeout {
+                        if !idle_notified && last_read.elapsed() >= idle_timeout {
+                            idle_notified = true;
+                            if read_idle_close {
+                                report_timeout(format!(
+                                    "客户端 {} 读取空闲超时：{} 秒内未收到数据，已断开",
+                                    label,
+                                    idle_timeout.as_secs()
+                                )).await;
+                                break;
+                            }
+                            report_timeout(format!(
+                                "客户端 {} 读取空闲超时：已 {} 秒未收到数据",
+                                label,
+                                idle_timeout.as_secs()
+                            )).await;
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    // 发送消息循环
+    let send_fut = async {
+        let mut encoder = encoder;
+        loop {
+            match rx.recv().await {
+                Ok(message) => {
+                    // 合并发送时先收取等待时间内排队的消息
+                    let messages = collect_coalesced(&rx, message, &send_shaping).await;
+                    let mut buffer = BytesMut::with_capacity(messages.iter().map(Vec::len).sum());
+
+                    // 使用encoder逐条编码数据
+                    if let Err(e) = messages
+                        .into_iter()
+                        .try_for_each(|message| encoder.encode(BytesMut::from(message.as_slice()), &mut buffer))
+                    {
+                        error!("服务器编码消息时发生错误: {:?}", e);
+                        break;
+                    }
+
+                    // 按发送整形写入数据
+                    if let Err(e) = write_shaped(&mut socket_write, &buffer, &send_shaping, write_timeout).await {
+                        error!("服务器向 {} 发送消息时发生错误: {:?}", label, e);
+                        if e.kind() == std::io::ErrorKind::TimedOut {
+                            report_timeout(format!(
+                                "向客户端 {} 写入超时：{} 秒内未接收数据，已断开",
+                                label,
+                                write_timeout.map(|t| t.as_secs()).unwrap_or_default()
+                            )).await;
+                        }
+                        break;
+                    }
+                    raw_capture::record(&session.raw_capture, Some(addr), MessageDirection::Sent, &buffer);
+                    debug!("服务器向 {} 发送 {} 字节", label, buffer.len());
+                },
+                Err(_) => {
+                    debug!("服务器发送消息通道已关闭");
+                    break;
+                }
+            }
+        }
+    };
+
+    // 同时运行接收和发送循环，任何一个结束都终止另一个；也可由服务端主动关闭
+    let close_mode = tokio::select! {
+        _ = recv_fut => {
+            debug!("服务器接收循环结束");
+            None
+        },
+        _ = send_fut => {
+            debug!("服务器发送循环结束");
+            None
+        },
+        mode = close_rx => mode.ok(),
+    };
+
+    if close_mode == Some(ClientCloseMode::Graceful) {
+        // 发送 FIN，随后释放套接字
+        let _ = socket_write.shutdown().await;
+        debug!("服务器已优雅关闭客户端 {}", label);
+    }
+    close_mode
+}
+
+/// 解析服务端的全部监听地址（主机名绑定首个解析结果），并把主机名的解析结果通知UI
+pub(crate) async fn resolve_listen_addrs(
+    config: &ServerConfig,
+    event_sender: &Option<Sender<ConnectionEvent>>,
+    tag: &str,
+) -> Result<Vec<SocketAddr>, String> {
+    let mut listen_addrs: Vec<SocketAddr> = Vec::new();
+    let mut resolved: Vec<SocketAddr> = Vec::new();
+    for host in config.listen_hosts() {
+        let addrs = resolve::resolve(&host, config.listen_port).await?;
+        if !resolve::is_ip_literal(&host) {
+            info!("[{}] {} 解析为: {}", tag, host, resolve::format_addresses(&addrs));
+            for addr in &addrs {
+                if !resolved.contains(addr) {
+                    resolved.push(*addr);
+                }
+            }
+        }
+        if !listen_addrs.contains(&addrs[0]) {
+            listen_addrs.push(addrs[0]);
+        }
+    }
+    if listen_addrs.is_empty() {
+        return Err("监听地址不能为空".to_string());
+    }
+    if !resolved.is_empty() {
+        if let Some(sender) = event_sender {
+            let _ = sender.send(ConnectionEvent::Resolved(config.id.clone(), resolved)).await;
+        }
+    }
+    Ok(listen_addrs)
+}
+
+/// 自签名证书使用的名称：localhost、具体的监听IP和监听主机名
+pub(crate) fn listen_tls_names(config: &ServerConfig, listen_addrs: &[SocketAddr]) -> Vec<String> {
+    let mut names = vec!["localhost".to_string()];
+    for addr in listen_addrs {
+        if !addr.ip().is_unspecified() {
+            names.push(addr.ip().to_string());
+        }
+    }
+    for host in config.listen_hosts() {
+        if !resolve::is_ip_literal(&host) {
+            names.push(host);
+        }
+    }
+    names
+}
+
+/// 绑定全部监听地址，任一地址绑定失败时返回错误
+///
+/// 双栈监听时IPv6套接字关闭 IPV6_V6ONLY；否则同时监听IPv4地址时开启 IPV6_V6ONLY，
+/// 避免 :: 与IPv4地址的同一端口冲突；其余情况沿用系统默认值。
+pub(crate) fn bind_listeners(listen_addrs: &[SocketAddr], dual_stack: bool) -> Result<Vec<TcpListener>, String> {
+    let has_ipv4 = listen_addrs.iter().any(SocketAddr::is_ipv4);
+    listen_addrs
+        .iter()
+        .map(|addr| {
+            let v6_only = match addr {
+                SocketAddr::V6(_) if dual_stack => Some(false),
+                SocketAddr::V6(_) if has_ipv4 => Some(true),
+                _ => None,
+            };
+            bind_listener(*addr, v6_only).map_err(|e| format!("绑定地址 {} 失败: {}", addr, e))
+        })
+        .collect()
+}
+
+fn bind_listener(addr: SocketAddr, v6_only: Option<bool>) -> std::io::Result<TcpListener> {
+    let socket = socket2::Socket::new(socket2::Domain::for_address(addr), socket2::Type::STREAM, Some(socket2::Protocol::TCP))?;
+    if let Some(v6_only) = v6_only {
+        socket.set_only_v6(v6_only)?;
+    }
+    // 与 TcpListener::bind 一致，非 Windows 平台允许立即重用处于 TIME_WAIT 的端口
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    socket.set_nonblocking(true)?;
+    TcpListener::from_std(socket.into())
+}
+
+/// 监听地址列表的显示文本
+pub(crate) fn format_listen_addrs(listen_addrs: &[SocketAddr]) -> String {
+    listen_addrs.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// 为每个监听套接字运行一个接受循环，任务被取消时全部监听套接字随之关闭
+pub(crate) async fn run_accept_loops<F, Fut>(listeners: Vec<TcpListener>, accept_loop: F)
+where
+    F: Fn(TcpListener) -> Fut,
+    Fut: std::future::Future<Output = ()> + Send + 'static,
+{
+    let mut loops = tokio::task::JoinSet::new();
+    for listener in listeners {
+        loops.spawn(accept_loop(listener));
+    }
+    while loops.join_next().await.is_some() {}
+}
+
+/// 通知UI客户端连接到的本地端点（多地址监听时区分客户端来自哪个监听地址）
+pub(crate) async fn report_client_local_addr(
+    config: &ServerConfig,
+    event_sender: &Option<Sender<ConnectionEvent>>,
+    socket: &TcpStream,
+    addr: SocketAddr,
+) {
+    if let (Some(sender), Ok(local_addr)) = (event_sender, socket.local_addr()) {
+        let _ = sender.send(ConnectionEvent::ServerClientLocalAddress(config.id.clone(), addr, local_addr)).await;
+    }
+}
+
+/// TCP服务端各监听地址的接受循环共用的上下文
+#[derive(Clone)]
+struct TcpAcceptContext {
+    config: ServerConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    message_processor: Arc<dyn MessageProcessor>,
+    clients: Arc<Mutex<HashMap<SocketAddr, WriteSender>>>,
+    client_handles: Arc<Mutex<HashMap<SocketAddr, JoinHandle<()>>>>,
+    client_controls: Arc<Mutex<HashMap<SocketAddr, ClientControl>>>,
+    raw_capture: RawCaptureSlot,
+    tls_acceptor: Option<tokio_rustls::TlsAcceptor>,
+}
+
+impl TcpAcceptContext {
+    /// 接受一个监听地址上的连接，直到监听失败或任务被取消
+    async fn accept_loop(self, listener: TcpListener) {
+        loop {
+            let (socket, addr) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    // 监听失败，可能是因为listener被关闭
+                    debug!("TCP服务器监听失败: {:?}", e);
+                    break;
+                }
+            };
+            debug!("TCP服务器接收到来自 {} 的连接", addr);
+            if let Some(warning) = apply_stream_options(&socket, &self.config.socket_options, &self.config.send_shaping) {
+                warn!("[TCP服务器] 客户端 {} {}", addr, warning);
+                if let Some(sender) = &self.event_sender {
+                    let _ = sender.send(ConnectionEvent::Warning(
+                        self.config.id.clone(),
+                        format!("客户端 {} {}", addr, warning),
+                    )).await;
+                }
+            }
+            
+            // 按最大连接数策略接纳客户端并注册控制句柄
+            let Some(close_rx) = admit_client(&self.config, &self.client_controls, addr, &self.event_sender).await else {
+                drop(socket);
+                continue;
+            };
+            
+            let session = ServerClientSession {
+                server_id: self.config.id.clone(),
+                addr,
+                label: addr.to_string(),
+                config: self.config.clone(),
+                event_sender: self.event_sender.clone(),
+                message_processor: self.message_processor.clone(),
+                raw_capture: self.raw_capture.clone(),
+            };
+            let rx = register_client(&self.clients, &session).await;
+            report_client_local_addr(&self.config, &self.event_sender, &socket, addr).await;
+            
+            // 处理客户端连接
+            let clients_for_client = self.clients.clone();
+            let client_handles_for_client = self.client_handles.clone();
+            let client_controls_for_client = self.client_controls.clone();
+            let client_tls_acceptor = self.tls_acceptor.clone();
+            
+            // 启动客户端连接的任务并保存句柄
+            spawn_client_task(&self.client_handles, addr, async move { 
+                // 强制关闭时需设置 SO_LINGER，TLS 包装后无法再取回 TcpStream，先保留一个套接字句柄
+                let abort_handle = socket2::SockRef::from(&socket).try_clone();
+                
+                // 启用TLS时先完成握手，握手失败视为该客户端断开
+                let stream: Option<Box<dyn AsyncStream>> = match &client_tls_acceptor {
+                    Some(acceptor) => match tls::accept(acceptor, socket).await {
+                        Ok((stream, tls_info)) => {
+                            info!("TCP服务器与 {} 完成TLS握手: {} {}", addr, tls_info.version, tls_info.cipher);
+                            if let Some(sender) = &session.event_sender {
+                                let _ = sender.send(ConnectionEvent::TlsEstablished(session.server_id.clone(), Some(addr), tls_info)).await;
+                            }
+                            Some(Box::new(stream))
+                        }
+                        Err(e) => {
+                            error!("TCP服务器与 {} 的TLS握手失败: {}", addr, e);
+                            if let Some(sender) = &session.event_sender {
+                                let _ = sender.send(ConnectionEvent::Warning(session.server_id.clone(), format!("客户端 {} {}", addr, e))).await;
+                            }
+                            None
+                        }
+                    },
+                    None => Some(Box::new(socket)),
+                };
+                
+                if let Some(stream) = stream {
+                    if serve_client_stream(stream, rx, close_rx, &session).await == Some(ClientCloseMode::Abort) {
+                        // SO_LINGER 为 0 时关闭套接字会直接发送 RST
+                        match &abort_handle {
+                            Ok(handle) => {
+                                if let Err(e) = handle.set_linger(Some(Duration::ZERO)) {
+                                    error!("TCP服务器设置 SO_LINGER 失败: {:?}", e);
+                                }
+                            }
+                            Err(e) => error!("TCP服务器无法获取客户端 {} 的套接字: {:?}", addr, e),
+                        }
+                        debug!("TCP服务器已强制关闭客户端 {}", addr);
+                    }
+                }
+                drop(abort_handle);
+                
+                release_client(&clients_for_client, &client_handles_for_client, &client_controls_for_client, &session).await;
+            }).await;
         }
     }
 }
@@ -316,30 +1448,13 @@ impl NetworkServer for TcpServer {
             return Pin::from(Box::new(async move { Ok(()) }));
         }
         
-        // 绑定地址，支持IPv4和IPv6
-        let address = if self.config.listen_address.contains(':') && !self.config.listen_address.contains('[') {
-            // IPv6地址需要方括号
-            format!("[{}]:{}", self.config.listen_address, self.config.listen_port)
-        } else {
-            format!("{}:{}", self.config.listen_address, self.config.listen_port)
-        };
-        
-        let socket_addr = match SocketAddr::from_str(&address) {
-            Ok(addr) => addr,
-            Err(e) => {
-                error!("无效的监听地址格式 '{}': {}", address, e);
-                let error_msg = format!("无效的监听地址格式: {}", e);
-                return Pin::from(Box::new(async move { Err(error_msg.into()) }));
-            }
-        };
-        
-        info!("TCP服务器启动在地址: {}", socket_addr);
+        info!("TCP服务器启动在地址: {}", self.config.listen_hosts().join(", "));
         
         // 更新状态为运行中
         self.is_running = true;
         
-        // 创建oneshot通道，用于在异步任务中传递listener和task handle
-        let (tx, rx) = tokio::sync::oneshot::channel::<(Arc<TcpListener>, JoinHandle<()>)>();
+        // 创建oneshot通道，用于在异步任务中传递监听任务的句柄（或启动失败的原因）
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<JoinHandle<()>, String>>();
         
         // 保存需要在异步块中使用的字段的克隆
         let config = self.config.clone();
@@ -347,266 +1462,90 @@ impl NetworkServer for TcpServer {
         let message_processor = self.message_processor.clone();
         let clients = self.clients.clone();
         let client_handles = self.client_handles.clone();
+        let client_controls = self.client_controls.clone();
+        let raw_capture = self.raw_capture.clone();
         
         // 启动一个任务来创建listener并启动监听
         tokio::spawn(async move {
-            // 绑定地址
-            match TcpListener::bind(socket_addr).await {
-                Ok(listener) => {
-                    info!("TCP服务器开始监听: {}", address);
-                    
-                    // 发送监听事件到UI线程
-                    if let Some(sender) = &event_sender {
-                        if let Err(e) = sender.send(ConnectionEvent::Listening(config.id.clone())).await {
-                            error!("[TCP服务器] 发送 Listening 事件失败: {:?}", e);
-                        }
-                    }
-                    
-                    // 将listener包装在Arc中
-                    let listener_arc = Arc::new(listener);
-                    
-                    // 启动独立的监听任务
-                    let listener_task = tokio::spawn({ 
-                        let listener_clone = listener_arc.clone();
-                        let config = config.clone();
-                        let event_sender = event_sender.clone();
-                        let message_processor = message_processor.clone();
-                        let clients = clients.clone();
-                        let client_handles = client_handles.clone();
-                        async move { 
-                            loop {
-                                match listener_clone.accept().await {
-                                    Ok((socket, addr)) => {
-                                        debug!("TCP服务器接收到来自 {} 的连接", addr);
-                                        
-                                        // 创建客户端连接的发送器和接收器
-                                        let (tx, rx) = smol_unbounded::<Vec<u8>>();
-                                        
-                                        // 保存客户端连接到共享的clients哈希表
-                                        let mut clients_guard: tokio::sync::MutexGuard<'_, HashMap<SocketAddr, Sender<Vec<u8>>>> = clients.lock().await;
-                                        clients_guard.insert(addr, tx.clone());
-                                        drop(clients_guard);
-                                        
-                                        // 发送客户端连接事件到UI线程
-                                        if let Some(sender) = &event_sender {
-                                            if let Err(e) = sender.send(ConnectionEvent::ServerClientConnected(
-                                                config.id.clone(),
-                                                addr,
-                                                tx,
-                                            )).await {
-                                                error!("[TCP服务器] 发送 ServerClientConnected 事件失败: {:?}", e);
-                                            }
-                                        }
-                                        
-                                        // 处理客户端连接
-                                        let client_id_clone = config.id.clone();
-                                        let client_event_sender = event_sender.clone();
-                                        let client_message_processor = message_processor.clone();
-                                        let clients_clone_for_disconnect = clients.clone();
-                                        let config_clone_for_client = config.clone();
-                                        let client_handles_clone_for_client = client_handles.clone();
-                                        
-                                        // 创建客户端连接的任务句柄
-                                        let client_task = tokio::spawn(async move { 
-                                            // 创建decoder和encoder
-                                            let (mut socket_read, mut socket_write) = tokio::io::split(socket);
-                                            
-                                            // 根据配置创建具体的解码器
-                                            let decoder_config = config_clone_for_client.decoder_config.clone();
-                                            let encoder = CodecFactory::create_encoder(&config_clone_for_client.decoder_config);
-                                            
-                                            // 启动接收消息循环
-                                            let recv_fut = async { 
-                                                let mut buffer = BytesMut::with_capacity(16384); // 16KB缓冲区
-                                                
-                                                // 使用CodecFactory创建解码器（所有解码器现在都支持force_flush）
-                                                let mut decoder = crate::network::protocol::decoder::CodecFactory::create_decoder(&decoder_config);
-                                                
-                                                loop {
-                                                    tokio::select! {
-                                                        // 数据读取事件
-                                                        result = socket_read.read_buf(&mut buffer) => {
-                                                            match result {
-                                                                Ok(0) => {
-                                                                    // 客户端关闭连接
-                                                                    debug!("TCP客户端 {} 断开连接", addr);
-                                                                    break;
-                                                                },
-                                                                Ok(n) => {
-                                                                    debug!("TCP服务器从 {} 读取了 {} 字节数据", addr, n);
-                                                                        
-                                                                    // 使用decoder解码数据，循环处理所有可用消息
-                                                                    loop {
-                                                                        match decoder.decode(&mut buffer) {
-                                                                            Ok(Some(data)) => {
-                                                                                // 处理接收到的消息
-                                                                                let data: BytesMut = data;
-                                                                                process_decoded_data_with_addr(
-                                                                                    data,
-                                                                                    &client_message_processor,
-                                                                                    &client_event_sender,
-                                                                                    &client_id_clone,
-                                                                                    &addr.to_string()
-                                                                                );
-                                                                            },
-                                                                            Ok(None) => {
-                                                                                // 解码器需要更多数据，退出循环
-                                                                                break;
-                                                                            },
-                                                                            Err(e) => {
-                                                                                // 处理解码错误
-                                                                                error!("TCP服务器读取来自 {} 的消息时发生错误: {:?}", addr, e);
-                                                                                break;
-                                                                            }
-                                                                        }
-                                                                    }
-                                                                },
-                                                                Err(e) => {
-                                                                    error!("TCP服务器读取来自 {} 的消息时发生错误: {:?}", addr, e);
-                                                                    break;
-                                                                }
-                                                            }
-                                                        }
-                                                        
-                                                        // 50ms超时事件 - 强制刷新缓冲区
-                                                        _ = tokio::time::sleep(Duration::from_millis(50)) => {
-                                                            // 强制刷新解码器缓冲区
-                                                            if let Some(data) = decoder.force_flush() {
-                                                                let data: BytesMut = data;
-                                                                process_decoded_data_with_addr(
-                                                                    data,
-                                                                    &client_message_processor,
-                                                                    &client_event_sender,
-                                                                    &client_id_clone,
-                                                                    &addr.to_string()
-                                                                );
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            };
-                                            
-                                            // 启动发送消息循环
-                                            let send_fut = async { 
-                                                let mut encoder = encoder;
-                                                loop {
-                                                    match rx.recv().await {
-                                                        Ok(message) => {
-                                                            let mut buffer = BytesMut::with_capacity(message.len());
-                                                            let data_bytes = BytesMut::from(message.as_slice());
-                                                            
-                                                            // 使用encoder编码数据
-                                                            if let Err(e) = encoder.encode(data_bytes, &mut buffer) {
-                                                                error!("TCP服务器编码消息时发生错误: {:?}", e);
-                                                                break;
-                                                            }
-                                                            
-                                                            // 写入数据
-                                                            if let Err(e) = socket_write.write_all(&buffer).await {
-                                                                error!("TCP服务器向 {} 发送消息时发生错误: {:?}", addr, e);
-                                                                break;
-                                                            }
-                                                            
-                                                            // 尝试将消息转换为文本，如果失败则显示十六进制
-                                                            let send_message_str = match String::from_utf8(message.clone()) {
-                                                                Ok(s) => s,
-                                                                Err(_) => {
-                                                                    // 转换为十六进制
-                                                                    let hex: Vec<String> = message.iter().map(|b| format!("{:02x}", b)).collect();
-                                                                    hex.join(" ")
-                                                                }
-                                                            };
-                                                            debug!("TCP服务器向 {} 发送消息: {}", addr, send_message_str);
-                                                        },
-                                                        Err(_) => {
-                                                            debug!("TCP服务器发送消息通道已关闭");
-                                                            break;
-                                                        }
-                                                    }
-                                                }
-                                            };
-                                            
-                                            // 同时运行接收和发送循环，任何一个结束都终止另一个
-                                            tokio::select! {
-                                                _ = recv_fut => {
-                                                    debug!("TCP服务器接收循环结束");
-                                                },
-                                                _ = send_fut => {
-                                                    debug!("TCP服务器发送循环结束");
-                                                },
-                                            }
-                                            
-                                            // 从共享的clients哈希表中移除断开连接的客户端
-                                            let mut clients_guard: tokio::sync::MutexGuard<'_, HashMap<SocketAddr, Sender<Vec<u8>>>> = clients_clone_for_disconnect.lock().await;
-                                            clients_guard.remove(&addr);
-                                            drop(clients_guard);
-                                            
-                                            // 从客户端任务句柄表中移除
-                                            let mut handles_guard: tokio::sync::MutexGuard<'_, HashMap<SocketAddr, JoinHandle<()>>> = client_handles_clone_for_client.lock().await;
-                                            handles_guard.remove(&addr);
-                                            drop(handles_guard);
-                                            
-                                            // 发送客户端断开连接事件到UI线程
-                                            if let Some(sender) = &client_event_sender {
-                                                if let Err(e) = sender.send(ConnectionEvent::ServerClientDisconnected(
-                                                    client_id_clone.clone(),
-                                                    addr,
-                                                )).await {
-                                                    error!("[TCP服务器] 发送 ServerClientDisconnected 事件失败: {:?}", e);
-                                                }
-                                            }
-                                        });
-                                        
-                                        // 保存客户端任务句柄到client_handles
-                                        let client_handles_clone = client_handles.clone();
-                                        let mut handles_guard: tokio::sync::MutexGuard<'_, HashMap<SocketAddr, JoinHandle<()>>> = client_handles_clone.lock().await;
-                                        handles_guard.insert(addr, client_task);
-                                        drop(handles_guard);
-                                    },
-                                    Err(e) => {
-                                        // 监听失败，可能是因为listener被关闭
-                                        debug!("TCP服务器监听失败: {:?}", e);
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                    });
-                    
-                    // 发送listener和task handle到通道
-                    if let Err(e) = tx.send((listener_arc, listener_task)) {
-                        error!("TCP服务器无法发送listener和task handle到通道: {:?}", e);
-                    }
-                },
+            // 解析全部监听地址（支持主机名，绑定首个解析结果）
+            let listen_addrs = match resolve_listen_addrs(&config, &event_sender, "TCP服务器").await {
+                Ok(addrs) => addrs,
                 Err(e) => {
-                    error!("TCP服务器绑定地址失败: {:?}", e);
-                    // 尝试绑定回退地址，如果失败则发送错误
-                    match TcpListener::bind("127.0.0.1:0").await {
-                        Ok(listener) => {
-                            let _ = tx.send((Arc::new(listener), tokio::spawn(async {})));
-                        }
-                        Err(fallback_error) => {
-                            error!("TCP服务器回退地址绑定也失败: {:?}", fallback_error);
-                            // 发送空结果表示完全失败
-                            let _ = tx.send((Arc::new(TcpListener::bind("127.0.0.1:0").await.unwrap_or_else(|_| panic!("无法绑定任何TCP地址"))), tokio::spawn(async {})));
-                        }
+                    error!("[TCP服务器] {}", e);
+                    let _ = tx.send(Err(e));
+                    return;
+                }
+            };
+            
+            // 启用TLS时加载证书（未指定证书时生成临时自签名证书）
+            let (tls_acceptor, tls_notice) = if config.tls.enabled {
+                match tls::acceptor(&config.tls, &listen_tls_names(&config, &listen_addrs)) {
+                    Ok((acceptor, notice)) => (Some(acceptor), notice),
+                    Err(e) => {
+                        error!("[TCP服务器] {}", e);
+                        let _ = tx.send(Err(e));
+                        return;
                     }
                 }
+            } else {
+                (None, None)
+            };
+            
+            // 绑定全部地址，任一地址绑定失败则启动失败
+            let listeners = match bind_listeners(&listen_addrs, config.dual_stack) {
+                Ok(listeners) => listeners,
+                Err(e) => {
+                    error!("TCP服务器{}", e);
+                    let _ = tx.send(Err(e));
+                    return;
+                }
+            };
+            info!("TCP服务器开始监听: {}", format_listen_addrs(&listen_addrs));
+            
+            // 发送监听事件到UI线程
+            if let Some(sender) = &event_sender {
+                if let Err(e) = sender.send(ConnectionEvent::Listening(config.id.clone())).await {
+                    error!("[TCP服务器] 发送 Listening 事件失败: {:?}", e);
+                }
+                if let Some(notice) = tls_notice {
+                    let _ = sender.send(ConnectionEvent::Warning(config.id.clone(), notice)).await;
+                }
+            }
+            
+            // 启动独立的监听任务，每个监听地址一个接受循环
+            let ctx = TcpAcceptContext {
+                config,
+                event_sender,
+                message_processor,
+                clients,
+                client_handles,
+                client_controls,
+                raw_capture,
+                tls_acceptor,
+            };
+            let listener_task = tokio::spawn(run_accept_loops(listeners, move |listener| ctx.clone().accept_loop(listener)));
+            
+            // 发送task handle到通道
+            if tx.send(Ok(listener_task)).is_err() {
+                error!("TCP服务器无法发送task handle到通道");
             }
         });
         
-        // 返回一个future，该future会等待通道中的listener和task handle，并将它们保存到self中
+        // 返回一个future，该future会等待通道中的task handle，并将其保存到self中
         Pin::from(Box::new(async move {
-            // 等待通道中的listener和task handle
+            // 等待通道中的task handle
             match rx.await {
-                Ok((listener_arc, listener_task)) => {
-                    // 保存listener和listener_handle到self中
-                    self.listener = Some(listener_arc);
+                Ok(Ok(listener_task)) => {
                     self.listener_handle = Some(listener_task);
                     Ok(())
                 },
+                Ok(Err(message)) => {
+                    // 启动失败，由连接管理器将错误转发给UI
+                    self.is_running = false;
+                    Err(message.into())
+                },
                 Err(e) => {
-                    error!("TCP服务器无法从通道接收listener和task handle: {:?}", e);
+                    error!("TCP服务器无法从通道接收task handle: {:?}", e);
                     // 更新状态为停止
                     self.is_running = false;
                     Err(Box::new(e) as Box<dyn std::error::Error>)
@@ -620,6 +1559,7 @@ impl NetworkServer for TcpServer {
         let server_id = self.config.id.clone();
         let clients = self.clients.clone();
         let client_handles = self.client_handles.clone();
+        let client_controls = self.client_controls.clone();
         
         // 如果服务器已经停止，直接返回
         if !self.is_running {
@@ -629,19 +1569,12 @@ impl NetworkServer for TcpServer {
             }));
         }
         
-        // 取消监听任务
+        // 取消监听任务，全部监听套接字随之关闭，不再接收新连接
         if let Some(handle) = self.listener_handle.take() {
             handle.abort();
             debug!("TCP服务器监听任务已取消");
         }
         
-        // 关闭监听套接字
-        if let Some(_listener) = self.listener.take() {
-            // 当我们从self.listener中取出listener并drop它时，会自动关闭监听套接字
-            // 这将导致所有正在进行的accept()调用返回错误，从而停止接收新连接
-            debug!("TCP服务器监听套接字已关闭");
-        }
-        
         // 更新状态为停止
         self.is_running = false;
         
@@ -657,6 +1590,9 @@ impl NetworkServer for TcpServer {
                 debug!("TCP服务器已关闭客户端 {} 的发送通道", addr);
             }
             
+            // 清空客户端控制句柄
+            client_controls.lock().await.clear();
+            
             // 取消所有客户端连接任务
             let mut handles_guard: tokio::sync::MutexGuard<'_, HashMap<SocketAddr, JoinHandle<()>>> = client_handles.lock().await;
             let handles = std::mem::take(&mut *handles_guard);
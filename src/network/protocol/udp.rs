@@ -1,55 +1,195 @@
 use std::collections::HashMap;
 use std::future::Future;
 use std::sync::Arc;
-use std::str::FromStr;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use tokio::sync::Mutex;
 use std::pin::Pin;
-use std::net::SocketAddr;
-use tokio::net::UdpSocket;
-use smol::channel::{Sender, unbounded as smol_unbounded};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpStream, UdpSocket};
+use smol::channel::Sender;
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
-use crate::config::connection::{ClientConfig, ServerConfig};
-use crate::message::MessageType;
+use crate::config::connection::{ClientConfig, MulticastConfig, ServerConfig};
+use crate::message::{MessageDirection, MessageType};
 use crate::network::events::ConnectionEvent;
 use crate::network::interfaces::{NetworkConnection, NetworkServer};
+use crate::network::proxy::{self, ProxyTarget};
+use crate::network::queue::{self, WriteSender};
+use crate::network::resolve;
 use crate::core::message_processor::{MessageProcessor, DefaultMessageProcessor};
+use crate::raw_capture::{self, RawCaptureSlot};
+
+/// 接收一个数据报，返回数据、来源地址以及是否被截断
+///
+/// 缓冲区比配置的接收长度多预留 1 字节：读满该字节即说明数据报超出了配置的长度，
+/// 超出部分已被丢弃。
+pub(crate) async fn recv_datagram(socket: &UdpSocket, buffer: &mut [u8]) -> std::io::Result<(Vec<u8>, SocketAddr, bool)> {
+    let limit = buffer.len() - 1;
+    let (n, addr, truncated) = recv_from(socket, buffer).await?;
+    let truncated = truncated || n > limit;
+    if truncated {
+        warn!("UDP数据报来自 {} 超过接收缓冲区 {} 字节，已截断", addr, limit);
+    }
+    Ok((buffer[..n.min(limit)].to_vec(), addr, truncated))
+}
+
+/// 接收一个数据报，返回读取的长度、来源地址以及系统是否报告了截断
+#[cfg(not(windows))]
+async fn recv_from(socket: &UdpSocket, buffer: &mut [u8]) -> std::io::Result<(usize, SocketAddr, bool)> {
+    let (n, addr) = socket.recv_from(buffer).await?;
+    Ok((n, addr, false))
+}
+
+/// 接收一个数据报，返回读取的长度、来源地址以及系统是否报告了截断
+///
+/// Windows 上数据报超出缓冲区时 recvfrom 以 WSAEMSGSIZE（10040）失败，而不是返回截断后的数据。
+/// 这里改用 WSARecvFrom 读取，该错误被视为截断，已读取的部分和来源地址照常返回。
+#[cfg(windows)]
+async fn recv_from(socket: &UdpSocket, buffer: &mut [u8]) -> std::io::Result<(usize, SocketAddr, bool)> {
+    // SAFETY: 缓冲区已初始化，系统调用只会向其中写入字节
+    let buffer = unsafe { &mut *(buffer as *mut [u8] as *mut [std::mem::MaybeUninit<u8>]) };
+    loop {
+        socket.readable().await?;
+        let result = socket.try_io(tokio::io::Interest::READABLE, || {
+            let mut buffers = [socket2::MaybeUninitSlice::new(&mut *buffer)];
+            socket2::SockRef::from(socket).recv_from_vectored(&mut buffers)
+        });
+        match result {
+            Ok((n, flags, addr)) => {
+                let addr = addr.as_socket().ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, "UDP数据报的来源地址无效")
+                })?;
+                return Ok((n, addr, flags.is_truncated()));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// 绑定UDP套接字
+///
+/// reuse_address 为 true 时设置 SO_REUSEADDR：服务端启用组播时允许多个实例同时监听同一端口，
+/// 客户端指定本地端口时允许快速重新绑定该端口。
+fn bind_udp_socket(addr: SocketAddr, reuse_address: bool) -> std::io::Result<UdpSocket> {
+    let socket = socket2::Socket::new(
+        socket2::Domain::for_address(addr),
+        socket2::Type::DGRAM,
+        Some(socket2::Protocol::UDP),
+    )?;
+    if reuse_address {
+        socket.set_reuse_address(true)?;
+    }
+    socket.bind(&addr.into())?;
+    socket.set_nonblocking(true)?;
+    UdpSocket::from_std(socket.into())
+}
+
+/// 按配置加入组播组，并设置发出组播数据报的 TTL 与回环
+///
+/// 组播组的地址族必须与监听地址一致；源特定组播仅支持 IPv4。
+fn join_multicast(socket: &UdpSocket, config: &MulticastConfig) -> Result<(), String> {
+    let local_addr = socket.local_addr().map_err(|e| format!("获取UDP套接字本地地址失败: {}", e))?;
+
+    for group in &config.groups {
+        match (group, local_addr) {
+            (IpAddr::V4(group), SocketAddr::V4(_)) => {
+                let interface = config.interface_v4()?;
+                let result = match &config.source {
+                    Some(source) => socket2::SockRef::from(socket).join_ssm_v4(source, group, &interface),
+                    None => socket.join_multicast_v4(*group, interface),
+                };
+                result.map_err(|e| format!("加入组播组 {} 失败: {}", group, e))?;
+            }
+            (IpAddr::V6(group), SocketAddr::V6(_)) => {
+                if config.source.is_some() {
+                    return Err(format!("加入组播组 {} 失败: 源特定组播暂不支持IPv6", group));
+                }
+                socket
+                    .join_multicast_v6(group, config.interface_v6()?)
+                    .map_err(|e| format!("加入组播组 {} 失败: {}", group, e))?;
+            }
+            _ => {
+                return Err(format!("组播组 {} 与监听地址 {} 的地址族不一致", group, local_addr));
+            }
+        }
+        match &config.source {
+            Some(source) => info!("UDP服务器已加入组播组 {}（源 {}）", group, source),
+            None => info!("UDP服务器已加入组播组 {}", group),
+        }
+    }
+
+    let options = if local_addr.is_ipv4() {
+        socket
+            .set_multicast_ttl_v4(config.ttl)
+            .and_then(|_| socket.set_multicast_loop_v4(config.loopback))
+    } else {
+        socket2::SockRef::from(socket)
+            .set_multicast_hops_v6(config.ttl)
+            .and_then(|_| socket.set_multicast_loop_v6(config.loopback))
+    };
+    options.map_err(|e| format!("设置组播选项失败: {}", e))
+}
+
+/// 保持 SOCKS5 UDP 关联的控制连接，连接断开时结束本次会话并通知UI
+fn spawn_proxy_control(
+    mut control: TcpStream,
+    id: String,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    session_token: CancellationToken,
+) {
+    tokio::spawn(async move {
+        let mut buffer = [0u8; 64];
+        loop {
+            tokio::select! {
+                result = control.read(&mut buffer) => {
+                    // 控制连接上不应有数据，读到 EOF 或出错说明代理已结束关联
+                    if matches!(result, Ok(0) | Err(_)) {
+                        warn!("[UDP客户端] SOCKS5 控制连接已断开，UDP 关联失效");
+                        session_token.cancel();
+                        if let Some(sender) = &event_sender {
+                            let _ = sender.send(ConnectionEvent::Error(id.clone(), "SOCKS5 代理关闭了 UDP 关联".to_string())).await;
+                            let _ = sender.send(ConnectionEvent::Disconnected(id.clone())).await;
+                        }
+                        break;
+                    }
+                }
+                _ = session_token.cancelled() => break,
+            }
+        }
+    });
+}
 
 /// UDP客户端实现
 pub struct UdpClient {
     config: ClientConfig,
-    server_addr: SocketAddr,
     event_sender: Option<Sender<ConnectionEvent>>,
     message_processor: Arc<dyn MessageProcessor>,
     is_connected: bool,
     cancel_token: CancellationToken,
+    raw_capture: RawCaptureSlot,
 }
 
 impl UdpClient {
+    /// 创建客户端，服务器地址格式无效时返回错误
     pub fn new(
         config: ClientConfig,
-        event_sender: Option<Sender<ConnectionEvent>>
-    ) -> Self {
-        // 解析地址，支持IPv4和IPv6
-        let address = if config.server_address.contains(':') && !config.server_address.contains('[') {
-            // IPv6地址需要方括号
-            format!("[{}]:{}", config.server_address, config.server_port)
-        } else {
-            format!("{}:{}", config.server_address, config.server_port)
-        };
-        
-        let server_addr = SocketAddr::from_str(&address)
-            .expect(&format!("无效的UDP服务器地址: {}", address));
-            
-        UdpClient {
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Result<Self, String> {
+        resolve::validate_remote(&config.server_address, config.server_port)
+            .map_err(|e| format!("无效的UDP服务器地址: {}", e))?;
+        config.proxy.validate(config.protocol)?;
+
+        Ok(UdpClient {
             config,
-            server_addr,
             event_sender,
             message_processor: Arc::new(DefaultMessageProcessor),
             is_connected: false,
             cancel_token: CancellationToken::new(),
-        }
+            raw_capture,
+        })
     }
 }
 
@@ -62,27 +202,69 @@ impl NetworkConnection for UdpClient {
         }
         
         let config = self.config.clone();
-        let server_addr = self.server_addr;
-        let server_host = self.config.server_address.clone();
         let event_sender = self.event_sender.clone();
         let message_processor = self.message_processor.clone();
         let cancel_token = self.cancel_token.clone();
+        let raw_capture = self.raw_capture.clone();
         
         self.is_connected = true;
         
         Pin::from(Box::new(async move {
+            // 经 SOCKS5 代理时数据报发往代理的中继端口，由代理转发给目标
+            let mut proxy_session = None;
+            let server_addr = if config.proxy.is_enabled() {
+                let (control, relay) = proxy::socks5_udp_associate(&config.proxy, config.connect_timeout()).await?;
+                let (target, resolved) = ProxyTarget::from_host(&config.proxy, &config.server_address, config.server_port).await?;
+                if let (Some(addrs), Some(sender)) = (resolved, &event_sender) {
+                    let _ = sender.send(ConnectionEvent::Resolved(config.id.clone(), addrs)).await;
+                }
+                info!("[UDP客户端] 经 {} 代理发送到 {}", config.proxy, target);
+                proxy_session = Some((control, target));
+                relay
+            } else {
+                // 解析服务器地址（支持主机名），UDP 使用首个解析结果
+                match resolve::resolve(&config.server_address, config.server_port).await {
+                    Ok(addrs) => {
+                        if !resolve::is_ip_literal(&config.server_address) {
+                            info!("[UDP客户端] {} 解析为: {}", config.server_address, resolve::format_addresses(&addrs));
+                            if let Some(sender) = &event_sender {
+                                let _ = sender.send(ConnectionEvent::Resolved(config.id.clone(), addrs.clone())).await;
+                            }
+                        }
+                        addrs[0]
+                    }
+                    Err(e) => {
+                        error!("[UDP客户端] {}", e);
+                        return Err(e.into());
+                    }
+                }
+            };
             info!("UDP客户端连接到地址: {}", server_addr);
             
-            let bind_addr = if server_addr.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" };
-            let socket = UdpSocket::bind(bind_addr).await?;
+            let local_bind = config.local_bind_addr(&server_addr)?;
+            let bind_addr = local_bind.unwrap_or_else(|| {
+                let unspecified: IpAddr = if server_addr.is_ipv6() {
+                    Ipv6Addr::UNSPECIFIED.into()
+                } else {
+                    Ipv4Addr::UNSPECIFIED.into()
+                };
+                SocketAddr::new(unspecified, 0)
+            });
+            let socket = bind_udp_socket(bind_addr, local_bind.is_some())
+                .map_err(|e| format!("绑定本地UDP地址 {} 失败: {}", bind_addr, e))?;
             let local_addr = socket.local_addr()
                 .map_err(|e| {
                     error!("获取UDP套接字本地地址失败: {:?}", e);
                     e
                 })?;
             info!("UDP客户端绑定到本地端口: {:?}", local_addr);
+            // 允许向广播地址（如 255.255.255.255 或子网广播地址）发送
+            if server_addr.is_ipv4() && proxy_session.is_none() {
+                socket.set_broadcast(true)
+                    .map_err(|e| format!("启用UDP广播失败: {}", e))?;
+            }
             
-            let (tx, rx) = smol_unbounded::<Vec<u8>>();
+            let (tx, rx) = queue::write_queue(&config.queue);
             
             if let Some(sender) = &event_sender {
                 info!("[UDP客户端] 发送 Connected 事件");
@@ -93,10 +275,28 @@ impl NetworkConnection for UdpClient {
                 if let Err(e) = sender.send(ConnectionEvent::ClientWriteSenderReady(config.id.clone(), tx)).await {
                     error!("[UDP客户端] 发送 ClientWriteSenderReady 事件失败: {:?}", e);
                 }
+                let _ = sender.send(ConnectionEvent::LocalAddress(config.id.clone(), local_addr)).await;
             } else {
                 error!("[UDP客户端] event_sender 为空，无法发送事件");
             }
             
+            // 本次会话的取消令牌，代理控制连接断开时只结束本次会话
+            let session_token = cancel_token.child_token();
+            // 与解析后的服务器IP比较，主机名连接时不会把正常回复误判为意外来源；
+            // 由代理解析的主机名无法比较，不做判断
+            let expected_host = match &proxy_session {
+                Some((_, ProxyTarget::Addr(addr))) => Some(addr.ip().to_string()),
+                Some((_, ProxyTarget::Domain(..))) => None,
+                None => Some(server_addr.ip().to_string()),
+            };
+            let proxy_target = match proxy_session {
+                Some((control, target)) => {
+                    spawn_proxy_control(control, config.id.clone(), event_sender.clone(), session_token.clone());
+                    Some(target)
+                }
+                None => None,
+            };
+
             let shared_socket = Arc::new(socket);
             let socket_read = shared_socket.clone();
             let socket_write = shared_socket.clone();
@@ -104,23 +304,45 @@ impl NetworkConnection for UdpClient {
             let event_sender_clone = event_sender.clone();
             let id_clone = config.id.clone();
             let message_processor_clone = message_processor.clone();
-            let read_cancel_token = cancel_token.clone();
-            let expected_host = server_host;
+            let read_cancel_token = session_token.clone();
+            let read_raw_capture = raw_capture.clone();
+            let recv_buffer_len = config.udp_recv_buffer_len();
+            let proxied = proxy_target.is_some();
             
             tokio::spawn(async move {
-                let mut buffer = [0; 1024];
+                // 经代理时为 SOCKS5 UDP 头预留空间
+                let header_len = if proxied { proxy::SOCKS5_UDP_HEADER_MAX } else { 0 };
+                let mut buffer = vec![0u8; recv_buffer_len + header_len + 1];
                 loop {
                     tokio::select! {
-                        result = socket_read.recv_from(&mut buffer) => {
+                        result = recv_datagram(&socket_read, &mut buffer) => {
                             match result {
-                                Ok((n, addr)) => {
+                                Ok((raw_data, addr, truncated)) => {
+                                    let (source, raw_data, truncated) = if proxied {
+                                        match proxy::socks5_udp_decode(&raw_data) {
+                                            Ok((source, payload)) => {
+                                                let payload_truncated = truncated || payload.len() > recv_buffer_len;
+                                                let payload = payload[..payload.len().min(recv_buffer_len)].to_vec();
+                                                (source, payload, payload_truncated)
+                                            }
+                                            Err(e) => {
+                                                warn!("[UDP客户端] 丢弃来自代理 {} 的数据报: {}", addr, e);
+                                                continue;
+                                            }
+                                        }
+                                    } else {
+                                        (addr.to_string(), raw_data, truncated)
+                                    };
                                     // 移除源地址过滤，允许接收来自任何地址的回复
                                     // 这对于广播场景很重要：下位机回复来自其真实IP而非广播地址
-                                    let raw_data = buffer[..n].to_vec();
+                                    let n = raw_data.len();
+                                    raw_capture::record(&read_raw_capture, None, MessageDirection::Received, &raw_data);
+                                    let source_host = source.split(':').next().unwrap_or_default().to_string();
                                     let message = message_processor_clone.process_received_message(raw_data, MessageType::Text)
-                                        .with_unexpected_source(addr.to_string(), &expected_host);
+                                        .with_unexpected_source(source.clone(), expected_host.as_deref().unwrap_or(&source_host))
+                                        .with_truncated(truncated);
                                     
-                                    info!("UDP客户端从 {} 收到 {} 字节", addr, n);
+                                    info!("UDP客户端从 {} 收到 {} 字节", source, n);
                                     
                                     if let Some(sender) = &event_sender_clone {
                                         if let Err(e) = sender.send(ConnectionEvent::MessageReceived(id_clone.clone(), message)).await {
@@ -150,7 +372,8 @@ impl NetworkConnection for UdpClient {
             
             let event_sender_clone_write = event_sender.clone();
             let id_clone_write = config.id.clone();
-            let write_cancel_token = cancel_token.clone();
+            let write_cancel_token = session_token.clone();
+            let write_raw_capture = raw_capture.clone();
             
             tokio::spawn(async move {
                 loop {
@@ -158,7 +381,18 @@ impl NetworkConnection for UdpClient {
                         data = rx.recv() => {
                             match data {
                                 Ok(data) => {
-                                    if let Err(e) = socket_write.send_to(&data, &server_addr).await {
+                                    let encoded = match &proxy_target {
+                                        Some(target) => match proxy::socks5_udp_encode(target, &data) {
+                                            Ok(packet) => Some(packet),
+                                            Err(e) => {
+                                                error!("[UDP客户端] {}", e);
+                                                continue;
+                                            }
+                                        },
+                                        None => None,
+                                    };
+                                    let packet = encoded.as_deref().unwrap_or(&data);
+                                    if let Err(e) = socket_write.send_to(packet, &server_addr).await {
                                         error!("UDP发送错误: {:?}", e);
                                         if let Some(sender) = &event_sender_clone_write {
                                             if let Err(e) = sender.send(ConnectionEvent::Disconnected(id_clone_write.clone())).await {
@@ -167,6 +401,7 @@ impl NetworkConnection for UdpClient {
                                         }
                                         break;
                                     }
+                                    raw_capture::record(&write_raw_capture, None, MessageDirection::Sent, &data);
                                 },
                                 Err(_) => {
                                     debug!("UDP消息发送通道已关闭");
@@ -201,27 +436,31 @@ impl NetworkConnection for UdpClient {
             Ok(())
         }))
     }
-    
 
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
 }
 
 /// UDP服务器实现
 pub struct UdpServer {
     config: ServerConfig,
     event_sender: Option<Sender<ConnectionEvent>>,
-    clients: Arc<Mutex<HashMap<SocketAddr, Sender<Vec<u8>>>>>,
+    clients: Arc<Mutex<HashMap<SocketAddr, WriteSender>>>,
     message_processor: Arc<dyn MessageProcessor>,
     is_running: bool,
     read_handle: Option<JoinHandle<()>>,
     write_handle: Option<JoinHandle<()>>,
     /// 主发送通道，用于手动添加客户端时接入发送链路
     main_send_tx: Arc<Mutex<Option<Sender<(SocketAddr, Vec<u8>)>>>>,
+    raw_capture: RawCaptureSlot,
 }
 
 impl UdpServer {
     pub fn new(
         config: ServerConfig,
-        event_sender: Option<Sender<ConnectionEvent>>
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
     ) -> Self {
         UdpServer {
             config,
@@ -232,12 +471,13 @@ impl UdpServer {
             read_handle: None,
             write_handle: None,
             main_send_tx: Arc::new(Mutex::new(None)),
+            raw_capture,
         }
     }
 
     /// 手动添加客户端地址（仅UDP有效，不需要真实网络连接）
     /// 本质：在 clients 列表中注册一个地址，创建发送通道接入 socket 发送链路
-    pub async fn add_client(&self, addr: SocketAddr) -> Result<Sender<Vec<u8>>, String> {
+    pub async fn add_client(&self, addr: SocketAddr) -> Result<WriteSender, String> {
         // 检查是否已存在
         {
             let clients = self.clients.lock().await;
@@ -257,7 +497,7 @@ impl UdpServer {
         };
 
         // 创建客户端发送通道
-        let (client_tx, client_rx) = smol_unbounded::<Vec<u8>>();
+        let (client_tx, client_rx) = queue::write_queue(&self.config.queue);
 
         // 转发任务：client_rx → main_tx(主发送通道) → socket.send_to
         let main_tx_clone = main_tx.clone();
@@ -290,6 +530,25 @@ impl UdpServer {
         info!("[UDP服务器] 手动添加客户端: {}", addr);
         Ok(client_tx)
     }
+
+    /// 从客户端列表中移除地址（UDP无连接，仅停止向其发送）
+    pub async fn remove_client(&self, addr: SocketAddr) -> Result<(), String> {
+        if self.clients.lock().await.remove(&addr).is_none() {
+            return Err(format!("客户端 {} 不存在", addr));
+        }
+
+        if let Some(sender) = &self.event_sender {
+            if let Err(e) = sender.send(ConnectionEvent::ServerClientDisconnected(
+                self.config.id.clone(),
+                addr,
+            )).await {
+                error!("[UDP服务器] 发送 ServerClientDisconnected 事件失败: {:?}", e);
+            }
+        }
+
+        info!("[UDP服务器] 移除客户端: {}", addr);
+        Ok(())
+    }
 }
 
 impl NetworkServer for UdpServer {
@@ -302,30 +561,40 @@ impl NetworkServer for UdpServer {
         let clients = self.clients.clone();
         // 保存主发送通道，用于手动添加客户端
         let main_send_tx = self.main_send_tx.clone();
+        let raw_capture = self.raw_capture.clone();
         
         Pin::from(Box::new(async move {
-            // 绑定地址，支持IPv4和IPv6
-            let address = if config.listen_address.contains(':') && !config.listen_address.contains('[') {
-                // IPv6地址需要方括号
-                format!("[{}]:{}", config.listen_address, config.listen_port)
-            } else {
-                format!("{}:{}", config.listen_address, config.listen_port)
-            };
-            
-            let socket_addr = match SocketAddr::from_str(&address) {
-                Ok(addr) => addr,
+            // 解析监听地址（支持主机名，绑定首个解析结果）
+            let socket_addr = match resolve::resolve(&config.listen_address, config.listen_port).await {
+                Ok(addrs) => {
+                    if !resolve::is_ip_literal(&config.listen_address) {
+                        info!("[UDP服务器] {} 解析为: {}", config.listen_address, resolve::format_addresses(&addrs));
+                        if let Some(sender) = &event_sender {
+                            let _ = sender.send(ConnectionEvent::Resolved(config.id.clone(), addrs.clone())).await;
+                        }
+                    }
+                    addrs[0]
+                }
                 Err(e) => {
-                    error!("无效的UDP监听地址格式 '{}': {}", address, e);
-                    return Err(format!("无效的监听地址格式: {}", e).into());
+                    error!("无效的UDP监听地址 '{}': {}", config.listen_address, e);
+                    return Err(e.into());
                 }
             };
             
             info!("UDP服务器启动在地址: {}", socket_addr);
             debug!("UDP服务器配置: {:?}", config);
             
-            let socket = UdpSocket::bind(socket_addr).await?;
-            info!("UDP服务器成功绑定到地址: {}", address);
+            let socket = bind_udp_socket(socket_addr, config.multicast.is_enabled())
+                .map_err(|e| format!("绑定地址 {} 失败: {}", socket_addr, e))?;
+            info!("UDP服务器成功绑定到地址: {}", socket_addr);
             debug!("UDP套接字创建成功: {:?}", socket);
+
+            if config.multicast.is_enabled() {
+                join_multicast(&socket, &config.multicast).map_err(|e| {
+                    error!("[UDP服务器] {}", e);
+                    e
+                })?;
+            }
             
             // 使用Arc来共享socket，解决移动问题
             let socket_arc = Arc::new(socket);
@@ -337,8 +606,8 @@ impl NetworkServer for UdpServer {
                 }
             }
             
-            // 创建发送器和接收器
-            let (tx, rx) = smol_unbounded::<(SocketAddr, Vec<u8>)>();
+            // 创建发送器和接收器，各客户端的发送队列经此汇总，有界以便写入变慢时各队列随之积压
+            let (tx, rx) = smol::channel::bounded::<(SocketAddr, Vec<u8>)>(config.queue.capacity());
             
             // 保存主发送通道，供 add_client 使用
             {
@@ -353,15 +622,18 @@ impl NetworkServer for UdpServer {
             let id_clone = config.id.clone();
             let message_processor_clone = message_processor.clone();
             let socket_recv = socket_arc.clone();
+            let recv_raw_capture = raw_capture.clone();
+            let recv_buffer_len = config.udp_recv_buffer_len();
+            let queue_config = config.queue;
             
             tokio::spawn(async move {
-                let mut buffer = [0; 1024];
+                let mut buffer = vec![0u8; recv_buffer_len + 1];
                 loop {
-                    match socket_recv.recv_from(&mut buffer).await {
-                        Ok((n, addr)) => {
+                    match recv_datagram(&socket_recv, &mut buffer).await {
+                        Ok((data, addr, truncated)) => {
                             // 处理接收到的消息
-                            let data = buffer[..n].to_vec();
                             info!("UDP服务器从 {} 收到消息: {:?}", addr, data);
+                            raw_capture::record(&recv_raw_capture, Some(addr), MessageDirection::Received, &data);
                             
                             // 检查是否是新客户端
                             let mut clients_guard = clients_clone.lock().await;
@@ -370,7 +642,7 @@ impl NetworkServer for UdpServer {
                             // 如果是新客户端，添加到客户端列表并发送连接事件
                             if is_new_client {
                                 // 创建客户端发送通道
-                                let (client_tx, client_rx) = smol_unbounded::<Vec<u8>>();
+                                let (client_tx, client_rx) = queue::write_queue(&queue_config);
                                 
                                 // 保存客户端信息
                                 clients_guard.insert(addr, client_tx.clone());
@@ -406,7 +678,7 @@ impl NetworkServer for UdpServer {
                                 data, 
                                 MessageType::Text
                             );
-                            message = message.with_source(addr.to_string());
+                            message = message.with_source(addr.to_string()).with_truncated(truncated);
                             
                             // 发送消息事件到UI线程
                             if let Some(sender) = &event_sender_clone {
@@ -435,6 +707,7 @@ impl NetworkServer for UdpServer {
                         error!("UDP服务器发送消息时发生错误: {:?}", e);
                     } else {
                         info!("UDP服务器向 {} 发送消息: {:?}", addr, message);
+                        raw_capture::record(&raw_capture, Some(addr), MessageDirection::Sent, &message);
                     }
                 }
             });
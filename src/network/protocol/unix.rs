@@ -0,0 +1,689 @@
+//! Unix域套接字（流式与数据报）
+//!
+//! 流式客户端复用 TcpClient 的会话（见 connect_stream），流式服务端复用TCP服务端的
+//! 客户端接纳、收发循环和清理逻辑。Unix域套接字的对端没有IP地址，而界面和消息以
+//! SocketAddr 区分服务端的各个客户端，因此服务端为每个对端分配 0.0.0.0:序号 形式的
+//! 虚拟地址，并通过 ServerClientLabel 事件告知界面对端的实际名称。
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use log::{debug, error, info, warn};
+use smol::channel::Sender;
+use tokio::net::{UnixDatagram, UnixListener, UnixStream};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use crate::config::connection::{ClientConfig, ServerConfig};
+use crate::core::message_processor::{DefaultMessageProcessor, MessageProcessor};
+use crate::message::{MessageDirection, MessageType};
+use crate::network::events::ConnectionEvent;
+use crate::network::interfaces::{NetworkConnection, NetworkServer};
+use crate::network::protocol::tcp::{self, ClientCloseMode, ClientControl, ClientSession, ServerClientSession};
+use crate::network::queue::{self, WriteSender};
+use crate::raw_capture::{self, RawCaptureSlot};
+
+/// 分配下一个未被占用的虚拟地址（端口号从 1 开始循环使用）
+fn next_virtual_addr(seq: &mut u16, in_use: impl Fn(&SocketAddr) -> bool) -> SocketAddr {
+    loop {
+        *seq = seq.wrapping_add(1).max(1);
+        let addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, *seq));
+        if !in_use(&addr) {
+            return addr;
+        }
+    }
+}
+
+/// 检查套接字路径是否有效
+fn validate_path(path: &str) -> Result<(), String> {
+    if path.trim().is_empty() {
+        return Err("Unix域套接字路径不能为空".to_string());
+    }
+    Ok(())
+}
+
+/// 绑定前处理已存在的文件
+///
+/// 仍有进程在监听的套接字返回错误；无人监听的残留套接字文件会被删除；
+/// 同名的普通文件或目录不会被删除，返回错误。
+async fn remove_stale_socket(path: &Path, datagram: bool) -> Result<(), String> {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return Ok(()),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(format!("{} 已存在且不是套接字文件", path.display()));
+    }
+
+    let in_use = if datagram {
+        std::os::unix::net::UnixDatagram::unbound()
+            .and_then(|socket| socket.connect(path))
+            .is_ok()
+    } else {
+        UnixStream::connect(path).await.is_ok()
+    };
+    if in_use {
+        return Err(format!("{} 已有进程在监听", path.display()));
+    }
+
+    info!("[Unix] 删除残留的套接字文件: {}", path.display());
+    std::fs::remove_file(path).map_err(|e| format!("删除残留的套接字文件 {} 失败: {}", path.display(), e))
+}
+
+/// 按配置设置套接字文件权限
+fn apply_permissions(path: &Path, permissions: Option<u32>) -> Result<(), String> {
+    match permissions {
+        Some(mode) => std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+            .map_err(|e| format!("设置 {} 的权限 {:o} 失败: {}", path.display(), mode, e)),
+        None => Ok(()),
+    }
+}
+
+/// 删除本端创建的套接字文件
+fn remove_socket_file(path: &Path) {
+    if let Err(e) = std::fs::remove_file(path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            warn!("[Unix] 删除套接字文件 {} 失败: {:?}", path.display(), e);
+        }
+    }
+}
+
+/// 在可选的超时时间内连接流式套接字，供 TcpClient 建立Unix域套接字会话
+pub(crate) async fn connect_stream(config: &ClientConfig) -> Result<ClientSession, String> {
+    let path = config.unix.path.trim();
+    let connect = UnixStream::connect(path);
+    let stream = match config.connect_timeout() {
+        Some(timeout) => tokio::time::timeout(timeout, connect)
+            .await
+            .map_err(|_| format!("连接 {} 超时", path))?,
+        None => connect.await,
+    }
+    .map_err(|e| format!("连接 {} 失败: {}", path, e))?;
+    info!("[Unix客户端] 已连接: {}", path);
+
+    Ok(ClientSession {
+        stream: Box::new(stream),
+        local_addr: None,
+        tls_info: None,
+        notices: Vec::new(),
+    })
+}
+
+/// 流式服务端为客户端显示的名称：序号及对端进程凭据
+fn describe_stream_peer(stream: &UnixStream, addr: SocketAddr) -> String {
+    match stream.peer_cred() {
+        Ok(cred) => match cred.pid() {
+            Some(pid) => format!("#{} (pid {}, uid {})", addr.port(), pid, cred.uid()),
+            None => format!("#{} (uid {})", addr.port(), cred.uid()),
+        },
+        Err(_) => format!("#{}", addr.port()),
+    }
+}
+
+/// Unix域流式套接字服务端
+pub struct UnixStreamServer {
+    ctx: UnixStreamServerContext,
+    listener_handle: Option<JoinHandle<()>>,
+}
+
+/// 流式服务端的共享状态，与接受连接的任务共用
+#[derive(Clone)]
+struct UnixStreamServerContext {
+    config: ServerConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    message_processor: Arc<dyn MessageProcessor>,
+    raw_capture: RawCaptureSlot,
+    clients: Arc<Mutex<HashMap<SocketAddr, WriteSender>>>,
+    client_handles: Arc<Mutex<HashMap<SocketAddr, JoinHandle<()>>>>,
+    client_controls: Arc<Mutex<HashMap<SocketAddr, ClientControl>>>,
+}
+
+impl UnixStreamServerContext {
+    /// 发送事件到UI线程
+    async fn emit(&self, event: ConnectionEvent) {
+        if let Some(sender) = &self.event_sender {
+            if let Err(e) = sender.send(event).await {
+                error!("[Unix服务器] 发送事件失败: {:?}", e);
+            }
+        }
+    }
+
+    /// 接受连接循环，直到监听套接字出错（任务被取消时随之结束）
+    async fn accept_loop(self, listener: UnixListener) {
+        let mut seq: u16 = 0;
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    debug!("Unix服务器监听失败: {:?}", e);
+                    break;
+                }
+            };
+            let addr = {
+                let controls = self.client_controls.lock().await;
+                next_virtual_addr(&mut seq, |addr| controls.contains_key(addr))
+            };
+            let label = describe_stream_peer(&stream, addr);
+            debug!("Unix服务器接受客户端 {} ({})", label, addr);
+
+            let Some(close_rx) = tcp::admit_client(&self.config, &self.client_controls, addr, &self.event_sender).await else {
+                drop(stream);
+                continue;
+            };
+
+            self.emit(ConnectionEvent::ServerClientLabel(self.config.id.clone(), addr, label.clone())).await;
+            let session = ServerClientSession {
+                server_id: self.config.id.clone(),
+                addr,
+                label,
+                config: self.config.clone(),
+                event_sender: self.event_sender.clone(),
+                message_processor: self.message_processor.clone(),
+                raw_capture: self.raw_capture.clone(),
+            };
+            let rx = tcp::register_client(&self.clients, &session).await;
+
+            let ctx = self.clone();
+            tcp::spawn_client_task(&self.client_handles, addr, async move {
+                // Unix域套接字没有 RST，强制关闭时直接释放连接
+                tcp::serve_client_stream(stream, rx, close_rx, &session).await;
+                tcp::release_client(&ctx.clients, &ctx.client_handles, &ctx.client_controls, &session).await;
+            }).await;
+        }
+    }
+}
+
+impl UnixStreamServer {
+    pub fn new(
+        config: ServerConfig,
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Self {
+        UnixStreamServer {
+            ctx: UnixStreamServerContext {
+                config,
+                event_sender,
+                message_processor: Arc::new(DefaultMessageProcessor),
+                raw_capture,
+                clients: Arc::new(Mutex::new(HashMap::new())),
+                client_handles: Arc::new(Mutex::new(HashMap::new())),
+                client_controls: Arc::new(Mutex::new(HashMap::new())),
+            },
+            listener_handle: None,
+        }
+    }
+
+    /// 关闭单个客户端连接，其余客户端和监听不受影响
+    pub async fn close_client(&self, addr: SocketAddr, mode: ClientCloseMode) -> Result<(), String> {
+        let control = self.ctx.client_controls.lock().await.remove(&addr);
+        match control {
+            Some(control) => {
+                info!("[Unix服务器] 关闭客户端 {} ({:?})", addr, mode);
+                let _ = control.close_tx.send(mode);
+                Ok(())
+            }
+            None => Err(format!("客户端 {} 不存在", addr)),
+        }
+    }
+}
+
+impl Drop for UnixStreamServer {
+    fn drop(&mut self) {
+        if let Some(handle) = self.listener_handle.take() {
+            handle.abort();
+            remove_socket_file(Path::new(self.ctx.config.unix.path.trim()));
+        }
+    }
+}
+
+impl NetworkServer for UnixStreamServer {
+    fn start(&mut self) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error>>> + Send + '_>> {
+        Pin::from(Box::new(async move {
+            if self.listener_handle.is_some() {
+                debug!("Unix服务器已经在运行中");
+                return Ok(());
+            }
+
+            let path = PathBuf::from(self.ctx.config.unix.path.trim());
+            validate_path(&self.ctx.config.unix.path)?;
+            remove_stale_socket(&path, false).await?;
+
+            let listener = UnixListener::bind(&path)
+                .map_err(|e| format!("绑定 {} 失败: {}", path.display(), e))?;
+            if let Err(e) = apply_permissions(&path, self.ctx.config.unix.permissions) {
+                drop(listener);
+                remove_socket_file(&path);
+                return Err(e.into());
+            }
+            info!("Unix服务器开始监听: {}", path.display());
+
+            self.ctx.emit(ConnectionEvent::Listening(self.ctx.config.id.clone())).await;
+            self.listener_handle = Some(tokio::spawn(self.ctx.clone().accept_loop(listener)));
+            Ok(())
+        }))
+    }
+
+    fn stop(&mut self) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        let ctx = self.ctx.clone();
+        let listener_handle = self.listener_handle.take();
+
+        Pin::from(Box::new(async move {
+            let Some(listener_handle) = listener_handle else {
+                debug!("Unix服务器已经停止");
+                return Ok(());
+            };
+            listener_handle.abort();
+            remove_socket_file(Path::new(ctx.config.unix.path.trim()));
+
+            // 关闭所有客户端的发送通道，取消客户端任务
+            ctx.clients.lock().await.clear();
+            ctx.client_controls.lock().await.clear();
+            let handles = std::mem::take(&mut *ctx.client_handles.lock().await);
+            for (addr, handle) in handles {
+                handle.abort();
+                debug!("Unix服务器已取消客户端 {} 的连接任务", addr);
+            }
+
+            ctx.emit(ConnectionEvent::Disconnected(ctx.config.id.clone())).await;
+            info!("Unix服务器已停止");
+            Ok(())
+        }))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// 接收一个数据报，返回数据、来源路径（对端未绑定路径时为 None）以及是否被截断
+///
+/// 与UDP相同，缓冲区比配置的接收长度多预留 1 字节用于判断截断。
+async fn recv_datagram(socket: &UnixDatagram, buffer: &mut [u8]) -> std::io::Result<(Vec<u8>, Option<PathBuf>, bool)> {
+    let limit = buffer.len() - 1;
+    let (n, addr) = socket.recv_from(buffer).await?;
+    let truncated = n > limit;
+    if truncated {
+        warn!("Unix数据报超过接收缓冲区 {} 字节，已截断", limit);
+    }
+    Ok((buffer[..n.min(limit)].to_vec(), addr.as_pathname().map(Path::to_path_buf), truncated))
+}
+
+/// Unix域数据报客户端
+pub struct UnixDatagramClient {
+    config: ClientConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    message_processor: Arc<dyn MessageProcessor>,
+    cancel_token: CancellationToken,
+    raw_capture: RawCaptureSlot,
+}
+
+impl UnixDatagramClient {
+    /// 创建客户端，套接字路径为空时返回错误
+    pub fn new(
+        config: ClientConfig,
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Result<Self, String> {
+        validate_path(&config.unix.path)?;
+
+        Ok(UnixDatagramClient {
+            config,
+            event_sender,
+            message_processor: Arc::new(DefaultMessageProcessor),
+            cancel_token: CancellationToken::new(),
+            raw_capture,
+        })
+    }
+}
+
+impl NetworkConnection for UnixDatagramClient {
+    fn connect(&mut self) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        let config = self.config.clone();
+        let event_sender = self.event_sender.clone();
+        let message_processor = self.message_processor.clone();
+        let cancel_token = self.cancel_token.clone();
+        let raw_capture = self.raw_capture.clone();
+
+        Pin::from(Box::new(async move {
+            let server_path = config.unix.path.trim().to_string();
+
+            // 绑定本地路径，服务端才能回复；未指定时在临时目录生成
+            let local_path = match config.unix.local_path.trim() {
+                "" => std::env::temp_dir().join(format!("netassistant-{}.sock", uuid::Uuid::new_v4())),
+                path => PathBuf::from(path),
+            };
+            remove_stale_socket(&local_path, true).await?;
+            let socket = UnixDatagram::bind(&local_path)
+                .map_err(|e| format!("绑定本地路径 {} 失败: {}", local_path.display(), e))?;
+            if let Err(e) = socket.connect(&server_path) {
+                drop(socket);
+                remove_socket_file(&local_path);
+                return Err(format!("连接 {} 失败: {}", server_path, e).into());
+            }
+            info!("[Unix数据报客户端] {} -> {}", local_path.display(), server_path);
+
+            let (tx, rx) = queue::write_queue(&config.queue);
+            if let Some(sender) = &event_sender {
+                let _ = sender.send(ConnectionEvent::Connected(config.id.clone())).await;
+                let _ = sender.send(ConnectionEvent::ClientWriteSenderReady(config.id.clone(), tx)).await;
+            }
+
+            tokio::spawn(async move {
+                let mut buffer = vec![0u8; config.udp_recv_buffer_len() + 1];
+                let mut session_error = None;
+                loop {
+                    tokio::select! {
+                        result = recv_datagram(&socket, &mut buffer) => {
+                            match result {
+                                Ok((data, _, truncated)) => {
+                                    raw_capture::record(&raw_capture, None, MessageDirection::Received, &data);
+                                    let message = message_processor
+                                        .process_received_message(data, MessageType::Text)
+                                        .with_truncated(truncated);
+                                    if let Some(sender) = &event_sender {
+                                        if let Err(e) = sender.send(ConnectionEvent::MessageReceived(config.id.clone(), message)).await {
+                                            error!("[Unix数据报客户端] 发送 MessageReceived 事件失败: {:?}", e);
+                                        }
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("Unix数据报读取错误: {:?}", e);
+                                    session_error = Some(format!("读取错误: {}", e));
+                                    break;
+                                }
+                            }
+                        }
+
+                        data = rx.recv() => {
+                            let Ok(data) = data else {
+                                debug!("Unix数据报发送通道已关闭");
+                                break;
+                            };
+                            match socket.send(&data).await {
+                                Ok(_) => raw_capture::record(&raw_capture, None, MessageDirection::Sent, &data),
+                                Err(e) => {
+                                    // 服务端未启动或已退出时发送失败，仅提示，保留连接以便服务端恢复后继续发送
+                                    warn!("Unix数据报发送错误: {:?}", e);
+                                    if let Some(sender) = &event_sender {
+                                        let _ = sender.send(ConnectionEvent::Warning(
+                                            config.id.clone(),
+                                            format!("发送到 {} 失败: {}", server_path, e),
+                                        )).await;
+                                    }
+                                }
+                            }
+                        }
+
+                        _ = cancel_token.cancelled() => {
+                            info!("Unix数据报客户端收到取消信号，退出");
+                            break;
+                        }
+                    }
+                }
+
+                drop(socket);
+                remove_socket_file(&local_path);
+                if let Some(sender) = &event_sender {
+                    let event = match session_error {
+                        Some(reason) => ConnectionEvent::Error(config.id.clone(), reason),
+                        None => ConnectionEvent::Disconnected(config.id.clone()),
+                    };
+                    let _ = sender.send(event).await;
+                }
+            });
+
+            Ok(())
+        }))
+    }
+
+    fn disconnect(&mut self) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        self.cancel_token.cancel();
+
+        Pin::from(Box::new(async move {
+            Ok(())
+        }))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// Unix域数据报服务端
+///
+/// 与UDP服务端一样，以来源路径区分客户端；未绑定路径的对端发来的数据报只显示，无法回复。
+pub struct UnixDatagramServer {
+    config: ServerConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    message_processor: Arc<dyn MessageProcessor>,
+    raw_capture: RawCaptureSlot,
+    /// 客户端虚拟地址对应的发送通道
+    clients: Arc<Mutex<HashMap<SocketAddr, WriteSender>>>,
+    /// 客户端来源路径与虚拟地址的对应关系
+    peers: Arc<Mutex<HashMap<PathBuf, SocketAddr>>>,
+    cancel_token: Option<CancellationToken>,
+}
+
+impl UnixDatagramServer {
+    pub fn new(
+        config: ServerConfig,
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Self {
+        UnixDatagramServer {
+            config,
+            event_sender,
+            message_processor: Arc::new(DefaultMessageProcessor),
+            raw_capture,
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            peers: Arc::new(Mutex::new(HashMap::new())),
+            cancel_token: None,
+        }
+    }
+
+    /// 从客户端列表中移除（数据报无连接，仅停止向其发送）
+    pub async fn remove_client(&self, addr: SocketAddr) -> Result<(), String> {
+        if self.clients.lock().await.remove(&addr).is_none() {
+            return Err(format!("客户端 {} 不存在", addr));
+        }
+        self.peers.lock().await.retain(|_, peer_addr| *peer_addr != addr);
+
+        if let Some(sender) = &self.event_sender {
+            let _ = sender.send(ConnectionEvent::ServerClientDisconnected(self.config.id.clone(), addr)).await;
+        }
+        info!("[Unix数据报服务器] 移除客户端: {}", addr);
+        Ok(())
+    }
+}
+
+impl NetworkServer for UnixDatagramServer {
+    fn start(&mut self) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error>>> + Send + '_>> {
+        Pin::from(Box::new(async move {
+            if self.cancel_token.is_some() {
+                debug!("Unix数据报服务器已经在运行中");
+                return Ok(());
+            }
+
+            let path = PathBuf::from(self.config.unix.path.trim());
+            validate_path(&self.config.unix.path)?;
+            remove_stale_socket(&path, true).await?;
+
+            let socket = UnixDatagram::bind(&path)
+                .map_err(|e| format!("绑定 {} 失败: {}", path.display(), e))?;
+            if let Err(e) = apply_permissions(&path, self.config.unix.permissions) {
+                drop(socket);
+                remove_socket_file(&path);
+                return Err(e.into());
+            }
+            info!("Unix数据报服务器开始监听: {}", path.display());
+
+            let event_sender = self.event_sender.clone();
+            if let Some(sender) = &event_sender {
+                let _ = sender.send(ConnectionEvent::Listening(self.config.id.clone())).await;
+            }
+
+            let cancel_token = CancellationToken::new();
+            self.cancel_token = Some(cancel_token.clone());
+
+            let socket = Arc::new(socket);
+            // 各客户端的发送队列经此汇总，有界以便写入变慢时各队列随之积压
+            let (main_tx, main_rx) = smol::channel::bounded::<(PathBuf, SocketAddr, Vec<u8>)>(self.config.queue.capacity());
+
+            // 发送任务：各客户端通道汇总后按来源路径发送
+            let write_socket = socket.clone();
+            let write_raw_capture = self.raw_capture.clone();
+            let write_cancel_token = cancel_token.clone();
+            tokio::spawn(async move {
+                loop {
+                    tokio::select! {
+                        item = main_rx.recv() => {
+                            let Ok((peer_path, addr, data)) = item else { break };
+                            match write_socket.send_to(&data, &peer_path).await {
+                                Ok(_) => raw_capture::record(&write_raw_capture, Some(addr), MessageDirection::Sent, &data),
+                                Err(e) => error!("Unix数据报服务器向 {} 发送失败: {:?}", peer_path.display(), e),
+                            }
+                        }
+                        _ = write_cancel_token.cancelled() => break,
+                    }
+                }
+            });
+
+            // 接收任务
+            let server_id = self.config.id.clone();
+            let recv_buffer_len = self.config.udp_recv_buffer_len();
+            let queue_config = self.config.queue;
+            let message_processor = self.message_processor.clone();
+            let raw_capture = self.raw_capture.clone();
+            let clients = self.clients.clone();
+            let peers = self.peers.clone();
+            tokio::spawn(async move {
+                let mut buffer = vec![0u8; recv_buffer_len + 1];
+                let mut seq: u16 = 0;
+                let mut unnamed_notified = false;
+                loop {
+                    let result = tokio::select! {
+                        result = recv_datagram(&socket, &mut buffer) => result,
+                        _ = cancel_token.cancelled() => break,
+                    };
+                    let (data, peer_path, truncated) = match result {
+                        Ok(received) => received,
+                        Err(e) => {
+                            error!("Unix数据报服务器读取错误: {:?}", e);
+                            break;
+                        }
+                    };
+
+                    // 未绑定路径的对端无法回复，只显示数据
+                    let Some(peer_path) = peer_path else {
+                        raw_capture::record(&raw_capture, None, MessageDirection::Received, &data);
+                        if !unnamed_notified {
+                            unnamed_notified = true;
+                            if let Some(sender) = &event_sender {
+                                let _ = sender.send(ConnectionEvent::Warning(
+                                    server_id.clone(),
+                                    "收到来自未绑定路径的对端的数据报，无法向其回复".to_string(),
+                                )).await;
+                            }
+                        }
+                        let message = message_processor
+                            .process_received_message(data, MessageType::Text)
+                            .with_truncated(truncated);
+                        if let Some(sender) = &event_sender {
+                            let _ = sender.send(ConnectionEvent::MessageReceived(server_id.clone(), message)).await;
+                        }
+                        continue;
+                    };
+
+                    // 新的来源路径分配虚拟地址，并创建发送通道
+                    let mut peers_guard = peers.lock().await;
+                    let addr = match peers_guard.get(&peer_path) {
+                        Some(addr) => *addr,
+                        None => {
+                            let addr = next_virtual_addr(&mut seq, |addr| peers_guard.values().any(|used| used == addr));
+                            peers_guard.insert(peer_path.clone(), addr);
+                            let (client_tx, client_rx) = queue::write_queue(&queue_config);
+                            clients.lock().await.insert(addr, client_tx.clone());
+
+                            let main_tx = main_tx.clone();
+                            let peer_path = peer_path.clone();
+                            tokio::spawn(async move {
+                                while let Ok(data) = client_rx.recv().await {
+                                    if main_tx.send((peer_path.clone(), addr, data)).await.is_err() {
+                                        break;
+                                    }
+                                }
+                            });
+
+                            if let Some(sender) = &event_sender {
+                                let _ = sender.send(ConnectionEvent::ServerClientLabel(
+                                    server_id.clone(),
+                                    addr,
+                                    peer_path.display().to_string(),
+                                )).await;
+                                let _ = sender.send(ConnectionEvent::ServerClientConnected(server_id.clone(), addr, client_tx)).await;
+                            }
+                            addr
+                        }
+                    };
+                    drop(peers_guard);
+
+                    raw_capture::record(&raw_capture, Some(addr), MessageDirection::Received, &data);
+                    let message = message_processor
+                        .process_received_message(data, MessageType::Text)
+                        .with_source(addr.to_string())
+                        .with_truncated(truncated);
+                    if let Some(sender) = &event_sender {
+                        if let Err(e) = sender.send(ConnectionEvent::MessageReceived(server_id.clone(), message)).await {
+                            error!("[Unix数据报服务器] 发送 MessageReceived 事件失败: {:?}", e);
+                        }
+                    }
+                }
+            });
+
+            Ok(())
+        }))
+    }
+
+    fn stop(&mut self) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        let cancel_token = self.cancel_token.take();
+        let path = PathBuf::from(self.config.unix.path.trim());
+        let server_id = self.config.id.clone();
+        let event_sender = self.event_sender.clone();
+        let clients = self.clients.clone();
+        let peers = self.peers.clone();
+
+        Pin::from(Box::new(async move {
+            let Some(cancel_token) = cancel_token else {
+                debug!("Unix数据报服务器已经停止");
+                return Ok(());
+            };
+            cancel_token.cancel();
+            remove_socket_file(&path);
+            clients.lock().await.clear();
+            peers.lock().await.clear();
+
+            if let Some(sender) = &event_sender {
+                let _ = sender.send(ConnectionEvent::Disconnected(server_id)).await;
+            }
+            info!("Unix数据报服务器已停止");
+            Ok(())
+        }))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Drop for UnixDatagramServer {
+    fn drop(&mut self) {
+        if let Some(cancel_token) = self.cancel_token.take() {
+            cancel_token.cancel();
+            remove_socket_file(Path::new(self.config.unix.path.trim()));
+        }
+    }
+}
@@ -0,0 +1,867 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, error, info, warn};
+use smol::channel::Sender;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request as ClientRequest;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::header::SEC_WEBSOCKET_PROTOCOL;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::{self, Message as WsMessage};
+use tokio_tungstenite::WebSocketStream;
+use tokio_util::sync::CancellationToken;
+use crate::config::connection::{ClientConfig, ServerConfig, WebSocketSendFrame};
+use crate::core::message_processor::{DefaultMessageProcessor, MessageProcessor};
+use crate::message::{MessageDirection, MessageType};
+use crate::network::events::ConnectionEvent;
+use crate::network::interfaces::{NetworkConnection, NetworkServer};
+use crate::network::protocol::tcp::{self, AsyncStream, ClientCloseMode, ClientControl, ClientSession};
+use crate::network::queue::{self, WriteReceiver, WriteSender};
+use crate::network::resolve;
+use crate::network::tls::{self, TlsSessionInfo};
+use crate::raw_capture::{self, RawCaptureSlot};
+
+/// WebSocket握手与控制帧事件
+#[derive(Debug, Clone)]
+pub enum WebSocketEvent {
+    /// 握手完成（请求路径，协商的子协议）
+    Opened { path: String, subprotocol: Option<String> },
+    /// 收到 Ping（已自动回复 Pong）
+    Ping(Vec<u8>),
+    /// 收到 Pong
+    Pong(Vec<u8>),
+    /// 收到关闭帧（关闭码和原因）
+    Close { code: Option<u16>, reason: String },
+}
+
+/// 发送关闭帧后等待对端回复的最长时间
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 控制帧负载的显示形式：可打印文本原样显示，否则显示十六进制
+fn describe_payload(payload: &[u8]) -> String {
+    match std::str::from_utf8(payload) {
+        Ok(text) if !text.chars().any(char::is_control) => format!("\"{}\"", text),
+        _ => payload.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" "),
+    }
+}
+
+impl fmt::Display for WebSocketEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebSocketEvent::Opened { path, subprotocol } => match subprotocol {
+                Some(subprotocol) => write!(f, "握手完成 {}，子协议 {}", path, subprotocol),
+                None => write!(f, "握手完成 {}", path),
+            },
+            WebSocketEvent::Ping(payload) if payload.is_empty() => write!(f, "收到 Ping"),
+            WebSocketEvent::Ping(payload) => write!(f, "收到 Ping {}", describe_payload(payload)),
+            WebSocketEvent::Pong(payload) if payload.is_empty() => write!(f, "收到 Pong"),
+            WebSocketEvent::Pong(payload) => write!(f, "收到 Pong {}", describe_payload(payload)),
+            WebSocketEvent::Close { code: Some(code), reason } if !reason.is_empty() => {
+                write!(f, "收到关闭帧 {} {}", code, reason)
+            }
+            WebSocketEvent::Close { code: Some(code), .. } => write!(f, "收到关闭帧 {}", code),
+            WebSocketEvent::Close { code: None, .. } => write!(f, "收到关闭帧（无关闭码）"),
+        }
+    }
+}
+
+/// 按配置的帧类型将待发送数据封装为数据帧
+fn data_frame(data: Vec<u8>, send_frame: WebSocketSendFrame) -> WsMessage {
+    match send_frame {
+        WebSocketSendFrame::Binary => WsMessage::binary(data),
+        // 文本帧必须是合法 UTF-8，非法字节替换为 U+FFFD
+        WebSocketSendFrame::Text => WsMessage::text(String::from_utf8_lossy(&data).into_owned()),
+        WebSocketSendFrame::Auto => match String::from_utf8(data) {
+            Ok(text) => WsMessage::text(text),
+            Err(e) => WsMessage::binary(e.into_bytes()),
+        },
+    }
+}
+
+/// 握手失败的说明，HTTP 响应只显示状态码
+fn describe_handshake_error(e: &tungstenite::Error) -> String {
+    match e {
+        tungstenite::Error::Http(response) => format!("WebSocket握手失败：服务器返回 HTTP {}", response.status()),
+        e => format!("WebSocket握手失败：{}", e),
+    }
+}
+
+/// 单个WebSocket会话所需的上下文（客户端的 peer 为 None）
+struct SessionContext {
+    id: String,
+    peer: Option<SocketAddr>,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    message_processor: Arc<dyn MessageProcessor>,
+    raw_capture: RawCaptureSlot,
+    send_frame: WebSocketSendFrame,
+    write_timeout: Option<Duration>,
+    read_idle_timeout: Option<Duration>,
+    read_idle_close: bool,
+}
+
+impl SessionContext {
+    /// 发送事件到UI线程
+    async fn emit(&self, event: ConnectionEvent) {
+        if let Some(sender) = &self.event_sender {
+            if let Err(e) = sender.send(event).await {
+                error!("[WebSocket] 发送事件失败: {:?}", e);
+            }
+        }
+    }
+
+    /// 消息来源显示的对端描述
+    fn peer_label(&self) -> String {
+        self.peer.map(|addr| format!("客户端 {} ", addr)).unwrap_or_default()
+    }
+}
+
+/// 运行一个已完成握手的WebSocket会话
+///
+/// 文本帧以文本消息、二进制帧以十六进制消息上报，Ping/Pong/关闭帧以 WebSocket 事件上报
+/// （Ping 由协议栈自动回复）。收到取消信号或写入通道关闭时发送关闭帧（1000）后返回。
+/// 因读取错误、读取空闲超时或写入超时而结束时返回错误描述。
+async fn run_session<S>(
+    ws: WebSocketStream<S>,
+    rx: WriteReceiver,
+    ctx: &SessionContext,
+    close_token: CancellationToken,
+) -> Option<String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (mut sink, mut stream) = ws.split();
+    let mut last_read = Instant::now();
+    let mut idle_notified = false;
+    let mut close_sent = false;
+    let mut close_deadline = tokio::time::Instant::now();
+
+    loop {
+        let idle_deadline = ctx
+            .read_idle_timeout
+            .filter(|_| !idle_notified)
+            .map(|timeout| tokio::time::Instant::from_std(last_read + timeout));
+        let idle_reached = async {
+            match idle_deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            frame = stream.next() => {
+                let frame = match frame {
+                    Some(Ok(frame)) => frame,
+                    Some(Err(tungstenite::Error::ConnectionClosed)) | None => {
+                        info!("[WebSocket] {}连接已关闭", ctx.peer_label());
+                        return None;
+                    }
+                    Some(Err(e)) => {
+                        error!("[WebSocket] {}读取错误: {:?}", ctx.peer_label(), e);
+                        // 已发送关闭帧后对端直接断开属于正常关闭
+                        return (!close_sent).then(|| format!("{}WebSocket读取错误：{}", ctx.peer_label(), e));
+                    }
+                };
+                last_read = Instant::now();
+                idle_notified = false;
+
+                let (payload, message_type) = match frame {
+                    WsMessage::Text(text) => (text.as_bytes().to_vec(), MessageType::Text),
+                    WsMessage::Binary(data) => (data.to_vec(), MessageType::Hex),
+                    WsMessage::Ping(payload) => {
+                        ctx.emit(ConnectionEvent::WebSocket(ctx.id.clone(), ctx.peer, WebSocketEvent::Ping(payload.to_vec()))).await;
+                        continue;
+                    }
+                    WsMessage::Pong(payload) => {
+                        ctx.emit(ConnectionEvent::WebSocket(ctx.id.clone(), ctx.peer, WebSocketEvent::Pong(payload.to_vec()))).await;
+                        continue;
+                    }
+                    WsMessage::Close(frame) => {
+                        let (code, reason) = match frame {
+                            Some(frame) => (Some(u16::from(frame.code)), frame.reason.as_str().to_string()),
+                            None => (None, String::new()),
+                        };
+                        info!("[WebSocket] {}收到关闭帧: {:?} {}", ctx.peer_label(), code, reason);
+                        ctx.emit(ConnectionEvent::WebSocket(ctx.id.clone(), ctx.peer, WebSocketEvent::Close { code, reason })).await;
+                        // 协议栈已自动回复关闭帧，继续读取直到连接结束
+                        continue;
+                    }
+                    WsMessage::Frame(_) => continue,
+                };
+
+                raw_capture::record(&ctx.raw_capture, ctx.peer, MessageDirection::Received, &payload);
+                let mut message = ctx.message_processor
+                    .process_received_message(payload, message_type)
+                    .with_frame_type(message_type);
+                if let Some(addr) = ctx.peer {
+                    message = message.with_source(addr.to_string());
+                }
+                ctx.emit(ConnectionEvent::MessageReceived(ctx.id.clone(), message)).await;
+            }
+
+            data = rx.recv(), if !close_sent => {
+                let Ok(data) = data else {
+                    debug!("[WebSocket] 消息发送通道已关闭");
+                    close_token.cancel();
+                    continue;
+                };
+                let frame = data_frame(data, ctx.send_frame);
+                let payload = match &frame {
+                    WsMessage::Text(text) => text.as_bytes().to_vec(),
+                    WsMessage::Binary(data) => data.to_vec(),
+                    _ => Vec::new(),
+                };
+                let result = match ctx.write_timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, sink.send(frame)).await,
+                    None => Ok(sink.send(frame).await),
+                };
+                match result {
+                    Ok(Ok(())) => raw_capture::record(&ctx.raw_capture, ctx.peer, MessageDirection::Sent, &payload),
+                    Ok(Err(e)) => {
+                        error!("[WebSocket] {}写入错误: {:?}", ctx.peer_label(), e);
+                        return Some(format!("{}WebSocket写入错误：{}", ctx.peer_label(), e));
+                    }
+                    Err(_) => {
+                        return Some(format!(
+                            "{}写入超时：对端 {} 秒内未接收数据，已断开",
+                            ctx.peer_label(),
+                            ctx.write_timeout.map(|t| t.as_secs()).unwrap_or_default()
+                        ));
+                    }
+                }
+            }
+
+            _ = close_token.cancelled(), if !close_sent => {
+                info!("[WebSocket] {}发送关闭帧", ctx.peer_label());
+                close_sent = true;
+                close_deadline = tokio::time::Instant::now() + CLOSE_TIMEOUT;
+                let close = WsMessage::Close(Some(CloseFrame { code: CloseCode::Normal, reason: "".into() }));
+                if let Err(e) = sink.send(close).await {
+                    debug!("[WebSocket] 发送关闭帧失败: {:?}", e);
+                    return None;
+                }
+                // 继续读取，等待对端回复关闭帧
+            }
+
+            _ = tokio::time::sleep_until(close_deadline), if close_sent => {
+                debug!("[WebSocket] {}等待关闭帧回复超时", ctx.peer_label());
+                return None;
+            }
+
+            _ = idle_reached => {
+                let idle_timeout = ctx.read_idle_timeout.unwrap_or_default();
+                if ctx.read_idle_close {
+                    info!("[WebSocket] {}读取空闲超时，断开连接", ctx.peer_label());
+                    return Some(format!(
+                        "{}读取空闲超时：{} 秒内未收到数据，已断开连接",
+                        ctx.peer_label(),
+                        idle_timeout.as_secs()
+                    ));
+                }
+                idle_notified = true;
+                ctx.emit(ConnectionEvent::TimeoutError(
+                    ctx.id.clone(),
+                    ctx.peer,
+                    format!("{}读取空闲超时：已 {} 秒未收到数据", ctx.peer_label(), idle_timeout.as_secs()),
+                )).await;
+            }
+        }
+    }
+}
+
+/// 根据客户端配置构造握手请求（URL、附加头部、子协议）
+fn handshake_request(config: &ClientConfig) -> Result<ClientRequest, String> {
+    let websocket = &config.websocket;
+    let url = websocket.url(&config.server_address, config.server_port, config.tls.enabled);
+    let mut request = url
+        .as_str()
+        .into_client_request()
+        .map_err(|e| format!("无效的WebSocket地址 {}: {}", url, e))?;
+
+    // 自定义头部覆盖同名的默认头部，同名的多个自定义头部全部保留
+    let mut overridden: Vec<HeaderName> = Vec::new();
+    for (name, value) in &websocket.headers {
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| format!("无效的头部名称 '{}'", name))?;
+        let header_value = HeaderValue::from_str(value)
+            .map_err(|_| format!("头部 {} 的值无效", name))?;
+        if !overridden.contains(&header_name) {
+            request.headers_mut().remove(&header_name);
+            overridden.push(header_name.clone());
+        }
+        request.headers_mut().append(header_name, header_value);
+    }
+
+    if !websocket.subprotocols.is_empty() {
+        let value = HeaderValue::from_str(&websocket.subprotocols.join(", "))
+            .map_err(|_| "无效的子协议".to_string())?;
+        request.headers_mut().insert(SEC_WEBSOCKET_PROTOCOL, value);
+    }
+    Ok(request)
+}
+
+/// WebSocket客户端实现
+pub struct WebSocketClient {
+    config: ClientConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    message_processor: Arc<dyn MessageProcessor>,
+    cancel_token: CancellationToken,
+    raw_capture: RawCaptureSlot,
+    /// wss:// 使用的TLS连接器
+    tls_connector: Option<tokio_rustls::TlsConnector>,
+}
+
+/// WebSocket客户端会话上下文，自动重连时每次会话复用同一份
+#[derive(Clone)]
+struct WebSocketClientContext {
+    config: ClientConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    message_processor: Arc<dyn MessageProcessor>,
+    raw_capture: RawCaptureSlot,
+    tls_connector: Option<tokio_rustls::TlsConnector>,
+}
+
+/// 已完成WebSocket握手的客户端连接
+struct WebSocketClientSession {
+    ws: WebSocketStream<Box<dyn AsyncStream>>,
+    local_addr: Option<SocketAddr>,
+    tls_info: Option<TlsSessionInfo>,
+    notices: Vec<String>,
+    path: String,
+    subprotocol: Option<String>,
+}
+
+impl WebSocketClientContext {
+    /// 发送事件到UI线程
+    async fn emit(&self, event: ConnectionEvent) {
+        if let Some(sender) = &self.event_sender {
+            if let Err(e) = sender.send(event).await {
+                error!("[WebSocket客户端] 发送事件失败: {:?}", e);
+            }
+        }
+    }
+}
+
+impl WebSocketClient {
+    /// 创建客户端，服务器地址、握手头部或TLS配置无效时返回错误
+    pub fn new(
+        config: ClientConfig,
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Result<Self, String> {
+        resolve::validate_remote(&config.server_address, config.server_port)
+            .map_err(|e| format!("无效的WebSocket服务器地址: {}", e))?;
+        config.proxy.validate(config.protocol)?;
+        handshake_request(&config)?;
+        let tls_connector = if config.tls.enabled {
+            Some(tls::connector(&config.tls)?)
+        } else {
+            None
+        };
+
+        Ok(WebSocketClient {
+            config,
+            event_sender,
+            message_processor: Arc::new(DefaultMessageProcessor),
+            cancel_token: CancellationToken::new(),
+            raw_capture,
+            tls_connector,
+        })
+    }
+
+    /// 建立TCP（及TLS）连接并完成WebSocket握手
+    async fn establish(ctx: &WebSocketClientContext) -> Result<WebSocketClientSession, String> {
+        let config = &ctx.config;
+        let request = handshake_request(config)?;
+        let path = request.uri().path_and_query().map(|p| p.to_string()).unwrap_or_else(|| "/".to_string());
+
+        let ClientSession { stream, local_addr, tls_info, notices } =
+            tcp::establish_client_stream(config, ctx.tls_connector.as_ref(), &ctx.event_sender).await?;
+        let handshake = tokio_tungstenite::client_async_with_config(request, stream, None);
+        let (ws, response) = match config.connect_timeout() {
+            Some(timeout) => tokio::time::timeout(timeout, handshake)
+                .await
+                .map_err(|_| format!("WebSocket握手超时：{} 秒内未完成", timeout.as_secs()))?,
+            None => handshake.await,
+        }
+        .map_err(|e| describe_handshake_error(&e))?;
+
+        let subprotocol = response
+            .headers()
+            .get(SEC_WEBSOCKET_PROTOCOL)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        info!("[WebSocket客户端] 握手完成: {} 子协议 {:?}", path, subprotocol);
+        Ok(WebSocketClientSession { ws, local_addr, tls_info, notices, path, subprotocol })
+    }
+
+    /// 运行一次已握手的会话，通知UI连接成功并交出写入发送器
+    async fn run_client_session(
+        session: WebSocketClientSession,
+        ctx: WebSocketClientContext,
+        cancel_token: CancellationToken,
+    ) -> Option<String> {
+        let config = &ctx.config;
+        let (tx, rx) = queue::write_queue(&config.queue);
+
+        ctx.emit(ConnectionEvent::Connected(config.id.clone())).await;
+        ctx.emit(ConnectionEvent::ClientWriteSenderReady(config.id.clone(), tx)).await;
+        for notice in session.notices {
+            ctx.emit(ConnectionEvent::Warning(config.id.clone(), notice)).await;
+        }
+        if let Some(local_addr) = session.local_addr {
+            ctx.emit(ConnectionEvent::LocalAddress(config.id.clone(), local_addr)).await;
+        }
+        if let Some(tls_info) = session.tls_info {
+            ctx.emit(ConnectionEvent::TlsEstablished(config.id.clone(), None, tls_info)).await;
+        }
+        ctx.emit(ConnectionEvent::WebSocket(
+            config.id.clone(),
+            None,
+            WebSocketEvent::Opened { path: session.path, subprotocol: session.subprotocol },
+        )).await;
+
+        let session_ctx = SessionContext {
+            id: config.id.clone(),
+            peer: None,
+            event_sender: ctx.event_sender.clone(),
+            message_processor: ctx.message_processor.clone(),
+            raw_capture: ctx.raw_capture.clone(),
+            send_frame: config.websocket.send_frame,
+            write_timeout: config.write_timeout(),
+            read_idle_timeout: config.read_idle_timeout(),
+            read_idle_close: config.read_idle_close,
+        };
+        run_session(session.ws, rx, &session_ctx, cancel_token.child_token()).await
+    }
+
+    /// 自动重连监督循环（与TCP客户端相同的指数退避策略）
+    async fn run_with_reconnect(ctx: WebSocketClientContext, cancel_token: CancellationToken) {
+        let id = ctx.config.id.clone();
+        let mut attempt: u32 = 0;
+
+        loop {
+            let result = tokio::select! {
+                result = Self::establish(&ctx) => result,
+                _ = cancel_token.cancelled() => break,
+            };
+
+            match result {
+                Ok(session) => {
+                    attempt = 0;
+                    let session_error = Self::run_client_session(session, ctx.clone(), cancel_token.clone()).await;
+                    if cancel_token.is_cancelled() {
+                        break;
+                    }
+                    if let Some(reason) = session_error {
+                        ctx.emit(ConnectionEvent::Warning(id.clone(), reason)).await;
+                    }
+                    info!("[WebSocket客户端] 连接断开，准备重连: {}", id);
+                }
+                Err(e) => {
+                    error!("[WebSocket客户端] {}", e);
+                    ctx.emit(ConnectionEvent::Warning(id.clone(), e)).await;
+                }
+            }
+
+            attempt += 1;
+            if ctx.config.reconnect_attempts_exhausted(attempt) {
+                error!("[WebSocket客户端] 重连 {} 次仍未成功，放弃重连", ctx.config.reconnect_max_attempts);
+                ctx.emit(ConnectionEvent::Error(
+                    id.clone(),
+                    format!("重连失败：已尝试 {} 次", ctx.config.reconnect_max_attempts),
+                )).await;
+                break;
+            }
+
+            let delay = ctx.config.reconnect_delay(attempt);
+            info!("[WebSocket客户端] 第 {} 次重连将在 {:?} 后进行", attempt, delay);
+            ctx.emit(ConnectionEvent::Reconnecting(id.clone(), attempt, delay)).await;
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = cancel_token.cancelled() => break,
+            }
+        }
+
+        debug!("[WebSocket客户端] 重连循环已退出: {}", id);
+    }
+}
+
+impl NetworkConnection for WebSocketClient {
+    fn connect(&mut self) -> Pin<Box<dyn std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        let ctx = WebSocketClientContext {
+            config: self.config.clone(),
+            event_sender: self.event_sender.clone(),
+            message_processor: self.message_processor.clone(),
+            raw_capture: self.raw_capture.clone(),
+            tls_connector: self.tls_connector.clone(),
+        };
+        let cancel_token = self.cancel_token.clone();
+
+        Pin::from(Box::new(async move {
+            let config = &ctx.config;
+            info!(
+                "WebSocket客户端连接到: {}",
+                config.websocket.url(&config.server_address, config.server_port, config.tls.enabled)
+            );
+
+            if config.auto_reconnect {
+                tokio::spawn(Self::run_with_reconnect(ctx, cancel_token));
+                return Ok(());
+            }
+
+            // 连接或握手失败时由连接管理器将错误转发给UI
+            let session = Self::establish(&ctx).await?;
+
+            tokio::spawn(async move {
+                let id = ctx.config.id.clone();
+                match Self::run_client_session(session, ctx.clone(), cancel_token).await {
+                    Some(reason) => ctx.emit(ConnectionEvent::Error(id, reason)).await,
+                    None => ctx.emit(ConnectionEvent::Disconnected(id)).await,
+                }
+            });
+
+            Ok(())
+        }))
+    }
+
+    fn disconnect(&mut self) -> Pin<Box<dyn std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        // 取消后会话发送关闭帧（1000）并等待对端回复
+        self.cancel_token.cancel();
+
+        Pin::from(Box::new(async move {
+            Ok(())
+        }))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// 从客户端请求的子协议中选择第一个服务端支持的
+fn select_subprotocol(request: &Request, supported: &[String]) -> Option<String> {
+    request
+        .headers()
+        .get_all(SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .find(|requested| supported.iter().any(|s| s == requested))
+        .map(str::to_string)
+}
+
+/// WebSocket服务端的共享状态，监听任务和各客户端任务各持有一份
+#[derive(Clone)]
+struct WebSocketServerContext {
+    config: ServerConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    message_processor: Arc<dyn MessageProcessor>,
+    raw_capture: RawCaptureSlot,
+    clients: Arc<Mutex<HashMap<SocketAddr, WriteSender>>>,
+    client_handles: Arc<Mutex<HashMap<SocketAddr, JoinHandle<()>>>>,
+    client_controls: Arc<Mutex<HashMap<SocketAddr, ClientControl>>>,
+    tls_acceptor: Option<tokio_rustls::TlsAcceptor>,
+}
+
+impl WebSocketServerContext {
+    /// 发送事件到UI线程
+    async fn emit(&self, event: ConnectionEvent) {
+        if let Some(sender) = &self.event_sender {
+            if let Err(e) = sender.send(event).await {
+                error!("[WebSocket服务器] 发送事件失败: {:?}", e);
+            }
+        }
+    }
+
+    /// 接受连接循环，直到监听套接字出错（任务被取消时随之结束）
+    async fn accept_loop(self, listener: TcpListener) {
+        loop {
+            let (socket, addr) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    debug!("WebSocket服务器监听失败: {:?}", e);
+                    break;
+                }
+            };
+            debug!("WebSocket服务器接收到来自 {} 的连接", addr);
+            if let Some(warning) = tcp::apply_socket_options(&socket, &self.config.socket_options) {
+                warn!("[WebSocket服务器] 客户端 {} {}", addr, warning);
+                self.emit(ConnectionEvent::Warning(self.config.id.clone(), format!("客户端 {} {}", addr, warning))).await;
+            }
+
+            // 最大连接数限制（与TCP服务端一致）
+            let Some(close_rx) = tcp::admit_client(&self.config, &self.client_controls, addr, &self.event_sender).await else {
+                drop(socket);
+                continue;
+            };
+
+            tcp::report_client_local_addr(&self.config, &self.event_sender, &socket, addr).await;
+            tcp::spawn_client_task(&self.client_handles, addr, self.clone().serve_client(socket, addr, close_rx)).await;
+        }
+    }
+
+    /// 处理单个客户端：TLS握手、WebSocket握手，然后运行会话直到结束或被服务端关闭
+    async fn serve_client(
+        self,
+        socket: TcpStream,
+        addr: SocketAddr,
+        close_rx: tokio::sync::oneshot::Receiver<ClientCloseMode>,
+    ) {
+        // 强制关闭时需设置 SO_LINGER，包装后无法再取回 TcpStream，先保留一个套接字句柄
+        let abort_handle = socket2::SockRef::from(&socket).try_clone();
+        let mut announced = false;
+
+        match self.handshake(socket, addr).await {
+            Ok((ws, path, subprotocol)) => {
+                let (tx, rx) = queue::write_queue(&self.config.queue);
+                self.clients.lock().await.insert(addr, tx.clone());
+                self.emit(ConnectionEvent::ServerClientConnected(self.config.id.clone(), addr, tx)).await;
+                self.emit(ConnectionEvent::WebSocket(
+                    self.config.id.clone(),
+                    Some(addr),
+                    WebSocketEvent::Opened { path, subprotocol },
+                )).await;
+                announced = true;
+
+                let session_ctx = SessionContext {
+                    id: self.config.id.clone(),
+                    peer: Some(addr),
+                    event_sender: self.event_sender.clone(),
+                    message_processor: self.message_processor.clone(),
+                    raw_capture: self.raw_capture.clone(),
+                    send_frame: self.config.websocket.send_frame,
+                    write_timeout: self.config.write_timeout(),
+                    read_idle_timeout: self.config.read_idle_timeout(),
+                    read_idle_close: self.config.read_idle_close,
+                };
+                let close_token = CancellationToken::new();
+                let session = run_session(ws, rx, &session_ctx, close_token.clone());
+                tokio::pin!(session);
+
+                let close_mode = tokio::select! {
+                    reason = &mut session => {
+                        if let Some(reason) = reason {
+                            session_ctx.emit(ConnectionEvent::Warning(self.config.id.clone(), reason)).await;
+                        }
+                        None
+                    }
+                    mode = close_rx => mode.ok(),
+                };
+
+                match close_mode {
+                    Some(ClientCloseMode::Graceful) => {
+                        // 发送关闭帧（1000）并等待对端回复，超时由会话自行结束
+                        close_token.cancel();
+                        let _ = (&mut session).await;
+                        debug!("WebSocket服务器已关闭客户端 {}", addr);
+                    }
+                    Some(ClientCloseMode::Abort) => {
+                        // SO_LINGER 为 0 时关闭套接字会直接发送 RST
+                        match &abort_handle {
+                            Ok(handle) => {
+                                if let Err(e) = handle.set_linger(Some(Duration::ZERO)) {
+                                    error!("WebSocket服务器设置 SO_LINGER 失败: {:?}", e);
+                                }
+                            }
+                            Err(e) => error!("WebSocket服务器无法获取客户端 {} 的套接字: {:?}", addr, e),
+                        }
+                        debug!("WebSocket服务器已强制关闭客户端 {}", addr);
+                    }
+                    None => {}
+                }
+            }
+            Err(e) => {
+                error!("WebSocket服务器与 {} 握手失败: {}", addr, e);
+                self.emit(ConnectionEvent::Warning(self.config.id.clone(), format!("客户端 {} {}", addr, e))).await;
+            }
+        }
+        drop(abort_handle);
+
+        self.client_controls.lock().await.remove(&addr);
+        self.clients.lock().await.remove(&addr);
+        self.client_handles.lock().await.remove(&addr);
+        if announced {
+            self.emit(ConnectionEvent::ServerClientDisconnected(self.config.id.clone(), addr)).await;
+        }
+    }
+
+    /// 完成TLS（如启用）和WebSocket握手，返回会话流、请求路径和协商的子协议
+    async fn handshake(
+        &self,
+        socket: TcpStream,
+        addr: SocketAddr,
+    ) -> Result<(WebSocketStream<Box<dyn AsyncStream>>, String, Option<String>), String> {
+        let stream: Box<dyn AsyncStream> = match &self.tls_acceptor {
+            Some(acceptor) => {
+                let (stream, tls_info) = tls::accept(acceptor, socket).await?;
+                info!("WebSocket服务器与 {} 完成TLS握手: {} {}", addr, tls_info.version, tls_info.cipher);
+                self.emit(ConnectionEvent::TlsEstablished(self.config.id.clone(), Some(addr), tls_info)).await;
+                Box::new(stream)
+            }
+            None => Box::new(socket),
+        };
+
+        let supported = &self.config.websocket.subprotocols;
+        let mut negotiated: Option<(String, Option<String>)> = None;
+        let callback = |request: &Request, mut response: Response| -> Result<Response, ErrorResponse> {
+            let subprotocol = select_subprotocol(request, supported);
+            if let Some(value) = subprotocol.as_deref().and_then(|p| HeaderValue::from_str(p).ok()) {
+                response.headers_mut().insert(SEC_WEBSOCKET_PROTOCOL, value);
+            }
+            negotiated = Some((request.uri().to_string(), subprotocol));
+            Ok(response)
+        };
+
+        let ws = tokio::time::timeout(tls::SERVER_HANDSHAKE_TIMEOUT, tokio_tungstenite::accept_hdr_async(stream, callback))
+            .await
+            .map_err(|_| format!("WebSocket握手超时：{} 秒内未完成", tls::SERVER_HANDSHAKE_TIMEOUT.as_secs()))?
+            .map_err(|e| describe_handshake_error(&e))?;
+        let (path, subprotocol) = negotiated.unwrap_or_else(|| ("/".to_string(), None));
+        Ok((ws, path, subprotocol))
+    }
+}
+
+/// WebSocket服务器实现
+pub struct WebSocketServer {
+    config: ServerConfig,
+    event_sender: Option<Sender<ConnectionEvent>>,
+    clients: Arc<Mutex<HashMap<SocketAddr, WriteSender>>>,
+    client_handles: Arc<Mutex<HashMap<SocketAddr, JoinHandle<()>>>>,
+    client_controls: Arc<Mutex<HashMap<SocketAddr, ClientControl>>>,
+    raw_capture: RawCaptureSlot,
+    listener_handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for WebSocketServer {
+    fn drop(&mut self) {
+        if let Some(handle) = self.listener_handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+impl WebSocketServer {
+    pub fn new(
+        config: ServerConfig,
+        event_sender: Option<Sender<ConnectionEvent>>,
+        raw_capture: RawCaptureSlot,
+    ) -> Self {
+        WebSocketServer {
+            config,
+            event_sender,
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            client_handles: Arc::new(Mutex::new(HashMap::new())),
+            client_controls: Arc::new(Mutex::new(HashMap::new())),
+            raw_capture,
+            listener_handle: None,
+        }
+    }
+
+    /// 关闭单个客户端连接：优雅关闭发送关闭帧（1000），强制关闭发送 RST
+    pub async fn close_client(&self, addr: SocketAddr, mode: ClientCloseMode) -> Result<(), String> {
+        match self.client_controls.lock().await.remove(&addr) {
+            Some(control) => {
+                info!("[WebSocket服务器] 关闭客户端 {} ({:?})", addr, mode);
+                let _ = control.close_tx.send(mode);
+                Ok(())
+            }
+            None => Err(format!("客户端 {} 不存在", addr)),
+        }
+    }
+}
+
+impl NetworkServer for WebSocketServer {
+    fn start(&mut self) -> Pin<Box<dyn std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send + '_>> {
+        if self.listener_handle.is_some() {
+            debug!("WebSocket服务器已经在运行中");
+            return Pin::from(Box::new(async move { Ok(()) }));
+        }
+
+        Pin::from(Box::new(async move {
+            let config = self.config.clone();
+            info!("WebSocket服务器启动在地址: {}", config.listen_hosts().join(", "));
+
+            // 解析全部监听地址（支持主机名，绑定首个解析结果）
+            let listen_addrs = tcp::resolve_listen_addrs(&config, &self.event_sender, "WebSocket服务器").await?;
+
+            // 启用TLS（wss://）时加载证书，未指定证书时生成临时自签名证书
+            let (tls_acceptor, tls_notice) = if config.tls.enabled {
+                let (acceptor, notice) = tls::acceptor(&config.tls, &tcp::listen_tls_names(&config, &listen_addrs))?;
+                (Some(acceptor), notice)
+            } else {
+                (None, None)
+            };
+
+            let listeners = tcp::bind_listeners(&listen_addrs, config.dual_stack)?;
+            info!("WebSocket服务器开始监听: {}", tcp::format_listen_addrs(&listen_addrs));
+
+            let ctx = WebSocketServerContext {
+                config,
+                event_sender: self.event_sender.clone(),
+                message_processor: Arc::new(DefaultMessageProcessor),
+                raw_capture: self.raw_capture.clone(),
+                clients: self.clients.clone(),
+                client_handles: self.client_handles.clone(),
+                client_controls: self.client_controls.clone(),
+                tls_acceptor,
+            };
+            ctx.emit(ConnectionEvent::Listening(ctx.config.id.clone())).await;
+            if let Some(notice) = tls_notice {
+                ctx.emit(ConnectionEvent::Warning(ctx.config.id.clone(), notice)).await;
+            }
+
+            self.listener_handle = Some(tokio::spawn(tcp::run_accept_loops(listeners, move |listener| ctx.clone().accept_loop(listener))));
+            Ok(())
+        }))
+    }
+
+    fn stop(&mut self) -> Pin<Box<dyn std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send>> {
+        let event_sender = self.event_sender.clone();
+        let server_id = self.config.id.clone();
+        let clients = self.clients.clone();
+        let client_handles = self.client_handles.clone();
+        let client_controls = self.client_controls.clone();
+
+        let Some(listener_handle) = self.listener_handle.take() else {
+            debug!("WebSocket服务器已经停止");
+            return Pin::from(Box::new(async move { Ok(()) }));
+        };
+        // 取消监听任务，监听套接字随之关闭
+        listener_handle.abort();
+
+        Pin::from(Box::new(async move {
+            clients.lock().await.clear();
+            client_controls.lock().await.clear();
+            let handles = std::mem::take(&mut *client_handles.lock().await);
+            for (addr, handle) in handles {
+                handle.abort();
+                debug!("WebSocket服务器已取消客户端 {} 的连接任务", addr);
+            }
+
+            if let Some(sender) = &event_sender {
+                if let Err(e) = sender.send(ConnectionEvent::Disconnected(server_id)).await {
+                    error!("[WebSocket服务器] 发送 Disconnected 事件失败: {:?}", e);
+                }
+            }
+            debug!("WebSocket服务器已停止");
+            Ok(())
+        }))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,395 @@
+//! 客户端上游代理：SOCKS5（RFC 1928/1929）和 HTTP CONNECT
+//!
+//! TCP连接先连到代理服务器，握手后得到通往目标的隧道，TLS等上层协议照常在隧道上进行。
+//! UDP通过 SOCKS5 UDP ASSOCIATE 转发：每个数据报加上目标地址头后发往代理的中继端口，
+//! 控制连接断开时关联随之失效。
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use base64::Engine as _;
+use log::{debug, info};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use crate::config::connection::{ProxyConfig, ProxyKind};
+use crate::network::resolve;
+
+/// HTTP CONNECT 响应头的最大长度
+const MAX_HTTP_RESPONSE_HEADER: usize = 8 * 1024;
+
+/// SOCKS5 UDP 数据报头的最大长度（RSV、FRAG、ATYP、255 字节域名和端口）
+pub(crate) const SOCKS5_UDP_HEADER_MAX: usize = 3 + 1 + 1 + 255 + 2;
+
+const SOCKS5_VERSION: u8 = 0x05;
+const SOCKS5_CMD_CONNECT: u8 = 0x01;
+const SOCKS5_CMD_UDP_ASSOCIATE: u8 = 0x03;
+const SOCKS5_ATYP_IPV4: u8 = 0x01;
+const SOCKS5_ATYP_DOMAIN: u8 = 0x03;
+const SOCKS5_ATYP_IPV6: u8 = 0x04;
+const SOCKS5_AUTH_NONE: u8 = 0x00;
+const SOCKS5_AUTH_PASSWORD: u8 = 0x02;
+const SOCKS5_AUTH_UNACCEPTABLE: u8 = 0xff;
+
+/// 经代理访问的目标
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ProxyTarget {
+    Addr(SocketAddr),
+    /// 由代理解析的主机名
+    Domain(String, u16),
+}
+
+impl fmt::Display for ProxyTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProxyTarget::Addr(addr) => write!(f, "{}", addr),
+            ProxyTarget::Domain(host, port) => write!(f, "{}", resolve::format_host_port(host, *port)),
+        }
+    }
+}
+
+impl ProxyTarget {
+    /// 确定发给代理的目标：IP地址直接使用；主机名在 SOCKS5 关闭远程解析时于本地解析，
+    /// 返回值的第二项为本地解析的结果
+    pub(crate) async fn from_host(proxy: &ProxyConfig, host: &str, port: u16) -> Result<(Self, Option<Vec<SocketAddr>>), String> {
+        let host = host.trim();
+        if resolve::is_ip_literal(host) {
+            let addr = resolve::resolve(host, port).await?[0];
+            return Ok((ProxyTarget::Addr(addr), None));
+        }
+        if proxy.kind == ProxyKind::Socks5 && !proxy.remote_dns {
+            let addrs = resolve::resolve(host, port).await?;
+            return Ok((ProxyTarget::Addr(addrs[0]), Some(addrs)));
+        }
+        Ok((ProxyTarget::Domain(host.to_string(), port), None))
+    }
+
+    /// 按 SOCKS5 地址格式（ATYP + 地址 + 端口）追加到 buf
+    fn encode_socks5(&self, buf: &mut Vec<u8>) -> Result<(), String> {
+        match self {
+            ProxyTarget::Addr(SocketAddr::V4(addr)) => {
+                buf.push(SOCKS5_ATYP_IPV4);
+                buf.extend_from_slice(&addr.ip().octets());
+                buf.extend_from_slice(&addr.port().to_be_bytes());
+            }
+            ProxyTarget::Addr(SocketAddr::V6(addr)) => {
+                buf.push(SOCKS5_ATYP_IPV6);
+                buf.extend_from_slice(&addr.ip().octets());
+                buf.extend_from_slice(&addr.port().to_be_bytes());
+            }
+            ProxyTarget::Domain(host, port) => {
+                let len = u8::try_from(host.len()).map_err(|_| format!("主机名 '{}' 过长", host))?;
+                buf.push(SOCKS5_ATYP_DOMAIN);
+                buf.push(len);
+                buf.extend_from_slice(host.as_bytes());
+                buf.extend_from_slice(&port.to_be_bytes());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 连接代理服务器
+pub(crate) async fn connect_proxy(proxy: &ProxyConfig, timeout: Option<Duration>) -> Result<TcpStream, String> {
+    let endpoint = proxy.endpoint();
+    let addrs = resolve::interleave_families(resolve::resolve(&proxy.address, proxy.port).await?);
+    let mut last_error = None;
+    for addr in addrs {
+        let connect = TcpStream::connect(addr);
+        let result = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, connect)
+                .await
+                .unwrap_or_else(|_| Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "连接超时"))),
+            None => connect.await,
+        };
+        match result {
+            Ok(stream) => {
+                info!("[代理] 已连接代理服务器 {} ({})", endpoint, addr);
+                return Ok(stream);
+            }
+            Err(e) => {
+                debug!("[代理] 连接 {} 失败: {:?}", addr, e);
+                last_error = Some(format!("{}: {}", addr, e));
+            }
+        }
+    }
+    Err(format!("连接代理服务器 {} 失败：{}", endpoint, last_error.unwrap_or_default()))
+}
+
+/// 在已连接代理的流上建立到目标的隧道，超时时间与建立连接相同
+pub(crate) async fn open_tunnel(
+    stream: &mut TcpStream,
+    proxy: &ProxyConfig,
+    target: &ProxyTarget,
+    timeout: Option<Duration>,
+) -> Result<(), String> {
+    let handshake = async {
+        match proxy.kind {
+            ProxyKind::Socks5 => {
+                socks5_handshake(stream, proxy).await?;
+                socks5_request(stream, SOCKS5_CMD_CONNECT, target).await.map(|_| ())
+            }
+            ProxyKind::HttpConnect => http_connect(stream, proxy, target).await,
+            ProxyKind::None => Ok(()),
+        }
+    };
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, handshake)
+            .await
+            .map_err(|_| format!("{} 代理握手超时", proxy.kind))??,
+        None => handshake.await?,
+    }
+    info!("[代理] 已通过 {} 连接到 {}", proxy, target);
+    Ok(())
+}
+
+/// 建立 SOCKS5 UDP 关联，返回控制连接和代理的UDP中继地址
+///
+/// 控制连接需在整个关联期间保持打开。
+pub(crate) async fn socks5_udp_associate(proxy: &ProxyConfig, timeout: Option<Duration>) -> Result<(TcpStream, SocketAddr), String> {
+    let mut stream = connect_proxy(proxy, timeout).await?;
+    let proxy_ip = stream
+        .peer_addr()
+        .map_err(|e| format!("获取代理服务器地址失败: {}", e))?
+        .ip();
+
+    // 客户端地址未知，按协议以全零地址请求
+    let unspecified = ProxyTarget::Addr(SocketAddr::new(
+        if proxy_ip.is_ipv6() { Ipv6Addr::UNSPECIFIED.into() } else { Ipv4Addr::UNSPECIFIED.into() },
+        0,
+    ));
+    let associate = async {
+        socks5_handshake(&mut stream, proxy).await?;
+        socks5_request(&mut stream, SOCKS5_CMD_UDP_ASSOCIATE, &unspecified).await
+    };
+    let relay = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, associate)
+            .await
+            .map_err(|_| "SOCKS5 UDP ASSOCIATE 超时".to_string())??,
+        None => associate.await?,
+    };
+
+    // 代理返回未指定地址时，中继端口位于代理服务器本身
+    let relay = if relay.ip().is_unspecified() { SocketAddr::new(proxy_ip, relay.port()) } else { relay };
+    info!("[代理] SOCKS5 UDP 中继地址: {}", relay);
+    Ok((stream, relay))
+}
+
+/// 为发往 target 的数据报加上 SOCKS5 UDP 头
+pub(crate) fn socks5_udp_encode(target: &ProxyTarget, data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut packet = Vec::with_capacity(SOCKS5_UDP_HEADER_MAX + data.len());
+    // RSV(2) + FRAG(1)
+    packet.extend_from_slice(&[0, 0, 0]);
+    target.encode_socks5(&mut packet)?;
+    packet.extend_from_slice(data);
+    Ok(packet)
+}
+
+/// 解析代理中继发来的数据报，返回来源地址和数据
+///
+/// 不支持分片，分片的数据报返回错误。
+pub(crate) fn socks5_udp_decode(packet: &[u8]) -> Result<(String, &[u8]), String> {
+    if packet.len() < 4 {
+        return Err("SOCKS5 UDP 数据报过短".to_string());
+    }
+    if packet[2] != 0 {
+        return Err(format!("不支持分片的 SOCKS5 UDP 数据报（FRAG={}）", packet[2]));
+    }
+    let (source, header_len) = parse_socks5_address(packet[3], &packet[4..])
+        .ok_or_else(|| "SOCKS5 UDP 数据报地址不完整".to_string())?;
+    Ok((source, &packet[4 + header_len..]))
+}
+
+/// 解析 SOCKS5 地址（ATYP 之后的部分），返回地址文本和占用的字节数
+fn parse_socks5_address(atyp: u8, bytes: &[u8]) -> Option<(String, usize)> {
+    let (host, len) = match atyp {
+        SOCKS5_ATYP_IPV4 => {
+            let octets: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+            (IpAddr::from(octets).to_string(), 4)
+        }
+        SOCKS5_ATYP_IPV6 => {
+            let octets: [u8; 16] = bytes.get(..16)?.try_into().ok()?;
+            (IpAddr::from(octets).to_string(), 16)
+        }
+        SOCKS5_ATYP_DOMAIN => {
+            let len = *bytes.first()? as usize;
+            (String::from_utf8_lossy(bytes.get(1..1 + len)?).into_owned(), 1 + len)
+        }
+        _ => return None,
+    };
+    let port = u16::from_be_bytes(bytes.get(len..len + 2)?.try_into().ok()?);
+    Some((resolve::format_host_port(&host, port), len + 2))
+}
+
+/// SOCKS5 方法协商，设置了用户名时使用用户名/密码认证
+async fn socks5_handshake(stream: &mut TcpStream, proxy: &ProxyConfig) -> Result<(), String> {
+    let use_password = !proxy.username.is_empty();
+    let greeting: &[u8] = if use_password {
+        &[SOCKS5_VERSION, 2, SOCKS5_AUTH_NONE, SOCKS5_AUTH_PASSWORD]
+    } else {
+        &[SOCKS5_VERSION, 1, SOCKS5_AUTH_NONE]
+    };
+    stream.write_all(greeting).await.map_err(|e| format!("发送 SOCKS5 握手失败: {}", e))?;
+
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await.map_err(|e| format!("读取 SOCKS5 握手响应失败: {}", e))?;
+    if reply[0] != SOCKS5_VERSION {
+        return Err(format!("代理不是 SOCKS5 服务器（版本 {}）", reply[0]));
+    }
+    match reply[1] {
+        SOCKS5_AUTH_NONE => Ok(()),
+        SOCKS5_AUTH_PASSWORD if use_password => {
+            let username = proxy.username.as_bytes();
+            let password = proxy.password.as_bytes();
+            if username.len() > 255 || password.len() > 255 {
+                return Err("SOCKS5 用户名或密码过长（最多 255 字节）".to_string());
+            }
+            let mut request = Vec::with_capacity(3 + username.len() + password.len());
+            request.push(0x01);
+            request.push(username.len() as u8);
+            request.extend_from_slice(username);
+            request.push(password.len() as u8);
+            request.extend_from_slice(password);
+            stream.write_all(&request).await.map_err(|e| format!("发送 SOCKS5 认证失败: {}", e))?;
+
+            let mut status = [0u8; 2];
+            stream.read_exact(&mut status).await.map_err(|e| format!("读取 SOCKS5 认证响应失败: {}", e))?;
+            if status[1] != 0 {
+                return Err("SOCKS5 代理认证失败：用户名或密码错误".to_string());
+            }
+            Ok(())
+        }
+        SOCKS5_AUTH_PASSWORD => Err("SOCKS5 代理要求用户名/密码认证".to_string()),
+        SOCKS5_AUTH_UNACCEPTABLE => Err("SOCKS5 代理不接受提供的认证方式".to_string()),
+        method => Err(format!("SOCKS5 代理选择了不支持的认证方式 {:#04x}", method)),
+    }
+}
+
+/// 发送 SOCKS5 请求，返回代理回复的绑定地址
+async fn socks5_request(stream: &mut TcpStream, command: u8, target: &ProxyTarget) -> Result<SocketAddr, String> {
+    let mut request = vec![SOCKS5_VERSION, command, 0x00];
+    target.encode_socks5(&mut request)?;
+    stream.write_all(&request).await.map_err(|e| format!("发送 SOCKS5 请求失败: {}", e))?;
+
+    let mut head = [0u8; 4];
+    stream.read_exact(&mut head).await.map_err(|e| format!("读取 SOCKS5 响应失败: {}", e))?;
+    if head[0] != SOCKS5_VERSION {
+        return Err(format!("SOCKS5 响应的版本 {} 无效", head[0]));
+    }
+    if head[1] != 0 {
+        return Err(format!("SOCKS5 代理拒绝请求：{}", socks5_reply_reason(head[1])));
+    }
+
+    let addr_len = match head[3] {
+        SOCKS5_ATYP_IPV4 => 4,
+        SOCKS5_ATYP_IPV6 => 16,
+        SOCKS5_ATYP_DOMAIN => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).await.map_err(|e| format!("读取 SOCKS5 响应失败: {}", e))?;
+            len[0] as usize
+        }
+        atyp => return Err(format!("SOCKS5 响应的地址类型 {} 无效", atyp)),
+    };
+    let mut rest = vec![0u8; addr_len + 2];
+    stream.read_exact(&mut rest).await.map_err(|e| format!("读取 SOCKS5 响应失败: {}", e))?;
+    let port = u16::from_be_bytes([rest[addr_len], rest[addr_len + 1]]);
+    let ip: IpAddr = match head[3] {
+        SOCKS5_ATYP_IPV4 => <[u8; 4]>::try_from(&rest[..4]).map(IpAddr::from).unwrap_or(Ipv4Addr::UNSPECIFIED.into()),
+        SOCKS5_ATYP_IPV6 => <[u8; 16]>::try_from(&rest[..16]).map(IpAddr::from).unwrap_or(Ipv6Addr::UNSPECIFIED.into()),
+        // 以域名回复绑定地址的代理极少见，视为未指定地址
+        _ => Ipv4Addr::UNSPECIFIED.into(),
+    };
+    Ok(SocketAddr::new(ip, port))
+}
+
+fn socks5_reply_reason(code: u8) -> &'static str {
+    match code {
+        0x01 => "代理服务器一般性故障",
+        0x02 => "规则不允许该连接",
+        0x03 => "网络不可达",
+        0x04 => "主机不可达",
+        0x05 => "连接被拒绝",
+        0x06 => "TTL 超时",
+        0x07 => "不支持的命令",
+        0x08 => "不支持的地址类型",
+        _ => "未知错误",
+    }
+}
+
+/// 发送 HTTP CONNECT 请求并读取响应头，状态码不是 2xx 时返回错误
+async fn http_connect(stream: &mut TcpStream, proxy: &ProxyConfig, target: &ProxyTarget) -> Result<(), String> {
+    let authority = target.to_string();
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", authority);
+    if !proxy.username.is_empty() {
+        let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", proxy.username, proxy.password));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", credentials));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await.map_err(|e| format!("发送 HTTP CONNECT 请求失败: {}", e))?;
+
+    // 逐字节读取到空行为止，避免读走隧道中紧随其后的数据
+    let mut header = Vec::new();
+    while !header.ends_with(b"\r\n\r\n") {
+        if header.len() >= MAX_HTTP_RESPONSE_HEADER {
+            return Err("HTTP CONNECT 响应头过长".to_string());
+        }
+        let byte = stream.read_u8().await.map_err(|e| format!("读取 HTTP CONNECT 响应失败: {}", e))?;
+        header.push(byte);
+    }
+
+    let header = String::from_utf8_lossy(&header);
+    let status_line = header.lines().next().unwrap_or_default();
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| format!("无效的 HTTP CONNECT 响应：{}", status_line))?;
+    match status {
+        200..=299 => Ok(()),
+        407 => Err("HTTP 代理要求认证（407），请检查用户名和密码".to_string()),
+        _ => Err(format!("HTTP 代理拒绝连接：{}", status_line)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// 测试 SOCKS5 UDP 头的编码与解析（IPv4、IPv6 和域名）
+    fn test_socks5_udp_round_trip() {
+        let targets = [
+            (ProxyTarget::Addr("192.168.1.2:8080".parse().unwrap()), "192.168.1.2:8080"),
+            (ProxyTarget::Addr("[2001:db8::1]:53".parse().unwrap()), "[2001:db8::1]:53"),
+            (ProxyTarget::Domain("example.com".to_string(), 443), "example.com:443"),
+        ];
+        for (target, expected) in targets {
+            let packet = socks5_udp_encode(&target, b"hello").unwrap();
+            let (source, data) = socks5_udp_decode(&packet).unwrap();
+            assert_eq!(source, expected);
+            assert_eq!(data, b"hello");
+
+            let (address, len) = parse_socks5_address(packet[3], &packet[4..]).unwrap();
+            assert_eq!(address, expected);
+            assert_eq!(len, packet.len() - 4 - 5);
+        }
+
+        assert!(socks5_udp_encode(&ProxyTarget::Domain("a".repeat(256), 80), b"").is_err());
+    }
+
+    #[test]
+    /// 测试拒绝分片和头部不完整的 SOCKS5 UDP 数据报
+    fn test_socks5_udp_decode_invalid() {
+        let mut packet = socks5_udp_encode(&ProxyTarget::Addr("10.0.0.1:9000".parse().unwrap()), b"data").unwrap();
+        packet[2] = 1;
+        assert!(socks5_udp_decode(&packet).is_err());
+
+        let packet = socks5_udp_encode(&ProxyTarget::Addr("[::1]:9000".parse().unwrap()), b"").unwrap();
+        assert!(socks5_udp_decode(&packet[..3]).is_err());
+        assert!(socks5_udp_decode(&packet[..10]).is_err());
+        assert!(socks5_udp_decode(&packet[..packet.len() - 1]).is_err());
+
+        let packet = socks5_udp_encode(&ProxyTarget::Domain("example.com".to_string(), 80), b"").unwrap();
+        assert!(socks5_udp_decode(&packet[..8]).is_err());
+        assert!(parse_socks5_address(0x09, &packet[4..]).is_none());
+    }
+}
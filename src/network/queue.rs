@@ -0,0 +1,357 @@
+//! 有界收发队列
+//!
+//! 发送队列是UI写入、网络写任务读取的有界通道，满时按策略丢弃消息，或在阻塞策略下
+//! 暂存一条消息、待写任务取走数据后自动放入队列（暂存位也被占用时拒绝新消息），
+//! 并统计尚未交给写任务的字节数。
+//!
+//! 接收队列由连接管理器为每个连接插入：网络任务产生的事件先进入该队列，再转发到
+//! 所有标签页共用的UI事件通道。UI事件通道积压时暂停转发，队列中的接收消息达到容量后
+//! 按策略暂停读取（网络任务等待，对端随之受到流量控制）或丢弃消息。
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+use log::{debug, warn};
+use smol::channel::{self, Receiver, RecvError, Sender, TrySendError, WeakReceiver, WeakSender};
+use crate::config::connection::{QueueConfig, QueuePolicy};
+use crate::network::events::ConnectionEvent;
+
+/// UI事件通道中允许积压的事件数，超过后各连接的接收队列暂停转发
+///
+/// UI事件通道还承载UI自身产生的事件（发送记录、周期发送等），保持为无界通道，
+/// 由各接收队列在转发前检查积压。
+const UI_BACKLOG_LIMIT: usize = 4096;
+
+/// UI事件通道积压时再次检查的间隔
+const UI_BACKLOG_POLL: Duration = Duration::from_millis(10);
+
+/// 标签页的队列统计，由连接管理器的接收队列、UI发送路径共同更新
+#[derive(Debug, Default)]
+pub struct QueueMetrics {
+    received_depth: AtomicUsize,
+    received_dropped: AtomicU64,
+    sent_dropped: AtomicU64,
+    sent_rejected: AtomicU64,
+}
+
+impl QueueMetrics {
+    /// 接收队列中等待交给UI的消息条数
+    pub fn received_depth(&self) -> usize {
+        self.received_depth.load(Ordering::Relaxed)
+    }
+
+    /// 因接收队列已满而丢弃的消息条数
+    pub fn received_dropped(&self) -> u64 {
+        self.received_dropped.load(Ordering::Relaxed)
+    }
+
+    /// 因发送队列已满而丢弃的消息条数
+    pub fn sent_dropped(&self) -> u64 {
+        self.sent_dropped.load(Ordering::Relaxed)
+    }
+
+    /// 阻塞策略下因发送队列已满而被拒绝的手动发送和跳过的周期发送次数
+    pub fn sent_rejected(&self) -> u64 {
+        self.sent_rejected.load(Ordering::Relaxed)
+    }
+
+    pub fn add_sent_dropped(&self, count: usize) {
+        self.sent_dropped.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_sent_rejected(&self, count: usize) {
+        self.sent_rejected.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// 清零丢弃和拒绝计数（队列深度随队列本身变化，不清零）
+    pub fn reset_dropped(&self) {
+        self.received_dropped.store(0, Ordering::Relaxed);
+        self.sent_dropped.store(0, Ordering::Relaxed);
+        self.sent_rejected.store(0, Ordering::Relaxed);
+    }
+}
+
+/// 放入发送队列失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnqueueError {
+    /// 队列已满，按“丢弃最新”策略丢弃了本条消息
+    Full,
+    /// 队列和暂存位都已满，按“阻塞”策略拒绝了本条消息，等队列有空位后再发送
+    Blocked,
+    /// 写任务已退出
+    Closed,
+}
+
+impl fmt::Display for EnqueueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnqueueError::Full => write!(f, "发送队列已满，已丢弃本条消息"),
+            EnqueueError::Blocked => write!(f, "发送队列已满，请等待队列中的数据发出后重试"),
+            EnqueueError::Closed => write!(f, "连接已关闭"),
+        }
+    }
+}
+
+/// 发送队列中的一项，待发送字节数按其长度统计
+pub trait QueueItem: Send + 'static {
+    fn byte_len(&self) -> usize;
+}
+
+impl QueueItem for Vec<u8> {
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+}
+
+/// 发送队列的写入端，由UI持有
+pub struct WriteSender<T = Vec<u8>> {
+    tx: Sender<T>,
+    /// 用于按策略丢弃最旧的消息；弱引用不会阻止写任务退出后通道关闭
+    oldest: WeakReceiver<T>,
+    /// 阻塞策略下队列已满时暂存的一条消息
+    overflow: Arc<Mutex<Option<T>>>,
+    policy: QueuePolicy,
+    pending_bytes: Arc<AtomicUsize>,
+}
+
+/// 发送队列的读取端，由网络写任务持有
+pub struct WriteReceiver<T = Vec<u8>> {
+    rx: Receiver<T>,
+    /// 用于把暂存的消息放入队列；弱引用不会阻止UI释放写入端后通道关闭
+    tx: WeakSender<T>,
+    overflow: Arc<Mutex<Option<T>>>,
+    pending_bytes: Arc<AtomicUsize>,
+}
+
+/// 创建一个发送队列
+pub fn write_queue<T: QueueItem>(config: &QueueConfig) -> (WriteSender<T>, WriteReceiver<T>) {
+    let (tx, rx) = channel::bounded(config.capacity());
+    let overflow = Arc::new(Mutex::new(None));
+    let pending_bytes = Arc::new(AtomicUsize::new(0));
+    (
+        WriteSender {
+            tx: tx.clone(),
+            oldest: rx.downgrade(),
+            overflow: overflow.clone(),
+            policy: config.policy,
+            pending_bytes: pending_bytes.clone(),
+        },
+        WriteReceiver {
+            tx: tx.downgrade(),
+            rx,
+            overflow,
+            pending_bytes,
+        },
+    )
+}
+
+impl<T: QueueItem> WriteSender<T> {
+    /// 按策略放入一条待发送的数据，返回为腾出空位而丢弃的旧消息条数
+    ///
+    /// 阻塞策略下队列已满时暂存这条数据，写任务取走数据后放入队列；
+    /// 暂存位已被占用时不放入数据，返回 Blocked，由调用方暂停发送或稍后重试。
+    pub fn enqueue(&self, data: T) -> Result<usize, EnqueueError> {
+        let len = data.byte_len();
+        self.pending_bytes.fetch_add(len, Ordering::Relaxed);
+        // 与写任务放入暂存消息互斥，暂存的消息总是先于之后的消息进入队列
+        let mut overflow = self.overflow.lock().unwrap_or_else(PoisonError::into_inner);
+        if overflow.is_some() {
+            self.pending_bytes.fetch_sub(len, Ordering::Relaxed);
+            return Err(if self.tx.is_closed() { EnqueueError::Closed } else { EnqueueError::Blocked });
+        }
+        let mut data = match self.tx.try_send(data) {
+            Ok(()) => return Ok(0),
+            Err(TrySendError::Closed(_)) => {
+                self.pending_bytes.fetch_sub(len, Ordering::Relaxed);
+                return Err(EnqueueError::Closed);
+            }
+            Err(TrySendError::Full(data)) => data,
+        };
+
+        match self.policy {
+            QueuePolicy::Block => {
+                *overflow = Some(data);
+                Ok(0)
+            }
+            QueuePolicy::DropNewest => {
+                self.pending_bytes.fetch_sub(len, Ordering::Relaxed);
+                Err(EnqueueError::Full)
+            }
+            QueuePolicy::DropOldest => {
+                let mut dropped = 0;
+                loop {
+                    let Some(rx) = self.oldest.upgrade() else {
+                        self.pending_bytes.fetch_sub(len, Ordering::Relaxed);
+                        return Err(EnqueueError::Closed);
+                    };
+                    if let Ok(oldest) = rx.try_recv() {
+                        self.pending_bytes.fetch_sub(oldest.byte_len(), Ordering::Relaxed);
+                        dropped += 1;
+                    }
+                    match self.tx.try_send(data) {
+                        Ok(()) => return Ok(dropped),
+                        Err(TrySendError::Full(rejected)) => data = rejected,
+                        Err(TrySendError::Closed(_)) => {
+                            self.pending_bytes.fetch_sub(len, Ordering::Relaxed);
+                            return Err(EnqueueError::Closed);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T> WriteSender<T> {
+    pub fn policy(&self) -> QueuePolicy {
+        self.policy
+    }
+
+    fn has_overflow(&self) -> bool {
+        self.overflow.lock().unwrap_or_else(PoisonError::into_inner).is_some()
+    }
+
+    /// 队列和暂存位都已满（阻塞策略下周期发送据此暂停）
+    pub fn is_full(&self) -> bool {
+        self.tx.is_full() && self.has_overflow()
+    }
+
+    /// 队列中的消息条数（包括暂存的消息）
+    pub fn len(&self) -> usize {
+        self.tx.len() + usize::from(self.has_overflow())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 已放入队列但写任务尚未取走的字节数
+    pub fn pending_bytes(&self) -> usize {
+        self.pending_bytes.load(Ordering::Relaxed)
+    }
+}
+
+impl<T> Clone for WriteSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            oldest: self.oldest.clone(),
+            overflow: self.overflow.clone(),
+            policy: self.policy,
+            pending_bytes: self.pending_bytes.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for WriteSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteSender")
+            .field("policy", &self.policy)
+            .field("len", &self.len())
+            .field("pending_bytes", &self.pending_bytes())
+            .finish()
+    }
+}
+
+impl<T: QueueItem> WriteReceiver<T> {
+    /// 取出下一条待发送的数据，所有写入端都关闭后返回错误
+    ///
+    /// 取走数据后队列有了空位，把阻塞策略下暂存的消息放入队列。
+    pub async fn recv(&self) -> Result<T, RecvError> {
+        let data = self.rx.recv().await?;
+        self.pending_bytes.fetch_sub(data.byte_len(), Ordering::Relaxed);
+        let mut overflow = self.overflow.lock().unwrap_or_else(PoisonError::into_inner);
+        if let (Some(held), Some(tx)) = (overflow.take(), self.tx.upgrade()) {
+            if let Err(TrySendError::Full(held)) = tx.try_send(held) {
+                *overflow = Some(held);
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// 为一个连接创建接收队列，返回交给网络任务的事件发送端
+pub(crate) fn event_queue(
+    config: &QueueConfig,
+    metrics: Arc<QueueMetrics>,
+    ui_sender: Sender<ConnectionEvent>,
+) -> Sender<ConnectionEvent> {
+    let capacity = config.capacity();
+    let (tx, rx) = channel::bounded(capacity);
+    tokio::spawn(forward_events(rx, ui_sender, capacity, config.policy, metrics));
+    tx
+}
+
+/// 把连接的事件按顺序转发到UI事件通道，直到网络任务全部退出且队列已清空
+///
+/// 只有接收消息计入容量并可能被丢弃，连接状态等其他事件总是按顺序转发。
+async fn forward_events(
+    rx: Receiver<ConnectionEvent>,
+    ui_sender: Sender<ConnectionEvent>,
+    capacity: usize,
+    policy: QueuePolicy,
+    metrics: Arc<QueueMetrics>,
+) {
+    let mut pending: VecDeque<ConnectionEvent> = VecDeque::new();
+    let mut messages = 0usize;
+    let mut closed = false;
+
+    loop {
+        while ui_sender.len() < UI_BACKLOG_LIMIT {
+            let Some(event) = pending.pop_front() else {
+                break;
+            };
+            if is_message(&event) {
+                messages -= 1;
+                metrics.received_depth.fetch_sub(1, Ordering::Relaxed);
+            }
+            if ui_sender.try_send(event).is_err() {
+                debug!("[接收队列] UI事件通道已关闭");
+                metrics.received_depth.fetch_sub(messages, Ordering::Relaxed);
+                return;
+            }
+        }
+        if closed && pending.is_empty() {
+            break;
+        }
+
+        // 阻塞策略下队列满时不再读取，网络任务在发送事件时等待
+        let accepting = !closed && (policy != QueuePolicy::Block || messages < capacity);
+        tokio::select! {
+            event = rx.recv(), if accepting => {
+                let Ok(event) = event else {
+                    closed = true;
+                    continue;
+                };
+                if is_message(&event) {
+                    if messages >= capacity {
+                        metrics.received_dropped.fetch_add(1, Ordering::Relaxed);
+                        match policy {
+                            QueuePolicy::DropNewest => continue,
+                            _ => {
+                                if let Some(index) = pending.iter().position(is_message) {
+                                    pending.remove(index);
+                                    messages -= 1;
+                                    metrics.received_depth.fetch_sub(1, Ordering::Relaxed);
+                                }
+                            }
+                        }
+                        if metrics.received_dropped() == 1 {
+                            warn!("[接收队列] 队列已满（{} 条），开始按“{}”策略丢弃消息", capacity, policy);
+                        }
+                    }
+                    messages += 1;
+                    metrics.received_depth.fetch_add(1, Ordering::Relaxed);
+                }
+                pending.push_back(event);
+            }
+            _ = tokio::time::sleep(UI_BACKLOG_POLL), if !pending.is_empty() => {}
+        }
+    }
+}
+
+fn is_message(event: &ConnectionEvent) -> bool {
+    matches!(event, ConnectionEvent::MessageReceived(..))
+}
@@ -0,0 +1,232 @@
+use std::net::{IpAddr, SocketAddr, SocketAddrV6};
+use log::debug;
+use tokio::net::lookup_host;
+
+/// 将主机和端口拼接为地址字符串，IPv6地址自动加方括号
+pub fn format_host_port(host: &str, port: u16) -> String {
+    if host.contains(':') && !host.contains('[') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// 主机是否为IP地址字面量（无需DNS解析），带区域的IPv6地址同样视为字面量
+pub fn is_ip_literal(host: &str) -> bool {
+    parse_ip_literal(host, 0).is_some()
+}
+
+/// 解析IP地址字面量，IPv6地址可带区域（scope ID），如 fe80::1%eth0 或 fe80::1%2
+///
+/// 可以加一对方括号；不是IP地址字面量（主机名、方括号不配对）时返回 None；区域无效时返回错误。
+pub fn parse_ip_literal(host: &str, port: u16) -> Option<Result<SocketAddr, String>> {
+    let host = host.trim();
+    let host = match host.strip_prefix('[') {
+        Some(inner) => inner.strip_suffix(']')?,
+        None => host,
+    };
+    let (address, zone) = match host.split_once('%') {
+        Some((address, zone)) => (address, Some(zone)),
+        None => (host, None),
+    };
+    let ip = address.parse::<IpAddr>().ok()?;
+    let Some(zone) = zone else {
+        return Some(Ok(SocketAddr::new(ip, port)));
+    };
+    Some(match ip {
+        IpAddr::V4(_) => Err(format!("无效的地址 '{}': IPv4地址不能指定区域", host)),
+        IpAddr::V6(ip) => zone_index(zone)
+            .map(|scope_id| SocketAddr::V6(SocketAddrV6::new(ip, port, 0, scope_id)))
+            .map_err(|e| format!("无效的地址 '{}': {}", host, e)),
+    })
+}
+
+/// 解析“IP:端口”形式的地址，IPv6地址须加方括号并可带区域，如 [fe80::1%eth0]:8080
+pub fn parse_socket_addr(text: &str) -> Result<SocketAddr, String> {
+    let text = text.trim();
+    let invalid = || format!("无效的地址格式: {}", text);
+    let (host, port) = text.rsplit_once(':').ok_or_else(invalid)?;
+    if host.contains(':') && !(host.starts_with('[') && host.ends_with(']')) {
+        return Err(invalid());
+    }
+    let port = port.parse::<u16>().map_err(|_| invalid())?;
+    parse_ip_literal(host, port).unwrap_or_else(|| Err(invalid()))
+}
+
+/// 将IPv6区域解析为接口索引：数字直接作为索引，否则按接口名称（如 eth0）查找
+pub fn zone_index(zone: &str) -> Result<u32, String> {
+    if zone.is_empty() {
+        return Err("区域不能为空".to_string());
+    }
+    if let Ok(index) = zone.parse::<u32>() {
+        return Ok(index);
+    }
+    interface_index(zone)
+}
+
+#[cfg(unix)]
+fn interface_index(name: &str) -> Result<u32, String> {
+    let c_name = std::ffi::CString::new(name).map_err(|_| format!("无效的接口名称 '{}'", name))?;
+    // SAFETY: c_name 是以 NUL 结尾的有效C字符串，在调用期间保持存活
+    match unsafe { libc::if_nametoindex(c_name.as_ptr()) } {
+        0 => Err(format!("找不到网络接口 '{}'", name)),
+        index => Ok(index),
+    }
+}
+
+#[cfg(not(unix))]
+fn interface_index(name: &str) -> Result<u32, String> {
+    Err(format!("不支持按名称 '{}' 指定区域，请使用接口索引（如 fe80::1%12）", name))
+}
+
+/// 校验远端主机和端口的格式（不做DNS解析）
+pub fn validate_remote(host: &str, port: u16) -> Result<(), String> {
+    let host = host.trim();
+    if host.is_empty() {
+        return Err("主机地址不能为空".to_string());
+    }
+    if host.chars().any(char::is_whitespace) {
+        return Err(format!("无效的主机地址 '{}': 不能包含空白字符", host));
+    }
+    if port == 0 {
+        return Err("远端端口不能为 0".to_string());
+    }
+    if let Some(Err(e)) = parse_ip_literal(host, port) {
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// 异步解析主机名，返回全部地址（已去重，保持系统返回的优先顺序）
+///
+/// IP地址字面量直接返回，不经过DNS；主机名按系统解析器规则解析（包括 /etc/hosts）。
+pub async fn resolve(host: &str, port: u16) -> Result<Vec<SocketAddr>, String> {
+    let host = host.trim();
+    if host.is_empty() {
+        return Err("主机地址不能为空".to_string());
+    }
+
+    if let Some(addr) = parse_ip_literal(host, port) {
+        return addr.map(|addr| vec![addr]);
+    }
+
+    let address = format_host_port(host, port);
+
+    let mut addrs: Vec<SocketAddr> = Vec::new();
+    for addr in lookup_host(address.as_str())
+        .await
+        .map_err(|e| format!("无法解析主机名 '{}': {}", host, e))?
+    {
+        if !addrs.contains(&addr) {
+            addrs.push(addr);
+        }
+    }
+
+    if addrs.is_empty() {
+        return Err(format!("无法解析主机名 '{}': 未找到任何地址", host));
+    }
+
+    debug!("主机名 {} 解析结果: {:?}", host, addrs);
+    Ok(addrs)
+}
+
+/// 按地址族交替排列（RFC 8305），首选地址族保持在最前
+///
+/// 例如 [v6a, v6b, v4a] 排列为 [v6a, v4a, v6b]，使双栈主机在某一地址族不可达时能尽快尝试另一族。
+pub fn interleave_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let prefer_v6 = addrs.first().is_some_and(|addr| addr.is_ipv6());
+    let (preferred, other): (Vec<SocketAddr>, Vec<SocketAddr>) =
+        addrs.into_iter().partition(|addr| addr.is_ipv6() == prefer_v6);
+
+    let mut result = Vec::with_capacity(preferred.len() + other.len());
+    let mut preferred = preferred.into_iter();
+    let mut other = other.into_iter();
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => break,
+            (a, b) => result.extend(a.into_iter().chain(b)),
+        }
+    }
+    result
+}
+
+/// 将地址列表格式化为逗号分隔的字符串（用于界面显示），带区域的IPv6地址显示为 fe80::1%2
+pub fn format_addresses(addrs: &[SocketAddr]) -> String {
+    addrs
+        .iter()
+        .map(|addr| match addr {
+            SocketAddr::V6(addr) if addr.scope_id() != 0 => format!("{}%{}", addr.ip(), addr.scope_id()),
+            _ => addr.ip().to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interleave_families, parse_ip_literal, parse_socket_addr, zone_index};
+    use std::net::{SocketAddr, SocketAddrV6};
+
+    fn addrs(list: &[&str]) -> Vec<SocketAddr> {
+        list.iter().map(|addr| addr.parse().unwrap()).collect()
+    }
+
+    #[test]
+    /// 测试按地址族交替排列，首选地址族保持在最前
+    fn test_interleave_families() {
+        let v6a = "[2001:db8::1]:80";
+        let v6b = "[2001:db8::2]:80";
+        let v4a = "192.0.2.1:80";
+        let v4b = "192.0.2.2:80";
+        assert_eq!(interleave_families(addrs(&[v6a, v6b, v4a])), addrs(&[v6a, v4a, v6b]));
+        assert_eq!(interleave_families(addrs(&[v4a, v4b, v6a, v6b])), addrs(&[v4a, v6a, v4b, v6b]));
+
+        // 只有一个地址族时保持原顺序
+        assert_eq!(interleave_families(addrs(&[v4a, v4b])), addrs(&[v4a, v4b]));
+        assert_eq!(interleave_families(addrs(&[v6b, v6a])), addrs(&[v6b, v6a]));
+
+        assert!(interleave_families(Vec::new()).is_empty());
+    }
+
+    #[test]
+    /// 测试带区域的IPv6地址字面量：数字区域、接口名称区域，IPv4地址和空区域无效
+    fn test_parse_ip_literal_zone() {
+        let link_local = "fe80::1".parse().unwrap();
+        assert_eq!(parse_ip_literal("fe80::1%2", 80), Some(Ok(SocketAddr::V6(SocketAddrV6::new(link_local, 80, 0, 2)))));
+        #[cfg(target_os = "linux")]
+        {
+            let lo = zone_index("lo").unwrap();
+            assert_eq!(parse_ip_literal("fe80::1%lo", 80), Some(Ok(SocketAddr::V6(SocketAddrV6::new(link_local, 80, 0, lo)))));
+        }
+        assert_eq!(parse_ip_literal("[fe80::1%2]", 80), Some(Ok(SocketAddr::V6(SocketAddrV6::new(link_local, 80, 0, 2)))));
+
+        assert!(matches!(parse_ip_literal("192.168.1.1%2", 80), Some(Err(_))));
+        assert!(matches!(parse_ip_literal("fe80::1%", 80), Some(Err(_))));
+        assert_eq!(parse_ip_literal("example.com", 80), None);
+        assert_eq!(parse_ip_literal("example.com%2", 80), None);
+    }
+
+    #[test]
+    /// 测试方括号只去掉一对，不配对或多余的方括号不是IP地址字面量
+    fn test_parse_ip_literal_brackets() {
+        assert_eq!(parse_ip_literal("[::1]", 80), Some(Ok("[::1]:80".parse().unwrap())));
+        assert_eq!(parse_ip_literal("[[::1]]", 80), None);
+        assert_eq!(parse_ip_literal("::1]", 80), None);
+        assert_eq!(parse_ip_literal("[::1", 80), None);
+        assert!(parse_socket_addr("[[::1]]:80").is_err());
+    }
+
+    #[test]
+    /// 测试解析带区域的“[IPv6%区域]:端口”地址
+    fn test_parse_socket_addr_zone() {
+        let link_local = "fe80::1".parse().unwrap();
+        assert_eq!(parse_socket_addr("[fe80::1%2]:8080"), Ok(SocketAddr::V6(SocketAddrV6::new(link_local, 8080, 0, 2))));
+        // eth0 不一定存在，结果应与按名称查找接口一致
+        match zone_index("eth0") {
+            Ok(index) => assert_eq!(parse_socket_addr("[fe80::1%eth0]:8080"), Ok(SocketAddr::V6(SocketAddrV6::new(link_local, 8080, 0, index)))),
+            Err(_) => assert!(parse_socket_addr("[fe80::1%eth0]:8080").is_err()),
+        }
+        assert!(parse_socket_addr("fe80::1%2:8080").is_err());
+        assert!(parse_socket_addr("[192.168.1.1%2]:8080").is_err());
+    }
+}
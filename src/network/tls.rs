@@ -0,0 +1,328 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use log::{info, warn};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{CryptoProvider, ring};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{CommonState, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use tokio::net::TcpStream;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use crate::config::connection::TlsConfig;
+
+/// 服务端等待TLS握手完成的最长时间
+pub const SERVER_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 证书摘要（用于界面显示）
+#[derive(Debug, Clone, PartialEq)]
+pub struct CertificateSummary {
+    pub subject: String,
+    pub issuer: String,
+    pub not_after: String,
+}
+
+impl fmt::Display for CertificateSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}（签发者: {}，有效期至 {}）", self.subject, self.issuer, self.not_after)
+    }
+}
+
+/// 协商完成的TLS会话信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsSessionInfo {
+    /// 协议版本，如 TLS 1.3
+    pub version: String,
+    /// 密码套件
+    pub cipher: String,
+    /// 对端证书链（首个为对端自身证书），对端未提供证书时为空
+    pub peer_certificates: Vec<CertificateSummary>,
+}
+
+impl TlsSessionInfo {
+    fn from_state(state: &CommonState) -> Self {
+        let version = match state.protocol_version() {
+            Some(rustls::ProtocolVersion::TLSv1_2) => "TLS 1.2".to_string(),
+            Some(rustls::ProtocolVersion::TLSv1_3) => "TLS 1.3".to_string(),
+            Some(other) => format!("{:?}", other),
+            None => "未知".to_string(),
+        };
+        let cipher = state
+            .negotiated_cipher_suite()
+            .map(|suite| format!("{:?}", suite.suite()))
+            .unwrap_or_else(|| "未知".to_string());
+        let peer_certificates = state
+            .peer_certificates()
+            .unwrap_or_default()
+            .iter()
+            .map(summarize_certificate)
+            .collect();
+        Self { version, cipher, peer_certificates }
+    }
+}
+
+/// 解析证书的主题、签发者和到期时间
+fn summarize_certificate(cert: &CertificateDer<'_>) -> CertificateSummary {
+    match x509_parser::parse_x509_certificate(cert.as_ref()) {
+        Ok((_, parsed)) => CertificateSummary {
+            subject: parsed.subject().to_string(),
+            issuer: parsed.issuer().to_string(),
+            not_after: parsed.validity().not_after.to_string(),
+        },
+        Err(e) => CertificateSummary {
+            subject: format!("无法解析的证书（{} 字节）", cert.as_ref().len()),
+            issuer: e.to_string(),
+            not_after: "未知".to_string(),
+        },
+    }
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+/// 读取 PEM 文件中的全部证书
+fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("读取证书 {} 失败: {}", path, e))?;
+    if certs.is_empty() {
+        return Err(format!("证书文件 {} 中没有证书", path));
+    }
+    Ok(certs)
+}
+
+/// 读取 PEM 文件中的私钥
+fn load_key(path: &str) -> Result<PrivateKeyDer<'static>, String> {
+    PrivateKeyDer::from_pem_file(path).map_err(|e| format!("读取私钥 {} 失败: {}", path, e))
+}
+
+/// 由 CA 证书文件构建信任根
+fn load_roots(path: &str) -> Result<RootCertStore, String> {
+    let mut roots = RootCertStore::empty();
+    for cert in load_certs(path)? {
+        roots.add(cert).map_err(|e| format!("CA证书 {} 无效: {}", path, e))?;
+    }
+    Ok(roots)
+}
+
+/// 系统根证书
+fn native_roots() -> Result<RootCertStore, String> {
+    let result = rustls_native_certs::load_native_certs();
+    for e in &result.errors {
+        warn!("加载系统根证书时出错: {}", e);
+    }
+    let mut roots = RootCertStore::empty();
+    let (added, ignored) = roots.add_parsable_certificates(result.certs);
+    if added == 0 {
+        return Err("未能加载任何系统根证书，请指定CA证书".to_string());
+    }
+    info!("已加载 {} 个系统根证书（忽略 {} 个）", added, ignored);
+    Ok(roots)
+}
+
+/// 不校验服务器证书的验证器（仅用于调试），握手签名仍按正常规则校验
+#[derive(Debug)]
+struct AcceptAnyServerCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyServerCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// 根据配置创建TLS客户端连接器
+pub fn connector(tls: &TlsConfig) -> Result<TlsConnector, String> {
+    let provider = provider();
+    let builder = rustls::ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("TLS配置错误: {}", e))?;
+
+    let builder = if tls.insecure {
+        warn!("TLS客户端已关闭证书校验");
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyServerCert(provider)))
+    } else if tls.ca_cert_path.trim().is_empty() {
+        builder.with_root_certificates(native_roots()?)
+    } else {
+        builder.with_root_certificates(load_roots(tls.ca_cert_path.trim())?)
+    };
+
+    let config = if tls.cert_path.trim().is_empty() {
+        builder.with_no_client_auth()
+    } else {
+        let certs = load_certs(tls.cert_path.trim())?;
+        let key = load_key(tls.key_path.trim())?;
+        builder
+            .with_client_auth_cert(certs, key)
+            .map_err(|e| format!("客户端证书无效: {}", e))?
+    };
+
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+/// 根据配置创建TLS服务端接受器
+///
+/// 未指定证书时为 names 生成临时自签名证书，并在返回值中附带提示。
+pub fn acceptor(tls: &TlsConfig, names: &[String]) -> Result<(TlsAcceptor, Option<String>), String> {
+    let provider = provider();
+    let builder = rustls::ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("TLS配置错误: {}", e))?;
+
+    let builder = if tls.require_client_cert {
+        if tls.ca_cert_path.trim().is_empty() {
+            return Err("要求客户端证书时必须指定CA证书".to_string());
+        }
+        let roots = load_roots(tls.ca_cert_path.trim())?;
+        let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+            .build()
+            .map_err(|e| format!("客户端证书校验配置错误: {}", e))?;
+        builder.with_client_cert_verifier(verifier)
+    } else {
+        builder.with_no_client_auth()
+    };
+
+    let (certs, key, notice) = if tls.cert_path.trim().is_empty() {
+        let (cert_pem, key_pem) = generate_self_signed(names)?;
+        let certs = CertificateDer::pem_slice_iter(cert_pem.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("生成自签名证书失败: {}", e))?;
+        let key = PrivateKeyDer::from_pem_slice(key_pem.as_bytes())
+            .map_err(|e| format!("生成自签名证书失败: {}", e))?;
+        let notice = format!("未指定服务端证书，已为 {} 生成临时自签名证书", names.join(", "));
+        (certs, key, Some(notice))
+    } else {
+        (load_certs(tls.cert_path.trim())?, load_key(tls.key_path.trim())?, None)
+    };
+
+    let config = builder
+        .with_single_cert(certs, key)
+        .map_err(|e| format!("服务端证书无效: {}", e))?;
+    Ok((TlsAcceptor::from(Arc::new(config)), notice))
+}
+
+/// 作为客户端完成TLS握手
+pub async fn connect(
+    connector: &TlsConnector,
+    server_name: &str,
+    stream: TcpStream,
+    timeout: Option<Duration>,
+) -> Result<(tokio_rustls::client::TlsStream<TcpStream>, TlsSessionInfo), String> {
+    let name = ServerName::try_from(server_name.to_string())
+        .map_err(|_| format!("无效的TLS服务器名称 '{}'", server_name))?;
+    let handshake = connector.connect(name, stream);
+    let result = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, handshake)
+            .await
+            .map_err(|_| format!("TLS握手超时：{} 秒内未完成", timeout.as_secs()))?,
+        None => handshake.await,
+    };
+    let stream = result.map_err(|e| format!("TLS握手失败: {}", e))?;
+    let info = TlsSessionInfo::from_state(stream.get_ref().1);
+    Ok((stream, info))
+}
+
+/// 作为服务端完成TLS握手
+pub async fn accept(
+    acceptor: &TlsAcceptor,
+    stream: TcpStream,
+) -> Result<(tokio_rustls::server::TlsStream<TcpStream>, TlsSessionInfo), String> {
+    let stream = tokio::time::timeout(SERVER_HANDSHAKE_TIMEOUT, acceptor.accept(stream))
+        .await
+        .map_err(|_| format!("TLS握手超时：{} 秒内未完成", SERVER_HANDSHAKE_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("TLS握手失败: {}", e))?;
+    let info = TlsSessionInfo::from_state(stream.get_ref().1);
+    Ok((stream, info))
+}
+
+/// 为给定的主机名或IP地址生成自签名证书，返回 (证书PEM, 私钥PEM)
+pub fn generate_self_signed(names: &[String]) -> Result<(String, String), String> {
+    let mut names: Vec<String> = names
+        .iter()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect();
+    if !names.iter().any(|name| name == "localhost") {
+        names.push("localhost".to_string());
+    }
+    let rcgen::CertifiedKey { cert, key_pair } = rcgen::generate_simple_self_signed(names)
+        .map_err(|e| format!("生成自签名证书失败: {}", e))?;
+    Ok((cert.pem(), key_pair.serialize_pem()))
+}
+
+/// 生成自签名证书并保存到文档目录，返回 (证书路径, 私钥路径)
+///
+/// 保存位置：{documents_dir}/NetAssistant/certs/{file_stem}.crt 与 .key
+pub fn save_self_signed(names: &[String], file_stem: &str) -> Result<(PathBuf, PathBuf), String> {
+    let (cert_pem, key_pem) = generate_self_signed(names)?;
+
+    let mut dir = dirs::document_dir().unwrap_or_else(|| PathBuf::from("."));
+    dir.push("NetAssistant");
+    dir.push("certs");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建目录 {} 失败: {}", dir.display(), e))?;
+
+    let cert_path = dir.join(format!("{}.crt", file_stem));
+    let key_path = dir.join(format!("{}.key", file_stem));
+    std::fs::write(&cert_path, cert_pem)
+        .map_err(|e| format!("保存证书 {} 失败: {}", cert_path.display(), e))?;
+    write_private_key(&key_path, key_pem.as_bytes())
+        .map_err(|e| format!("保存私钥 {} 失败: {}", key_path.display(), e))?;
+    info!("已生成自签名证书: {}", cert_path.display());
+    Ok((cert_path, key_path))
+}
+
+/// 写入私钥文件，unix 上仅允许所有者读写（0600）
+#[cfg(unix)]
+fn write_private_key(path: &Path, pem: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // 覆盖已存在的文件时 mode 不生效，另行收紧权限
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    file.write_all(pem)
+}
+
+#[cfg(not(unix))]
+fn write_private_key(path: &Path, pem: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, pem)
+}
+
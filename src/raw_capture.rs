@@ -0,0 +1,220 @@
+use crate::message::MessageDirection;
+use smol::channel::{Sender, TrySendError, bounded as smol_bounded};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::fs::File;
+use tokio::io::{AsyncWriteExt, BufWriter};
+
+/// 录制文件的刷新间隔（缓冲的数据最迟在该间隔后写入磁盘）
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 原始数据录制插槽
+///
+/// 由连接管理器按连接ID持有，网络读写任务在连接时克隆一份，
+/// UI 开关录制时只替换其中的内容，无需重新建立连接。
+pub type RawCaptureSlot = Arc<Mutex<Option<Arc<RawCapture>>>>;
+
+/// 创建空的录制插槽
+pub fn new_slot() -> RawCaptureSlot {
+    Arc::new(Mutex::new(None))
+}
+
+/// 向插槽中的录制器写入一段原始数据（未开启录制时直接忽略）
+pub fn record(slot: &RawCaptureSlot, peer: Option<SocketAddr>, direction: MessageDirection, data: &[u8]) {
+    if data.is_empty() {
+        return;
+    }
+    let capture = match slot.lock() {
+        Ok(guard) => guard.clone(),
+        Err(_) => None,
+    };
+    if let Some(capture) = capture {
+        capture.record(peer, direction, data);
+    }
+}
+
+/// 原始字节流录制器
+///
+/// 在解码之前记录接收到的原始字节（可选同时记录编码后实际发送的字节），
+/// 按方向写入独立的二进制文件，便于直接 diff 或回放。
+/// 服务端可选择按客户端拆分文件。
+pub struct RawCapture {
+    base_path: PathBuf,
+    include_sent: bool,
+    data_tx: Sender<(Option<SocketAddr>, MessageDirection, Vec<u8>)>,
+    /// 因录制队列已满（磁盘写入跟不上）而丢弃的数据段数
+    dropped: Arc<AtomicU64>,
+}
+
+impl RawCapture {
+    /// 启动录制器
+    ///
+    /// 文件在收到第一段数据时才创建，命名格式：
+    /// - 按连接：{base}_rx.bin / {base}_tx.bin
+    /// - 按客户端：{base}_{客户端地址}_rx.bin / {base}_{客户端地址}_tx.bin
+    ///
+    /// 录制队列最多容纳 capacity 段数据（取标签页的队列容量）。网络任务不能等待录制，
+    /// 队列已满时不论队列策略都丢弃新数据并计数。
+    pub fn start(base_path: PathBuf, include_sent: bool, per_client: bool, capacity: usize) -> Self {
+        let (data_tx, data_rx) = smol_bounded::<(Option<SocketAddr>, MessageDirection, Vec<u8>)>(capacity.max(1));
+        let dropped = Arc::new(AtomicU64::new(0));
+
+        let base_for_task = base_path.clone();
+        let dropped_for_task = dropped.clone();
+        tokio::spawn(async move {
+            let mut writers: HashMap<(Option<SocketAddr>, MessageDirection), BufWriter<File>> = HashMap::new();
+            let mut flush = tokio::time::interval(FLUSH_INTERVAL);
+
+            loop {
+                let (peer, direction, data) = tokio::select! {
+                    received = data_rx.recv() => match received {
+                        Ok(received) => received,
+                        Err(_) => break,
+                    },
+                    _ = flush.tick() => {
+                        for writer in writers.values_mut() {
+                            if let Err(e) = writer.flush().await {
+                                log::error!("[原始录制] flush 失败: {:?}", e);
+                            }
+                        }
+                        continue;
+                    }
+                };
+                let key = (if per_client { peer } else { None }, direction);
+
+                if !writers.contains_key(&key) {
+                    let path = Self::file_path(&base_for_task, key.0, direction);
+                    match File::create(&path).await {
+                        Ok(file) => {
+                            log::debug!("[原始录制] 创建录制文件: {:?}", path);
+                            writers.insert(key, BufWriter::new(file));
+                        }
+                        Err(e) => {
+                            log::error!("[原始录制] 创建文件 {:?} 失败: {:?}", path, e);
+                            continue;
+                        }
+                    }
+                }
+
+                if let Some(writer) = writers.get_mut(&key) {
+                    if let Err(e) = writer.write_all(&data).await {
+                        log::error!("[原始录制] 写入失败: {:?}", e);
+                    }
+                }
+            }
+
+            // 发送端全部释放后关闭所有文件
+            for (_, mut writer) in writers {
+                let _ = writer.flush().await;
+                let _ = writer.get_mut().shutdown().await;
+            }
+            let dropped = dropped_for_task.load(Ordering::Relaxed);
+            if dropped > 0 {
+                log::warn!("[原始录制] 录制队列已满，共丢弃 {} 段数据: {:?}", dropped, base_for_task);
+            }
+            log::debug!("[原始录制] 录制已结束: {:?}", base_for_task);
+        });
+
+        Self {
+            base_path,
+            include_sent,
+            data_tx,
+            dropped,
+        }
+    }
+
+    /// 生成默认录制路径前缀（数字递增）
+    ///
+    /// 格式：{documents_dir}/NetAssistant/captures/{connection_label}_{n}
+    pub fn default_base_path(connection_label: &str) -> PathBuf {
+        let connection_label = sanitize_file_name(connection_label);
+        let mut dir = dirs::document_dir().unwrap_or_else(|| PathBuf::from("."));
+        dir.push("NetAssistant");
+        dir.push("captures");
+
+        // 确保目录存在
+        let _ = std::fs::create_dir_all(&dir);
+
+        // 扫描目录，找到该连接前缀的最大序号
+        let prefix = format!("{}_", connection_label);
+        let mut max_num = 0;
+
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                let file_name = entry.file_name();
+                let name = file_name.to_string_lossy();
+                if let Some(rest) = name.strip_prefix(&prefix) {
+                    // 提取序号: prefix{n}_...bin
+                    let num_part: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+                    if let Ok(n) = num_part.parse::<u32>() {
+                        max_num = max_num.max(n);
+                    }
+                }
+            }
+        }
+
+        dir.push(format!("{}_{}", connection_label, max_num + 1));
+        dir
+    }
+
+    /// 录制文件所在目录
+    pub fn directory(&self) -> PathBuf {
+        self.base_path
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /// 录制文件名前缀（用于界面显示）
+    pub fn display_name(&self) -> String {
+        self.base_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// 记录一段原始数据，发送方向的数据仅在开启“含发送”时记录
+    pub fn record(&self, peer: Option<SocketAddr>, direction: MessageDirection, data: &[u8]) {
+        if direction == MessageDirection::Sent && !self.include_sent {
+            return;
+        }
+        match self.data_tx.try_send((peer, direction, data.to_vec())) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+                    log::warn!("[原始录制] 录制队列已满，开始丢弃数据: {:?}", self.base_path);
+                }
+            }
+            Err(e) => log::error!("[原始录制] 提交数据失败: {:?}", e),
+        }
+    }
+
+    fn file_path(base_path: &Path, peer: Option<SocketAddr>, direction: MessageDirection) -> PathBuf {
+        let suffix = match direction {
+            MessageDirection::Received => "rx",
+            MessageDirection::Sent => "tx",
+        };
+        let base_name = base_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("capture")
+            .to_string();
+        let file_name = match peer {
+            Some(addr) => format!("{}_{}_{}.bin", base_name, sanitize_file_name(&addr.to_string()), suffix),
+            None => format!("{}_{}.bin", base_name, suffix),
+        };
+        base_path.with_file_name(file_name)
+    }
+}
+
+/// 地址中的 ':'、'%' 等字符在部分文件系统上不可用，统一替换为 '_'
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '_' })
+        .collect()
+}
@@ -6,7 +6,7 @@ use gpui_component::ActiveTheme as _;
 use crate::custom_icons::CustomIconName;
 
 use crate::app::NetAssistantApp;
-use crate::config::connection::ConnectionConfig;
+use crate::config::connection::{ConnectionConfig, LoadTestConfig, MaxConnectionsPolicy, MqttConfig, MulticastConfig, ProxyConfig, RelayConfig, SerialConfig, WebSocketSendFrame, MAX_UDP_RECV_BUFFER_SIZE};
 
 pub struct ConnectionPanel<'a> {
     app: &'a NetAssistantApp,
@@ -24,44 +24,22 @@ impl<'a> ConnectionPanel<'a> {
     ) -> impl IntoElement {
         let theme = cx.theme().clone();
         
-        // 提取客户端连接信息（ID、IP、端口、类型）
-        let client_info: Vec<(String, String, u16, String)> = self
+        // 提取客户端连接信息（ID、显示地址、类型）
+        let client_info: Vec<(String, String, String)> = self
             .app
             .storage
             .client_connections()
             .iter()
-            .map(|c| {
-                if let ConnectionConfig::Client(client) = c {
-                    (
-                        client.id.clone(),
-                        client.server_address.clone(),
-                        client.server_port,
-                        client.protocol.to_string(),
-                    )
-                } else {
-                    (String::new(), String::new(), 0, String::new())
-                }
-            })
+            .map(|c| (c.id().to_string(), c.display_address(), c.protocol().to_string()))
             .collect();
 
-        // 提取服务端连接信息（ID、IP、端口、类型）
-        let server_info: Vec<(String, String, u16, String)> = self
+        // 提取服务端连接信息（ID、显示地址、类型）
+        let server_info: Vec<(String, String, String)> = self
             .app
             .storage
             .server_connections()
             .iter()
-            .map(|c| {
-                if let ConnectionConfig::Server(server) = c {
-                    (
-                        server.id.clone(),
-                        server.listen_address.clone(),
-                        server.listen_port,
-                        server.protocol.to_string(),
-                    )
-                } else {
-                    (String::new(), String::new(), 0, String::new())
-                }
-            })
+            .map(|c| (c.id().to_string(), c.display_address(), c.protocol().to_string()))
             .collect();
 
         div()
@@ -112,21 +90,19 @@ impl<'a> ConnectionPanel<'a> {
         content_id: &'static str,
         title: &'static str,
         is_expanded: bool,
-        items: Vec<(String, String, u16, String)>,
+        items: Vec<(String, String, String)>,
         new_button_id: &'static str,
         is_client: bool,
     ) -> Div {
         let theme = cx.theme().clone();
         let mut content_div = div().flex().flex_col().gap_2().id(content_id).pl_3();
 
-        for (conn_id, host, port, protocol) in items.iter() {
+        for (conn_id, address, protocol) in items.iter() {
             let conn_id_clone1 = conn_id.clone();
             let conn_id_clone2 = conn_id.clone();
-            let _host_clone = host.clone();
-            let _port_clone = *port;
             let _protocol_clone = protocol.clone();
             let is_client_clone = is_client;
-            let display_text = format!("{}:{} [{}]", host, port, protocol);
+            let display_text = format!("{} [{}]", address, protocol);
 
             content_div = content_div.child(
                 div()
@@ -244,6 +220,101 @@ impl<'a> ConnectionPanel<'a> {
                             input.set_value(default_host.to_string(), window, cx);
                             cx.notify();
                         });
+                        app.timeout_input.update(cx, |input, cx| {
+                            input.set_value("30".to_string(), window, cx);
+                            cx.notify();
+                        });
+                        app.read_idle_timeout_input.update(cx, |input, cx| {
+                            input.set_value("0".to_string(), window, cx);
+                            cx.notify();
+                        });
+                        app.new_connection_read_idle_close = true;
+                        app.max_connections_input.update(cx, |input, cx| {
+                            input.set_value("100".to_string(), window, cx);
+                            cx.notify();
+                        });
+                        app.new_connection_max_connections_policy = MaxConnectionsPolicy::default();
+                        app.new_connection_dual_stack = false;
+                        app.udp_recv_buffer_input.update(cx, |input, cx| {
+                            input.set_value(MAX_UDP_RECV_BUFFER_SIZE.to_string(), window, cx);
+                            cx.notify();
+                        });
+                        let multicast = MulticastConfig::default();
+                        for (input, value) in [
+                            (&app.local_address_input, String::new()),
+                            (&app.local_port_input, String::new()),
+                            (&app.keepalive_idle_input, String::new()),
+                            (&app.keepalive_interval_input, String::new()),
+                            (&app.keepalive_count_input, String::new()),
+                            (&app.linger_input, String::new()),
+                            (&app.send_buffer_input, String::new()),
+                            (&app.recv_buffer_input, String::new()),
+                            (&app.ip_ttl_input, String::new()),
+                            (&app.ip_tos_input, String::new()),
+                            (&app.tls_server_name_input, String::new()),
+                            (&app.tls_ca_input, String::new()),
+                            (&app.tls_cert_input, String::new()),
+                            (&app.tls_key_input, String::new()),
+                            (&app.multicast_groups_input, String::new()),
+                            (&app.multicast_interface_input, multicast.interface),
+                            (&app.multicast_ttl_input, multicast.ttl.to_string()),
+                            (&app.multicast_source_input, String::new()),
+                            (&app.ws_path_input, "/".to_string()),
+                            (&app.ws_headers_input, String::new()),
+                            (&app.ws_subprotocols_input, String::new()),
+                            (&app.serial_path_input, String::new()),
+                            (&app.serial_baud_input, SerialConfig::default().baud_rate.to_string()),
+                            (&app.unix_path_input, String::new()),
+                            (&app.unix_permissions_input, String::new()),
+                            (&app.unix_local_path_input, String::new()),
+                            (&app.mqtt_client_id_input, String::new()),
+                            (&app.mqtt_username_input, String::new()),
+                            (&app.mqtt_password_input, String::new()),
+                            (&app.mqtt_keep_alive_input, MqttConfig::default().keep_alive.to_string()),
+                            (&app.mqtt_subscriptions_input, String::new()),
+                            (&app.mqtt_publish_topic_input, String::new()),
+                            (&app.relay_upstream_address_input, String::new()),
+                            (&app.relay_upstream_port_input, String::new()),
+                            (&app.load_test_clients_input, LoadTestConfig::default().clients.to_string()),
+                            (&app.load_test_ramp_up_input, LoadTestConfig::default().ramp_up_rate.to_string()),
+                            (&app.load_test_send_rate_input, LoadTestConfig::default().send_rate.to_string()),
+                            (&app.load_test_payload_input, LoadTestConfig::default().payload),
+                            (&app.load_test_duration_input, LoadTestConfig::default().duration.to_string()),
+                            (&app.proxy_address_input, String::new()),
+                            (&app.proxy_port_input, ProxyConfig::default().port.to_string()),
+                            (&app.proxy_username_input, String::new()),
+                            (&app.proxy_password_input, String::new()),
+                        ] {
+                            input.update(cx, |input, cx| {
+                                input.set_value(value, window, cx);
+                                cx.notify();
+                            });
+                        }
+                        for input in app.relay_to_upstream_inputs.all().into_iter().chain(app.relay_to_client_inputs.all()) {
+                            input.update(cx, |input, cx| {
+                                input.set_value(String::new(), window, cx);
+                                cx.notify();
+                            });
+                        }
+                        app.new_connection_multicast_loopback = multicast.loopback;
+                        app.new_connection_nodelay = false;
+                        app.new_connection_keepalive = false;
+                        app.new_connection_tls_enabled = false;
+                        app.new_connection_tls_insecure = false;
+                        app.new_connection_tls_require_client_cert = false;
+                        app.new_connection_ws_send_frame = WebSocketSendFrame::default();
+                        app.new_connection_serial = SerialConfig::default();
+                        app.new_connection_mqtt = MqttConfig::default();
+                        app.new_connection_relay = RelayConfig::default();
+                        app.new_connection_load_test = LoadTestConfig::default();
+                        app.new_connection_proxy = ProxyConfig::default();
+                        // 串口、MQTT和压力测试仅支持客户端，中继仅支持服务端
+                        if !is_client_clone && matches!(app.new_connection_protocol.as_str(), "Serial" | "MQTT" | "Load Test") {
+                            app.new_connection_protocol = String::from("TCP");
+                        }
+                        if is_client_clone && app.new_connection_protocol == "Relay" {
+                            app.new_connection_protocol = String::from("TCP");
+                        }
                     },
                 ),
             );
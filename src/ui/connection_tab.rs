@@ -12,18 +12,30 @@ use gpui_component::{
 };
 
 use log::{debug, error, info, warn};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
 use tokio::task::JoinHandle;
 
 use crate::app::NetAssistantApp;
-use crate::config::connection::{ConnectionConfig, ConnectionStatus, ConnectionType};
+use crate::config::connection::{ConnectionConfig, ConnectionStatus, ConnectionType, MqttQos, QueuePolicy, RttCorrelation, SendShaping};
 use crate::custom_icons::CustomIconName;
 use crate::log_writer::LogWriter;
-use crate::message::{Message, MessageDirection, MessageListState};
+use crate::message::{Message, MessageDirection, MessageListState, RelayDirection};
+use crate::network::latency::{format_latency, RttTracker, RTT_ROLLING_WINDOW};
+use crate::network::protocol::loadtest::LoadTestReport;
+use crate::network::protocol::relay::RelayHoldState;
+use crate::network::protocol::tcp::ClientCloseMode;
+use crate::network::queue::QueueMetrics;
+use crate::network::resolve;
+use crate::network::protocol::serial::{SerialLine, SerialLineState};
+use crate::network::protocol::websocket::WebSocketEvent;
+use crate::network::tls::TlsSessionInfo;
 use crate::utils::hex::hex_to_bytes;
 
+/// 标签页保留的WebSocket事件条数
+const MAX_WEBSOCKET_EVENTS: usize = 50;
+
 /// 连接标签页状态
 #[derive(Clone)]
 pub struct ConnectionTabState {
@@ -35,6 +47,26 @@ pub struct ConnectionTabState {
     pub auto_reply_enabled: bool,
     pub auto_scroll_enabled: bool,
     pub client_connections: Vec<SocketAddr>,
+    /// 主机名解析得到的地址（地址为IP字面量时为空）
+    pub resolved_addresses: Vec<SocketAddr>,
+    // 客户端连接建立后实际使用的本地端点
+    pub local_address: Option<SocketAddr>,
+    // 对端已关闭写方向（收到 FIN）
+    pub peer_write_closed: bool,
+    // 本端已关闭写方向（已发送 FIN）
+    pub local_write_closed: bool,
+    // 客户端连接协商的TLS会话信息
+    pub tls_info: Option<TlsSessionInfo>,
+    // 服务端各客户端协商的TLS会话信息
+    pub client_tls: HashMap<SocketAddr, TlsSessionInfo>,
+    // 服务端各客户端的显示名称（Unix域套接字客户端的对端名称）
+    pub client_labels: HashMap<SocketAddr, String>,
+    // 服务端各客户端连接到的本地端点（监听多个地址时区分客户端所在的监听地址）
+    pub client_local_addresses: HashMap<SocketAddr, SocketAddr>,
+    // 最近的WebSocket握手与控制帧事件（时间，客户端地址，事件）
+    pub websocket_events: VecDeque<(String, Option<SocketAddr>, WebSocketEvent)>,
+    // 串口 DTR/RTS 控制线电平（串口打开后才有）
+    pub serial_lines: Option<SerialLineState>,
     pub selected_client: Option<SocketAddr>,
 
     // GPUI List 状态
@@ -46,6 +78,26 @@ pub struct ConnectionTabState {
     pub auto_clear_input: bool,
     pub periodic_send_enabled: bool,
     pub periodic_interval_input: Option<Entity<InputState>>,
+    // MQTT发布设置：主题输入框仅MQTT客户端有，发送时读取主题供周期发送和自动回复沿用
+    pub mqtt_topic_input: Option<Entity<InputState>>,
+    pub mqtt_publish_topic: String,
+    pub mqtt_publish_qos: MqttQos,
+    pub mqtt_publish_retain: bool,
+    // 中继：暂停状态和暂存队列，以及发送时注入的方向
+    pub relay_hold: RelayHoldState,
+    pub relay_inject_direction: RelayDirection,
+    // 压力测试的最近一次统计（停止后保留最终结果）
+    pub load_test_report: Option<LoadTestReport>,
+    // 发送整形参数：拆分次数/分块字节数/合并等待毫秒，以及拆分和分块的写入间隔毫秒
+    pub send_shaping_value_input: Entity<InputState>,
+    pub send_shaping_delay_input: Entity<InputState>,
+    // 往返时间测量：配对记录和统计，以及相同字节规则的偏移/长度输入框
+    pub rtt: RttTracker,
+    pub rtt_offset_input: Entity<InputState>,
+    pub rtt_length_input: Entity<InputState>,
+    // 收发队列容量输入框，以及本标签页的队列统计（连接时交给连接管理器）
+    pub queue_capacity_input: Entity<InputState>,
+    pub queue_metrics: Arc<QueueMetrics>,
     // 使用 Arc<Mutex> 包装以支持克隆
     pub periodic_send_timer: Option<Arc<Mutex<Option<JoinHandle<()>>>>>,
 
@@ -60,6 +112,13 @@ pub struct ConnectionTabState {
     pub log_file_path: Option<String>,
     pub custom_log_path: Option<String>,
     pub log_writer: Option<Arc<tokio::sync::Mutex<LogWriter>>>,
+
+    // 原始数据录制相关（录制器本身由网络连接管理器持有）
+    pub raw_capture_enabled: bool,
+    pub raw_capture_include_sent: bool,
+    pub raw_capture_per_client: bool,
+    pub raw_capture_name: Option<String>,
+    pub raw_capture_dir: Option<String>,
 }
 
 impl ConnectionTabState {
@@ -68,6 +127,43 @@ impl ConnectionTabState {
         window: &mut Window,
         cx: &mut Context<NetAssistantApp>,
     ) -> Self {
+        let mqtt_publish_topic = match &connection_config {
+            ConnectionConfig::Client(config) if config.protocol == ConnectionType::Mqtt => Some(config.mqtt.publish_topic.clone()),
+            _ => None,
+        };
+        let mqtt_topic_input = mqtt_publish_topic.as_ref().map(|topic| {
+            let input = cx.new(|cx| InputState::new(window, cx).placeholder("发布主题，如 devices/1/cmd"));
+            input.update(cx, |input, cx| {
+                input.set_value(topic.clone(), window, cx);
+            });
+            input
+        });
+
+        let (shaping_value, shaping_delay) = Self::send_shaping_input_values(connection_config.send_shaping());
+        let send_shaping_value_input = cx.new(|cx| InputState::new(window, cx).placeholder("参数"));
+        send_shaping_value_input.update(cx, |input, cx| {
+            input.set_value(shaping_value, window, cx);
+        });
+        let send_shaping_delay_input = cx.new(|cx| InputState::new(window, cx).placeholder("0"));
+        send_shaping_delay_input.update(cx, |input, cx| {
+            input.set_value(shaping_delay, window, cx);
+        });
+        let rtt = RttTracker::new(connection_config.rtt_correlation());
+        let (rtt_offset, rtt_length) = Self::rtt_input_values(connection_config.rtt_correlation());
+        let rtt_offset_input = cx.new(|cx| InputState::new(window, cx).placeholder("0"));
+        rtt_offset_input.update(cx, |input, cx| {
+            input.set_value(rtt_offset, window, cx);
+        });
+        let rtt_length_input = cx.new(|cx| InputState::new(window, cx).placeholder("2"));
+        rtt_length_input.update(cx, |input, cx| {
+            input.set_value(rtt_length, window, cx);
+        });
+        let queue_capacity_input = cx.new(|cx| InputState::new(window, cx).placeholder("容量"));
+        let queue_capacity = connection_config.queue().capacity().to_string();
+        queue_capacity_input.update(cx, |input, cx| {
+            input.set_value(queue_capacity, window, cx);
+        });
+
         Self {
             connection_config,
             connection_status: ConnectionStatus::NotConnected,
@@ -77,6 +173,16 @@ impl ConnectionTabState {
             auto_reply_enabled: false,
             auto_scroll_enabled: true,
             client_connections: Vec::new(),
+            resolved_addresses: Vec::new(),
+            local_address: None,
+            peer_write_closed: false,
+            local_write_closed: false,
+            tls_info: None,
+            client_tls: HashMap::new(),
+            client_labels: HashMap::new(),
+            client_local_addresses: HashMap::new(),
+            websocket_events: VecDeque::new(),
+            serial_lines: None,
             selected_client: None,
 
             // GPUI List 状态
@@ -100,6 +206,20 @@ impl ConnectionTabState {
                 Some(input)
             },
             periodic_send_timer: None,
+            mqtt_topic_input,
+            mqtt_publish_topic: mqtt_publish_topic.unwrap_or_default(),
+            mqtt_publish_qos: MqttQos::default(),
+            mqtt_publish_retain: false,
+            relay_hold: RelayHoldState::default(),
+            relay_inject_direction: RelayDirection::ToUpstream,
+            load_test_report: None,
+            send_shaping_value_input,
+            send_shaping_delay_input,
+            rtt,
+            rtt_offset_input,
+            rtt_length_input,
+            queue_capacity_input,
+            queue_metrics: Arc::new(QueueMetrics::default()),
 
             // 初始化服务端和客户端的控制句柄
             server_handle: None,
@@ -112,6 +232,13 @@ impl ConnectionTabState {
             log_file_path: None,
             custom_log_path: None,
             log_writer: None,
+
+            // 初始化原始数据录制
+            raw_capture_enabled: false,
+            raw_capture_include_sent: false,
+            raw_capture_per_client: false,
+            raw_capture_name: None,
+            raw_capture_dir: None,
         }
     }
 
@@ -119,22 +246,40 @@ impl ConnectionTabState {
         self.connection_config.name()
     }
 
+    /// 发送整形参数在输入框中的显示值（参数, 间隔）
+    pub fn send_shaping_input_values(shaping: SendShaping) -> (String, String) {
+        match shaping {
+            SendShaping::Off => (String::new(), String::new()),
+            SendShaping::Split { parts, delay_ms } => (parts.to_string(), delay_ms.to_string()),
+            SendShaping::Chunks { size, delay_ms } => (size.to_string(), delay_ms.to_string()),
+            SendShaping::Coalesce { window_ms } => (window_ms.to_string(), String::new()),
+        }
+    }
+
+    /// 相同字节配对规则在输入框中的显示值（偏移, 长度）
+    pub fn rtt_input_values(correlation: RttCorrelation) -> (String, String) {
+        match correlation {
+            RttCorrelation::SameBytes { offset, length } => (offset.to_string(), length.to_string()),
+            _ => (String::new(), String::new()),
+        }
+    }
+
     pub fn protocol(&self) -> &str {
         match self.connection_config.protocol() {
             ConnectionType::Tcp => "TCP",
             ConnectionType::Udp => "UDP",
+            ConnectionType::WebSocket => "WebSocket",
+            ConnectionType::Serial => "Serial",
+            ConnectionType::Mqtt => "MQTT",
+            ConnectionType::UnixStream => "Unix",
+            ConnectionType::UnixDatagram => "Unix DGRAM",
+            ConnectionType::Relay => "Relay",
+            ConnectionType::LoadTest => "Load Test",
         }
     }
 
     pub fn address(&self) -> String {
-        match &self.connection_config {
-            ConnectionConfig::Client(config) => {
-                format!("{}:{}", config.server_address, config.server_port)
-            }
-            ConnectionConfig::Server(config) => {
-                format!("{}:{}", config.listen_address, config.listen_port)
-            }
-        }
+        self.connection_config.display_address()
     }
 
     pub fn decoder(&self) -> String {
@@ -148,6 +293,20 @@ impl ConnectionTabState {
         }
     }
 
+    /// 是否正在等待自动重连
+    pub fn is_reconnecting(&self) -> bool {
+        matches!(self.connection_status, ConnectionStatus::Reconnecting { .. })
+    }
+
+    /// 记录一条WebSocket事件，只保留最近的 MAX_WEBSOCKET_EVENTS 条
+    pub fn push_websocket_event(&mut self, peer: Option<SocketAddr>, event: WebSocketEvent) {
+        if self.websocket_events.len() >= MAX_WEBSOCKET_EVENTS {
+            self.websocket_events.pop_front();
+        }
+        let time = chrono::Local::now().format("%H:%M:%S").to_string();
+        self.websocket_events.push_back((time, peer, event));
+    }
+
     pub fn add_message(&mut self, message: Message) {
         // 日志记录：异步写入文件
         if self.log_enabled {
@@ -190,6 +349,7 @@ impl ConnectionTabState {
             });
         }
         self.log_enabled = false;
+        self.raw_capture_enabled = false;
 
         // 停止服务端任务
         if let Some(handle) = &self.server_handle {
@@ -262,6 +422,7 @@ impl<'a> ConnectionTab<'a> {
         cx: &mut Context<NetAssistantApp>,
     ) -> impl IntoElement {
         let theme = cx.theme().clone();
+        let is_load_test = self.tab_state.connection_config.protocol() == ConnectionType::LoadTest;
 
         div()
             .flex()
@@ -269,17 +430,202 @@ impl<'a> ConnectionTab<'a> {
             .flex_1()
             .bg(theme.background)
             .child(self.render_connection_info(window, cx))
+            // 压力测试不逐条显示消息，也不从发送框发送
+            .when(is_load_test, |this| {
+                this.child(self.render_load_test_report(cx))
+            })
+            .when(!is_load_test, |this| {
+                this.child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .flex_1()
+                        .child(self.render_message_area(window, cx))
+                        .child(self.render_send_area(window, cx)),
+                )
+            })
+    }
+
+    /// 渲染压力测试的汇总统计：连接、吞吐量和响应延迟
+    fn render_load_test_report(&self, cx: &mut Context<NetAssistantApp>) -> impl IntoElement {
+        let theme = cx.theme().clone();
+        let report = self.tab_state.load_test_report.clone();
+        let target_clients = match &self.tab_state.connection_config {
+            ConnectionConfig::Client(config) => config.load_test.clients,
+            ConnectionConfig::Server(_) => 0,
+        };
+
+        let row = |label: &'static str, value: String| {
+            div()
+                .flex()
+                .items_center()
+                .gap_2()
+                .child(
+                    div()
+                        .w_24()
+                        .text_xs()
+                        .text_color(gpui::rgb(0x6b7280))
+                        .child(label),
+                )
+                .child(
+                    div()
+                        .text_sm()
+                        .font_medium()
+                        .text_color(theme.foreground)
+                        .child(value),
+                )
+        };
+        let section = |title: &'static str| {
+            div()
+                .flex()
+                .flex_col()
+                .gap_1()
+                .child(
+                    div()
+                        .text_sm()
+                        .font_semibold()
+                        .text_color(theme.foreground)
+                        .child(title),
+                )
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .flex_1()
+            .h_full()
+            .p_4()
+            .gap_4()
             .child(
                 div()
-                    .flex()
-                    .flex_col()
-                    .flex_1()
-                    .child(self.render_message_area(window, cx))
-                    .child(self.render_send_area(window, cx)),
+                    .text_sm()
+                    .font_medium()
+                    .text_color(gpui::rgb(0x6b7280))
+                    .child(match &report {
+                        Some(report) if report.finished => format!("测试已结束，用时 {} 秒", report.elapsed.as_secs()),
+                        Some(report) => format!("测试进行中，已运行 {} 秒", report.elapsed.as_secs()),
+                        None => "点击连接开始测试".to_string(),
+                    }),
             )
+            .when_some(report, |this, report| {
+                let latency = |value: Option<std::time::Duration>| value.map(format_latency).unwrap_or_else(|| "-".to_string());
+                this.child(
+                    section("连接")
+                        .child(row("已启动", format!("{} / {}", report.started, target_clients)))
+                        .child(row("连接成功", report.connected.to_string()))
+                        .child(row("连接失败", report.connect_failed.to_string()))
+                        .child(row("当前在线", report.active.to_string()))
+                        .child(row("中途出错", report.errors.to_string())),
+                )
+                .child(
+                    section("吞吐量")
+                        .child(row("发送", format!("{} 条 / {} 字节", report.sent, report.sent_bytes)))
+                        .child(row("响应", format!("{} 条 / {} 字节", report.responses, report.received_bytes)))
+                        .child(row("无效响应", report.invalid.to_string()))
+                        .child(row("未配对响应", report.unmatched.to_string()))
+                        .child(row("丢失请求", report.lost.to_string()))
+                        .child(row("发送速率", format!("{:.1} 条/秒", report.send_rate)))
+                        .child(row("响应速率", format!("{:.1} 条/秒", report.response_rate)))
+                        .child(row("接收速率", format!("{:.0} 字节/秒", report.receive_throughput))),
+                )
+                .child(
+                    section("响应延迟")
+                        .child(row("最小", latency(report.latency.map(|l| l.min))))
+                        .child(row("平均", latency(report.latency.map(|l| l.mean))))
+                        .child(row("p50", latency(report.latency.map(|l| l.p50))))
+                        .child(row("p95", latency(report.latency.map(|l| l.p95))))
+                        .child(row("p99", latency(report.latency.map(|l| l.p99))))
+                        .child(row("最大", latency(report.latency.map(|l| l.max)))),
+                )
+                .when_some(report.last_error, |this, error| {
+                    this.child(
+                        div()
+                            .text_xs()
+                            .text_color(gpui::rgb(0xef4444))
+                            .child(format!("最近错误: {}", error)),
+                    )
+                })
+            })
     }
 
     /// 渲染连接信息区域（左侧面板）
+    /// 渲染TLS协议版本、密码套件与对端证书链
+    fn render_tls_info(tls_info: &TlsSessionInfo) -> Div {
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(gpui::rgb(0x6b7280))
+                            .child("TLS:"),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .font_medium()
+                            .text_color(gpui::rgb(0x111827))
+                            .child(format!("{} / {}", tls_info.version, tls_info.cipher)),
+                    ),
+            )
+            .when(tls_info.peer_certificates.is_empty(), |this| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(gpui::rgb(0x9ca3af))
+                        .child("对端未提供证书"),
+                )
+            })
+            .children(tls_info.peer_certificates.iter().enumerate().map(|(depth, cert)| {
+                div()
+                    .text_xs()
+                    .text_color(gpui::rgb(0x111827))
+                    .child(format!("证书[{}]: {}", depth, cert))
+            }))
+    }
+
+    /// 渲染最近的WebSocket握手与控制帧事件，最新的在前
+    fn render_websocket_events(&self) -> Div {
+        let selected_client = self.tab_state.selected_client;
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(gpui::rgb(0x6b7280))
+                    .child("WebSocket:"),
+            )
+            .children(
+                self.tab_state
+                    .websocket_events
+                    .iter()
+                    .rev()
+                    .filter(|(_, peer, _)| selected_client.is_none() || *peer == selected_client)
+                    .take(8)
+                    .map(|(time, peer, event)| {
+                        let text = match peer {
+                            Some(addr) if selected_client.is_none() => format!("{} [{}] {}", time, addr, event),
+                            _ => format!("{} {}", time, event),
+                        };
+                        div()
+                            .text_xs()
+                            .text_color(match event {
+                                WebSocketEvent::Close { .. } => gpui::rgb(0xf59e0b),
+                                _ => gpui::rgb(0x111827),
+                            })
+                            .child(text)
+                    }),
+            )
+    }
+
     fn render_connection_info(
         &self,
         window: &mut Window,
@@ -288,8 +634,17 @@ impl<'a> ConnectionTab<'a> {
         let theme = cx.theme().clone();
         let tab_id = self.tab_id.clone();
 
-        let is_connected = self.tab_state.is_connected;
+        // 等待重连时按钮同样显示为断开，用于停止重连
+        let is_connected = self.tab_state.is_connected || self.tab_state.is_reconnecting();
         let is_client = self.tab_state.connection_config.is_client();
+        let is_relay = self.tab_state.connection_config.protocol() == ConnectionType::Relay;
+        let is_tcp_client = is_client && matches!(self.tab_state.connection_config.protocol(), ConnectionType::Tcp | ConnectionType::UnixStream);
+        let auto_reconnect = match &self.tab_state.connection_config {
+            ConnectionConfig::Client(config) if matches!(config.protocol, ConnectionType::Tcp | ConnectionType::WebSocket | ConnectionType::Serial | ConnectionType::UnixStream | ConnectionType::Mqtt) => {
+                Some((config.auto_reconnect, config.reconnect_max_attempts))
+            }
+            _ => None,
+        };
 
         div()
             .flex()
@@ -378,44 +733,658 @@ impl<'a> ConnectionTab<'a> {
                             .gap_2()
                             .child(
                                 div()
-                                    .text_xs()
-                                    .text_color(gpui::rgb(0x6b7280))
-                                    .child("地址:"),
+                                    .text_xs()
+                                    .text_color(gpui::rgb(0x6b7280))
+                                    .child("地址:"),
+                            )
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .font_medium()
+                                    .text_color(gpui::rgb(0x111827))
+                                    .child(self.tab_state.address()),
+                            ),
+                    )
+                    // 客户端使用的上游代理
+                    .when_some(
+                        match &self.tab_state.connection_config {
+                            ConnectionConfig::Client(client) if client.proxy.is_enabled() => Some(client.proxy.to_string()),
+                            _ => None,
+                        },
+                        |this, proxy| {
+                            this.child(
+                                div()
+                                    .flex()
+                                    .items_center()
+                                    .gap_2()
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(gpui::rgb(0x6b7280))
+                                            .child("代理:"),
+                                    )
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(gpui::rgb(0x111827))
+                                            .child(proxy),
+                                    ),
+                            )
+                        },
+                    )
+                    // 客户端实际使用的本地端点
+                    .when_some(self.tab_state.local_address, |this, local_address| {
+                        this.child(
+                            div()
+                                .flex()
+                                .items_center()
+                                .gap_2()
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(gpui::rgb(0x6b7280))
+                                        .child("本地:"),
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(gpui::rgb(0x111827))
+                                        .child(local_address.to_string()),
+                                ),
+                        )
+                    })
+                    // TLS会话信息（客户端显示自身连接，服务端显示选中的客户端）
+                    .when_some(
+                        self.tab_state.tls_info.as_ref().or_else(|| {
+                            self.tab_state
+                                .selected_client
+                                .and_then(|addr| self.tab_state.client_tls.get(&addr))
+                        }),
+                        |this, tls_info| this.child(Self::render_tls_info(tls_info)),
+                    )
+                    // 最近的WebSocket事件（服务端选中客户端时只显示该客户端的）
+                    .when(!self.tab_state.websocket_events.is_empty(), |this| {
+                        this.child(self.render_websocket_events())
+                    })
+                    // 已加入的组播组（仅UDP服务端）
+                    .when_some(
+                        match &self.tab_state.connection_config {
+                            ConnectionConfig::Server(config) if config.multicast.is_enabled() => {
+                                Some(config.multicast.groups.iter().map(|group| group.to_string()).collect::<Vec<_>>().join(", "))
+                            }
+                            _ => None,
+                        },
+                        |this, groups| {
+                            this.child(
+                                div()
+                                    .flex()
+                                    .items_start()
+                                    .gap_2()
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(gpui::rgb(0x6b7280))
+                                            .child("组播:"),
+                                    )
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(gpui::rgb(0x111827))
+                                            .child(groups),
+                                    ),
+                            )
+                        },
+                    )
+                    // 主机名解析结果
+                    .when(!self.tab_state.resolved_addresses.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .flex()
+                                .items_start()
+                                .gap_2()
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(gpui::rgb(0x6b7280))
+                                        .child("解析:"),
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(gpui::rgb(0x111827))
+                                        .child(resolve::format_addresses(&self.tab_state.resolved_addresses)),
+                                ),
+                        )
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(gpui::rgb(0x6b7280))
+                                    .child("状态:"),
+                            )
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .font_medium()
+                                    .when(self.tab_state.is_connected, |div| {
+                                        div.text_color(gpui::rgb(0x22c55e))
+                                    })
+                                    .when(!self.tab_state.is_connected, |div| {
+                                        div.text_color(gpui::rgb(0x9ca3af))
+                                    })
+                                    .child(format!("{}", self.tab_state.connection_status)),
+                            ),
+                    )
+                    // TCP客户端半关闭状态与手动关闭写方向
+                    .when(
+                        self.tab_state.is_connected && is_tcp_client,
+                        |div_builder| {
+                            let half_close_state = match (self.tab_state.peer_write_closed, self.tab_state.local_write_closed) {
+                                (true, _) => Some("对端已关闭写方向（收到 FIN），仍可发送"),
+                                (false, true) => Some("本端已关闭写方向（已发送 FIN），仍可接收"),
+                                (false, false) => None,
+                            };
+                            div_builder.child(
+                                div()
+                                    .flex()
+                                    .items_center()
+                                    .gap_2()
+                                    .when_some(half_close_state, |this, state| {
+                                        this.child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0xf59e0b))
+                                                .child(state),
+                                        )
+                                    })
+                                    .when(!self.tab_state.local_write_closed, |this| {
+                                        this.child(
+                                            div()
+                                                .text_xs()
+                                                .px_1()
+                                                .py_0()
+                                                .bg(gpui::rgb(0xf59e0b))
+                                                .text_color(gpui::rgb(0xffffff))
+                                                .rounded_md()
+                                                .cursor_pointer()
+                                                .child("关闭写方向 (FIN)")
+                                                .on_mouse_down(MouseButton::Left, cx.listener({
+                                                    let tab_id_shutdown = tab_id.clone();
+                                                    move |app, _event, _window, cx| {
+                                                        app.shutdown_client_write(tab_id_shutdown.clone(), cx);
+                                                    }
+                                                })),
+                                        )
+                                    }),
+                            )
+                        },
+                    )
+                    // 串口 DTR/RTS 控制线，点击切换电平
+                    .when_some(self.tab_state.serial_lines.filter(|_| self.tab_state.is_connected), |div_builder, lines| {
+                        div_builder.child(
+                            div()
+                                .flex()
+                                .items_center()
+                                .gap_2()
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(gpui::rgb(0x6b7280))
+                                        .child("控制线:"),
+                                )
+                                .children([SerialLine::Dtr, SerialLine::Rts].map(|line| {
+                                    let level = lines.get(line);
+                                    div()
+                                        .text_xs()
+                                        .px_1()
+                                        .py_0()
+                                        .rounded_md()
+                                        .cursor_pointer()
+                                        .text_color(gpui::rgb(0xffffff))
+                                        .when(level, |this| this.bg(gpui::rgb(0x22c55e)))
+                                        .when(!level, |this| this.bg(gpui::rgb(0x9ca3af)))
+                                        .child(format!("{} {}", line, if level { "高" } else { "低" }))
+                                        .on_mouse_down(MouseButton::Left, cx.listener({
+                                            let tab_id_line = tab_id.clone();
+                                            move |app, _event, _window, cx| {
+                                                app.set_serial_line(tab_id_line.clone(), line, !level, cx);
+                                            }
+                                        }))
+                                })),
+                        )
+                    })
+                    // TCP、WebSocket和串口客户端自动重连开关（下次连接时生效）
+                    .when_some(auto_reconnect, |div_builder, (enabled, max_attempts)| {
+                        div_builder.child(
+                            div()
+                                .flex()
+                                .items_center()
+                                .gap_2()
+                                .child(
+                                    div()
+                                        .w_4()
+                                        .h_4()
+                                        .border_1()
+                                        .border_color(gpui::rgb(0xd1d5db))
+                                        .rounded(px(4.))
+                                        .cursor_pointer()
+                                        .when(enabled, |this| {
+                                            this.bg(gpui::rgb(0x3b82f6))
+                                                .flex()
+                                                .items_center()
+                                                .justify_center()
+                                                .child(
+                                                    div()
+                                                        .text_xs()
+                                                        .text_color(gpui::rgb(0xffffff))
+                                                        .font_bold()
+                                                        .child("✓"),
+                                                )
+                                        })
+                                        .on_mouse_down(MouseButton::Left, cx.listener({
+                                            let tab_id_reconnect = tab_id.clone();
+                                            move |app, _event, _window, cx| {
+                                                app.toggle_auto_reconnect(tab_id_reconnect.clone(), cx);
+                                            }
+                                        })),
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(gpui::rgb(0x6b7280))
+                                        .child(if max_attempts == 0 {
+                                            "自动重连".to_string()
+                                        } else {
+                                            format!("自动重连 (最多{}次)", max_attempts)
+                                        }),
+                                ),
+                        )
+                    })
+                    // TCP和Unix流式套接字的发送整形（下次连接时生效）
+                    .when(matches!(self.tab_state.connection_config.protocol(), ConnectionType::Tcp | ConnectionType::UnixStream), |div_builder| {
+                        let shaping = self.tab_state.connection_config.send_shaping();
+                        let value_label = match shaping {
+                            SendShaping::Off => None,
+                            SendShaping::Split { .. } => Some("次数"),
+                            SendShaping::Chunks { .. } => Some("字节"),
+                            SendShaping::Coalesce { .. } => Some("等待ms"),
+                        };
+                        let has_delay = matches!(shaping, SendShaping::Split { .. } | SendShaping::Chunks { .. });
+                        div_builder.child(
+                            div()
+                                .flex()
+                                .flex_col()
+                                .gap_1()
+                                .child(
+                                    div()
+                                        .flex()
+                                        .flex_wrap()
+                                        .items_center()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x6b7280))
+                                                .child("发送整形:"),
+                                        )
+                                        .children([
+                                            ("关", SendShaping::Off),
+                                            ("拆分", SendShaping::Split { parts: 2, delay_ms: 0 }),
+                                            ("分块", SendShaping::Chunks { size: 1, delay_ms: 0 }),
+                                            ("合并", SendShaping::Coalesce { window_ms: 100 }),
+                                        ].map(|(label, template)| {
+                                            let selected = std::mem::discriminant(&shaping) == std::mem::discriminant(&template);
+                                            div()
+                                                .text_xs()
+                                                .px_1()
+                                                .rounded_md()
+                                                .cursor_pointer()
+                                                .when(selected, |this| this.bg(theme.primary).text_color(theme.background))
+                                                .when(!selected, |this| this.bg(theme.border).text_color(theme.foreground))
+                                                .child(label)
+                                                .on_mouse_down(MouseButton::Left, cx.listener({
+                                                    let tab_id_shaping = tab_id.clone();
+                                                    move |app, _event, window, cx| {
+                                                        app.set_send_shaping(tab_id_shaping.clone(), template, selected, window, cx);
+                                                    }
+                                                }))
+                                        })),
+                                )
+                                .when_some(value_label, |this, value_label| {
+                                    this.child(
+                                        div()
+                                            .flex()
+                                            .items_center()
+                                            .gap_1()
+                                            .child(
+                                                div()
+                                                    .text_xs()
+                                                    .text_color(gpui::rgb(0x6b7280))
+                                                    .child(value_label),
+                                            )
+                                            .child(div().w_16().child(Input::new(&self.tab_state.send_shaping_value_input)))
+                                            .when(has_delay, |this| {
+                                                this.child(
+                                                    div()
+                                                        .text_xs()
+                                                        .text_color(gpui::rgb(0x6b7280))
+                                                        .child("间隔ms"),
+                                                )
+                                                .child(div().w_16().child(Input::new(&self.tab_state.send_shaping_delay_input)))
+                                            })
+                                            .child(
+                                                div()
+                                                    .text_xs()
+                                                    .px_1()
+                                                    .rounded_md()
+                                                    .cursor_pointer()
+                                                    .bg(theme.primary)
+                                                    .text_color(theme.background)
+                                                    .child("应用")
+                                                    .on_mouse_down(MouseButton::Left, cx.listener({
+                                                        let tab_id_apply = tab_id.clone();
+                                                        move |app, _event, window, cx| {
+                                                            app.set_send_shaping(tab_id_apply.clone(), shaping, true, window, cx);
+                                                        }
+                                                    })),
+                                            ),
+                                    )
+                                })
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(gpui::rgb(0x9ca3af))
+                                        .child(if is_connected {
+                                            format!("{}（下次连接时生效）", shaping)
+                                        } else {
+                                            shaping.to_string()
+                                        }),
+                                ),
+                        )
+                    })
+                    // 客户端的往返时间测量（立即生效）：配对规则、最近样本的最小/平均/最大值和分布
+                    .when(self.tab_state.connection_config.is_client() && self.tab_state.connection_config.protocol() != ConnectionType::LoadTest, |div_builder| {
+                        let rtt = &self.tab_state.rtt;
+                        let correlation = rtt.correlation();
+                        let bins = rtt.histogram_bins();
+                        let max_count = bins.iter().map(|(_, count)| *count).max().unwrap_or(0).max(1);
+                        div_builder.child(
+                            div()
+                                .flex()
+                                .flex_col()
+                                .gap_1()
+                                .child(
+                                    div()
+                                        .flex()
+                                        .flex_wrap()
+                                        .items_center()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x6b7280))
+                                                .child("往返时间:"),
+                                        )
+                                        .children([
+                                            ("关", RttCorrelation::Off),
+                                            ("下一帧", RttCorrelation::NextFrame),
+                                            ("相同字节", RttCorrelation::SameBytes { offset: 0, length: 2 }),
+                                        ].map(|(label, template)| {
+                                            let selected = std::mem::discriminant(&correlation) == std::mem::discriminant(&template);
+                                            div()
+                                                .text_xs()
+                                                .px_1()
+                                                .rounded_md()
+                                                .cursor_pointer()
+                                                .when(selected, |this| this.bg(theme.primary).text_color(theme.background))
+                                                .when(!selected, |this| this.bg(theme.border).text_color(theme.foreground))
+                                                .child(label)
+                                                .on_mouse_down(MouseButton::Left, cx.listener({
+                                                    let tab_id_rtt = tab_id.clone();
+                                                    move |app, _event, window, cx| {
+                                                        if !selected {
+                                                            app.set_rtt_correlation(tab_id_rtt.clone(), template, false, window, cx);
+                                                        }
+                                                    }
+                                                }))
+                                        }))
+                                        .when(correlation.is_enabled(), |this| {
+                                            this.child(
+                                                div()
+                                                    .text_xs()
+                                                    .px_1()
+                                                    .rounded_md()
+                                                    .cursor_pointer()
+                                                    .bg(theme.border)
+                                                    .text_color(theme.foreground)
+                                                    .child("重置")
+                                                    .on_mouse_down(MouseButton::Left, cx.listener({
+                                                        let tab_id_reset = tab_id.clone();
+                                                        move |app, _event, _window, cx| {
+                                                            app.reset_rtt(&tab_id_reset, cx);
+                                                        }
+                                                    })),
+                                            )
+                                        }),
+                                )
+                                .when(matches!(correlation, RttCorrelation::SameBytes { .. }), |this| {
+                                    this.child(
+                                        div()
+                                            .flex()
+                                            .items_center()
+                                            .gap_1()
+                                            .child(
+                                                div()
+                                                    .text_xs()
+                                                    .text_color(gpui::rgb(0x6b7280))
+                                                    .child("偏移"),
+                                            )
+                                            .child(div().w_16().child(Input::new(&self.tab_state.rtt_offset_input)))
+                                            .child(
+                                                div()
+                                                    .text_xs()
+                                                    .text_color(gpui::rgb(0x6b7280))
+                                                    .child("长度"),
+                                            )
+                                            .child(div().w_16().child(Input::new(&self.tab_state.rtt_length_input)))
+                                            .child(
+                                                div()
+                                                    .text_xs()
+                                                    .px_1()
+                                                    .rounded_md()
+                                                    .cursor_pointer()
+                                                    .bg(theme.primary)
+                                                    .text_color(theme.background)
+                                                    .child("应用")
+                                                    .on_mouse_down(MouseButton::Left, cx.listener({
+                                                        let tab_id_apply = tab_id.clone();
+                                                        move |app, _event, window, cx| {
+                                                            app.set_rtt_correlation(tab_id_apply.clone(), correlation, true, window, cx);
+                                                        }
+                                                    })),
+                                            ),
+                                    )
+                                })
+                                .when(correlation.is_enabled(), |this| {
+                                    this.child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(gpui::rgb(0x9ca3af))
+                                            .child(correlation.to_string()),
+                                    )
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(gpui::rgb(0x6b7280))
+                                            .child(match rtt.rolling() {
+                                                Some(rolling) => format!(
+                                                    "最近 {} 条: 最小 {} / 平均 {} / 最大 {}",
+                                                    rolling.samples,
+                                                    format_latency(rolling.min),
+                                                    format_latency(rolling.mean),
+                                                    format_latency(rolling.max)
+                                                ),
+                                                None => format!("尚无样本（统计最近 {} 条）", RTT_ROLLING_WINDOW),
+                                            }),
+                                    )
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(gpui::rgb(0x6b7280))
+                                            .child(format!(
+                                                "共 {} 条，未应答 {}，未配对 {}",
+                                                rtt.histogram().count(),
+                                                rtt.pending(),
+                                                rtt.unmatched()
+                                            )),
+                                    )
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(gpui::rgb(0x9ca3af))
+                                            .child("从请求放入发送队列时开始计时，包含排队和发送整形的时间"),
+                                    )
+                                    .when(!rtt.histogram().is_empty(), |this| {
+                                        this.children(bins.into_iter().map(|(label, count)| {
+                                            div()
+                                                .flex()
+                                                .items_center()
+                                                .gap_1()
+                                                .child(
+                                                    div()
+                                                        .w_16()
+                                                        .text_xs()
+                                                        .text_color(gpui::rgb(0x6b7280))
+                                                        .child(label),
+                                                )
+                                                .child(
+                                                    div()
+                                                        .h(px(6.))
+                                                        .w(px(120. * count as f32 / max_count as f32))
+                                                        .rounded_sm()
+                                                        .bg(theme.primary),
+                                                )
+                                                .child(
+                                                    div()
+                                                        .text_xs()
+                                                        .text_color(gpui::rgb(0x9ca3af))
+                                                        .child(count.to_string()),
+                                                )
+                                        }))
+                                    })
+                                }),
+                        )
+                    })
+                    // 收发队列容量和满时策略（下次连接时生效），连接后显示队列深度和丢弃计数
+                    .child({
+                        let queue = self.tab_state.connection_config.queue();
+                        let metrics = &self.tab_state.queue_metrics;
+                        let (sent_depth, sent_bytes) = self.app.send_queue_depth(&tab_id);
+                        div()
+                            .flex()
+                            .flex_col()
+                            .gap_1()
+                            .child(
+                                div()
+                                    .flex()
+                                    .flex_wrap()
+                                    .items_center()
+                                    .gap_1()
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(gpui::rgb(0x6b7280))
+                                            .child("队列:"),
+                                    )
+                                    .children([QueuePolicy::Block, QueuePolicy::DropOldest, QueuePolicy::DropNewest].map(|policy| {
+                                        let selected = queue.policy == policy;
+                                        div()
+                                            .text_xs()
+                                            .px_1()
+                                            .rounded_md()
+                                            .cursor_pointer()
+                                            .when(selected, |this| this.bg(theme.primary).text_color(theme.background))
+                                            .when(!selected, |this| this.bg(theme.border).text_color(theme.foreground))
+                                            .child(policy.to_string())
+                                            .on_mouse_down(MouseButton::Left, cx.listener({
+                                                let tab_id_queue = tab_id.clone();
+                                                move |app, _event, window, cx| {
+                                                    app.set_queue(tab_id_queue.clone(), policy, false, window, cx);
+                                                }
+                                            }))
+                                    })),
                             )
                             .child(
                                 div()
-                                    .text_xs()
-                                    .font_medium()
-                                    .text_color(gpui::rgb(0x111827))
-                                    .child(self.tab_state.address()),
-                            ),
-                    )
-                    .child(
-                        div()
-                            .flex()
-                            .items_center()
-                            .gap_2()
-                            .child(
-                                div()
-                                    .text_xs()
-                                    .text_color(gpui::rgb(0x6b7280))
-                                    .child("状态:"),
+                                    .flex()
+                                    .items_center()
+                                    .gap_1()
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(gpui::rgb(0x6b7280))
+                                            .child("容量"),
+                                    )
+                                    .child(div().w_16().child(Input::new(&self.tab_state.queue_capacity_input)))
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .px_1()
+                                            .rounded_md()
+                                            .cursor_pointer()
+                                            .bg(theme.primary)
+                                            .text_color(theme.background)
+                                            .child("应用")
+                                            .on_mouse_down(MouseButton::Left, cx.listener({
+                                                let tab_id_apply = tab_id.clone();
+                                                move |app, _event, window, cx| {
+                                                    app.set_queue(tab_id_apply.clone(), queue.policy, true, window, cx);
+                                                }
+                                            })),
+                                    ),
                             )
                             .child(
                                 div()
                                     .text_xs()
-                                    .font_medium()
-                                    .when(self.tab_state.is_connected, |div| {
-                                        div.text_color(gpui::rgb(0x22c55e))
-                                    })
-                                    .when(!self.tab_state.is_connected, |div| {
-                                        div.text_color(gpui::rgb(0x9ca3af))
-                                    })
-                                    .child(format!("{}", self.tab_state.connection_status)),
-                            ),
-                    )
-                    // 只在TCP协议下显示解码器信息
-                    .when(self.tab_state.connection_config.protocol() == ConnectionType::Tcp, |div_builder| {
+                                    .text_color(gpui::rgb(0x9ca3af))
+                                    .child(if is_connected {
+                                        format!("{}（下次连接时生效）", queue)
+                                    } else {
+                                        queue.to_string()
+                                    }),
+                            )
+                            .when(is_connected, |this| {
+                                this.child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(gpui::rgb(0x6b7280))
+                                        .child(format!(
+                                            "发送队列: {} 条 / {} 字节，丢弃 {}，拒绝 {}",
+                                            sent_depth,
+                                            sent_bytes,
+                                            metrics.sent_dropped(),
+                                            metrics.sent_rejected()
+                                        )),
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(gpui::rgb(0x6b7280))
+                                        .child(format!(
+                                            "接收队列: {} 条，丢弃 {}",
+                                            metrics.received_depth(),
+                                            metrics.received_dropped()
+                                        )),
+                                )
+                            })
+                    })
+                    // 只在TCP、Unix流式套接字和串口下显示解码器信息
+                    .when(matches!(self.tab_state.connection_config.protocol(), ConnectionType::Tcp | ConnectionType::UnixStream | ConnectionType::Serial), |div_builder| {
                         div_builder.child(
                             div()
                                 .flex()
@@ -622,6 +1591,8 @@ impl<'a> ConnectionTab<'a> {
                                 )
                             }),
                     )
+                    // 原始数据录制开关
+                    .child(self.render_raw_capture_config(cx))
                     .child(
                         div()
                             .flex()
@@ -699,7 +1670,11 @@ impl<'a> ConnectionTab<'a> {
                             ),
                     ),
             )
-            .when(!is_client, |this| {
+            // 中继的暂停/放行控制（中继的消息不参与自动回复）
+            .when(is_relay, |this| {
+                this.child(self.render_relay_control(cx))
+            })
+            .when(!is_client && !is_relay, |this| {
                 this.child(self.render_auto_reply_config(window, cx))
             })
             // 连接相关错误信息显示
@@ -716,6 +1691,310 @@ impl<'a> ConnectionTab<'a> {
             })
     }
 
+    /// 渲染原始数据录制配置（解码前的原始字节写入二进制文件）
+    fn render_raw_capture_config(&self, cx: &mut Context<NetAssistantApp>) -> impl IntoElement {
+        let tab_id = self.tab_id.clone();
+        let is_server = self.tab_state.connection_config.is_server();
+        let capture_enabled = self.tab_state.raw_capture_enabled;
+
+        let checkbox = |checked: bool| {
+            div()
+                .w_4()
+                .h_4()
+                .border_1()
+                .border_color(gpui::rgb(0xd1d5db))
+                .rounded(px(4.))
+                .cursor_pointer()
+                .when(checked, |this| {
+                    this.bg(gpui::rgb(0x3b82f6))
+                        .flex()
+                        .items_center()
+                        .justify_center()
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(gpui::rgb(0xffffff))
+                                .font_bold()
+                                .child("✓"),
+                        )
+                })
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .mt_2()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        checkbox(capture_enabled)
+                            .on_mouse_down(MouseButton::Left, cx.listener({
+                                let tab_id_capture = tab_id.clone();
+                                move |app, _event, _window, cx| {
+                                    app.toggle_raw_capture(tab_id_capture.clone(), cx);
+                                }
+                            })),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(gpui::rgb(0x6b7280))
+                            .child("原始录制"),
+                    ),
+            )
+            // 录制选项：仅在未录制时可修改
+            .when(!capture_enabled, |this| {
+                this.child(
+                    div()
+                        .flex()
+                        .flex_wrap()
+                        .items_center()
+                        .gap_2()
+                        .pl_6()
+                        .child(
+                            checkbox(self.tab_state.raw_capture_include_sent)
+                                .on_mouse_down(MouseButton::Left, cx.listener({
+                                    let tab_id_sent = tab_id.clone();
+                                    move |app, _event, _window, cx| {
+                                        if let Some(tab_state) = app.connection_tabs.get_mut(&tab_id_sent) {
+                                            tab_state.raw_capture_include_sent = !tab_state.raw_capture_include_sent;
+                                            cx.notify();
+                                        }
+                                    }
+                                })),
+                        )
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(gpui::rgb(0x6b7280))
+                                .child("含发送"),
+                        )
+                        .when(is_server, |this| {
+                            this.child(
+                                checkbox(self.tab_state.raw_capture_per_client)
+                                    .on_mouse_down(MouseButton::Left, cx.listener({
+                                        let tab_id_per_client = tab_id.clone();
+                                        move |app, _event, _window, cx| {
+                                            if let Some(tab_state) = app.connection_tabs.get_mut(&tab_id_per_client) {
+                                                tab_state.raw_capture_per_client = !tab_state.raw_capture_per_client;
+                                                cx.notify();
+                                            }
+                                        }
+                                    })),
+                            )
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(gpui::rgb(0x6b7280))
+                                    .child("按客户端分文件"),
+                            )
+                        }),
+                )
+            })
+            // 录制文件名：可点击打开目录
+            .when_some(self.tab_state.raw_capture_name.clone(), |this, name| {
+                this.child(
+                    div()
+                        .cursor_pointer()
+                        .text_xs()
+                        .text_color(gpui::rgb(0x3b82f6))
+                        .hover(|style| style.text_color(gpui::rgb(0x2563eb)))
+                        .max_w(px(150.0))
+                        .overflow_x_hidden()
+                        .whitespace_nowrap()
+                        .child(format!("{}_*.bin", name))
+                        .on_mouse_down(MouseButton::Left, cx.listener({
+                            let tab_id_dir = tab_id.clone();
+                            move |app, _event, _window, _cx| {
+                                app.open_raw_capture_directory(tab_id_dir.clone());
+                            }
+                        })),
+                )
+            })
+    }
+
+    /// 渲染中继控制区域：暂停转发、逐条放行/丢弃/编辑暂存的数据
+    fn render_relay_control(&self, cx: &mut Context<NetAssistantApp>) -> impl IntoElement {
+        let tab_id = self.tab_id.clone();
+        let hold = &self.tab_state.relay_hold;
+        let paused = hold.paused;
+        let is_connected = self.tab_state.is_connected;
+
+        let button = |label: &'static str, color: u32| {
+            div()
+                .text_xs()
+                .px_1()
+                .py_0()
+                .rounded_md()
+                .cursor_pointer()
+                .text_color(gpui::rgb(0xffffff))
+                .bg(gpui::rgb(color))
+                .child(label)
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .mt_2()
+            .child(
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(gpui::rgb(0x6b7280))
+                            .child("中继:"),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(if paused { gpui::rgb(0xf59e0b) } else { gpui::rgb(0x111827) })
+                            .child(if paused { format!("已暂停，暂存 {} 条", hold.held_total) } else { "转发中".to_string() }),
+                    )
+                    .when(hold.undelivered > 0, |this| {
+                        this.child(
+                            div()
+                                .text_xs()
+                                .text_color(gpui::rgb(0xef4444))
+                                .child(format!("未能转发 {} 段", hold.undelivered)),
+                        )
+                    })
+                    .when(is_connected, |this| {
+                        this.child(
+                            button(if paused { "恢复" } else { "暂停" }, if paused { 0x22c55e } else { 0xf59e0b })
+                                .on_mouse_down(MouseButton::Left, cx.listener({
+                                    let tab_id_pause = tab_id.clone();
+                                    move |app, _event, _window, cx| {
+                                        app.set_relay_paused(tab_id_pause.clone(), !paused, cx);
+                                    }
+                                })),
+                        )
+                    })
+                    .when(hold.held_total > 0, |this| {
+                        this.child(
+                            button("全部放行", 0x3b82f6)
+                                .on_mouse_down(MouseButton::Left, cx.listener({
+                                    let tab_id_release = tab_id.clone();
+                                    move |app, _event, _window, cx| {
+                                        app.release_relay_held(tab_id_release.clone(), None, cx);
+                                    }
+                                })),
+                        )
+                        .child(
+                            button("全部丢弃", 0xef4444)
+                                .on_mouse_down(MouseButton::Left, cx.listener({
+                                    let tab_id_drop = tab_id.clone();
+                                    move |app, _event, _window, cx| {
+                                        app.drop_relay_held(tab_id_drop.clone(), None, cx);
+                                    }
+                                })),
+                        )
+                    }),
+            )
+            // 已启用的网络损伤
+            .children(match &self.tab_state.connection_config {
+                ConnectionConfig::Server(config) => [
+                    (RelayDirection::ToUpstream, &config.relay.to_upstream),
+                    (RelayDirection::ToClient, &config.relay.to_client),
+                ]
+                .into_iter()
+                .filter(|(_, impairment)| impairment.is_enabled())
+                .map(|(direction, impairment)| {
+                    div()
+                        .text_xs()
+                        .text_color(gpui::rgb(0xf59e0b))
+                        .child(format!("损伤 {}: {}", direction, impairment.summary()))
+                })
+                .collect::<Vec<_>>(),
+                ConnectionConfig::Client(_) => Vec::new(),
+            })
+            // 暂存的数据按到达顺序列出
+            .children(hold.held.iter().map(|chunk| {
+                let preview = match &chunk.data {
+                    Some(data) => {
+                        let hex = data.iter().take(16).map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ");
+                        if data.len() > 16 {
+                            format!("{} 字节: {} …", data.len(), hex)
+                        } else {
+                            format!("{} 字节: {}", data.len(), hex)
+                        }
+                    }
+                    None => "关闭写方向 (FIN)".to_string(),
+                };
+                let id = chunk.id;
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_1()
+                    .pl_2()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(gpui::rgb(0x111827))
+                            .child(format!("#{} {} {}", id, chunk.client, chunk.direction)),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(gpui::rgb(0x6b7280))
+                            .overflow_x_hidden()
+                            .whitespace_nowrap()
+                            .child(preview),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .gap_1()
+                            .child(
+                                button("放行", 0x3b82f6)
+                                    .on_mouse_down(MouseButton::Left, cx.listener({
+                                        let tab_id_release = tab_id.clone();
+                                        move |app, _event, _window, cx| {
+                                            app.release_relay_held(tab_id_release.clone(), Some(id), cx);
+                                        }
+                                    })),
+                            )
+                            .child(
+                                button("丢弃", 0xef4444)
+                                    .on_mouse_down(MouseButton::Left, cx.listener({
+                                        let tab_id_drop = tab_id.clone();
+                                        move |app, _event, _window, cx| {
+                                            app.drop_relay_held(tab_id_drop.clone(), Some(id), cx);
+                                        }
+                                    })),
+                            )
+                            .when(chunk.data.is_some(), |this| {
+                                this.child(
+                                    button("编辑", 0x6b7280)
+                                        .on_mouse_down(MouseButton::Left, cx.listener({
+                                            let tab_id_edit = tab_id.clone();
+                                            let chunk = chunk.clone();
+                                            move |app, _event, window, cx| {
+                                                app.edit_relay_held(tab_id_edit.clone(), chunk.clone(), window, cx);
+                                            }
+                                        })),
+                                )
+                            }),
+                    )
+            }))
+            .when(hold.held_total > hold.held.len(), |this| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(gpui::rgb(0x9ca3af))
+                        .child(format!("另有 {} 条未显示", hold.held_total - hold.held.len())),
+                )
+            })
+    }
+
     /// 渲染自动回复配置区域
     fn render_auto_reply_config(
         &self,
@@ -727,7 +2006,19 @@ impl<'a> ConnectionTab<'a> {
         let tab_id_for_toggle = tab_id.clone();
         let auto_reply_enabled = self.tab_state.auto_reply_enabled;
         let is_connected = self.tab_state.is_connected;
-        let is_udp_server = self.tab_state.connection_config.protocol() == crate::config::connection::ConnectionType::Udp;
+        let protocol = self.tab_state.connection_config.protocol();
+        let is_udp_server = protocol == ConnectionType::Udp;
+        // 数据报服务端的客户端只能从列表移除，没有连接可断开
+        let is_datagram_server = is_udp_server || protocol == ConnectionType::UnixDatagram;
+        let is_unix_server = protocol == ConnectionType::UnixStream;
+        let client_count_label = match &self.tab_state.connection_config {
+            ConnectionConfig::Server(config) if !is_datagram_server && config.max_connections > 0 => format!(
+                "客户端连接 ({}/{})",
+                self.tab_state.client_connections.len(),
+                config.max_connections
+            ),
+            _ => "客户端连接".to_string(),
+        };
 
         div()
             .flex()
@@ -828,7 +2119,7 @@ impl<'a> ConnectionTab<'a> {
                                     .text_xs()
                                     .font_semibold()
                                     .text_color(theme.foreground)
-                                    .child("客户端连接"),
+                                    .child(client_count_label),
                             )
                             // 添加客户端按钮（仅UDP服务端显示）
                             .when(is_udp_server && is_connected, |this| {
@@ -929,7 +2220,73 @@ impl<'a> ConnectionTab<'a> {
                                                                 div()
                                                                     .text_xs()
                                                                     .text_color(theme.foreground)
-                                                                    .child(addr.to_string()),
+                                                                    .child(match self.tab_state.client_labels.get(addr) {
+                                                                        Some(label) => label.clone(),
+                                                                        None => addr.to_string(),
+                                                                    }),
+                                                            )
+                                                            // 客户端连接到的本地端点
+                                                            .when_some(self.tab_state.client_local_addresses.get(addr), |this, local_addr| {
+                                                                this.child(
+                                                                    div()
+                                                                        .text_xs()
+                                                                        .text_color(theme.muted_foreground)
+                                                                        .child(format!("→ {}", local_addr)),
+                                                                )
+                                                            })
+                                                            // 单个客户端的断开操作：TCP 可选 FIN 或 RST，数据报仅移除
+                                                            .child(
+                                                                div()
+                                                                    .ml_auto()
+                                                                    .flex()
+                                                                    .gap_1()
+                                                                    .when(!is_datagram_server, |this| {
+                                                                        let tab_id_fin = tab_id.clone();
+                                                                        this.child(
+                                                                            div()
+                                                                                .id(SharedString::from(format!("client-fin-{}", addr)))
+                                                                                .px_1()
+                                                                                .rounded_sm()
+                                                                                .text_xs()
+                                                                                .text_color(theme.muted_foreground)
+                                                                                .cursor_pointer()
+                                                                                .hover(|style| style.text_color(theme.foreground))
+                                                                                .tooltip(|window, cx| {
+                                                                                    Tooltip::new("断开（发送 FIN）").build(window, cx)
+                                                                                })
+                                                                                .child("FIN")
+                                                                                .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                                                    cx.stop_propagation();
+                                                                                    app.close_server_client(tab_id_fin.clone(), addr_clone, ClientCloseMode::Graceful, cx);
+                                                                                })),
+                                                                        )
+                                                                    })
+                                                                    .child({
+                                                                        let tab_id_rst = tab_id.clone();
+                                                                        div()
+                                                                            .id(SharedString::from(format!("client-rst-{}", addr)))
+                                                                            .px_1()
+                                                                            .rounded_sm()
+                                                                            .text_xs()
+                                                                            .text_color(gpui::rgb(0xef4444))
+                                                                            .cursor_pointer()
+                                                                            .hover(|style| style.opacity(0.7))
+                                                                            .tooltip(move |window, cx| {
+                                                                                Tooltip::new(if is_datagram_server {
+                                                                                    "从列表中移除"
+                                                                                } else if is_unix_server {
+                                                                                    "强制断开（不等待对端关闭）"
+                                                                                } else {
+                                                                                    "强制断开（发送 RST）"
+                                                                                })
+                                                                                .build(window, cx)
+                                                                            })
+                                                                            .child(if is_datagram_server { "移除" } else { "RST" })
+                                                                            .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                                                cx.stop_propagation();
+                                                                                app.close_server_client(tab_id_rst.clone(), addr_clone, ClientCloseMode::Abort, cx);
+                                                                            }))
+                                                                    }),
                                                             )
                                                     })
                                                 )
@@ -1180,7 +2537,46 @@ impl<'a> ConnectionTab<'a> {
                                                                     this_div
                                                                 }
                                                             },
-                                                        ),
+                                                        )
+                                                        .when(message.truncated, |this_div| {
+                                                            this_div.child(
+                                                                div()
+                                                                    .id(ElementId::named_usize("truncated", ix))
+                                                                    .text_xs()
+                                                                    .text_color(gpui::rgb(0xf59e0b))
+                                                                    .child("[已截断]")
+                                                                    .tooltip(|window, cx| {
+                                                                        Tooltip::new("数据报超过接收缓冲区大小，超出部分已丢弃").build(window, cx)
+                                                                    }),
+                                                            )
+                                                        })
+                                                        // MQTT消息的主题、QoS和保留标志
+                                                        .when_some(message.mqtt.as_ref(), |this_div, mqtt| {
+                                                            this_div.child(
+                                                                div()
+                                                                    .text_xs()
+                                                                    .text_color(gpui::rgb(0x8b5cf6))
+                                                                    .child(mqtt.to_string()),
+                                                            )
+                                                        })
+                                                        // 中继消息的转发方向、上游地址和注入标记
+                                                        .when_some(message.relay.as_ref(), |this_div, relay| {
+                                                            this_div.child(
+                                                                div()
+                                                                    .text_xs()
+                                                                    .text_color(if relay.injected { gpui::rgb(0xf59e0b) } else { gpui::rgb(0x8b5cf6) })
+                                                                    .child(format!("{} {}{}", relay.direction, relay.upstream, if relay.injected { " [注入]" } else { "" })),
+                                                            )
+                                                        })
+                                                        // 响应的往返时间
+                                                        .when_some(message.rtt, |this_div, rtt| {
+                                                            this_div.child(
+                                                                div()
+                                                                    .text_xs()
+                                                                    .text_color(gpui::rgb(0x10b981))
+                                                                    .child(format!("RTT {}", format_latency(rtt))),
+                                                            )
+                                                        }),
                                                 )
                                                 .child(
                                                     div()
@@ -1334,6 +2730,112 @@ impl<'a> ConnectionTab<'a> {
                         .child(target_text),
                 )
             })
+            // 中继注入方向：发往上游或发往客户端
+            .when(self.tab_state.connection_config.protocol() == ConnectionType::Relay, |el| {
+                let inject_direction = self.tab_state.relay_inject_direction;
+                el.child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(theme.muted_foreground)
+                                .child("注入方向"),
+                        )
+                        .children(RelayDirection::ALL.map(|direction| {
+                            let tab_id = tab_id.clone();
+                            div()
+                                .px_2()
+                                .py_1()
+                                .rounded_md()
+                                .cursor_pointer()
+                                .text_xs()
+                                .when(inject_direction == direction, |div| {
+                                    div.bg(theme.primary)
+                                        .text_color(theme.background)
+                                })
+                                .when(inject_direction != direction, |div| {
+                                    div.bg(theme.border)
+                                        .text_color(theme.foreground)
+                                })
+                                .child(direction.to_string())
+                                .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                    if let Some(tab_state) = app.connection_tabs.get_mut(&tab_id) {
+                                        tab_state.relay_inject_direction = direction;
+                                        cx.notify();
+                                    }
+                                }))
+                        })),
+                )
+            })
+            // MQTT发布设置：主题、QoS和保留标志
+            .when_some(self.tab_state.mqtt_topic_input.as_ref(), |el, topic_input| {
+                let publish_qos = self.tab_state.mqtt_publish_qos;
+                let publish_retain = self.tab_state.mqtt_publish_retain;
+                el.child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(theme.muted_foreground)
+                                .child("主题"),
+                        )
+                        .child(div().flex_1().child(Input::new(topic_input)))
+                        .children(MqttQos::ALL.map(|qos| {
+                            let tab_id = tab_id.clone();
+                            div()
+                                .px_2()
+                                .py_1()
+                                .rounded_md()
+                                .cursor_pointer()
+                                .text_xs()
+                                .when(publish_qos == qos, |div| {
+                                    div.bg(theme.primary)
+                                        .text_color(theme.background)
+                                })
+                                .when(publish_qos != qos, |div| {
+                                    div.bg(theme.border)
+                                        .text_color(theme.foreground)
+                                })
+                                .child(qos.to_string())
+                                .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                    if let Some(tab_state) = app.connection_tabs.get_mut(&tab_id) {
+                                        tab_state.mqtt_publish_qos = qos;
+                                        cx.notify();
+                                    }
+                                }))
+                        }))
+                        .child({
+                            let tab_id = tab_id.clone();
+                            div()
+                                .px_2()
+                                .py_1()
+                                .rounded_md()
+                                .cursor_pointer()
+                                .text_xs()
+                                .when(publish_retain, |div| {
+                                    div.bg(theme.primary)
+                                        .text_color(theme.background)
+                                })
+                                .when(!publish_retain, |div| {
+                                    div.bg(theme.border)
+                                        .text_color(theme.foreground)
+                                })
+                                .child("保留")
+                                .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                    if let Some(tab_state) = app.connection_tabs.get_mut(&tab_id) {
+                                        tab_state.mqtt_publish_retain = !tab_state.mqtt_publish_retain;
+                                        cx.notify();
+                                    }
+                                }))
+                        }),
+                )
+            })
             .child(
                 div()
                     .flex_1()
@@ -1609,6 +3111,12 @@ impl<'a> ConnectionTab<'a> {
                                                 periodic_send_enabled = tab_state.periodic_send_enabled;
                                                 connection_config = Some(tab_state.connection_config.clone());
 
+                                                // MQTT发布主题取自主题输入框
+                                                let topic = tab_state.mqtt_topic_input.as_ref().map(|input| input.read(cx).text().to_string().trim().to_string());
+                                                if let Some(topic) = topic {
+                                                    tab_state.mqtt_publish_topic = topic;
+                                                }
+
                                                 // 在发送前再次验证十六进制输入是否有效
                                                 let is_hex_valid = if message_input_mode == "hex" {
                                                     let content = message_input.read(cx).text().to_string();
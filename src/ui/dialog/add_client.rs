@@ -3,6 +3,7 @@ use gpui::*;
 use gpui_component::{ActiveTheme, StyledExt, input::{Input, InputState}};
 
 use crate::app::NetAssistantApp;
+use crate::network::resolve;
 
 pub struct AddClientDialog {
     input: Entity<InputState>,
@@ -22,8 +23,8 @@ impl AddClientDialog {
         if !addr.contains(':') {
             return Err("格式错误，需要 IP:端口（如 192.168.1.100:8080）");
         }
-        if addr.parse::<std::net::SocketAddr>().is_err() {
-            return Err("无效的地址格式，需要 IP:端口（如 192.168.1.100:8080）");
+        if resolve::parse_socket_addr(addr).is_err() {
+            return Err("无效的地址格式，需要 IP:端口（如 192.168.1.100:8080 或 [fe80::1%eth0]:8080）");
         }
         Ok(())
     }
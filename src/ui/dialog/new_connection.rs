@@ -1,11 +1,15 @@
 use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::StyledExt;
-use gpui_component::input::Input;
+use gpui_component::input::{Input, InputState};
 use gpui_component::ActiveTheme as _;
 
 use crate::app::NetAssistantApp;
-use crate::config::connection::{ConnectionConfig, ConnectionType};
+use crate::network::resolve;
+use crate::network::tls;
+use log::error;
+use crate::config::connection::{ConnectionConfig, ConnectionType, LoadTestConfig, LoadTestTransport, MaxConnectionsPolicy, MqttConfig, MqttVersion, MulticastConfig, ProxyConfig, ProxyKind, RelayConfig, RelayTransport, SerialConfig, SerialFlowControl, SerialParity, ServerConfig, TcpSocketOptions, TlsConfig, UnixSocketConfig, WebSocketConfig, WebSocketSendFrame, MAX_UDP_RECV_BUFFER_SIZE};
+use crate::network::protocol::serial::SerialLine;
 
 pub struct NewConnectionDialog<'a> {
     app: &'a NetAssistantApp,
@@ -22,6 +26,11 @@ impl<'a> NewConnectionDialog<'a> {
         cx: &mut Context<NetAssistantApp>,
     ) -> impl IntoElement {
         let theme = cx.theme().clone();
+        // TCP和WebSocket服务端可同时监听多个地址
+        let multi_address_server = !self.app.new_connection_is_client
+            && matches!(self.app.new_connection_protocol.as_str(), "TCP" | "WebSocket");
+        // UDP服务端只有一个套接字，不支持多地址监听
+        let udp_server = !self.app.new_connection_is_client && self.app.new_connection_protocol == "UDP";
         div()
             .absolute()
             .inset_0()
@@ -49,42 +58,135 @@ impl<'a> NewConnectionDialog<'a> {
                             .flex()
                             .flex_col()
                             .gap_4()
-                            .child(
-                                div()
-                                    .flex()
-                                    .flex_col()
-                                    .gap_1()
-                                    .child(
-                                        div()
-                                            .text_sm()
-                                            .font_semibold()
-                                            .text_color(theme.foreground)
-                                            .child("主机地址"),
-                                    )
-                                    .child(Input::new(&self.app.host_input))
-                                    .when(!self.app.new_connection_is_client, |this| {
-                                        this.child(
+                            // 主机与端口（串口和Unix域套接字使用路径，见下方各自的设置）
+                            .when(!matches!(self.app.new_connection_protocol.as_str(), "Serial" | "Unix" | "Unix DGRAM"), |this| {
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("主机地址"),
+                                        )
+                                        .child(Input::new(&self.app.host_input))
+                                        .when(!self.app.new_connection_is_client, |this| {
+                                            this.child(
+                                                div()
+                                                    .text_xs()
+                                                    .text_color(gpui::rgb(0x9ca3af))
+                                                    .child("IPv4: 127.0.0.1(本地) | 0.0.0.0(全局) | IPv6: ::1(本地) | ::(全局)"),
+                                            )
+                                        })
+                                        .when(multi_address_server, |this| {
+                                            this.child(
+                                                div()
+                                                    .text_xs()
+                                                    .text_color(gpui::rgb(0x9ca3af))
+                                                    .child("可同时监听多个地址，以逗号分隔，如 192.168.1.10, ::"),
+                                            )
+                                        })
+                                        .when(udp_server, |this| {
+                                            this.child(
+                                                div()
+                                                    .text_xs()
+                                                    .text_color(gpui::rgb(0x9ca3af))
+                                                    .child("UDP服务端只能监听一个地址，同时接收IPv4和IPv6请分别新建服务端"),
+                                            )
+                                        })
+                                        .when(
+                                            !self.app.new_connection_is_client && !multi_address_server && self.app.host_input.read(cx).value().contains(','),
+                                            |this| {
+                                                this.child(
+                                                    div()
+                                                        .text_xs()
+                                                        .text_color(gpui::rgb(0xef4444))
+                                                        .child(format!("{} 服务端只能监听一个地址", self.app.new_connection_protocol)),
+                                                )
+                                            },
+                                        )
+                                        .child(
                                             div()
                                                 .text_xs()
                                                 .text_color(gpui::rgb(0x9ca3af))
-                                                .child("IPv4: 127.0.0.1(本地) | 0.0.0.0(全局) | IPv6: ::1(本地) | ::(全局)"),
+                                                .child("IPv6链路本地地址需指定区域（接口名称或索引）: fe80::1%eth0 | fe80::1%2"),
                                         )
-                                    }),
-                            )
-                            .child(
-                                div()
-                                    .flex()
-                                    .flex_col()
-                                    .gap_1()
-                                    .child(
-                                        div()
-                                            .text_sm()
-                                            .font_semibold()
-                                            .text_color(theme.foreground)
-                                            .child("端口"),
-                                    )
-                                    .child(Input::new(&self.app.port_input)),
-                            )
+                                        // 带区域的IPv6地址在此校验区域是否有效
+                                        .when_some(
+                                            self.app.host_input.read(cx).value()
+                                                .split(',')
+                                                .find_map(|host| resolve::parse_ip_literal(host, 0).and_then(Result::err)),
+                                            |this, error| {
+                                                this.child(
+                                                    div()
+                                                        .text_xs()
+                                                        .text_color(gpui::rgb(0xef4444))
+                                                        .child(error),
+                                                )
+                                            },
+                                        )
+                                        // 双栈监听（TCP和WebSocket服务端）
+                                        .when(multi_address_server, |this| {
+                                            let dual_stack = self.app.new_connection_dual_stack;
+                                            this.child(
+                                                div()
+                                                    .text_xs()
+                                                    .flex()
+                                                    .items_center()
+                                                    .gap_2()
+                                                    .child(
+                                                        div()
+                                                            .w_4()
+                                                            .h_4()
+                                                            .border_1()
+                                                            .border_color(gpui::rgb(0xd1d5db))
+                                                            .rounded(px(4.))
+                                                            .cursor_pointer()
+                                                            .when(dual_stack, |this| {
+                                                                this.bg(gpui::rgb(0x3b82f6))
+                                                                    .flex()
+                                                                    .items_center()
+                                                                    .justify_center()
+                                                                    .child(
+                                                                        div()
+                                                                            .text_xs()
+                                                                            .text_color(gpui::rgb(0xffffff))
+                                                                            .font_bold()
+                                                                            .child("✓"),
+                                                                    )
+                                                            })
+                                                            .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                                app.new_connection_dual_stack = !app.new_connection_dual_stack;
+                                                                cx.notify();
+                                                            })),
+                                                    )
+                                                    .child(
+                                                        div()
+                                                            .text_xs()
+                                                            .text_color(theme.foreground)
+                                                            .child("双栈：IPv6地址（如 ::）同时接受IPv4客户端"),
+                                                    ),
+                                            )
+                                        }),
+                                )
+                                .child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("端口"),
+                                        )
+                                        .child(Input::new(&self.app.port_input)),
+                                )
+                            })
                             .child(
                                 div()
                                     .flex()
@@ -100,17 +202,19 @@ impl<'a> NewConnectionDialog<'a> {
                                     .child(
                                         div()
                                             .flex()
+                                            .flex_wrap()
                                             .gap_2()
-                                            .child(
+                                            // 串口、MQTT和压力测试仅支持客户端，中继仅支持服务端
+                                            .children(["TCP", "UDP", "WebSocket", "Serial", "Unix", "Unix DGRAM", "MQTT", "Relay", "Load Test"].into_iter().filter(|protocol| if self.app.new_connection_is_client { *protocol != "Relay" } else { !matches!(*protocol, "Serial" | "MQTT" | "Load Test") }).map(|protocol| {
                                                 div()
                                                     .px_3()
                                                     .py_1()
                                                     .cursor_pointer()
-                                                    .when(self.app.new_connection_protocol == "TCP", |div| {
+                                                    .when(self.app.new_connection_protocol == protocol, |div| {
                                                         div.bg(theme.primary)
                                                             .text_color(theme.background)
                                                     })
-                                                    .when(self.app.new_connection_protocol != "TCP", |div| {
+                                                    .when(self.app.new_connection_protocol != protocol, |div| {
                                                         div.bg(theme.border)
                                                             .text_color(theme.foreground)
                                                     })
@@ -119,40 +223,1273 @@ impl<'a> NewConnectionDialog<'a> {
                                                         div()
                                                             .text_sm()
                                                             .font_medium()
-                                                            .child("TCP"),
+                                                            .child(protocol),
                                                     )
-                                                    .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
-                                                        app.new_connection_protocol = String::from("TCP");
+                                                    .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                        app.new_connection_protocol = String::from(protocol);
                                                         cx.notify();
-                                                    })),
+                                                    }))
+                                            })),
+                                    ),
+                            )
+                            // 串口设置（仅串口客户端）
+                            .when(self.app.new_connection_protocol == "Serial", |this| {
+                                let serial = self.app.new_connection_serial.clone();
+                                let field = |label: &'static str, input: &Entity<InputState>| {
+                                    div()
+                                        .flex_1()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child(label),
+                                        )
+                                        .child(Input::new(input))
+                                };
+                                let row = |label: &'static str| {
+                                    div()
+                                        .flex()
+                                        .items_center()
+                                        .gap_2()
+                                        .child(
+                                            div()
+                                                .w_12()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child(label),
+                                        )
+                                };
+                                let option = |label: String, selected: bool| {
+                                    div()
+                                        .px_2()
+                                        .py_1()
+                                        .cursor_pointer()
+                                        .rounded_md()
+                                        .when(selected, |div| {
+                                            div.bg(theme.primary)
+                                                .text_color(theme.background)
+                                        })
+                                        .when(!selected, |div| {
+                                            div.bg(theme.border)
+                                                .text_color(theme.foreground)
+                                        })
+                                        .child(div().text_xs().child(label))
+                                };
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("串口"),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .gap_2()
+                                                .child(field("设备路径", &self.app.serial_path_input))
+                                                .child(div().w_24().child(field("波特率", &self.app.serial_baud_input))),
+                                        )
+                                        .child(row("数据位").children([5u8, 6, 7, 8].map(|bits| {
+                                            option(bits.to_string(), serial.data_bits == bits)
+                                                .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                    app.new_connection_serial.data_bits = bits;
+                                                    cx.notify();
+                                                }))
+                                        })))
+                                        .child(row("校验位").children([SerialParity::None, SerialParity::Odd, SerialParity::Even].map(|parity| {
+                                            option(parity.to_string(), serial.parity == parity)
+                                                .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                    app.new_connection_serial.parity = parity;
+                                                    cx.notify();
+                                                }))
+                                        })))
+                                        .child(row("停止位").children([1u8, 2].map(|bits| {
+                                            option(bits.to_string(), serial.stop_bits == bits)
+                                                .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                    app.new_connection_serial.stop_bits = bits;
+                                                    cx.notify();
+                                                }))
+                                        })))
+                                        .child(row("流控").children([SerialFlowControl::None, SerialFlowControl::Software, SerialFlowControl::Hardware].map(|flow_control| {
+                                            option(flow_control.to_string(), serial.flow_control == flow_control)
+                                                .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                    app.new_connection_serial.flow_control = flow_control;
+                                                    cx.notify();
+                                                }))
+                                        })))
+                                        // 打开串口后控制线的初始电平
+                                        .child(row("控制线").children([SerialLine::Dtr, SerialLine::Rts].map(|line| {
+                                            let level = match line {
+                                                SerialLine::Dtr => serial.dtr,
+                                                SerialLine::Rts => serial.rts,
+                                            };
+                                            option(format!("{} {}", line, if level { "高" } else { "低" }), level)
+                                                .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                    match line {
+                                                        SerialLine::Dtr => app.new_connection_serial.dtr = !level,
+                                                        SerialLine::Rts => app.new_connection_serial.rts = !level,
+                                                    }
+                                                    cx.notify();
+                                                }))
+                                        }))),
+                                )
+                            })
+                            // MQTT设置（仅MQTT客户端）
+                            .when(self.app.new_connection_protocol == "MQTT", |this| {
+                                let mqtt = self.app.new_connection_mqtt.clone();
+                                let field = |label: &'static str, input: &Entity<InputState>| {
+                                    div()
+                                        .flex_1()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child(label),
+                                        )
+                                        .child(Input::new(input))
+                                };
+                                let option = |label: String, selected: bool| {
+                                    div()
+                                        .px_2()
+                                        .py_1()
+                                        .cursor_pointer()
+                                        .rounded_md()
+                                        .when(selected, |div| {
+                                            div.bg(theme.primary)
+                                                .text_color(theme.background)
+                                        })
+                                        .when(!selected, |div| {
+                                            div.bg(theme.border)
+                                                .text_color(theme.foreground)
+                                        })
+                                        .child(div().text_xs().child(label))
+                                };
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("MQTT"),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .items_center()
+                                                .gap_2()
+                                                .child(
+                                                    div()
+                                                        .text_xs()
+                                                        .text_color(gpui::rgb(0x9ca3af))
+                                                        .child("协议版本"),
+                                                )
+                                                .children([MqttVersion::V311, MqttVersion::V5].map(|version| {
+                                                    option(version.to_string(), mqtt.version == version)
+                                                        .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                            app.new_connection_mqtt.version = version;
+                                                            cx.notify();
+                                                        }))
+                                                }))
+                                                .child(
+                                                    option("清除会话".to_string(), mqtt.clean_session)
+                                                        .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                            app.new_connection_mqtt.clean_session = !app.new_connection_mqtt.clean_session;
+                                                            cx.notify();
+                                                        })),
+                                                ),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .gap_2()
+                                                .child(field("客户端ID", &self.app.mqtt_client_id_input))
+                                                .child(div().w_24().child(field("心跳（秒）", &self.app.mqtt_keep_alive_input))),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .gap_2()
+                                                .child(field("用户名", &self.app.mqtt_username_input))
+                                                .child(field("密码", &self.app.mqtt_password_input)),
+                                        )
+                                        .child(field("订阅（主题过滤器:QoS，逗号分隔）", &self.app.mqtt_subscriptions_input))
+                                        .child(field("默认发布主题", &self.app.mqtt_publish_topic_input)),
+                                )
+                            })
+                            // 压力测试设置（仅客户端）：目标为上方的主机和端口
+                            .when(self.app.new_connection_protocol == "Load Test", |this| {
+                                let load_test = self.app.new_connection_load_test.clone();
+                                let field = |label: &'static str, input: &Entity<InputState>| {
+                                    div()
+                                        .flex_1()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child(label),
+                                        )
+                                        .child(Input::new(input))
+                                };
+                                let option = |label: String, selected: bool| {
+                                    div()
+                                        .px_2()
+                                        .py_1()
+                                        .cursor_pointer()
+                                        .rounded_md()
+                                        .when(selected, |div| {
+                                            div.bg(theme.primary)
+                                                .text_color(theme.background)
+                                        })
+                                        .when(!selected, |div| {
+                                            div.bg(theme.border)
+                                                .text_color(theme.foreground)
+                                        })
+                                        .child(div().text_xs().child(label))
+                                };
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("压力测试"),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .items_center()
+                                                .gap_2()
+                                                .child(
+                                                    div()
+                                                        .text_xs()
+                                                        .text_color(gpui::rgb(0x9ca3af))
+                                                        .child("传输协议"),
+                                                )
+                                                .children([LoadTestTransport::Tcp, LoadTestTransport::Udp].map(|transport| {
+                                                    option(transport.to_string(), load_test.transport == transport)
+                                                        .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                            app.new_connection_load_test.transport = transport;
+                                                            cx.notify();
+                                                        }))
+                                                }))
+                                                .child(
+                                                    option("十六进制模板".to_string(), load_test.payload_hex)
+                                                        .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                            app.new_connection_load_test.payload_hex = !app.new_connection_load_test.payload_hex;
+                                                            cx.notify();
+                                                        })),
+                                                ),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .gap_2()
+                                                .child(field("客户端数", &self.app.load_test_clients_input))
+                                                .child(field("每秒新建", &self.app.load_test_ramp_up_input)),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .gap_2()
+                                                .child(field("每客户端条/秒", &self.app.load_test_send_rate_input))
+                                                .child(field("时长（秒）", &self.app.load_test_duration_input)),
+                                        )
+                                        .child(field("发送模板", &self.app.load_test_payload_input))
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child(if load_test.payload_hex {
+                                                    "{client} 和 {seq} 替换为 4 字节大端的客户端序号和消息序号"
+                                                } else {
+                                                    "{client} 替换为客户端序号，{seq} 替换为消息序号，支持 \\n \\r \\t"
+                                                }),
+                                        )
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child("响应按标签页的解码器切分，依次与最早未应答的请求配对计算延迟"),
+                                        ),
+                                )
+                            })
+                            // 代理设置（仅TCP、UDP和WebSocket客户端）
+                            .when(self.app.new_connection_is_client && matches!(self.app.new_connection_protocol.as_str(), "TCP" | "UDP" | "WebSocket"), |this| {
+                                let proxy = self.app.new_connection_proxy.clone();
+                                let is_udp = self.app.new_connection_protocol == "UDP";
+                                let field = |label: &'static str, input: &Entity<InputState>| {
+                                    div()
+                                        .flex_1()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child(label),
+                                        )
+                                        .child(Input::new(input))
+                                };
+                                let option = |label: String, selected: bool| {
+                                    div()
+                                        .px_2()
+                                        .py_1()
+                                        .cursor_pointer()
+                                        .rounded_md()
+                                        .when(selected, |div| {
+                                            div.bg(theme.primary)
+                                                .text_color(theme.background)
+                                        })
+                                        .when(!selected, |div| {
+                                            div.bg(theme.border)
+                                                .text_color(theme.foreground)
+                                        })
+                                        .child(div().text_xs().child(label))
+                                };
+                                // UDP只能经 SOCKS5 转发
+                                let kinds: &[ProxyKind] = if is_udp {
+                                    &[ProxyKind::None, ProxyKind::Socks5]
+                                } else {
+                                    &[ProxyKind::None, ProxyKind::Socks5, ProxyKind::HttpConnect]
+                                };
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("代理"),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .items_center()
+                                                .gap_2()
+                                                .children(kinds.iter().map(|&kind| {
+                                                    option(kind.to_string(), proxy.kind == kind)
+                                                        .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                            app.new_connection_proxy.kind = kind;
+                                                            cx.notify();
+                                                        }))
+                                                }))
+                                                .when(proxy.kind == ProxyKind::Socks5, |this| {
+                                                    this.child(
+                                                        option("远程DNS".to_string(), proxy.remote_dns)
+                                                            .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                                app.new_connection_proxy.remote_dns = !app.new_connection_proxy.remote_dns;
+                                                                cx.notify();
+                                                            })),
+                                                    )
+                                                }),
+                                        )
+                                        .when(proxy.is_enabled(), |this| {
+                                            this.child(
+                                                div()
+                                                    .flex()
+                                                    .gap_2()
+                                                    .child(field("代理地址", &self.app.proxy_address_input))
+                                                    .child(div().w_24().child(field("代理端口", &self.app.proxy_port_input))),
                                             )
                                             .child(
                                                 div()
-                                                    .px_3()
-                                                    .py_1()
-                                                    .cursor_pointer()
-                                                    .when(self.app.new_connection_protocol == "UDP", |div| {
-                                                        div.bg(theme.primary)
-                                                            .text_color(theme.background)
-                                                    })
-                                                    .when(self.app.new_connection_protocol != "UDP", |div| {
-                                                        div.bg(theme.border)
-                                                            .text_color(theme.foreground)
-                                                    })
-                                                    .rounded_md()
+                                                    .flex()
+                                                    .gap_2()
+                                                    .child(field("用户名", &self.app.proxy_username_input))
+                                                    .child(field("密码", &self.app.proxy_password_input)),
+                                            )
+                                        })
+                                        .when(is_udp && proxy.kind == ProxyKind::HttpConnect, |this| {
+                                            this.child(
+                                                div()
+                                                    .text_xs()
+                                                    .text_color(gpui::rgb(0x9ca3af))
+                                                    .child("UDP 仅支持 SOCKS5 代理"),
+                                            )
+                                        }),
+                                )
+                            })
+                            // Unix域套接字设置：服务端可设置文件权限，数据报客户端可指定本地路径
+                            .when(matches!(self.app.new_connection_protocol.as_str(), "Unix" | "Unix DGRAM"), |this| {
+                                let is_client = self.app.new_connection_is_client;
+                                let is_datagram = self.app.new_connection_protocol == "Unix DGRAM";
+                                let field = |label: &'static str, input: &Entity<InputState>| {
+                                    div()
+                                        .flex_1()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child(label),
+                                        )
+                                        .child(Input::new(input))
+                                };
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("Unix域套接字"),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .gap_2()
+                                                .child(field(if is_client { "连接路径" } else { "监听路径" }, &self.app.unix_path_input))
+                                                .when(!is_client, |this| {
+                                                    this.child(div().w_32().child(field("文件权限（八进制）", &self.app.unix_permissions_input)))
+                                                }),
+                                        )
+                                        .when(is_client && is_datagram, |this| {
+                                            this.child(field("本地路径（服务端据此回复）", &self.app.unix_local_path_input))
+                                        })
+                                        .when(!is_client, |this| {
+                                            this.child(
+                                                div()
+                                                    .text_xs()
+                                                    .text_color(gpui::rgb(0x9ca3af))
+                                                    .child("无进程监听的残留套接字文件会在启动时删除，停止时删除套接字文件"),
+                                            )
+                                        }),
+                                )
+                            })
+                            // 中继设置：转发协议与上游地址
+                            .when(self.app.new_connection_protocol == "Relay", |this| {
+                                let transport = self.app.new_connection_relay.transport;
+                                let field = |label: &'static str, input: &Entity<InputState>| {
+                                    div()
+                                        .flex_1()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child(label),
+                                        )
+                                        .child(Input::new(input))
+                                };
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("中继"),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .items_center()
+                                                .gap_2()
+                                                .child(
+                                                    div()
+                                                        .text_xs()
+                                                        .text_color(gpui::rgb(0x9ca3af))
+                                                        .child("转发协议"),
+                                                )
+                                                .children([RelayTransport::Tcp, RelayTransport::Udp].map(|option| {
+                                                    div()
+                                                        .px_2()
+                                                        .py_1()
+                                                        .cursor_pointer()
+                                                        .rounded_md()
+                                                        .when(transport == option, |div| {
+                                                            div.bg(theme.primary)
+                                                                .text_color(theme.background)
+                                                        })
+                                                        .when(transport != option, |div| {
+                                                            div.bg(theme.border)
+                                                                .text_color(theme.foreground)
+                                                        })
+                                                        .child(div().text_xs().child(option.to_string()))
+                                                        .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                            app.new_connection_relay.transport = option;
+                                                            cx.notify();
+                                                        }))
+                                                })),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .gap_2()
+                                                .child(field("上游地址", &self.app.relay_upstream_address_input))
+                                                .child(div().w_24().child(field("上游端口", &self.app.relay_upstream_port_input))),
+                                        )
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child("每个接入的客户端单独连接上游，双向数据均会被记录"),
+                                        )
+                                        // 网络损伤：每行一项，两列分别为发往上游和发往客户端
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("网络损伤（留空或 0 表示不启用）"),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .items_center()
+                                                .gap_2()
+                                                .child(div().w_24())
+                                                .children(["→ 上游", "← 客户端"].map(|label| {
+                                                    div()
+                                                        .flex_1()
+                                                        .text_xs()
+                                                        .text_color(gpui::rgb(0x9ca3af))
+                                                        .child(label)
+                                                })),
+                                        )
+                                        .children(
+                                            [
+                                                ("延迟（毫秒）", &self.app.relay_to_upstream_inputs.latency, &self.app.relay_to_client_inputs.latency, true),
+                                                ("抖动（毫秒）", &self.app.relay_to_upstream_inputs.jitter, &self.app.relay_to_client_inputs.jitter, true),
+                                                ("带宽（字节/秒）", &self.app.relay_to_upstream_inputs.bandwidth, &self.app.relay_to_client_inputs.bandwidth, true),
+                                                ("损坏率（%）", &self.app.relay_to_upstream_inputs.corrupt, &self.app.relay_to_client_inputs.corrupt, true),
+                                                ("丢包率（%）", &self.app.relay_to_upstream_inputs.loss, &self.app.relay_to_client_inputs.loss, transport == RelayTransport::Udp),
+                                                ("乱序率（%）", &self.app.relay_to_upstream_inputs.reorder, &self.app.relay_to_client_inputs.reorder, transport == RelayTransport::Udp),
+                                                ("分段（字节）", &self.app.relay_to_upstream_inputs.segment, &self.app.relay_to_client_inputs.segment, transport == RelayTransport::Tcp),
+                                            ]
+                                            .into_iter()
+                                            .filter(|(_, _, _, visible)| *visible)
+                                            .map(|(label, to_upstream, to_client, _)| {
+                                                div()
+                                                    .flex()
+                                                    .items_center()
+                                                    .gap_2()
                                                     .child(
                                                         div()
-                                                            .text_sm()
-                                                            .font_medium()
-                                                            .child("UDP"),
+                                                            .w_24()
+                                                            .text_xs()
+                                                            .text_color(gpui::rgb(0x9ca3af))
+                                                            .child(label),
                                                     )
-                                                    .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
-                                                        app.new_connection_protocol = String::from("UDP");
-                                                        cx.notify();
-                                                    })),
-                                            ),
-                                    ),
-                            ),
+                                                    .child(div().flex_1().child(Input::new(to_upstream)))
+                                                    .child(div().flex_1().child(Input::new(to_client)))
+                                            }),
+                                        ),
+                                )
+                            })
+                            // 最大连接数设置（TCP、WebSocket、Unix流式服务端和TCP中继）
+                            .when(!self.app.new_connection_is_client && !matches!(self.app.new_connection_protocol.as_str(), "UDP" | "Unix DGRAM") && !(self.app.new_connection_protocol == "Relay" && self.app.new_connection_relay.transport == RelayTransport::Udp), |this| {
+                                let policy = self.app.new_connection_max_connections_policy;
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("最大连接数（0 表示不限）"),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .items_center()
+                                                .gap_2()
+                                                .child(div().w_20().child(Input::new(&self.app.max_connections_input)))
+                                                .children([MaxConnectionsPolicy::Reject, MaxConnectionsPolicy::CloseOldest].map(|option| {
+                                                    div()
+                                                        .px_2()
+                                                        .py_1()
+                                                        .cursor_pointer()
+                                                        .rounded_md()
+                                                        .when(policy == option, |div| {
+                                                            div.bg(theme.primary)
+                                                                .text_color(theme.background)
+                                                        })
+                                                        .when(policy != option, |div| {
+                                                            div.bg(theme.border)
+                                                                .text_color(theme.foreground)
+                                                        })
+                                                        .child(div().text_xs().child(option.to_string()))
+                                                        .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                            app.new_connection_max_connections_policy = option;
+                                                            cx.notify();
+                                                        }))
+                                                })),
+                                        ),
+                                )
+                            })
+                            // 接收缓冲区设置（UDP、Unix数据报和UDP中继）
+                            .when(matches!(self.app.new_connection_protocol.as_str(), "UDP" | "Unix DGRAM") || (self.app.new_connection_protocol == "Relay" && self.app.new_connection_relay.transport == RelayTransport::Udp), |this| {
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child(format!("接收缓冲区（字节，最大 {}）", MAX_UDP_RECV_BUFFER_SIZE)),
+                                        )
+                                        .child(Input::new(&self.app.udp_recv_buffer_input))
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child("超过该长度的数据报会被截断并在消息上标记"),
+                                        ),
+                                )
+                            })
+                            // 本地绑定设置（仅TCP、UDP和WebSocket客户端）
+                            .when(self.app.new_connection_is_client && matches!(self.app.new_connection_protocol.as_str(), "TCP" | "UDP" | "WebSocket"), |this| {
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("本地地址与端口（可选）"),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .gap_2()
+                                                .child(div().flex_1().child(Input::new(&self.app.local_address_input)))
+                                                .child(div().w_24().child(Input::new(&self.app.local_port_input))),
+                                        )
+                                        .when_some(
+                                            resolve::parse_ip_literal(&self.app.local_address_input.read(cx).value(), 0).and_then(Result::err),
+                                            |this, error| {
+                                                this.child(
+                                                    div()
+                                                        .text_xs()
+                                                        .text_color(gpui::rgb(0xef4444))
+                                                        .child(error),
+                                                )
+                                            },
+                                        ),
+                                )
+                            })
+                            // 组播设置（仅UDP服务端）
+                            .when(!self.app.new_connection_is_client && self.app.new_connection_protocol == "UDP", |this| {
+                                let loopback = self.app.new_connection_multicast_loopback;
+                                let field = |label: &'static str, input: &Entity<InputState>| {
+                                    div()
+                                        .flex_1()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child(label),
+                                        )
+                                        .child(Input::new(input))
+                                };
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("组播（留空表示不加入组播组）"),
+                                        )
+                                        .child(field("组播组", &self.app.multicast_groups_input))
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .gap_2()
+                                                .child(field("接口", &self.app.multicast_interface_input))
+                                                .child(field("TTL", &self.app.multicast_ttl_input))
+                                                .child(field("源地址（IPv4）", &self.app.multicast_source_input)),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .items_center()
+                                                .gap_2()
+                                                .child(
+                                                    div()
+                                                        .w_4()
+                                                        .h_4()
+                                                        .border_1()
+                                                        .border_color(gpui::rgb(0xd1d5db))
+                                                        .rounded(px(4.))
+                                                        .cursor_pointer()
+                                                        .when(loopback, |this| {
+                                                            this.bg(gpui::rgb(0x3b82f6))
+                                                                .flex()
+                                                                .items_center()
+                                                                .justify_center()
+                                                                .child(
+                                                                    div()
+                                                                        .text_xs()
+                                                                        .text_color(gpui::rgb(0xffffff))
+                                                                        .font_bold()
+                                                                        .child("✓"),
+                                                                )
+                                                        })
+                                                        .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                            app.new_connection_multicast_loopback = !app.new_connection_multicast_loopback;
+                                                            cx.notify();
+                                                        })),
+                                                )
+                                                .child(
+                                                    div()
+                                                        .text_xs()
+                                                        .text_color(theme.foreground)
+                                                        .child("接收本机发出的组播数据（回环）"),
+                                                ),
+                                        ),
+                                )
+                            })
+                            // 套接字选项（TCP和WebSocket），留空使用系统默认
+                            .when(matches!(self.app.new_connection_protocol.as_str(), "TCP" | "WebSocket"), |this| {
+                                let field = |label: &'static str, input: &Entity<InputState>| {
+                                    div()
+                                        .flex_1()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child(label),
+                                        )
+                                        .child(Input::new(input))
+                                };
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("套接字选项（留空使用系统默认）"),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .gap_4()
+                                                .child(
+                                                    div()
+                                                        .flex()
+                                                        .items_center()
+                                                        .gap_2()
+                                                        .child(
+                                                            div()
+                                                                .w_4()
+                                                                .h_4()
+                                                                .border_1()
+                                                                .border_color(gpui::rgb(0xd1d5db))
+                                                                .rounded(px(4.))
+                                                                .cursor_pointer()
+                                                                .when(self.app.new_connection_nodelay, |this| {
+                                                                    this.bg(gpui::rgb(0x3b82f6))
+                                                                        .flex()
+                                                                        .items_center()
+                                                                        .justify_center()
+                                                                        .child(
+                                                                            div()
+                                                                                .text_xs()
+                                                                                .text_color(gpui::rgb(0xffffff))
+                                                                                .font_bold()
+                                                                                .child("✓"),
+                                                                        )
+                                                                })
+                                                                .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                                    app.new_connection_nodelay = !app.new_connection_nodelay;
+                                                                    cx.notify();
+                                                                })),
+                                                        )
+                                                        .child(
+                                                            div()
+                                                                .text_xs()
+                                                                .text_color(theme.foreground)
+                                                                .child("TCP_NODELAY（禁用Nagle）"),
+                                                        ),
+                                                )
+                                                .child(
+                                                    div()
+                                                        .flex()
+                                                        .items_center()
+                                                        .gap_2()
+                                                        .child(
+                                                            div()
+                                                                .w_4()
+                                                                .h_4()
+                                                                .border_1()
+                                                                .border_color(gpui::rgb(0xd1d5db))
+                                                                .rounded(px(4.))
+                                                                .cursor_pointer()
+                                                                .when(self.app.new_connection_keepalive, |this| {
+                                                                    this.bg(gpui::rgb(0x3b82f6))
+                                                                        .flex()
+                                                                        .items_center()
+                                                                        .justify_center()
+                                                                        .child(
+                                                                            div()
+                                                                                .text_xs()
+                                                                                .text_color(gpui::rgb(0xffffff))
+                                                                                .font_bold()
+                                                                                .child("✓"),
+                                                                        )
+                                                                })
+                                                                .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                                    app.new_connection_keepalive = !app.new_connection_keepalive;
+                                                                    cx.notify();
+                                                                })),
+                                                        )
+                                                        .child(
+                                                            div()
+                                                                .text_xs()
+                                                                .text_color(theme.foreground)
+                                                                .child("SO_KEEPALIVE"),
+                                                        ),
+                                                )
+                                        )
+                                        .when(self.app.new_connection_keepalive, |this| {
+                                            this.child(
+                                                div()
+                                                    .flex()
+                                                    .gap_2()
+                                                    .child(field("保活空闲（秒）", &self.app.keepalive_idle_input))
+                                                    .child(field("探测间隔（秒）", &self.app.keepalive_interval_input))
+                                                    .child(field("探测次数", &self.app.keepalive_count_input)),
+                                            )
+                                        })
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .gap_2()
+                                                .child(field("SO_LINGER（秒，0 为RST关闭）", &self.app.linger_input))
+                                                .child(field("SO_SNDBUF", &self.app.send_buffer_input))
+                                                .child(field("SO_RCVBUF", &self.app.recv_buffer_input)),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .gap_2()
+                                                .child(field("TTL", &self.app.ip_ttl_input))
+                                                .child(field("TOS", &self.app.ip_tos_input)),
+                                        ),
+                                )
+                            })
+                            // WebSocket设置
+                            .when(self.app.new_connection_protocol == "WebSocket", |this| {
+                                let is_client = self.app.new_connection_is_client;
+                                let send_frame = self.app.new_connection_ws_send_frame;
+                                let field = |label: &'static str, input: &Entity<InputState>| {
+                                    div()
+                                        .flex_1()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child(label),
+                                        )
+                                        .child(Input::new(input))
+                                };
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("WebSocket"),
+                                        )
+                                        .when(is_client, |this| {
+                                            this.child(field("路径（如 /chat?room=1）", &self.app.ws_path_input))
+                                                .child(field("握手头部（名称: 值，多个以分号分隔）", &self.app.ws_headers_input))
+                                        })
+                                        .child(field(
+                                            if is_client { "请求的子协议（逗号分隔）" } else { "支持的子协议（逗号分隔）" },
+                                            &self.app.ws_subprotocols_input,
+                                        ))
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .items_center()
+                                                .gap_2()
+                                                .child(
+                                                    div()
+                                                        .text_xs()
+                                                        .text_color(gpui::rgb(0x9ca3af))
+                                                        .child("发送帧类型"),
+                                                )
+                                                .children([WebSocketSendFrame::Auto, WebSocketSendFrame::Text, WebSocketSendFrame::Binary].map(|option| {
+                                                    div()
+                                                        .px_2()
+                                                        .py_1()
+                                                        .cursor_pointer()
+                                                        .rounded_md()
+                                                        .when(send_frame == option, |div| {
+                                                            div.bg(theme.primary)
+                                                                .text_color(theme.background)
+                                                        })
+                                                        .when(send_frame != option, |div| {
+                                                            div.bg(theme.border)
+                                                                .text_color(theme.foreground)
+                                                        })
+                                                        .child(div().text_xs().child(option.to_string()))
+                                                        .on_mouse_down(MouseButton::Left, cx.listener(move |app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                            app.new_connection_ws_send_frame = option;
+                                                            cx.notify();
+                                                        }))
+                                                })),
+                                        ),
+                                )
+                            })
+                            // TLS设置（TCP和WebSocket，WebSocket启用后为 wss://）
+                            .when(matches!(self.app.new_connection_protocol.as_str(), "TCP" | "WebSocket"), |this| {
+                                let is_client = self.app.new_connection_is_client;
+                                let field = |label: &'static str, input: &Entity<InputState>| {
+                                    div()
+                                        .flex_1()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0x9ca3af))
+                                                .child(label),
+                                        )
+                                        .child(Input::new(input))
+                                };
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .items_center()
+                                                .gap_2()
+                                                .child(
+                                                    div()
+                                                        .w_4()
+                                                        .h_4()
+                                                        .border_1()
+                                                        .border_color(gpui::rgb(0xd1d5db))
+                                                        .rounded(px(4.))
+                                                        .cursor_pointer()
+                                                        .when(self.app.new_connection_tls_enabled, |this| {
+                                                            this.bg(gpui::rgb(0x3b82f6))
+                                                                .flex()
+                                                                .items_center()
+                                                                .justify_center()
+                                                                .child(
+                                                                    div()
+                                                                        .text_xs()
+                                                                        .text_color(gpui::rgb(0xffffff))
+                                                                        .font_bold()
+                                                                        .child("✓"),
+                                                                )
+                                                        })
+                                                        .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                            app.new_connection_tls_enabled = !app.new_connection_tls_enabled;
+                                                            cx.notify();
+                                                        })),
+                                                )
+                                                .child(
+                                                    div()
+                                                        .text_xs()
+                                                        .text_color(theme.foreground)
+                                                        .child(if self.app.new_connection_protocol == "WebSocket" {
+                                                            "启用TLS（wss://）"
+                                                        } else {
+                                                            "启用TLS"
+                                                        }),
+                                                ),
+                                        )
+                                        .when(self.app.new_connection_tls_enabled, |this| {
+                                            this.when(is_client, |this| {
+                                                this.child(field("服务器名称（SNI，留空使用服务器地址）", &self.app.tls_server_name_input))
+                                            })
+                                            .child(field(
+                                                if is_client { "CA证书（留空使用系统根证书）" } else { "CA证书（用于校验客户端证书）" },
+                                                &self.app.tls_ca_input,
+                                            ))
+                                            .child(
+                                                div()
+                                                    .flex()
+                                                    .gap_2()
+                                                    .child(field(
+                                                        if is_client { "客户端证书（可选）" } else { "服务端证书（留空自动生成自签名证书）" },
+                                                        &self.app.tls_cert_input,
+                                                    ))
+                                                    .child(field("私钥", &self.app.tls_key_input)),
+                                            )
+                                            .when(is_client, |this| {
+                                                this.child(
+                                                    div()
+                                                        .flex()
+                                                        .items_center()
+                                                        .gap_2()
+                                                        .child(
+                                                            div()
+                                                                .w_4()
+                                                                .h_4()
+                                                                .border_1()
+                                                                .border_color(gpui::rgb(0xd1d5db))
+                                                                .rounded(px(4.))
+                                                                .cursor_pointer()
+                                                                .when(self.app.new_connection_tls_insecure, |this| {
+                                                                    this.bg(gpui::rgb(0x3b82f6))
+                                                                        .flex()
+                                                                        .items_center()
+                                                                        .justify_center()
+                                                                        .child(
+                                                                            div()
+                                                                                .text_xs()
+                                                                                .text_color(gpui::rgb(0xffffff))
+                                                                                .font_bold()
+                                                                                .child("✓"),
+                                                                        )
+                                                                })
+                                                                .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                                    app.new_connection_tls_insecure = !app.new_connection_tls_insecure;
+                                                                    cx.notify();
+                                                                })),
+                                                        )
+                                                        .child(
+                                                            div()
+                                                                .text_xs()
+                                                                .text_color(theme.foreground)
+                                                                .child("不校验服务器证书（仅调试）"),
+                                                        ),
+                                                )
+                                            })
+                                            .when(!is_client, |this| {
+                                                this.child(
+                                                    div()
+                                                        .flex()
+                                                        .items_center()
+                                                        .gap_2()
+                                                        .child(
+                                                            div()
+                                                                .w_4()
+                                                                .h_4()
+                                                                .border_1()
+                                                                .border_color(gpui::rgb(0xd1d5db))
+                                                                .rounded(px(4.))
+                                                                .cursor_pointer()
+                                                                .when(self.app.new_connection_tls_require_client_cert, |this| {
+                                                                    this.bg(gpui::rgb(0x3b82f6))
+                                                                        .flex()
+                                                                        .items_center()
+                                                                        .justify_center()
+                                                                        .child(
+                                                                            div()
+                                                                                .text_xs()
+                                                                                .text_color(gpui::rgb(0xffffff))
+                                                                                .font_bold()
+                                                                                .child("✓"),
+                                                                        )
+                                                                })
+                                                                .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                                    app.new_connection_tls_require_client_cert = !app.new_connection_tls_require_client_cert;
+                                                                    cx.notify();
+                                                                })),
+                                                        )
+                                                        .child(
+                                                            div()
+                                                                .text_xs()
+                                                                .text_color(theme.foreground)
+                                                                .child("要求客户端证书"),
+                                                        ),
+                                                )
+                                                    .child(
+                                                        div()
+                                                            .text_xs()
+                                                            .px_2()
+                                                            .py_1()
+                                                            .bg(theme.border)
+                                                            .text_color(theme.foreground)
+                                                            .rounded_md()
+                                                            .cursor_pointer()
+                                                            .child("生成自签名证书")
+                                                            .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                                let host = app.host_input.read(cx).value().trim().to_string();
+                                                                let port = app.port_input.read(cx).value().trim().to_string();
+                                                                let names = if host.is_empty() || host == "0.0.0.0" || host == "::" {
+                                                                    Vec::new()
+                                                                } else {
+                                                                    vec![host]
+                                                                };
+                                                                match tls::save_self_signed(&names, &format!("server_{}", port)) {
+                                                                    Ok((cert_path, key_path)) => {
+                                                                        app.tls_cert_input.update(cx, |input, cx| {
+                                                                            input.set_value(cert_path.display().to_string(), window, cx);
+                                                                        });
+                                                                        app.tls_key_input.update(cx, |input, cx| {
+                                                                            input.set_value(key_path.display().to_string(), window, cx);
+                                                                        });
+                                                                    }
+                                                                    Err(e) => error!("生成自签名证书失败: {}", e),
+                                                                }
+                                                                cx.notify();
+                                                            })),
+                                                    )
+                                            })
+                                        }),
+                                )
+                            })
+                            // 超时设置（TCP、WebSocket、Unix流式套接字、串口、TCP中继和压力测试）
+                            .when(!matches!(self.app.new_connection_protocol.as_str(), "UDP" | "Unix DGRAM" | "MQTT") && !(self.app.new_connection_protocol == "Relay" && self.app.new_connection_relay.transport == RelayTransport::Udp), |this| {
+                                let read_idle_close = self.app.new_connection_read_idle_close;
+                                let has_connect_timeout = self.app.new_connection_is_client && self.app.new_connection_protocol != "Serial";
+                                this.child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .font_semibold()
+                                                .text_color(theme.foreground)
+                                                .child("超时设置（秒，0 表示不启用）"),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .gap_2()
+                                                .child(
+                                                    div()
+                                                        .flex_1()
+                                                        .flex()
+                                                        .flex_col()
+                                                        .gap_1()
+                                                        .child(
+                                                            div()
+                                                                .text_xs()
+                                                                .text_color(gpui::rgb(0x9ca3af))
+                                                                .child(if has_connect_timeout {
+                                                                    "连接/写入超时"
+                                                                } else {
+                                                                    "写入超时"
+                                                                }),
+                                                        )
+                                                        .child(Input::new(&self.app.timeout_input)),
+                                                )
+                                                .child(
+                                                    div()
+                                                        .flex_1()
+                                                        .flex()
+                                                        .flex_col()
+                                                        .gap_1()
+                                                        .child(
+                                                            div()
+                                                                .text_xs()
+                                                                .text_color(gpui::rgb(0x9ca3af))
+                                                                .child("读取空闲超时"),
+                                                        )
+                                                        .child(Input::new(&self.app.read_idle_timeout_input)),
+                                                ),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .items_center()
+                                                .gap_2()
+                                                .child(
+                                                    div()
+                                                        .w_4()
+                                                        .h_4()
+                                                        .border_1()
+                                                        .border_color(gpui::rgb(0xd1d5db))
+                                                        .rounded(px(4.))
+                                                        .cursor_pointer()
+                                                        .when(read_idle_close, |this| {
+                                                            this.bg(gpui::rgb(0x3b82f6))
+                                                                .flex()
+                                                                .items_center()
+                                                                .justify_center()
+                                                                .child(
+                                                                    div()
+                                                                        .text_xs()
+                                                                        .text_color(gpui::rgb(0xffffff))
+                                                                        .font_bold()
+                                                                        .child("✓"),
+                                                                )
+                                                        })
+                                                        .on_mouse_down(MouseButton::Left, cx.listener(|app: &mut NetAssistantApp, _event: &MouseDownEvent, _window: &mut Window, cx: &mut Context<NetAssistantApp>| {
+                                                            app.new_connection_read_idle_close = !app.new_connection_read_idle_close;
+                                                            cx.notify();
+                                                        })),
+                                                )
+                                                .child(
+                                                    div()
+                                                        .text_xs()
+                                                        .text_color(theme.foreground)
+                                                        .child("空闲超时后断开（否则仅提示）"),
+                                                ),
+                                        ),
+                                )
+                            }),
                     )
                     .child(
                         div()
@@ -195,45 +1532,405 @@ impl<'a> NewConnectionDialog<'a> {
                                         let host = app.host_input.read(cx).value().to_string();
                                         let port_str = app.port_input.read(cx).value().to_string();
 
-                                        // 验证必填字段
-                                        if host.is_empty() || port_str.is_empty() {
+                                        let is_serial = app.new_connection_protocol == "Serial";
+                                        let is_unix = matches!(app.new_connection_protocol.as_str(), "Unix" | "Unix DGRAM");
+                                        let uses_path = is_serial || is_unix;
+
+                                        // 验证必填字段（串口和Unix域套接字不使用主机和端口）
+                                        if !uses_path && (host.is_empty() || port_str.is_empty()) {
                                             return;
                                         }
 
                                         // 解析端口
                                         let port: u16 = match port_str.parse() {
                                             Ok(p) => p,
+                                            Err(_) if uses_path => 0,
                                             Err(_) => return,
                                         };
 
+                                        // IPv6地址的区域（接口名称或索引）必须有效，服务端可填写多个地址
+                                        if !uses_path && host.split(',').any(|host| matches!(resolve::parse_ip_literal(host, port), Some(Err(_)))) {
+                                            return;
+                                        }
+
+                                        // 解析串口设置，参数无效时不创建
+                                        let serial = SerialConfig {
+                                            path: app.serial_path_input.read(cx).value().trim().to_string(),
+                                            baud_rate: match app.serial_baud_input.read(cx).value().trim().parse() {
+                                                Ok(baud_rate) => baud_rate,
+                                                Err(_) if is_serial => return,
+                                                Err(_) => SerialConfig::default().baud_rate,
+                                            },
+                                            ..app.new_connection_serial.clone()
+                                        };
+                                        if is_serial && serial.validate().is_err() {
+                                            return;
+                                        }
+
+                                        // 解析Unix域套接字设置，路径为空或权限无效时不创建
+                                        let unix = UnixSocketConfig {
+                                            path: app.unix_path_input.read(cx).value().trim().to_string(),
+                                            permissions: match UnixSocketConfig::parse_permissions(&app.unix_permissions_input.read(cx).value()) {
+                                                Ok(permissions) => permissions,
+                                                Err(_) if is_unix => return,
+                                                Err(_) => None,
+                                            },
+                                            local_path: app.unix_local_path_input.read(cx).value().trim().to_string(),
+                                        };
+                                        if is_unix && unix.path.is_empty() {
+                                            return;
+                                        }
+
+                                        // 解析MQTT设置，订阅或发布主题格式无效时不创建
+                                        let is_mqtt = app.new_connection_protocol == "MQTT";
+                                        let mqtt = {
+                                            let keep_alive = match app.mqtt_keep_alive_input.read(cx).value().trim() {
+                                                "" => MqttConfig::default().keep_alive,
+                                                value => match value.parse::<u16>() {
+                                                    Ok(keep_alive) => keep_alive,
+                                                    Err(_) if is_mqtt => return,
+                                                    Err(_) => MqttConfig::default().keep_alive,
+                                                },
+                                            };
+                                            let subscriptions = match MqttConfig::parse_subscriptions(&app.mqtt_subscriptions_input.read(cx).value()) {
+                                                Ok(subscriptions) => subscriptions,
+                                                Err(_) if is_mqtt => return,
+                                                Err(_) => Vec::new(),
+                                            };
+                                            let publish_topic = app.mqtt_publish_topic_input.read(cx).value().trim().to_string();
+                                            if is_mqtt && !publish_topic.is_empty() && MqttConfig::validate_topic(&publish_topic).is_err() {
+                                                return;
+                                            }
+                                            MqttConfig {
+                                                client_id: app.mqtt_client_id_input.read(cx).value().trim().to_string(),
+                                                username: app.mqtt_username_input.read(cx).value().trim().to_string(),
+                                                password: app.mqtt_password_input.read(cx).value().to_string(),
+                                                keep_alive,
+                                                subscriptions,
+                                                publish_topic,
+                                                ..app.new_connection_mqtt.clone()
+                                            }
+                                        };
+
+                                        // 解析压力测试设置，参数或发送模板无效时不创建
+                                        let is_load_test = app.new_connection_protocol == "Load Test";
+                                        let load_test = {
+                                            let read = |input: &Entity<InputState>| input.read(cx).value().trim().to_string();
+                                            match (
+                                                read(&app.load_test_clients_input).parse::<u32>(),
+                                                read(&app.load_test_ramp_up_input).parse::<u32>(),
+                                                read(&app.load_test_send_rate_input).parse::<f64>(),
+                                                read(&app.load_test_duration_input).parse::<u64>(),
+                                            ) {
+                                                (Ok(clients), Ok(ramp_up_rate), Ok(send_rate), Ok(duration)) => LoadTestConfig {
+                                                    clients,
+                                                    ramp_up_rate,
+                                                    send_rate,
+                                                    payload: app.load_test_payload_input.read(cx).value().to_string(),
+                                                    duration,
+                                                    ..app.new_connection_load_test.clone()
+                                                },
+                                                _ if is_load_test => return,
+                                                _ => LoadTestConfig::default(),
+                                            }
+                                        };
+                                        if is_load_test && load_test.validate().is_err() {
+                                            return;
+                                        }
+
+                                        // 解析中继上游地址和网络损伤，地址为空、端口或损伤参数无效时不创建
+                                        let is_relay = app.new_connection_protocol == "Relay";
+                                        let (to_upstream, to_client) = match (app.relay_to_upstream_inputs.read(cx), app.relay_to_client_inputs.read(cx)) {
+                                            (Some(to_upstream), Some(to_client)) => (to_upstream, to_client),
+                                            _ if is_relay => return,
+                                            _ => Default::default(),
+                                        };
+                                        // 只保留当前传输层协议支持的损伤项
+                                        let (to_upstream, to_client) = {
+                                            let transport = app.new_connection_relay.transport;
+                                            let [to_upstream, to_client] = [to_upstream, to_client].map(|mut impairment| {
+                                                match transport {
+                                                    RelayTransport::Tcp => {
+                                                        impairment.loss_percent = 0.0;
+                                                        impairment.reorder_percent = 0.0;
+                                                    }
+                                                    RelayTransport::Udp => impairment.segment_size = 0,
+                                                }
+                                                impairment
+                                            });
+                                            (to_upstream, to_client)
+                                        };
+                                        let relay = RelayConfig {
+                                            upstream_address: app.relay_upstream_address_input.read(cx).value().trim().to_string(),
+                                            upstream_port: app.relay_upstream_port_input.read(cx).value().trim().parse().unwrap_or(0),
+                                            to_upstream,
+                                            to_client,
+                                            ..app.new_connection_relay.clone()
+                                        };
+                                        if is_relay && (relay.upstream_address.is_empty() || relay.upstream_port == 0) {
+                                            return;
+                                        }
+
+                                        // 解析超时与最大连接数设置，留空视为 0（不启用/不限）
+                                        let parse_number = |value: String| -> Option<u64> {
+                                            let value = value.trim();
+                                            if value.is_empty() {
+                                                Some(0)
+                                            } else {
+                                                value.parse().ok()
+                                            }
+                                        };
+                                        let timeout = match parse_number(app.timeout_input.read(cx).value().to_string()) {
+                                            Some(t) => t,
+                                            None => return,
+                                        };
+                                        let read_idle_timeout = match parse_number(app.read_idle_timeout_input.read(cx).value().to_string()) {
+                                            Some(t) => t,
+                                            None => return,
+                                        };
+                                        let read_idle_close = app.new_connection_read_idle_close;
+                                        let max_connections = match parse_number(app.max_connections_input.read(cx).value().to_string()) {
+                                            Some(n) => n as usize,
+                                            None => return,
+                                        };
+                                        let udp_recv_buffer_size = match parse_number(app.udp_recv_buffer_input.read(cx).value().to_string()) {
+                                            Some(n) if (1..=MAX_UDP_RECV_BUFFER_SIZE as u64).contains(&n) => n as usize,
+                                            _ => return,
+                                        };
+
+                                        // 解析TCP套接字选项，留空表示使用系统默认
+                                        let parse_optional = |value: String| -> Option<Option<u64>> {
+                                            let value = value.trim();
+                                            if value.is_empty() {
+                                                Some(None)
+                                            } else {
+                                                value.parse().ok().map(Some)
+                                            }
+                                        };
+                                        let socket_options = {
+                                            let read_number = |input: &Entity<InputState>| parse_number(input.read(cx).value().to_string());
+                                            let read_optional = |input: &Entity<InputState>| parse_optional(input.read(cx).value().to_string());
+                                            let (
+                                                Some(keepalive_idle),
+                                                Some(keepalive_interval),
+                                                Some(keepalive_count),
+                                                Some(send_buffer_size),
+                                                Some(recv_buffer_size),
+                                                Some(ttl),
+                                                Some(linger),
+                                                Some(tos),
+                                            ) = (
+                                                read_number(&app.keepalive_idle_input),
+                                                read_number(&app.keepalive_interval_input),
+                                                read_number(&app.keepalive_count_input),
+                                                read_number(&app.send_buffer_input),
+                                                read_number(&app.recv_buffer_input),
+                                                read_number(&app.ip_ttl_input),
+                                                read_optional(&app.linger_input),
+                                                read_optional(&app.ip_tos_input),
+                                            ) else {
+                                                return;
+                                            };
+                                            if keepalive_count > u32::MAX as u64 || ttl > 255 || tos.is_some_and(|tos| tos > 255) {
+                                                return;
+                                            }
+                                            TcpSocketOptions {
+                                                nodelay: app.new_connection_nodelay,
+                                                keepalive: app.new_connection_keepalive,
+                                                keepalive_idle,
+                                                keepalive_interval,
+                                                keepalive_count: keepalive_count as u32,
+                                                linger,
+                                                send_buffer_size: send_buffer_size as usize,
+                                                recv_buffer_size: recv_buffer_size as usize,
+                                                ttl: ttl as u32,
+                                                tos: tos.map(|tos| tos as u32),
+                                            }
+                                        };
+
+                                        // TLS设置（TCP和WebSocket使用）
+                                        let tls_config = TlsConfig {
+                                            enabled: app.new_connection_tls_enabled,
+                                            server_name: app.tls_server_name_input.read(cx).value().trim().to_string(),
+                                            ca_cert_path: app.tls_ca_input.read(cx).value().trim().to_string(),
+                                            cert_path: app.tls_cert_input.read(cx).value().trim().to_string(),
+                                            key_path: app.tls_key_input.read(cx).value().trim().to_string(),
+                                            insecure: app.new_connection_tls_insecure,
+                                            require_client_cert: app.new_connection_tls_require_client_cert,
+                                        };
+                                        if tls_config.enabled && !tls_config.cert_path.is_empty() && tls_config.key_path.is_empty() {
+                                            return;
+                                        }
+
+                                        // 解析本地绑定设置（仅客户端使用）
+                                        let local_address = app.local_address_input.read(cx).value().trim().to_string();
+                                        if !local_address.is_empty() && !matches!(resolve::parse_ip_literal(&local_address, 0), Some(Ok(_))) {
+                                            return;
+                                        }
+                                        let local_port = match app.local_port_input.read(cx).value().trim() {
+                                            "" => 0,
+                                            value => match value.parse::<u16>() {
+                                                Ok(port) => port,
+                                                Err(_) => return,
+                                            },
+                                        };
+
+                                        // 解析组播设置（仅UDP服务端使用）
+                                        let multicast = {
+                                            let groups = match MulticastConfig::parse_groups(&app.multicast_groups_input.read(cx).value()) {
+                                                Ok(groups) => groups,
+                                                Err(_) => return,
+                                            };
+                                            let ttl = match app.multicast_ttl_input.read(cx).value().trim() {
+                                                "" => MulticastConfig::default().ttl,
+                                                value => match value.parse::<u8>() {
+                                                    Ok(ttl) => ttl as u32,
+                                                    Err(_) => return,
+                                                },
+                                            };
+                                            let source = match app.multicast_source_input.read(cx).value().trim() {
+                                                "" => None,
+                                                value => match value.parse() {
+                                                    Ok(source) => Some(source),
+                                                    Err(_) => return,
+                                                },
+                                            };
+                                            MulticastConfig {
+                                                groups,
+                                                interface: app.multicast_interface_input.read(cx).value().trim().to_string(),
+                                                ttl,
+                                                loopback: app.new_connection_multicast_loopback,
+                                                source,
+                                            }
+                                        };
+
+                                        // 解析WebSocket设置，握手头部格式无效时不创建
+                                        let websocket = WebSocketConfig {
+                                            path: match app.ws_path_input.read(cx).value().trim() {
+                                                "" => "/".to_string(),
+                                                path => path.to_string(),
+                                            },
+                                            headers: match WebSocketConfig::parse_headers(&app.ws_headers_input.read(cx).value()) {
+                                                Ok(headers) => headers,
+                                                Err(_) => return,
+                                            },
+                                            subprotocols: WebSocketConfig::parse_subprotocols(&app.ws_subprotocols_input.read(cx).value()),
+                                            send_frame: app.new_connection_ws_send_frame,
+                                        };
+
                                         // 根据协议类型创建连接配置
-                                        let connection_type = if app.new_connection_protocol == "TCP" {
-                                            ConnectionType::Tcp
-                                        } else {
-                                            ConnectionType::Udp
+                                        let connection_type = match app.new_connection_protocol.as_str() {
+                                            "TCP" => ConnectionType::Tcp,
+                                            "WebSocket" => ConnectionType::WebSocket,
+                                            "Serial" => ConnectionType::Serial,
+                                            "Unix" => ConnectionType::UnixStream,
+                                            "Unix DGRAM" => ConnectionType::UnixDatagram,
+                                            "MQTT" => ConnectionType::Mqtt,
+                                            "Relay" => ConnectionType::Relay,
+                                            "Load Test" => ConnectionType::LoadTest,
+                                            _ => ConnectionType::Udp,
+                                        };
+
+                                        // 解析代理设置（仅TCP、UDP和WebSocket客户端），地址或端口无效时不创建
+                                        let uses_proxy = app.new_connection_is_client
+                                            && matches!(connection_type, ConnectionType::Tcp | ConnectionType::Udp | ConnectionType::WebSocket);
+                                        let proxy = ProxyConfig {
+                                            address: app.proxy_address_input.read(cx).value().trim().to_string(),
+                                            port: match app.proxy_port_input.read(cx).value().trim() {
+                                                "" => ProxyConfig::default().port,
+                                                value => value.parse().unwrap_or(0),
+                                            },
+                                            username: app.proxy_username_input.read(cx).value().trim().to_string(),
+                                            password: app.proxy_password_input.read(cx).value().to_string(),
+                                            ..app.new_connection_proxy.clone()
                                         };
+                                        if uses_proxy && proxy.validate(connection_type).is_err() {
+                                            return;
+                                        }
 
                                         // 根据new_connection_is_client创建客户端或服务端连接
                                         let connection_config = if app.new_connection_is_client {
                                             // 创建客户端连接配置（自动生成ID）
-                                            let config = ConnectionConfig::new_client(
+                                            let mut config = ConnectionConfig::new_client(
                                                 String::new(),
                                                 host,
                                                 port,
                                                 connection_type,
                                             );
+                                            if let ConnectionConfig::Client(client_config) = &mut config {
+                                                client_config.timeout = timeout;
+                                                client_config.read_idle_timeout = read_idle_timeout;
+                                                client_config.read_idle_close = read_idle_close;
+                                                client_config.udp_recv_buffer_size = udp_recv_buffer_size;
+                                                client_config.local_address = local_address;
+                                                client_config.local_port = local_port;
+                                                client_config.socket_options = socket_options;
+                                                if matches!(connection_type, ConnectionType::Tcp | ConnectionType::WebSocket) {
+                                                    client_config.tls = tls_config;
+                                                }
+                                                if connection_type == ConnectionType::WebSocket {
+                                                    client_config.websocket = websocket;
+                                                }
+                                                if connection_type == ConnectionType::Serial {
+                                                    client_config.server_address = String::new();
+                                                    client_config.serial = serial;
+                                                }
+                                                if connection_type.is_unix() {
+                                                    client_config.server_address = String::new();
+                                                    client_config.unix = unix;
+                                                }
+                                                if connection_type == ConnectionType::Mqtt {
+                                                    client_config.mqtt = mqtt;
+                                                }
+                                                if connection_type == ConnectionType::LoadTest {
+                                                    client_config.load_test = load_test;
+                                                }
+                                                if uses_proxy {
+                                                    client_config.proxy = proxy;
+                                                }
+                                            }
                                             
                                             // 添加到配置存储
                                             app.storage.add_connection(config.clone());
                                             config
                                         } else {
-                                            // 创建服务端连接配置（自动生成ID）
-                                            let config = ConnectionConfig::new_server(
+                                            // 创建服务端连接配置（自动生成ID），逗号分隔的其余地址作为额外的监听地址
+                                            let (listen_address, extra_listen_addresses) = ServerConfig::split_listen_hosts(&host);
+                                            let mut config = ConnectionConfig::new_server(
                                                 String::new(),
-                                                host,
+                                                listen_address,
                                                 port,
                                                 connection_type,
                                             );
+                                            if let ConnectionConfig::Server(server_config) = &mut config {
+                                                server_config.extra_listen_addresses = extra_listen_addresses;
+                                                server_config.dual_stack = app.new_connection_dual_stack && matches!(connection_type, ConnectionType::Tcp | ConnectionType::WebSocket);
+                                                if server_config.validate_listen().is_err() {
+                                                    return;
+                                                }
+                                                server_config.timeout = timeout;
+                                                server_config.read_idle_timeout = read_idle_timeout;
+                                                server_config.read_idle_close = read_idle_close;
+                                                server_config.max_connections = max_connections;
+                                                server_config.max_connections_policy = app.new_connection_max_connections_policy;
+                                                server_config.udp_recv_buffer_size = udp_recv_buffer_size;
+                                                if connection_type == ConnectionType::Udp {
+                                                    server_config.multicast = multicast;
+                                                } else if matches!(connection_type, ConnectionType::Tcp | ConnectionType::WebSocket) {
+                                                    server_config.socket_options = socket_options;
+                                                    server_config.tls = tls_config;
+                                                }
+                                                if connection_type == ConnectionType::WebSocket {
+                                                    server_config.websocket = websocket;
+                                                }
+                                                if connection_type.is_unix() {
+                                                    server_config.listen_address = String::new();
+                                                    server_config.unix = unix;
+                                                }
+                                                if connection_type == ConnectionType::Relay {
+                                                    server_config.relay = relay;
+                                                }
+                                            }
                                             
                                             // 添加到配置存储
                                             app.storage.add_connection(config.clone());